@@ -0,0 +1,149 @@
+use std::sync::Arc;
+use axum::{
+    extract::Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use tracing::{error, info, instrument, warn};
+use validator::Validate;
+
+// Import domain entities and services
+use my_health_guide_domain::services::{create_default_user_service, user::{SharedUserService, UserServiceError}};
+use my_health_guide_domain::entities::user::{RegisterUserRequest, User as DomainUser};
+
+// Import our entities
+use crate::entities::auth::{PublicRegistrationRequest, PublicUserInfo};
+use super::blood_pressure::ErrorResponse;
+
+/// Create a default user service for the handlers to use
+pub fn create_service() -> SharedUserService {
+    Arc::new(create_default_user_service())
+}
+
+/// Register a new user with an email and password
+#[utoipa::path(
+    post,
+    path = "/auth/register",
+    request_body = PublicRegistrationRequest,
+    responses(
+        (status = 201, description = "User registered. Log in with the same email and password to get tokens.", body = PublicUserInfo),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 409, description = "Email already registered", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    tag = "Authentication"
+)]
+#[instrument(skip(service, request))]
+pub async fn register(
+    Extension(service): Extension<SharedUserService>,
+    Json(request): Json<PublicRegistrationRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Registering new user");
+
+    if let Err(e) = request.validate() {
+        warn!("Invalid registration data: {}", e);
+        return Err(ErrorResponse::validation_error(&e.to_string(), None).into_response());
+    }
+
+    let domain_request = RegisterUserRequest {
+        email: request.email,
+        password: request.password,
+        name: request.name,
+    };
+
+    match service.register(domain_request).await {
+        Ok(user) => Ok((StatusCode::CREATED, Json(convert_to_public_user(user)))),
+        Err(UserServiceError::ValidationError(message)) => {
+            warn!("Invalid registration data: {}", message);
+            Err(ErrorResponse::validation_error(&message, None).into_response())
+        },
+        Err(UserServiceError::EmailTaken(_)) => {
+            info!("Registration attempted with an email that is already registered");
+            Err(ErrorResponse::conflict("An account with this email already exists").into_response())
+        },
+        Err(UserServiceError::StorageUnavailable(message)) => {
+            warn!("Storage unavailable while registering user: {}", message);
+            Err(ErrorResponse::service_unavailable().into_response())
+        },
+        Err(e) => {
+            error!("Error registering user: {}", e);
+            Err(ErrorResponse::internal_error().into_response())
+        }
+    }
+}
+
+/// Convert a domain user to the public user info
+fn convert_to_public_user(user: DomainUser) -> PublicUserInfo {
+    PublicUserInfo {
+        user_id: user.id,
+        email: Some(user.email),
+        name: user.name,
+        picture: None,
+        roles: user.roles,
+        auth_source: "password".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use tower::ServiceExt;
+    use my_health_guide_domain::services::user::UserService;
+    use my_health_guide_data::repository::tests::MockUserRepository;
+
+    fn app() -> Router {
+        let service: SharedUserService = Arc::new(UserService::new(MockUserRepository::new()));
+        Router::new()
+            .route("/auth/register", post(register))
+            .route("/auth/login", post(my_health_guide_domain::auth::login))
+            .layer(Extension(service))
+    }
+
+    fn post_json(uri: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .uri(uri)
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_register_then_login() {
+        if std::env::var("JWT_SECRET").is_err() {
+            std::env::set_var("JWT_SECRET", "test_secret_key_for_testing_only");
+        }
+
+        let app = app();
+        let registration = serde_json::json!({
+            "email": "alice@example.com",
+            "password": "password123",
+            "name": "Alice"
+        });
+
+        let response = app.clone().oneshot(post_json("/auth/register", registration.clone())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app.clone().oneshot(post_json("/auth/register", registration)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let login = serde_json::json!({ "username": "alice@example.com", "password": "password123" });
+        let response = app.clone().oneshot(post_json("/auth/login", login)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let login = serde_json::json!({ "username": "alice@example.com", "password": "wrong-password" });
+        let response = app.oneshot(post_json("/auth/login", login)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_short_password() {
+        let registration = serde_json::json!({ "email": "bob@example.com", "password": "short" });
+
+        let response = app().oneshot(post_json("/auth/register", registration)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
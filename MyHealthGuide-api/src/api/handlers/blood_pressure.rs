@@ -3,22 +3,37 @@ use axum::{
     extract::{Json, Query, State, Path},
     http::StatusCode,
     response::{IntoResponse, Response},
+    Extension,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, instrument, warn};
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use uuid::Uuid;
 use utoipa::{IntoParams, ToSchema};
 
 // Import domain entities and services
+use my_health_guide_domain::auth::UserInfo;
 use my_health_guide_domain::services::{BloodPressureServiceTrait, create_default_blood_pressure_service};
-use my_health_guide_domain::entities::blood_pressure::BloodPressureReading as DomainBloodPressureReading;
+use my_health_guide_domain::services::alert_rules::spawn_alert_rule_evaluation;
+use my_health_guide_domain::services::blood_pressure::BloodPressureServiceError;
+use my_health_guide_domain::services::profile::HEIGHT_RANGE_CM;
+use my_health_guide_domain::entities::profile::Profile;
+use my_health_guide_domain::entities::blood_pressure::{
+    AlertRule as DomainAlertRule, BloodPressureAlert as DomainBloodPressureAlert, BloodPressureCategory, BloodPressureFilter,
+    BloodPressureReading as DomainBloodPressureReading,
+    BloodPressureSession as DomainBloodPressureSession, MeasurementArm, MeasurementPosition, PediatricPatient, Sex,
+};
 
 // Import our entities
-use crate::entities::blood_pressure::{BloodPressureReading, CreateBloodPressureRequest};
+use crate::entities::blood_pressure::{
+    AlertRule, BloodPressureAlert, BloodPressureReading, BloodPressureSession, CreateBloodPressureRequest,
+    CreateAlertRuleRequest, CreateBloodPressureResponse, CreateBloodPressureSessionRequest, UpdateBloodPressureRequest,
+};
+use crate::entities::weight::PublicWeightReading;
+use super::profile::{service_error_response as profile_error_response, ProfileService};
 
-/// Query parameters for retrieving blood pressure history
-#[derive(Debug, Deserialize, Clone, IntoParams, ToSchema)]
+/// Query parameters for retrieving reading history
+#[derive(Debug, Default, Deserialize, Clone, IntoParams, ToSchema)]
 pub struct HistoryQueryParams {
     /// ISO 8601 start date (default: 30 days ago)
     pub start_date: Option<String>,
@@ -34,6 +49,21 @@ pub struct HistoryQueryParams {
 
     /// Sort direction (asc/desc, default: desc)
     pub sort: Option<String>,
+
+    /// Only readings in this category (blood pressure history only)
+    pub category: Option<BloodPressureCategory>,
+
+    /// Only readings taken in this position (blood pressure history only)
+    pub position: Option<MeasurementPosition>,
+
+    /// Only readings taken on this arm (blood pressure history only)
+    pub arm: Option<MeasurementArm>,
+
+    /// Only readings from this device (blood pressure history only)
+    pub device_id: Option<String>,
+
+    /// Average each measurement session into a single reading (blood pressure history only, default: false)
+    pub sessions: Option<bool>,
 }
 
 /// Query parameters for retrieving blood pressure insights
@@ -41,11 +71,67 @@ pub struct HistoryQueryParams {
 pub struct InsightsQueryParams {
     /// Analysis period in days (default: 30, max: 365)
     pub timeframe: Option<u32>,
+
+    /// Guideline set to categorize with: acc_aha_2017, esc_esh_2018 or ish_2020 (default: the profile's, else the deployment's)
+    pub guideline: Option<String>,
+
+    /// The user's date of birth as YYYY-MM-DD; under 18 the AAP 2017 pediatric thresholds are used (default: the profile's)
+    pub date_of_birth: Option<String>,
+
+    /// The user's sex, male or female, required with a date of birth (default: the profile's)
+    pub sex: Option<String>,
+
+    /// The user's latest height in centimetres, for the pediatric height percentile, 50-272 (default: the profile's)
+    pub height_cm: Option<f64>,
+
+    /// Analyze each measurement session as a single averaged reading (default: false)
+    pub sessions: Option<bool>,
+}
+
+/// Query parameters for retrieving time of day patterns
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct PatternQueryParams {
+    /// Analysis period in days (default: 30, max: 365)
+    pub timeframe: Option<u32>,
+
+    /// IANA time zone for local times, e.g. Europe/Amsterdam (default: the profile's, else UTC)
+    pub time_zone: Option<String>,
+}
+
+/// Query parameters for listing measurement sessions
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct SessionQueryParams {
+    /// Maximum number of sessions to return (default: 100, max: 1000)
+    pub limit: Option<usize>,
+
+    /// Number of sessions to skip (default: 0)
+    pub offset: Option<usize>,
+}
+
+/// Query parameters for retrieving a home blood pressure monitoring report
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct HbpmQueryParams {
+    /// Local date of day 1 of the week as YYYY-MM-DD (default: the week ending today)
+    pub start_date: Option<String>,
+
+    /// IANA time zone for local times, e.g. Europe/Amsterdam (default: the profile's, else UTC)
+    pub time_zone: Option<String>,
+}
+
+/// Query parameters for retrieving paired reading alerts
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct AlertQueryParams {
+    /// Analysis period in days (default: 30, max: 365)
+    pub timeframe: Option<u32>,
 }
 
-/// Paginated response for blood pressure data
+/// Paginated response for health readings
 #[derive(Serialize, ToSchema)]
-#[aliases(BloodPressurePaginatedResponse = PaginatedResponse<BloodPressureReading>)]
+#[aliases(
+    BloodPressurePaginatedResponse = PaginatedResponse<BloodPressureReading>,
+    BloodPressureSessionPaginatedResponse = PaginatedResponse<BloodPressureSession>,
+    WeightPaginatedResponse = PaginatedResponse<PublicWeightReading>
+)]
 pub struct PaginatedResponse<T> {
     /// Total count of items available
     pub total_count: usize,
@@ -110,6 +196,24 @@ impl ErrorResponse {
         }
     }
 
+    /// Create a conflict error response
+    pub fn conflict(message: &str) -> Self {
+        Self {
+            error: "conflict".to_string(),
+            message: message.to_string(),
+            details: None,
+        }
+    }
+
+    /// Create a service unavailable error response
+    pub fn service_unavailable() -> Self {
+        Self {
+            error: "service_unavailable".to_string(),
+            message: "Storage is temporarily unavailable, please try again later".to_string(),
+            details: None,
+        }
+    }
+
     /// Create an internal error response
     pub fn internal_error() -> Self {
         Self {
@@ -126,6 +230,8 @@ impl IntoResponse for ErrorResponse {
             "not_found" => StatusCode::NOT_FOUND,
             "validation_error" => StatusCode::BAD_REQUEST,
             "bad_request" => StatusCode::BAD_REQUEST,
+            "conflict" => StatusCode::CONFLICT,
+            "service_unavailable" => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -137,8 +243,10 @@ impl IntoResponse for ErrorResponse {
 pub type BloodPressureService = Arc<dyn BloodPressureServiceTrait + Send + Sync>;
 
 /// Create a default service for the handlers to use
-pub fn create_service() -> BloodPressureService {
-    Arc::new(create_default_blood_pressure_service())
+///
+/// Readings are categorized with the guideline set in each user's profile.
+pub fn create_service(profiles: ProfileService) -> BloodPressureService {
+    Arc::new(create_default_blood_pressure_service(profiles))
 }
 
 /// Get a single blood pressure reading by ID
@@ -152,57 +260,56 @@ pub fn create_service() -> BloodPressureService {
         (status = 200, description = "Blood pressure reading found", body = BloodPressureReading),
         (status = 404, description = "Blood pressure reading not found", body = PublicErrorResponse),
         (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
     ),
     security(
         ("bearer" = [])
     ),
     tag = "blood_pressure"
 )]
-#[instrument(skip(service))]
+#[instrument(skip(service, user_info))]
 pub async fn get_blood_pressure(
     State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, Response> {
     info!("Fetching blood pressure reading with ID: {}", id);
 
-    // Call domain service
-    match service.get_reading_by_id(&id.to_string()).await {
+    // Call domain service, scoped to the authenticated user
+    match service.get_reading_by_id(&user_info.user_id, &id.to_string()).await {
         Ok(reading) => {
             // Convert domain entity to public entity
             let public_reading = convert_to_public_reading(reading);
             Ok((StatusCode::OK, Json(public_reading)))
         },
-        Err(e) => {
-            let error_message = e.to_string();
-            if error_message.contains("not found") {
-                info!("Blood pressure reading not found: {}", id);
-                Err((StatusCode::NOT_FOUND, Json(ErrorResponse::not_found("blood pressure reading"))).into_response())
-            } else {
-                error!("Error retrieving blood pressure reading: {}", error_message);
-                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::internal_error())).into_response())
-            }
-        }
+        Err(e) => Err(service_error_response(e, "retrieving")),
     }
 }
 
 /// Create a new blood pressure reading
+///
+/// A reading in the hypertensive crisis range, or the third stage 2 reading
+/// within a week, comes back with a warning; the alert is also stored and
+/// delivered through the configured notifiers.
 #[utoipa::path(
     post,
     path = "/api/v1/bloodpressure",
     request_body = CreateBloodPressureRequest,
     responses(
-        (status = 201, description = "Blood pressure reading created", body = BloodPressureReading),
+        (status = 201, description = "Blood pressure reading created", body = CreateBloodPressureResponse),
         (status = 400, description = "Invalid request", body = PublicErrorResponse),
         (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
     ),
     security(
         ("bearer" = [])
     ),
     tag = "blood_pressure"
 )]
-#[instrument(skip(service, request))]
+#[instrument(skip(service, user_info, request))]
 pub async fn create_blood_pressure(
     State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
     Json(request): Json<CreateBloodPressureRequest>,
 ) -> Result<impl IntoResponse, Response> {
     info!("Creating new blood pressure reading");
@@ -210,119 +317,265 @@ pub async fn create_blood_pressure(
     // Convert public request to domain request
     let domain_request = convert_to_domain_request(request);
 
-    // Call domain service
-    match service.create_reading(domain_request).await {
-        Ok(reading) => {
+    // Call domain service; the reading is owned by the authenticated user
+    match service.create_reading_with_alert(&user_info.user_id, domain_request).await {
+        Ok((reading, alert)) => {
             info!("Blood pressure reading created with ID: {}", reading.id);
-            // Convert domain entity to public entity for API response
-            let public_reading = convert_to_public_reading(reading);
-            Ok((StatusCode::CREATED, Json(public_reading)))
-        },
-        Err(e) => {
-            let error_message = e.to_string();
-            if error_message.contains("Validation") {
-                warn!("Invalid blood pressure reading data: {}", error_message);
-                Err((StatusCode::BAD_REQUEST, Json(ErrorResponse::validation_error(&error_message, None))).into_response())
-            } else {
-                error!("Error creating blood pressure reading: {}", error_message);
-                Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::internal_error())).into_response())
+            if let Some(alert) = &alert {
+                warn!("Blood pressure reading {} raised a {} alert", reading.id, alert.kind.as_str());
             }
-        }
+            spawn_alert_rule_evaluation(service.clone(), user_info.user_id.clone());
+            // Convert domain entities to public entities for API response
+            let response = CreateBloodPressureResponse {
+                reading: convert_to_public_reading(reading),
+                warning: alert.map(convert_to_public_alert),
+            };
+            Ok((StatusCode::CREATED, Json(response)))
+        },
+        Err(e) => Err(service_error_response(e, "creating")),
     }
 }
 
-/// Generate pagination links from the current request
-fn generate_pagination_links(
-    total_count: usize,
-    limit: usize,
-    offset: usize,
-    base_url: &str,
-    query_params: &HistoryQueryParams,
-) -> (Option<String>, Option<String>) {
-    let has_next = offset + limit < total_count;
-    let has_prev = offset > 0;
+/// Replace an existing blood pressure reading
+#[utoipa::path(
+    put,
+    path = "/api/v1/bloodpressure/{id}",
+    params(
+        ("id" = String, Path, description = "Blood pressure reading ID")
+    ),
+    request_body = CreateBloodPressureRequest,
+    responses(
+        (status = 200, description = "Blood pressure reading updated", body = BloodPressureReading),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 404, description = "Blood pressure reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn update_blood_pressure(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateBloodPressureRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Updating blood pressure reading with ID: {}", id);
 
-    // Build query string
-    let mut next_params = query_params.clone();
-    let mut prev_params = query_params.clone();
+    let domain_request = convert_to_domain_request(request);
 
-    // For next link
-    let next = if has_next {
-        next_params.offset = Some(offset + limit);
-        next_params.limit = Some(limit);
+    match service.update_reading(&user_info.user_id, &id.to_string(), domain_request).await {
+        Ok(reading) => Ok((StatusCode::OK, Json(convert_to_public_reading(reading)))),
+        Err(e) => Err(service_error_response(e, "updating")),
+    }
+}
 
-        let mut query_parts = Vec::new();
+/// Partially update an existing blood pressure reading
+#[utoipa::path(
+    patch,
+    path = "/api/v1/bloodpressure/{id}",
+    params(
+        ("id" = String, Path, description = "Blood pressure reading ID")
+    ),
+    request_body = UpdateBloodPressureRequest,
+    responses(
+        (status = 200, description = "Blood pressure reading updated", body = BloodPressureReading),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 404, description = "Blood pressure reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn patch_blood_pressure(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdateBloodPressureRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Patching blood pressure reading with ID: {}", id);
 
-        if let Some(start) = &next_params.start_date {
-            query_parts.push(format!("start_date={}", start));
-        }
+    let domain_request = convert_to_domain_update_request(request);
 
-        if let Some(end) = &next_params.end_date {
-            query_parts.push(format!("end_date={}", end));
-        }
+    match service.patch_reading(&user_info.user_id, &id.to_string(), domain_request).await {
+        Ok(reading) => Ok((StatusCode::OK, Json(convert_to_public_reading(reading)))),
+        Err(e) => Err(service_error_response(e, "patching")),
+    }
+}
 
-        if let Some(limit) = next_params.limit {
-            query_parts.push(format!("limit={}", limit));
-        }
+/// Delete a blood pressure reading
+///
+/// Readings are soft deleted and can be brought back with the restore endpoint.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/bloodpressure/{id}",
+    params(
+        ("id" = String, Path, description = "Blood pressure reading ID")
+    ),
+    responses(
+        (status = 204, description = "Blood pressure reading deleted"),
+        (status = 404, description = "Blood pressure reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn delete_blood_pressure(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Deleting blood pressure reading with ID: {}", id);
 
-        if let Some(offset) = next_params.offset {
-            query_parts.push(format!("offset={}", offset));
-        }
+    match service.delete_reading(&user_info.user_id, &id.to_string()).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(service_error_response(e, "deleting")),
+    }
+}
+
+/// Restore a deleted blood pressure reading
+#[utoipa::path(
+    post,
+    path = "/api/v1/bloodpressure/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Blood pressure reading ID")
+    ),
+    responses(
+        (status = 200, description = "Blood pressure reading restored", body = BloodPressureReading),
+        (status = 404, description = "Blood pressure reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn restore_blood_pressure(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Restoring blood pressure reading with ID: {}", id);
+
+    match service.restore_reading(&user_info.user_id, &id.to_string()).await {
+        Ok(reading) => Ok((StatusCode::OK, Json(convert_to_public_reading(reading)))),
+        Err(e) => Err(service_error_response(e, "restoring")),
+    }
+}
+
+/// Map a service error to the matching HTTP error response
+fn service_error_response(error: BloodPressureServiceError, action: &str) -> Response {
+    resource_error_response(error, "blood pressure reading", action)
+}
 
-        if let Some(sort) = &next_params.sort {
-            query_parts.push(format!("sort={}", sort));
+/// Map a service error from a session request to the matching HTTP error response
+fn session_error_response(error: BloodPressureServiceError, action: &str) -> Response {
+    resource_error_response(error, "blood pressure session", action)
+}
+
+/// Map a service error from an alert rule request to the matching HTTP error response
+fn alert_rule_error_response(error: BloodPressureServiceError, action: &str) -> Response {
+    resource_error_response(error, "blood pressure alert rule", action)
+}
+
+/// Map a service error about a resource to the matching HTTP error response
+fn resource_error_response(error: BloodPressureServiceError, resource: &str, action: &str) -> Response {
+    match error {
+        BloodPressureServiceError::NotFound(_) => {
+            info!("{} not found while {}", resource, action);
+            ErrorResponse::not_found(resource).into_response()
+        },
+        BloodPressureServiceError::ValidationError(message) => {
+            warn!("Invalid {} data: {}", resource, message);
+            ErrorResponse::validation_error(&message, None).into_response()
+        },
+        BloodPressureServiceError::StorageUnavailable(message) => {
+            warn!("Storage unavailable while {} {}: {}", action, resource, message);
+            ErrorResponse::service_unavailable().into_response()
+        },
+        e => {
+            error!("Error {} {}: {}", action, resource, e);
+            ErrorResponse::internal_error().into_response()
         }
+    }
+}
 
-        let query_string = if query_parts.is_empty() {
-            String::new()
-        } else {
-            format!("?{}", query_parts.join("&"))
-        };
+/// Build the query string for a page of history, keeping every filter
+///
+/// Every value is percent-encoded, so timestamps with a `+` offset and device
+/// IDs containing `&` or `=` survive the round trip.
+fn history_query_string(params: &HistoryQueryParams, limit: usize, offset: usize) -> String {
+    let mut query = form_urlencoded::Serializer::new(String::new());
 
-        Some(format!("{}{}", base_url, query_string))
-    } else {
-        None
-    };
+    if let Some(start) = &params.start_date {
+        query.append_pair("start_date", start);
+    }
 
-    // For previous link
-    let previous = if has_prev {
-        let new_offset = offset.saturating_sub(limit);
+    if let Some(end) = &params.end_date {
+        query.append_pair("end_date", end);
+    }
 
-        prev_params.offset = Some(new_offset);
-        prev_params.limit = Some(limit);
+    query.append_pair("limit", &limit.to_string());
+    query.append_pair("offset", &offset.to_string());
 
-        let mut query_parts = Vec::new();
+    if let Some(sort) = &params.sort {
+        query.append_pair("sort", sort);
+    }
 
-        if let Some(start) = &prev_params.start_date {
-            query_parts.push(format!("start_date={}", start));
-        }
+    if let Some(category) = &params.category {
+        query.append_pair("category", category.as_str());
+    }
 
-        if let Some(end) = &prev_params.end_date {
-            query_parts.push(format!("end_date={}", end));
-        }
+    if let Some(position) = &params.position {
+        query.append_pair("position", position.as_str());
+    }
 
-        if let Some(limit) = prev_params.limit {
-            query_parts.push(format!("limit={}", limit));
-        }
+    if let Some(arm) = &params.arm {
+        query.append_pair("arm", arm.as_str());
+    }
 
-        if let Some(offset) = prev_params.offset {
-            query_parts.push(format!("offset={}", offset));
-        }
+    if let Some(device_id) = &params.device_id {
+        query.append_pair("device_id", device_id);
+    }
 
-        if let Some(sort) = &prev_params.sort {
-            query_parts.push(format!("sort={}", sort));
-        }
+    if let Some(sessions) = params.sessions {
+        query.append_pair("sessions", &sessions.to_string());
+    }
 
-        let query_string = if query_parts.is_empty() {
-            String::new()
-        } else {
-            format!("?{}", query_parts.join("&"))
-        };
+    format!("?{}", query.finish())
+}
 
-        Some(format!("{}{}", base_url, query_string))
-    } else {
-        None
-    };
+/// Generate pagination links from the current request
+pub(crate) fn generate_pagination_links(
+    total_count: usize,
+    limit: usize,
+    offset: usize,
+    base_url: &str,
+    query_params: &HistoryQueryParams,
+) -> (Option<String>, Option<String>) {
+    let has_next = offset + limit < total_count;
+    let has_prev = offset > 0;
+
+    let next = has_next.then(|| {
+        format!("{}{}", base_url, history_query_string(query_params, limit, offset + limit))
+    });
+
+    let previous = has_prev.then(|| {
+        format!("{}{}", base_url, history_query_string(query_params, limit, offset.saturating_sub(limit)))
+    });
 
     (next, previous)
 }
@@ -337,15 +590,17 @@ fn generate_pagination_links(
     responses(
         (status = 200, description = "Blood pressure history retrieved", body = BloodPressurePaginatedResponse),
         (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
     ),
     security(
         ("bearer" = [])
     ),
     tag = "blood_pressure"
 )]
-#[instrument(skip(service))]
+#[instrument(skip(service, user_info))]
 pub async fn get_blood_pressure_history(
     State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
     Query(params): Query<HistoryQueryParams>,
 ) -> Result<impl IntoResponse, Response> {
     // Process query parameters
@@ -386,12 +641,22 @@ pub async fn get_blood_pressure_history(
         now
     };
 
-    // Convert dates to strings for filtering
-    let start_date_str = Some(start_date.to_rfc3339());
-    let end_date_str = Some(end_date.to_rfc3339());
+    let filter = BloodPressureFilter {
+        start_date: Some(start_date),
+        end_date: Some(end_date),
+        category: params.category,
+        position: params.position,
+        arm: params.arm,
+        device_id: params.device_id.clone(),
+    };
 
-    // Call domain service
-    match service.get_filtered_readings(start_date_str, end_date_str, Some(limit), Some(offset), Some(sort_desc)).await {
+    // Call domain service, scoped to the authenticated user
+    let readings = if params.sessions.unwrap_or(false) {
+        service.get_session_averaged_readings(&user_info.user_id, filter, Some(limit), Some(offset), Some(sort_desc)).await
+    } else {
+        service.get_filtered_readings(&user_info.user_id, filter, Some(limit), Some(offset), Some(sort_desc)).await
+    };
+    match readings {
         Ok((domain_readings, total_count)) => {
             // Base URL for pagination links
             let base_url = "/api/v1/bloodpressure";
@@ -422,11 +687,7 @@ pub async fn get_blood_pressure_history(
 
             Ok((StatusCode::OK, Json(response)))
         },
-        Err(e) => {
-            error!("Failed to get blood pressure history: {}", e);
-            let error = ErrorResponse::internal_error();
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(error)).into_response())
-        }
+        Err(e) => Err(service_error_response(e, "listing")),
     }
 }
 
@@ -434,108 +695,727 @@ pub async fn get_blood_pressure_history(
 #[utoipa::path(
     get,
     path = "/api/v1/bloodpressure/insights",
+    params(
+        InsightsQueryParams
+    ),
     responses(
-        (status = 200, description = "Blood pressure insights generated", body = BloodPressureReading),
+        (status = 200, description = "Blood pressure insights generated", body = my_health_guide_domain::entities::blood_pressure::BloodPressureInsights),
+        (status = 400, description = "Unknown guideline set or invalid patient details", body = PublicErrorResponse),
+        (status = 404, description = "No readings in the timeframe", body = PublicErrorResponse),
         (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
     ),
     security(
         ("bearer" = [])
     ),
     tag = "blood_pressure"
 )]
-#[instrument(skip(service))]
+#[instrument(skip(service, profiles, user_info))]
 pub async fn get_blood_pressure_insights(
     State(service): State<BloodPressureService>,
+    Extension(profiles): Extension<ProfileService>,
+    Extension(user_info): Extension<UserInfo>,
     Query(params): Query<InsightsQueryParams>,
 ) -> Result<impl IntoResponse, Response> {
     // Process query parameters
     let timeframe = params.timeframe.unwrap_or(30).min(365); // Default to 30 days, max 1 year
+    let guideline = profiles.guideline_set(&user_info.user_id, params.guideline.as_deref())
+        .await
+        .map_err(|e| profile_error_response(e, "reading"))?;
+    let profile = profiles.find_profile(&user_info.user_id)
+        .await
+        .map_err(|e| profile_error_response(e, "reading"))?;
+    let patient = parse_pediatric_patient(&params, profile.as_ref())
+        .map_err(|error| (StatusCode::BAD_REQUEST, Json(error)).into_response())?;
 
     info!("Generating blood pressure insights for {} days", timeframe);
 
     // Get all readings for the specified timeframe
     let now = Utc::now();
     let start_date = now - chrono::Duration::days(timeframe as i64);
-    let start_date_str = Some(start_date.to_rfc3339());
-    let end_date_str = Some(now.to_rfc3339());
 
-    // Get readings within timeframe
-    match service.get_filtered_readings(start_date_str, end_date_str, None, None, None).await {
+    // Get the authenticated user's readings within timeframe
+    let filter = BloodPressureFilter::date_range(Some(start_date), Some(now));
+    let readings = if params.sessions.unwrap_or(false) {
+        service.get_session_averaged_readings(&user_info.user_id, filter, None, None, None).await
+    } else {
+        service.get_filtered_readings(&user_info.user_id, filter, None, None, None).await
+    };
+    match readings {
         Ok((domain_readings, _)) => {
             // Calculate insights
-            match service.calculate_insights(&domain_readings, timeframe) {
+            let insights = match &patient {
+                Some(patient) => service.calculate_patient_insights(&domain_readings, timeframe, &guideline, patient),
+                None => service.calculate_insights(&domain_readings, timeframe, &guideline),
+            };
+            match insights {
                 Ok(insights) => {
                     info!("Blood pressure insights generated successfully");
                     Ok((StatusCode::OK, Json(insights)).into_response())
                 },
+                Err(BloodPressureServiceError::InsufficientData(_)) => {
+                    info!("Insufficient data for insights");
+                    Ok((
+                        StatusCode::NOT_FOUND,
+                        Json(ErrorResponse {
+                            error: "insufficient_data".to_string(),
+                            message: "Not enough data to generate insights".to_string(),
+                            details: None,
+                        }),
+                    ).into_response())
+                },
                 Err(e) => {
-                    let error_message = e.to_string();
-                    if error_message.contains("insufficient") {
-                        info!("Insufficient data for insights");
-                        Ok((
-                            StatusCode::NOT_FOUND,
-                            Json(ErrorResponse {
-                                error: "insufficient_data".to_string(),
-                                message: "Not enough data to generate insights".to_string(),
-                                details: None,
-                            }),
-                        ).into_response())
-                    } else {
-                        error!("Error generating blood pressure insights: {}", e);
-                        Ok((
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ErrorResponse {
-                                error: "internal_server_error".to_string(),
-                                message: "Failed to generate blood pressure insights".to_string(),
-                                details: None,
-                            }),
-                        ).into_response())
-                    }
+                    error!("Error generating blood pressure insights: {}", e);
+                    Ok((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ErrorResponse {
+                            error: "internal_server_error".to_string(),
+                            message: "Failed to generate blood pressure insights".to_string(),
+                            details: None,
+                        }),
+                    ).into_response())
                 }
             }
         },
-        Err(e) => {
-            error!("Failed to retrieve blood pressure readings: {}", e);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse::internal_error())).into_response())
-        }
+        Err(e) => Err(service_error_response(e, "retrieving")),
     }
 }
 
-// Convert public request to domain request
-fn convert_to_domain_request(request: CreateBloodPressureRequest) -> my_health_guide_domain::entities::blood_pressure::CreateBloodPressureRequest {
-    let timestamp = request.timestamp
-        .map_or_else(|| Utc::now().to_rfc3339(), |dt| dt.to_rfc3339());
+/// Get blood pressure broken down by time of day
+///
+/// Readings are placed in the morning, evening, day and night by their local
+/// time in the profile's time zone, or the `time_zone` given. Each reading is
+/// converted on its own, so daylight saving changes are followed.
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/patterns",
+    params(
+        PatternQueryParams
+    ),
+    responses(
+        (status = 200, description = "Time of day patterns generated", body = my_health_guide_domain::entities::blood_pressure::TimeOfDayPattern),
+        (status = 400, description = "Unknown time zone", body = PublicErrorResponse),
+        (status = 404, description = "No readings in the timeframe", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, profiles, user_info))]
+pub async fn get_blood_pressure_patterns(
+    State(service): State<BloodPressureService>,
+    Extension(profiles): Extension<ProfileService>,
+    Extension(user_info): Extension<UserInfo>,
+    Query(params): Query<PatternQueryParams>,
+) -> Result<impl IntoResponse, Response> {
+    let timeframe = params.timeframe.unwrap_or(30).min(365);
+    let time_zone = profiles.time_zone(&user_info.user_id, params.time_zone.as_deref())
+        .await
+        .map_err(|e| profile_error_response(e, "reading"))?;
 
-    my_health_guide_domain::entities::blood_pressure::CreateBloodPressureRequest {
-        systolic: request.systolic as u16,
-        diastolic: request.diastolic as u16,
-        pulse: request.pulse.map(|p| p as u16),
-        notes: request.notes,
-        timestamp,
-        position: None,
-        arm: None,
-        device_id: None,
+    info!("Generating blood pressure time of day patterns for {} days", timeframe);
+
+    let now = Utc::now();
+    let start_date = now - chrono::Duration::days(timeframe as i64);
+    let filter = BloodPressureFilter::date_range(Some(start_date), Some(now));
+
+    let (domain_readings, _) = service.get_filtered_readings(&user_info.user_id, filter, None, None, None)
+        .await
+        .map_err(|e| service_error_response(e, "retrieving"))?;
+
+    match service.calculate_time_of_day_pattern(&domain_readings, time_zone, timeframe) {
+        Ok(pattern) => Ok((StatusCode::OK, Json(pattern))),
+        Err(BloodPressureServiceError::InsufficientData(_)) => {
+            info!("Insufficient data for time of day patterns");
+            Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "insufficient_data".to_string(),
+                    message: "Not enough data to analyze time of day patterns".to_string(),
+                    details: None,
+                }),
+            ).into_response())
+        },
+        Err(e) => Err(service_error_response(e, "analysing")),
     }
 }
 
-// Convert domain reading to public reading
-fn convert_to_public_reading(reading: DomainBloodPressureReading) -> crate::entities::blood_pressure::BloodPressureReading {
-    let timestamp = match chrono::DateTime::parse_from_rfc3339(&reading.timestamp) {
-        Ok(dt) => dt.with_timezone(&chrono::Utc),
-        Err(_) => chrono::Utc::now(), // Fallback to current time if parsing fails
-    };
+/// Get alerts from paired blood pressure readings
+///
+/// Flags orthostatic hypotension from standing readings taken within five
+/// minutes of lying or sitting, and inter-arm differences from left and
+/// right arm readings taken within five minutes of each other.
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/insights/alerts",
+    params(
+        AlertQueryParams
+    ),
+    responses(
+        (status = 200, description = "Paired reading alerts generated", body = my_health_guide_domain::entities::blood_pressure::PairedReadingAlerts),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_blood_pressure_alerts(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Query(params): Query<AlertQueryParams>,
+) -> Result<impl IntoResponse, Response> {
+    let timeframe = params.timeframe.unwrap_or(30).min(365);
 
-    crate::entities::blood_pressure::BloodPressureReading {
-        id: uuid::Uuid::parse_str(&reading.id).unwrap_or_else(|_| uuid::Uuid::new_v4()),
-        systolic: reading.systolic as i32,
-        diastolic: reading.diastolic as i32,
-        pulse: reading.pulse.map(|p| p as i32),
-        notes: reading.notes,
-        recorded_at: timestamp,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-    }
-}
+    info!("Detecting paired blood pressure reading alerts for {} days", timeframe);
+
+    let now = Utc::now();
+    let start_date = now - chrono::Duration::days(timeframe as i64);
+    let filter = BloodPressureFilter::date_range(Some(start_date), Some(now));
+
+    let (domain_readings, _) = service.get_filtered_readings(&user_info.user_id, filter, None, None, None)
+        .await
+        .map_err(|e| service_error_response(e, "retrieving"))?;
+
+    let alerts = service.detect_paired_reading_alerts(&domain_readings, timeframe);
+    Ok((StatusCode::OK, Json(alerts)))
+}
+
+/// Work out the patient details the pediatric classifier needs
+///
+/// Each detail given in the insights query parameters wins over the one in
+/// the user's profile. Returns `None` without a date of birth. The sex is
+/// required with it, as the reference values differ for boys and girls.
+fn parse_pediatric_patient(params: &InsightsQueryParams, profile: Option<&Profile>)
+    -> Result<Option<PediatricPatient>, ErrorResponse>
+{
+    let date_of_birth = match params.date_of_birth.as_deref() {
+        Some(date_of_birth) => Some(NaiveDate::parse_from_str(date_of_birth, "%Y-%m-%d")
+            .map_err(|_| ErrorResponse::bad_request("Invalid date_of_birth format. Use YYYY-MM-DD"))?),
+        None => profile.and_then(|profile| profile.date_of_birth),
+    };
+    let Some(date_of_birth) = date_of_birth else {
+        return Ok(None);
+    };
+    let sex = match params.sex.as_deref() {
+        Some(sex) => Some(Sex::parse(sex).ok_or_else(|| ErrorResponse::bad_request("Invalid sex. Use male or female"))?),
+        None => profile.and_then(|profile| profile.sex),
+    };
+    let sex = sex.ok_or_else(|| ErrorResponse::bad_request("sex is required with date_of_birth"))?;
+    if params.height_cm.is_some_and(|height_cm| !HEIGHT_RANGE_CM.contains(&height_cm)) {
+        return Err(ErrorResponse::bad_request("height_cm must be between 50 and 272"));
+    }
+
+    Ok(Some(PediatricPatient {
+        date_of_birth,
+        sex,
+        height_cm: params.height_cm.or(profile.and_then(|profile| profile.height_cm)),
+    }))
+}
+
+/// Get the standard 7-day home blood pressure monitoring (HBPM) report
+///
+/// The protocol asks for two readings each morning and evening for 7 days.
+/// Day 1 is discarded and the rest are averaged; slots still missing
+/// readings are listed, so the report also works while the week is running.
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/hbpm",
+    params(
+        HbpmQueryParams
+    ),
+    responses(
+        (status = 200, description = "Home monitoring report generated", body = my_health_guide_domain::entities::blood_pressure::HbpmReport),
+        (status = 400, description = "Invalid start date or unknown time zone", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, profiles, user_info))]
+pub async fn get_blood_pressure_hbpm_report(
+    State(service): State<BloodPressureService>,
+    Extension(profiles): Extension<ProfileService>,
+    Extension(user_info): Extension<UserInfo>,
+    Query(params): Query<HbpmQueryParams>,
+) -> Result<impl IntoResponse, Response> {
+    let time_zone = profiles.time_zone(&user_info.user_id, params.time_zone.as_deref())
+        .await
+        .map_err(|e| profile_error_response(e, "reading"))?;
+    let start_date = match params.start_date.as_deref() {
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            let error = ErrorResponse::bad_request("Invalid start_date format. Use YYYY-MM-DD");
+            (StatusCode::BAD_REQUEST, Json(error)).into_response()
+        })?,
+        None => Utc::now().with_timezone(&time_zone).date_naive() - chrono::Days::new(6),
+    };
+
+    info!("Generating home blood pressure monitoring report from {}", start_date);
+
+    match service.get_hbpm_report(&user_info.user_id, start_date, time_zone).await {
+        Ok(report) => Ok((StatusCode::OK, Json(report))),
+        Err(e) => Err(service_error_response(e, "retrieving")),
+    }
+}
+
+/// Record a measurement session together with its readings
+///
+/// Guidelines recommend taking two or three readings a minute or two apart
+/// and averaging them. Each reading is stored as an ordinary reading as well.
+#[utoipa::path(
+    post,
+    path = "/api/v1/bloodpressure/sessions",
+    request_body = CreateBloodPressureSessionRequest,
+    responses(
+        (status = 201, description = "Measurement session created", body = BloodPressureSession),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn create_blood_pressure_session(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Json(request): Json<CreateBloodPressureSessionRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Creating new blood pressure session with {} readings", request.readings.len());
+
+    let domain_request = my_health_guide_domain::entities::blood_pressure::CreateBloodPressureSessionRequest {
+        readings: request.readings.into_iter().map(convert_to_domain_request).collect(),
+        discard_first: request.discard_first,
+        notes: request.notes,
+    };
+
+    match service.create_session(&user_info.user_id, domain_request).await {
+        Ok(session) => {
+            info!("Blood pressure session created with ID: {}", session.id);
+            spawn_alert_rule_evaluation(service.clone(), user_info.user_id.clone());
+            Ok((StatusCode::CREATED, Json(convert_to_public_session(session))))
+        },
+        Err(e) => Err(session_error_response(e, "creating")),
+    }
+}
+
+/// List the authenticated user's measurement sessions, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/sessions",
+    params(
+        SessionQueryParams
+    ),
+    responses(
+        (status = 200, description = "Measurement sessions retrieved", body = BloodPressureSessionPaginatedResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_blood_pressure_sessions(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Query(params): Query<SessionQueryParams>,
+) -> Result<impl IntoResponse, Response> {
+    let limit = params.limit.unwrap_or(100).min(1000);
+    let offset = params.offset.unwrap_or(0);
+
+    match service.get_sessions(&user_info.user_id, Some(limit), Some(offset)).await {
+        Ok((sessions, total_count)) => {
+            // Only the page goes into the links
+            let link_params = HistoryQueryParams { limit: Some(limit), offset: Some(offset), ..Default::default() };
+            let (next, previous) = generate_pagination_links(
+                total_count,
+                limit,
+                offset,
+                "/api/v1/bloodpressure/sessions",
+                &link_params,
+            );
+
+            let response = PaginatedResponse {
+                total_count,
+                offset,
+                limit,
+                next,
+                previous,
+                data: sessions.into_iter().map(convert_to_public_session).collect(),
+            };
+            Ok((StatusCode::OK, Json(response)))
+        },
+        Err(e) => Err(session_error_response(e, "listing")),
+    }
+}
+
+/// Get a single measurement session by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/sessions/{id}",
+    params(
+        ("id" = String, Path, description = "Measurement session ID")
+    ),
+    responses(
+        (status = 200, description = "Measurement session found", body = BloodPressureSession),
+        (status = 404, description = "Measurement session not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_blood_pressure_session(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Fetching blood pressure session with ID: {}", id);
+
+    match service.get_session_by_id(&user_info.user_id, &id.to_string()).await {
+        Ok(session) => Ok((StatusCode::OK, Json(convert_to_public_session(session)))),
+        Err(e) => Err(session_error_response(e, "retrieving")),
+    }
+}
+
+/// Delete a measurement session, keeping its readings
+#[utoipa::path(
+    delete,
+    path = "/api/v1/bloodpressure/sessions/{id}",
+    params(
+        ("id" = String, Path, description = "Measurement session ID")
+    ),
+    responses(
+        (status = 204, description = "Measurement session deleted"),
+        (status = 404, description = "Measurement session not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn delete_blood_pressure_session(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Deleting blood pressure session with ID: {}", id);
+
+    match service.delete_session(&user_info.user_id, &id.to_string()).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(session_error_response(e, "deleting")),
+    }
+}
+
+/// Get the alerts raised by the user's readings
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/alerts",
+    responses(
+        (status = 200, description = "Alerts retrieved, newest first", body = [BloodPressureAlert]),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_blood_pressure_alert_history(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+) -> Result<impl IntoResponse, Response> {
+    match service.get_alerts(&user_info.user_id).await {
+        Ok(alerts) => {
+            let public_alerts: Vec<BloodPressureAlert> = alerts.into_iter()
+                .map(convert_to_public_alert)
+                .collect();
+            Ok((StatusCode::OK, Json(public_alerts)))
+        },
+        Err(e) => Err(resource_error_response(e, "blood pressure alert", "listing")),
+    }
+}
+
+/// Create an alert rule
+///
+/// Rules are evaluated whenever the user records a reading and every hour,
+/// and the alerts they fire are listed with the user's other alerts.
+#[utoipa::path(
+    post,
+    path = "/api/v1/bloodpressure/alert-rules",
+    request_body = CreateAlertRuleRequest,
+    responses(
+        (status = 201, description = "Alert rule created", body = AlertRule),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn create_blood_pressure_alert_rule(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Json(request): Json<CreateAlertRuleRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Creating new alert rule: {}", request.name);
+
+    match service.create_alert_rule(&user_info.user_id, convert_to_domain_alert_rule_request(request)).await {
+        Ok(rule) => {
+            info!("Alert rule created with ID: {}", rule.id);
+            Ok((StatusCode::CREATED, Json(convert_to_public_alert_rule(rule))))
+        },
+        Err(e) => Err(alert_rule_error_response(e, "creating")),
+    }
+}
+
+/// List the authenticated user's alert rules, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/alert-rules",
+    responses(
+        (status = 200, description = "Alert rules retrieved", body = [AlertRule]),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_blood_pressure_alert_rules(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+) -> Result<impl IntoResponse, Response> {
+    match service.get_alert_rules(&user_info.user_id).await {
+        Ok(rules) => {
+            let public_rules: Vec<AlertRule> = rules.into_iter()
+                .map(convert_to_public_alert_rule)
+                .collect();
+            Ok((StatusCode::OK, Json(public_rules)))
+        },
+        Err(e) => Err(alert_rule_error_response(e, "listing")),
+    }
+}
+
+/// Get a single alert rule by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/bloodpressure/alert-rules/{id}",
+    params(
+        ("id" = String, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 200, description = "Alert rule found", body = AlertRule),
+        (status = 404, description = "Alert rule not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_blood_pressure_alert_rule(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Fetching alert rule with ID: {}", id);
+
+    match service.get_alert_rule_by_id(&user_info.user_id, &id.to_string()).await {
+        Ok(rule) => Ok((StatusCode::OK, Json(convert_to_public_alert_rule(rule)))),
+        Err(e) => Err(alert_rule_error_response(e, "retrieving")),
+    }
+}
+
+/// Replace an alert rule
+#[utoipa::path(
+    put,
+    path = "/api/v1/bloodpressure/alert-rules/{id}",
+    params(
+        ("id" = String, Path, description = "Alert rule ID")
+    ),
+    request_body = CreateAlertRuleRequest,
+    responses(
+        (status = 200, description = "Alert rule updated", body = AlertRule),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 404, description = "Alert rule not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn update_blood_pressure_alert_rule(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<CreateAlertRuleRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Updating alert rule with ID: {}", id);
+
+    match service.update_alert_rule(&user_info.user_id, &id.to_string(), convert_to_domain_alert_rule_request(request)).await {
+        Ok(rule) => Ok((StatusCode::OK, Json(convert_to_public_alert_rule(rule)))),
+        Err(e) => Err(alert_rule_error_response(e, "updating")),
+    }
+}
+
+/// Delete an alert rule, keeping the alerts it fired
+#[utoipa::path(
+    delete,
+    path = "/api/v1/bloodpressure/alert-rules/{id}",
+    params(
+        ("id" = String, Path, description = "Alert rule ID")
+    ),
+    responses(
+        (status = 204, description = "Alert rule deleted"),
+        (status = 404, description = "Alert rule not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "blood_pressure"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn delete_blood_pressure_alert_rule(
+    State(service): State<BloodPressureService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Deleting alert rule with ID: {}", id);
+
+    match service.delete_alert_rule(&user_info.user_id, &id.to_string()).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(alert_rule_error_response(e, "deleting")),
+    }
+}
+
+// Convert public request to domain request
+fn convert_to_domain_request(request: CreateBloodPressureRequest) -> my_health_guide_domain::entities::blood_pressure::CreateBloodPressureRequest {
+    let timestamp = request.timestamp
+        .map_or_else(|| Utc::now().to_rfc3339(), |dt| dt.to_rfc3339());
+
+    my_health_guide_domain::entities::blood_pressure::CreateBloodPressureRequest {
+        systolic: request.systolic as u16,
+        diastolic: request.diastolic as u16,
+        pulse: request.pulse.map(|p| p as u16),
+        notes: request.notes,
+        timestamp,
+        position: request.position,
+        arm: request.arm,
+        device_id: request.device_id,
+    }
+}
+
+// Convert public partial update to domain partial update
+fn convert_to_domain_update_request(request: UpdateBloodPressureRequest) -> my_health_guide_domain::entities::blood_pressure::UpdateBloodPressureRequest {
+    my_health_guide_domain::entities::blood_pressure::UpdateBloodPressureRequest {
+        systolic: request.systolic.map(|s| s as u16),
+        diastolic: request.diastolic.map(|d| d as u16),
+        pulse: request.pulse.map(|p| p as u16),
+        notes: request.notes,
+        timestamp: request.timestamp.map(|dt| dt.to_rfc3339()),
+        position: request.position,
+        arm: request.arm,
+        device_id: request.device_id,
+    }
+}
+
+// Convert domain reading to public reading
+fn convert_to_public_reading(reading: DomainBloodPressureReading) -> crate::entities::blood_pressure::BloodPressureReading {
+    crate::entities::blood_pressure::BloodPressureReading {
+        id: uuid::Uuid::parse_str(&reading.id).unwrap_or_else(|_| uuid::Uuid::new_v4()),
+        systolic: reading.systolic as i32,
+        diastolic: reading.diastolic as i32,
+        pulse: reading.pulse.map(|p| p as i32),
+        notes: reading.notes,
+        position: reading.position,
+        arm: reading.arm,
+        device_id: reading.device_id,
+        recorded_at: reading.timestamp,
+        created_at: reading.created_at,
+        updated_at: reading.updated_at,
+    }
+}
+
+// Convert domain session to public session
+fn convert_to_public_session(session: DomainBloodPressureSession) -> BloodPressureSession {
+    BloodPressureSession {
+        id: uuid::Uuid::parse_str(&session.id).unwrap_or_else(|_| uuid::Uuid::new_v4()),
+        discard_first: session.discard_first,
+        notes: session.notes,
+        readings: session.readings.into_iter().map(convert_to_public_reading).collect(),
+        average: session.average,
+        created_at: session.created_at,
+    }
+}
+
+// Convert domain alert to public alert
+fn convert_to_public_alert(alert: DomainBloodPressureAlert) -> BloodPressureAlert {
+    BloodPressureAlert {
+        id: uuid::Uuid::parse_str(&alert.id).unwrap_or_else(|_| uuid::Uuid::new_v4()),
+        kind: alert.kind,
+        rule_id: alert.rule_id.and_then(|rule_id| uuid::Uuid::parse_str(&rule_id).ok()),
+        message: alert.message,
+        systolic: alert.systolic as i32,
+        diastolic: alert.diastolic as i32,
+        reading_ids: alert.reading_ids.iter()
+            .filter_map(|reading_id| uuid::Uuid::parse_str(reading_id).ok())
+            .collect(),
+        created_at: alert.created_at,
+    }
+}
+
+// Convert public alert rule request to domain request
+fn convert_to_domain_alert_rule_request(request: CreateAlertRuleRequest) -> my_health_guide_domain::entities::blood_pressure::CreateAlertRuleRequest {
+    my_health_guide_domain::entities::blood_pressure::CreateAlertRuleRequest {
+        name: request.name,
+        condition: request.condition,
+        enabled: request.enabled,
+    }
+}
+
+// Convert domain alert rule to public alert rule
+fn convert_to_public_alert_rule(rule: DomainAlertRule) -> AlertRule {
+    AlertRule {
+        id: uuid::Uuid::parse_str(&rule.id).unwrap_or_else(|_| uuid::Uuid::new_v4()),
+        name: rule.name,
+        condition: rule.condition,
+        enabled: rule.enabled,
+        created_at: rule.created_at,
+        updated_at: rule.updated_at,
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -549,6 +1429,8 @@ mod tests {
             limit: Some(10),
             offset: Some(20),
             sort: Some("desc".to_string()),
+            category: Some(BloodPressureCategory::Hypertension1),
+            ..Default::default()
         };
 
         // Test with more results available
@@ -562,6 +1444,8 @@ mod tests {
 
         assert!(next_url.contains("offset=30"));
         assert!(prev_url.contains("offset=10"));
+        assert!(next_url.contains("category=Hypertension1"));
+        assert!(prev_url.contains("category=Hypertension1"));
 
         // Test boundary conditions
 
@@ -575,4 +1459,143 @@ mod tests {
         assert!(next.is_none()); // No next page
         assert!(prev.is_some());
     }
+
+    #[test]
+    fn test_pagination_links_encode_filter_values() {
+        let query_params = HistoryQueryParams {
+            start_date: Some("2023-01-01T00:00:00+02:00".to_string()),
+            device_id: Some("cuff a&b=c".to_string()),
+            ..Default::default()
+        };
+
+        let (next, _) = generate_pagination_links(50, 10, 0, "/api/v1/bloodpressure", &query_params);
+        let next_url = next.unwrap();
+
+        assert!(next_url.contains("start_date=2023-01-01T00%3A00%3A00%2B02%3A00"));
+        assert!(next_url.contains("device_id=cuff+a%26b%3Dc"));
+        assert!(next_url.contains("&limit=10&offset=10"));
+    }
+
+    #[tokio::test]
+    async fn test_insights_without_readings_are_not_found() {
+        use my_health_guide_domain::services::profile::ProfileService as DomainProfileService;
+        use my_health_guide_domain::testing::MockBloodPressureService;
+        use my_health_guide_data::repository::tests::MockProfileRepository;
+
+        let service: BloodPressureService = Arc::new(MockBloodPressureService::new());
+        let profiles: ProfileService = Arc::new(DomainProfileService::new(MockProfileRepository::new()));
+        let user_info = UserInfo {
+            user_id: "insights-without-readings".to_string(),
+            roles: vec!["user".to_string()],
+            email: None,
+            name: None,
+            picture: None,
+            auth_source: "jwt".to_string(),
+        };
+        let params = InsightsQueryParams {
+            timeframe: None,
+            guideline: None,
+            date_of_birth: None,
+            sex: None,
+            height_cm: None,
+            sessions: None,
+        };
+
+        let response = get_blood_pressure_insights(State(service), Extension(profiles), Extension(user_info), Query(params))
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_parse_pediatric_patient() {
+        let params = |date_of_birth: Option<&str>, sex: Option<&str>, height_cm: Option<f64>| InsightsQueryParams {
+            timeframe: None,
+            guideline: None,
+            date_of_birth: date_of_birth.map(str::to_string),
+            sex: sex.map(str::to_string),
+            height_cm,
+            sessions: None,
+        };
+
+        assert!(parse_pediatric_patient(&params(None, Some("male"), None), None).unwrap().is_none());
+
+        let patient = parse_pediatric_patient(&params(Some("2015-03-01"), Some("Female"), Some(130.0)), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(patient.date_of_birth, NaiveDate::from_ymd_opt(2015, 3, 1).unwrap());
+        assert_eq!(patient.sex, Sex::Female);
+        assert_eq!(patient.height_cm, Some(130.0));
+
+        assert!(parse_pediatric_patient(&params(Some("01/03/2015"), Some("female"), None), None).is_err());
+        assert!(parse_pediatric_patient(&params(Some("2015-03-01"), None, None), None).is_err());
+        assert!(parse_pediatric_patient(&params(Some("2015-03-01"), Some("other"), None), None).is_err());
+        assert!(parse_pediatric_patient(&params(Some("2015-03-01"), Some("male"), Some(20.0)), None).is_err());
+
+        // The profile fills in what the query leaves out
+        let profile = Profile {
+            user_id: "user-1".to_string(),
+            time_zone: None,
+            guideline: None,
+            date_of_birth: NaiveDate::from_ymd_opt(2016, 9, 12),
+            sex: Some(Sex::Male),
+            height_cm: Some(124.0),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let patient = parse_pediatric_patient(&params(None, None, None), Some(&profile)).unwrap().unwrap();
+        assert_eq!(patient.date_of_birth, NaiveDate::from_ymd_opt(2016, 9, 12).unwrap());
+        assert_eq!((patient.sex, patient.height_cm), (Sex::Male, Some(124.0)));
+
+        let patient = parse_pediatric_patient(&params(Some("2015-03-01"), None, Some(130.0)), Some(&profile))
+            .unwrap()
+            .unwrap();
+        assert_eq!(patient.date_of_birth, NaiveDate::from_ymd_opt(2015, 3, 1).unwrap());
+        assert_eq!((patient.sex, patient.height_cm), (Sex::Male, Some(130.0)));
+    }
+
+    #[test]
+    fn test_measurement_details_round_trip() {
+        let request: CreateBloodPressureRequest = serde_json::from_value(serde_json::json!({
+            "systolic": 118,
+            "diastolic": 76,
+            "position": "standing",
+            "arm": "right",
+            "device_id": "omron-1"
+        })).unwrap();
+
+        let domain_request = convert_to_domain_request(request);
+        assert_eq!(domain_request.position, Some(MeasurementPosition::Standing));
+        assert_eq!(domain_request.arm, Some(MeasurementArm::Right));
+
+        let reading = convert_to_public_reading(DomainBloodPressureReading {
+            id: Uuid::new_v4().to_string(),
+            user_id: "user-1".to_string(),
+            systolic: domain_request.systolic,
+            diastolic: domain_request.diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: domain_request.timestamp.parse().unwrap(),
+            position: domain_request.position,
+            arm: domain_request.arm,
+            device_id: domain_request.device_id,
+            created_at: "2024-01-01T08:00:05Z".parse().unwrap(),
+            updated_at: "2024-01-02T09:30:00Z".parse().unwrap(),
+        });
+        let json = serde_json::to_value(&reading).unwrap();
+        assert_eq!(json["position"], "standing");
+        assert_eq!(json["arm"], "right");
+        assert_eq!(json["device_id"], "omron-1");
+        assert_eq!(json["created_at"], "2024-01-01T08:00:05Z");
+        assert_eq!(json["updated_at"], "2024-01-02T09:30:00Z");
+
+        // Anything but the documented values is rejected
+        let invalid = serde_json::from_value::<CreateBloodPressureRequest>(serde_json::json!({
+            "systolic": 118,
+            "diastolic": 76,
+            "arm": "wrist"
+        }));
+        assert!(invalid.is_err());
+    }
 }
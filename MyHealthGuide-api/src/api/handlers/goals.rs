@@ -0,0 +1,228 @@
+use std::sync::Arc;
+use axum::{
+    extract::Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use tracing::{error, info, instrument, warn};
+
+// Import domain entities and services
+use my_health_guide_domain::auth::UserInfo;
+use my_health_guide_domain::services::{GoalServiceTrait, create_default_goal_service};
+use my_health_guide_domain::services::goal::GoalServiceError;
+use my_health_guide_domain::entities::goal::{
+    Goals as DomainGoals, GoalsWithProgress as DomainGoalsWithProgress, SetGoalsRequest as DomainSetGoalsRequest,
+};
+
+// Import our entities
+use crate::entities::goal::{PublicGoals, PublicGoalsResponse, PublicSetGoalsRequest};
+use super::blood_pressure::{BloodPressureService, ErrorResponse};
+use super::weight::WeightService;
+
+/// Service type for dependency injection
+pub type GoalService = Arc<dyn GoalServiceTrait + Send + Sync>;
+
+/// Create a default service for the handlers to use
+///
+/// Progress is computed from the readings of the given services.
+pub fn create_service(blood_pressure: BloodPressureService, weight: WeightService) -> GoalService {
+    Arc::new(create_default_goal_service(blood_pressure, weight))
+}
+
+/// Get the user's goals with their progress
+///
+/// Progress is computed from the readings: the share of the last 30 days'
+/// blood pressure readings below the targets with streaks, the weekly
+/// reading count with streaks, and the latest weight. Projected dates follow
+/// the trend and are left out when it heads away from the goal.
+#[utoipa::path(
+    get,
+    path = "/api/v1/goals",
+    responses(
+        (status = 200, description = "Goals with their progress", body = PublicGoalsResponse),
+        (status = 404, description = "No goals set", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "goals"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_goals(
+    Extension(service): Extension<GoalService>,
+    Extension(user_info): Extension<UserInfo>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Fetching goals with progress");
+
+    match service.get_progress(&user_info.user_id).await {
+        Ok(goals) => Ok((StatusCode::OK, Json(convert_to_public_response(goals)))),
+        Err(e) => Err(service_error_response(e, "fetching")),
+    }
+}
+
+/// Set the user's goals, replacing any set before
+#[utoipa::path(
+    put,
+    path = "/api/v1/goals",
+    request_body = PublicSetGoalsRequest,
+    responses(
+        (status = 200, description = "Goals set", body = PublicGoals),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "goals"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn set_goals(
+    Extension(service): Extension<GoalService>,
+    Extension(user_info): Extension<UserInfo>,
+    Json(request): Json<PublicSetGoalsRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Setting goals");
+
+    match service.set_goals(&user_info.user_id, convert_to_domain_request(request)).await {
+        Ok(goals) => Ok((StatusCode::OK, Json(convert_to_public_goals(goals)))),
+        Err(e) => Err(service_error_response(e, "setting")),
+    }
+}
+
+/// Clear the user's goals
+#[utoipa::path(
+    delete,
+    path = "/api/v1/goals",
+    responses(
+        (status = 204, description = "Goals cleared"),
+        (status = 404, description = "No goals set", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "goals"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn delete_goals(
+    Extension(service): Extension<GoalService>,
+    Extension(user_info): Extension<UserInfo>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Clearing goals");
+
+    match service.delete_goals(&user_info.user_id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(service_error_response(e, "clearing")),
+    }
+}
+
+/// Map a service error to the matching HTTP error response
+fn service_error_response(error: GoalServiceError, action: &str) -> Response {
+    match error {
+        GoalServiceError::NotFound(_) => {
+            info!("Goals not found while {}", action);
+            ErrorResponse::not_found("goals").into_response()
+        },
+        GoalServiceError::ValidationError(message) => {
+            warn!("Invalid goals data: {}", message);
+            ErrorResponse::validation_error(&message, None).into_response()
+        },
+        GoalServiceError::StorageUnavailable(message) => {
+            warn!("Storage unavailable while {} goals: {}", action, message);
+            ErrorResponse::service_unavailable().into_response()
+        },
+        e => {
+            error!("Error {} goals: {}", action, e);
+            ErrorResponse::internal_error().into_response()
+        }
+    }
+}
+
+// Convert public request to domain request
+fn convert_to_domain_request(request: PublicSetGoalsRequest) -> DomainSetGoalsRequest {
+    DomainSetGoalsRequest {
+        target_systolic: request.target_systolic,
+        target_diastolic: request.target_diastolic,
+        readings_per_week: request.readings_per_week,
+        target_weight_kg: request.target_weight_kg,
+    }
+}
+
+// Convert domain goals to public goals
+fn convert_to_public_goals(goals: DomainGoals) -> PublicGoals {
+    PublicGoals {
+        target_systolic: goals.target_systolic,
+        target_diastolic: goals.target_diastolic,
+        readings_per_week: goals.readings_per_week,
+        target_weight_kg: goals.target_weight_kg,
+        created_at: goals.created_at,
+        updated_at: goals.updated_at,
+    }
+}
+
+// Convert domain goals with progress to the public response
+fn convert_to_public_response(goals: DomainGoalsWithProgress) -> PublicGoalsResponse {
+    PublicGoalsResponse {
+        goals: convert_to_public_goals(goals.goals),
+        progress: goals.progress,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use my_health_guide_domain::entities::goal::{GoalProgress, ReadingFrequencyProgress};
+
+    #[test]
+    fn test_public_response_leaves_out_unset_goals() {
+        let goals = DomainGoalsWithProgress {
+            goals: DomainGoals {
+                user_id: "user-1".to_string(),
+                target_systolic: None,
+                target_diastolic: None,
+                readings_per_week: Some(7),
+                target_weight_kg: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            progress: GoalProgress {
+                blood_pressure: None,
+                reading_frequency: Some(ReadingFrequencyProgress {
+                    target_per_week: 7,
+                    readings_last_7_days: 5,
+                    met: false,
+                    current_streak_weeks: 0,
+                    best_streak_weeks: 3,
+                }),
+                weight: None,
+                generated_at: Utc::now(),
+            },
+        };
+
+        let json = serde_json::to_value(convert_to_public_response(goals)).unwrap();
+        assert_eq!(json["goals"]["readings_per_week"], 7);
+        assert!(json["goals"].get("target_systolic").is_none());
+        assert!(json["goals"].get("user_id").is_none());
+        assert_eq!(json["progress"]["reading_frequency"]["best_streak_weeks"], 3);
+    }
+
+    #[test]
+    fn test_set_goals_request_format() {
+        let request: PublicSetGoalsRequest = serde_json::from_value(serde_json::json!({
+            "target_systolic": 130,
+            "target_diastolic": 80,
+            "target_weight_kg": 72.5
+        })).unwrap();
+
+        let domain = convert_to_domain_request(request);
+        assert_eq!((domain.target_systolic, domain.target_diastolic), (Some(130), Some(80)));
+        assert_eq!(domain.readings_per_week, None);
+        assert_eq!(domain.target_weight_kg, Some(72.5));
+    }
+}
@@ -247,6 +247,7 @@ pub fn create_health_service() -> Arc<dyn HealthServiceTrait + Send + Sync> {
 mod tests {
     use super::*;
     use axum::http::StatusCode;
+    use my_health_guide_domain::testing::create_mock_health_service;
 
     #[tokio::test]
     async fn test_health_check_response() {
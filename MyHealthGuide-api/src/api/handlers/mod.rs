@@ -1,5 +1,9 @@
 pub mod health;
 pub mod blood_pressure;
+pub mod weight;
+pub mod goals;
+pub mod profile;
+pub mod auth;
 
 // Tests module
 #[cfg(test)]
@@ -8,5 +12,18 @@ mod tests;
 // Re-export handlers for easier imports
 pub use blood_pressure::{
     create_blood_pressure, get_blood_pressure, get_blood_pressure_history, get_blood_pressure_insights,
+    get_blood_pressure_patterns, get_blood_pressure_alerts, get_blood_pressure_hbpm_report,
+    create_blood_pressure_session, get_blood_pressure_sessions, get_blood_pressure_session,
+    delete_blood_pressure_session, get_blood_pressure_alert_history,
+    create_blood_pressure_alert_rule, get_blood_pressure_alert_rules, get_blood_pressure_alert_rule,
+    update_blood_pressure_alert_rule, delete_blood_pressure_alert_rule,
+    update_blood_pressure, patch_blood_pressure, delete_blood_pressure, restore_blood_pressure,
 };
+pub use weight::{
+    create_weight, get_weight, get_weight_history, get_weight_insights,
+    update_weight, delete_weight, restore_weight,
+};
+pub use goals::{get_goals, set_goals, delete_goals};
+pub use profile::{get_profile, set_profile};
+pub use auth::register;
 pub use health::health_check; 
\ No newline at end of file
@@ -0,0 +1,159 @@
+use std::sync::Arc;
+use axum::{
+    extract::Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use tracing::{error, info, instrument, warn};
+
+// Import domain entities and services
+use my_health_guide_domain::auth::UserInfo;
+use my_health_guide_domain::services::{ProfileServiceTrait, create_default_profile_service};
+use my_health_guide_domain::services::profile::ProfileServiceError;
+use my_health_guide_domain::entities::profile::{Profile as DomainProfile, SetProfileRequest as DomainSetProfileRequest};
+
+// Import our entities
+use crate::entities::profile::{PublicProfile, PublicSetProfileRequest};
+use super::blood_pressure::ErrorResponse;
+
+/// Service type for dependency injection
+pub type ProfileService = Arc<dyn ProfileServiceTrait + Send + Sync>;
+
+/// Create a default service for the handlers to use
+pub fn create_service() -> ProfileService {
+    Arc::new(create_default_profile_service())
+}
+
+/// Get the user's profile
+#[utoipa::path(
+    get,
+    path = "/api/v1/profile",
+    responses(
+        (status = 200, description = "The profile", body = PublicProfile),
+        (status = 404, description = "No profile set", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "profile"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_profile(
+    Extension(service): Extension<ProfileService>,
+    Extension(user_info): Extension<UserInfo>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Fetching profile");
+
+    match service.get_profile(&user_info.user_id).await {
+        Ok(profile) => Ok((StatusCode::OK, Json(convert_to_public_profile(profile)))),
+        Err(e) => Err(service_error_response(e, "fetching")),
+    }
+}
+
+/// Set the user's profile, replacing any set before
+///
+/// The time zone is used for local times, such as the time of day patterns
+/// and the home monitoring report, unless a request gives its own.
+#[utoipa::path(
+    put,
+    path = "/api/v1/profile",
+    request_body = PublicSetProfileRequest,
+    responses(
+        (status = 200, description = "Profile set", body = PublicProfile),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "profile"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn set_profile(
+    Extension(service): Extension<ProfileService>,
+    Extension(user_info): Extension<UserInfo>,
+    Json(request): Json<PublicSetProfileRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Setting profile");
+
+    match service.set_profile(&user_info.user_id, convert_to_domain_request(request)).await {
+        Ok(profile) => Ok((StatusCode::OK, Json(convert_to_public_profile(profile)))),
+        Err(e) => Err(service_error_response(e, "setting")),
+    }
+}
+
+/// Map a service error to the matching HTTP error response
+pub(crate) fn service_error_response(error: ProfileServiceError, action: &str) -> Response {
+    match error {
+        ProfileServiceError::NotFound(_) => {
+            info!("Profile not found while {}", action);
+            ErrorResponse::not_found("profile").into_response()
+        },
+        ProfileServiceError::ValidationError(message) => {
+            warn!("Invalid profile data: {}", message);
+            ErrorResponse::validation_error(&message, None).into_response()
+        },
+        ProfileServiceError::StorageUnavailable(message) => {
+            warn!("Storage unavailable while {} profile: {}", action, message);
+            ErrorResponse::service_unavailable().into_response()
+        },
+        e => {
+            error!("Error {} profile: {}", action, e);
+            ErrorResponse::internal_error().into_response()
+        }
+    }
+}
+
+// Convert public request to domain request
+fn convert_to_domain_request(request: PublicSetProfileRequest) -> DomainSetProfileRequest {
+    DomainSetProfileRequest {
+        time_zone: request.time_zone,
+        guideline: request.guideline,
+        date_of_birth: request.date_of_birth,
+        sex: request.sex,
+        height_cm: request.height_cm,
+    }
+}
+
+// Convert domain profile to public profile
+fn convert_to_public_profile(profile: DomainProfile) -> PublicProfile {
+    PublicProfile {
+        time_zone: profile.time_zone,
+        guideline: profile.guideline,
+        date_of_birth: profile.date_of_birth,
+        sex: profile.sex,
+        height_cm: profile.height_cm,
+        created_at: profile.created_at,
+        updated_at: profile.updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_public_profile_leaves_out_unset_fields() {
+        let profile = DomainProfile {
+            user_id: "user-1".to_string(),
+            time_zone: None,
+            guideline: None,
+            date_of_birth: None,
+            sex: None,
+            height_cm: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let json = serde_json::to_value(convert_to_public_profile(profile)).unwrap();
+        assert!(json.get("time_zone").is_none());
+        assert!(json.get("guideline").is_none());
+        assert!(json.get("user_id").is_none());
+        assert!(json.get("created_at").is_some());
+    }
+}
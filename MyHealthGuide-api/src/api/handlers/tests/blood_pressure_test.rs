@@ -1,14 +1,19 @@
 #[cfg(test)]
 mod blood_pressure_tests {
-    use MyHealthGuide_domain::entities::blood_pressure::{BloodPressureReading, CreateBloodPressureRequest};
-    use MyHealthGuide_domain::services::BloodPressureServiceTrait;
-    use MyHealthGuide_domain::testing::MockBloodPressureService;
+    use my_health_guide_domain::entities::blood_pressure::{
+        BloodPressureAlertKind, BloodPressureCategory, BloodPressureFilter, BloodPressureReading, CreateBloodPressureRequest, MeasurementArm,
+        CreateBloodPressureSessionRequest, MeasurementPosition, UpdateBloodPressureRequest,
+    };
+    use my_health_guide_domain::services::BloodPressureServiceTrait;
+    use my_health_guide_domain::testing::MockBloodPressureService;
     use std::sync::Arc;
     
     use chrono::Utc;
 
-    #[test]
-    fn test_mock_service_creation() {
+    const TEST_USER: &str = "test-user";
+
+    #[tokio::test]
+    async fn test_mock_service_creation() {
         // Verify we can create a mock service
         let mock_service = Arc::new(MockBloodPressureService::new());
         
@@ -16,8 +21,8 @@ mod blood_pressure_tests {
         let _: Arc<dyn BloodPressureServiceTrait + Send + Sync> = mock_service;
     }
     
-    #[test]
-    fn test_create_reading_with_mock() {
+    #[tokio::test]
+    async fn test_create_reading_with_mock() {
         // Create a mock service
         let mock_service = Arc::new(MockBloodPressureService::new());
         
@@ -34,7 +39,7 @@ mod blood_pressure_tests {
         };
         
         // Use the mock service to create a reading
-        let result = mock_service.create_reading(request);
+        let result = mock_service.create_reading(TEST_USER, request).await;
         
         // Verify the result
         assert!(result.is_ok());
@@ -44,8 +49,8 @@ mod blood_pressure_tests {
         assert_eq!(reading.pulse, Some(72));
     }
     
-    #[test]
-    fn test_mock_with_preconfigured_behavior() {
+    #[tokio::test]
+    async fn test_mock_with_preconfigured_behavior() {
         // Create a mock service with validation failure
         let mock_service = Arc::new(
             MockBloodPressureService::new()
@@ -65,27 +70,30 @@ mod blood_pressure_tests {
         };
         
         // Use the mock service to create a reading, which should fail validation
-        let result = mock_service.create_reading(request);
+        let result = mock_service.create_reading(TEST_USER, request).await;
         
         // Verify the result
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("validation"));
     }
     
-    #[test]
-    fn test_mock_with_preloaded_data() {
+    #[tokio::test]
+    async fn test_mock_with_preloaded_data() {
         // Create a test reading
         let test_id = "12345678-1234-1234-1234-123456789012".to_string();
         let preloaded_reading = BloodPressureReading {
             id: test_id.clone(),
+            user_id: TEST_USER.to_string(),
             systolic: 135,
             diastolic: 85,
             pulse: Some(75),
             notes: Some("Test reading".to_string()),
-            timestamp: Utc::now().to_rfc3339(),
-            position: Some("Sitting".to_string()),
-            arm: Some("Left".to_string()),
+            timestamp: Utc::now(),
+            position: Some(MeasurementPosition::Sitting),
+            arm: Some(MeasurementArm::Left),
             device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         };
         
         // Create a mock service with preloaded data
@@ -95,7 +103,7 @@ mod blood_pressure_tests {
         );
         
         // Retrieve the reading by ID
-        let result = mock_service.get_reading_by_id(&test_id);
+        let result = mock_service.get_reading_by_id(TEST_USER, &test_id).await;
         
         // Verify the result
         assert!(result.is_ok());
@@ -105,61 +113,70 @@ mod blood_pressure_tests {
         assert_eq!(reading.diastolic, 85);
         assert_eq!(reading.pulse, Some(75));
         assert_eq!(reading.notes, Some("Test reading".to_string()));
-        assert_eq!(reading.position, Some("Sitting".to_string()));
-        assert_eq!(reading.arm, Some("Left".to_string()));
+        assert_eq!(reading.position, Some(MeasurementPosition::Sitting));
+        assert_eq!(reading.arm, Some(MeasurementArm::Left));
         
         // Verify we can get all readings
-        let all_readings = mock_service.get_all_readings().unwrap();
+        let all_readings = mock_service.get_all_readings(TEST_USER).await.unwrap();
         assert_eq!(all_readings.len(), 1);
         
         // Verify filtered readings work too
-        let (filtered, count) = mock_service.get_filtered_readings(None, None, Some(10), Some(0), Some(true)).unwrap();
+        let (filtered, count) = mock_service.get_filtered_readings(TEST_USER, BloodPressureFilter::default(), Some(10), Some(0), Some(true)).await.unwrap();
         assert_eq!(count, 1);
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].id, test_id);
     }
     
-    #[test]
-    fn test_mock_with_multiple_readings() {
+    #[tokio::test]
+    async fn test_mock_with_multiple_readings() {
         // Create readings for testing
-        let now = Utc::now().to_rfc3339();
-        let yesterday = (Utc::now() - chrono::Duration::days(1)).to_rfc3339();
-        let two_days_ago = (Utc::now() - chrono::Duration::days(2)).to_rfc3339();
+        let now = Utc::now();
+        let yesterday = now - chrono::Duration::days(1);
+        let two_days_ago = now - chrono::Duration::days(2);
         
         let reading1 = BloodPressureReading {
             id: "reading1".to_string(),
+            user_id: TEST_USER.to_string(),
             systolic: 120,
             diastolic: 80,
             pulse: Some(72),
             notes: None,
-            timestamp: now.clone(),
+            timestamp: now,
             position: None,
             arm: None,
             device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         };
         
         let reading2 = BloodPressureReading {
             id: "reading2".to_string(),
+            user_id: TEST_USER.to_string(),
             systolic: 130,
             diastolic: 85,
             pulse: Some(75),
             notes: Some("After exercise".to_string()),
-            timestamp: yesterday.clone(),
-            position: Some("Sitting".to_string()),
-            arm: Some("Left".to_string()),
+            timestamp: yesterday,
+            position: Some(MeasurementPosition::Sitting),
+            arm: Some(MeasurementArm::Left),
             device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         };
         
         let reading3 = BloodPressureReading {
             id: "reading3".to_string(),
+            user_id: TEST_USER.to_string(),
             systolic: 115,
             diastolic: 75,
             pulse: Some(68),
             notes: Some("Morning reading".to_string()),
-            timestamp: two_days_ago.clone(),
-            position: Some("Sitting".to_string()),
-            arm: Some("Right".to_string()),
+            timestamp: two_days_ago,
+            position: Some(MeasurementPosition::Sitting),
+            arm: Some(MeasurementArm::Right),
             device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         };
         
         // Create a mock service with the pre-loaded readings
@@ -169,40 +186,50 @@ mod blood_pressure_tests {
         );
         
         // Test get_all_readings
-        let all_readings = mock_service.get_all_readings().unwrap();
+        let all_readings = mock_service.get_all_readings(TEST_USER).await.unwrap();
         assert_eq!(all_readings.len(), 3);
         
         // Test get_reading_by_id
-        let reading = mock_service.get_reading_by_id("reading2").unwrap();
+        let reading = mock_service.get_reading_by_id(TEST_USER, "reading2").await.unwrap();
         assert_eq!(reading.systolic, 130);
         assert_eq!(reading.diastolic, 85);
         
         // Test get_filtered_readings with limit
         let (limited_readings, total) = mock_service.get_filtered_readings(
-            None, None, Some(2), None, Some(true)
-        ).unwrap();
+            TEST_USER, BloodPressureFilter::default(), Some(2), None, Some(true)
+        ).await.unwrap();
         
         assert_eq!(total, 3);  // Total should be 3
         assert_eq!(limited_readings.len(), 2);  // But only 2 returned due to limit
         
         // Test get_filtered_readings with date range
-        let start_date = two_days_ago.clone();
-        let end_date = yesterday.clone();
-        
         let (ranged_readings, _) = mock_service.get_filtered_readings(
-            Some(start_date), Some(end_date), None, None, None
-        ).unwrap();
+            TEST_USER, BloodPressureFilter::date_range(Some(two_days_ago), Some(yesterday)), None, None, None
+        ).await.unwrap();
         
         // Should only include reading2 and reading3, not reading1 (which is today)
         assert_eq!(ranged_readings.len(), 2);
         assert!(ranged_readings.iter().any(|r| r.id == "reading2"));
         assert!(ranged_readings.iter().any(|r| r.id == "reading3"));
         assert!(!ranged_readings.iter().any(|r| r.id == "reading1"));
+
+        // Test get_filtered_readings with category and arm filters
+        let filter = BloodPressureFilter {
+            category: Some(BloodPressureCategory::Normal),
+            arm: Some(MeasurementArm::Right),
+            ..Default::default()
+        };
+        let (filtered_readings, filtered_total) = mock_service.get_filtered_readings(
+            TEST_USER, filter, None, None, None
+        ).await.unwrap();
+
+        assert_eq!(filtered_total, 1);
+        assert_eq!(filtered_readings[0].id, "reading3");
         
         // Test sorting (ascending by default)
         let (sorted_asc, _) = mock_service.get_filtered_readings(
-            None, None, None, None, Some(false)
-        ).unwrap();
+            TEST_USER, BloodPressureFilter::default(), None, None, Some(false)
+        ).await.unwrap();
         
         assert_eq!(sorted_asc.len(), 3);
         assert_eq!(sorted_asc[0].id, "reading3");  // Oldest first
@@ -210,11 +237,272 @@ mod blood_pressure_tests {
         
         // Test sorting (descending)
         let (sorted_desc, _) = mock_service.get_filtered_readings(
-            None, None, None, None, Some(true)
-        ).unwrap();
+            TEST_USER, BloodPressureFilter::default(), None, None, Some(true)
+        ).await.unwrap();
         
         assert_eq!(sorted_desc.len(), 3);
         assert_eq!(sorted_desc[0].id, "reading1");  // Newest first
         assert_eq!(sorted_desc[2].id, "reading3");  // Oldest last
     }
-} 
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_mock_hides_other_users_readings() {
+        let reading = BloodPressureReading {
+            id: "owned-reading".to_string(),
+            user_id: "another-user".to_string(),
+            systolic: 120,
+            diastolic: 80,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now(),
+            position: None,
+            arm: None,
+            device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        
+        let mock_service = Arc::new(MockBloodPressureService::new().with_reading(reading));
+        
+        // The reading exists, but not for this user
+        assert!(mock_service.get_reading_by_id(TEST_USER, "owned-reading").await.is_err());
+        assert!(mock_service.get_all_readings(TEST_USER).await.unwrap().is_empty());
+        
+        let (filtered, count) = mock_service.get_filtered_readings(TEST_USER, BloodPressureFilter::default(), None, None, None).await.unwrap();
+        assert_eq!(count, 0);
+        assert!(filtered.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_mock_update_delete_and_restore() {
+        let reading = BloodPressureReading {
+            id: "editable-reading".to_string(),
+            user_id: TEST_USER.to_string(),
+            systolic: 210,
+            diastolic: 80,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now(),
+            position: None,
+            arm: None,
+            device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let mock_service = Arc::new(MockBloodPressureService::new().with_reading(reading));
+        
+        // Fix a mistyped systolic value with a partial update
+        let patch = UpdateBloodPressureRequest {
+            systolic: Some(120),
+            ..Default::default()
+        };
+        let patched = mock_service.patch_reading(TEST_USER, "editable-reading", patch).await.unwrap();
+        assert_eq!(patched.systolic, 120);
+        assert_eq!(patched.diastolic, 80);
+        
+        // Soft delete hides the reading
+        mock_service.delete_reading(TEST_USER, "editable-reading").await.unwrap();
+        assert!(mock_service.get_reading_by_id(TEST_USER, "editable-reading").await.is_err());
+        assert!(mock_service.delete_reading(TEST_USER, "editable-reading").await.is_err());
+        
+        // Other users cannot restore it
+        assert!(mock_service.restore_reading("another-user", "editable-reading").await.is_err());
+        
+        // Restore brings it back unchanged
+        let restored = mock_service.restore_reading(TEST_USER, "editable-reading").await.unwrap();
+        assert_eq!(restored.systolic, 120);
+        assert!(mock_service.get_reading_by_id(TEST_USER, "editable-reading").await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_mock_sessions_average_their_readings() {
+        let mock_service = Arc::new(MockBloodPressureService::new());
+        let reading = |minute: i64, systolic: u16, diastolic: u16| CreateBloodPressureRequest {
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: (Utc::now() - chrono::Duration::minutes(10 - minute)).to_rfc3339(),
+            position: Some(MeasurementPosition::Sitting),
+            arm: None,
+            device_id: None,
+        };
+        let request = CreateBloodPressureSessionRequest {
+            readings: vec![reading(0, 146, 92), reading(1, 132, 86), reading(2, 128, 82)],
+            discard_first: true,
+            notes: Some("Morning".to_string()),
+        };
+        
+        let session = mock_service.create_session(TEST_USER, request).await.unwrap();
+        let average = session.average.unwrap();
+        assert_eq!((average.systolic, average.diastolic, average.reading_count), (130.0, 84.0, 2));
+        assert_eq!(mock_service.get_sessions(TEST_USER, None, None).await.unwrap().1, 1);
+        assert!(mock_service.get_session_by_id("another-user", &session.id).await.is_err());
+        
+        // The session stands in for its three readings
+        let (readings, count) = mock_service.get_session_averaged_readings(
+            TEST_USER, BloodPressureFilter::default(), None, None, None,
+        ).await.unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(readings[0].id, session.id);
+        assert_eq!(readings[0].systolic, 130);
+        assert_eq!(readings[0].position, Some(MeasurementPosition::Sitting));
+        
+        // Deleting the session keeps the readings
+        mock_service.delete_session(TEST_USER, &session.id).await.unwrap();
+        assert!(mock_service.get_session_by_id(TEST_USER, &session.id).await.is_err());
+        assert_eq!(mock_service.get_all_readings(TEST_USER).await.unwrap().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_mock_raises_alerts_for_crisis_and_repeated_stage_2() {
+        let mock_service = Arc::new(MockBloodPressureService::new());
+        let reading = |hours_ago: i64, systolic: u16, diastolic: u16| CreateBloodPressureRequest {
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: (Utc::now() - chrono::Duration::hours(hours_ago)).to_rfc3339(),
+            position: None,
+            arm: None,
+            device_id: None,
+        };
+        
+        let (_, alert) = mock_service.create_reading_with_alert(TEST_USER, reading(0, 120, 80)).await.unwrap();
+        assert!(alert.is_none());
+        
+        let (crisis, alert) = mock_service.create_reading_with_alert(TEST_USER, reading(2, 195, 125)).await.unwrap();
+        let alert = alert.unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::HypertensiveCrisis);
+        assert_eq!(alert.reading_ids, vec![crisis.id.clone()]);
+        
+        // The crisis reading counts towards repeated stage 2 as well
+        let (_, alert) = mock_service.create_reading_with_alert(TEST_USER, reading(48, 150, 95)).await.unwrap();
+        assert!(alert.is_none());
+        let (latest, alert) = mock_service.create_reading_with_alert(TEST_USER, reading(0, 148, 92)).await.unwrap();
+        let alert = alert.unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::RepeatedStage2);
+        assert_eq!(alert.reading_ids.len(), 3);
+        assert_eq!(alert.reading_ids[1], crisis.id);
+        assert_eq!(alert.reading_ids.last(), Some(&latest.id));
+        
+        // Alerts are kept per user, newest first
+        let alerts = mock_service.get_alerts(TEST_USER).await.unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].kind, BloodPressureAlertKind::RepeatedStage2);
+        assert!(mock_service.get_alerts("another-user").await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_mock_alert_rules_crud_and_firing() {
+        use my_health_guide_domain::entities::blood_pressure::{AlertRuleCondition, CreateAlertRuleRequest};
+        use my_health_guide_domain::services::blood_pressure::BloodPressureServiceError;
+        
+        let mock_service = Arc::new(MockBloodPressureService::new());
+        let reading = |systolic: u16| CreateBloodPressureRequest {
+            systolic,
+            diastolic: 75,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+            position: None,
+            arm: None,
+            device_id: None,
+        };
+        let rule_request = |count: usize, enabled: bool| CreateAlertRuleRequest {
+            name: "High readings in a row".to_string(),
+            condition: AlertRuleCondition::ConsecutiveAbove { count, systolic: Some(125), diastolic: None },
+            enabled,
+        };
+        
+        let rule = mock_service.create_alert_rule(TEST_USER, rule_request(3, false)).await.unwrap();
+        assert_eq!(mock_service.get_alert_rules(TEST_USER).await.unwrap().len(), 1);
+        assert!(matches!(
+            mock_service.get_alert_rule_by_id("another-user", &rule.id).await,
+            Err(BloodPressureServiceError::NotFound(_))
+        ));
+        
+        // Disabled rules do not fire, and elevated readings raise no built-in alert
+        mock_service.create_reading(TEST_USER, reading(126)).await.unwrap();
+        mock_service.create_reading(TEST_USER, reading(127)).await.unwrap();
+        assert_eq!(mock_service.evaluate_all_alert_rules().await.unwrap(), 0);
+        assert!(mock_service.get_alerts(TEST_USER).await.unwrap().is_empty());
+        
+        let updated = mock_service.update_alert_rule(TEST_USER, &rule.id, rule_request(2, true)).await.unwrap();
+        assert_eq!(updated.created_at, rule.created_at);
+        assert!(updated.enabled);
+        
+        // The next evaluation fires the rule once, and evaluating again does not repeat it
+        mock_service.create_reading(TEST_USER, reading(128)).await.unwrap();
+        assert!(mock_service.get_alerts(TEST_USER).await.unwrap().is_empty());
+        assert_eq!(mock_service.evaluate_all_alert_rules().await.unwrap(), 1);
+        let alerts = mock_service.get_alerts(TEST_USER).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, BloodPressureAlertKind::Rule);
+        assert_eq!(alerts[0].rule_id.as_ref(), Some(&rule.id));
+        assert_eq!(alerts[0].reading_ids.len(), 2);
+        assert_eq!(mock_service.evaluate_all_alert_rules().await.unwrap(), 0);
+        
+        mock_service.delete_alert_rule(TEST_USER, &rule.id).await.unwrap();
+        assert!(mock_service.get_alert_rules(TEST_USER).await.unwrap().is_empty());
+        assert_eq!(mock_service.get_alerts(TEST_USER).await.unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_alert_rule_request_format() {
+        use crate::entities::blood_pressure::CreateAlertRuleRequest;
+        use my_health_guide_domain::entities::blood_pressure::AlertRuleCondition;
+        
+        let request: CreateAlertRuleRequest = serde_json::from_value(serde_json::json!({
+            "name": "Weekly average above target",
+            "condition": { "type": "average_above", "days": 7, "systolic": 135 }
+        })).unwrap();
+        assert!(request.enabled);
+        assert_eq!(request.condition, AlertRuleCondition::AverageAbove { days: 7, systolic: Some(135), diastolic: None });
+        
+        let request: CreateAlertRuleRequest = serde_json::from_value(serde_json::json!({
+            "name": "No reading in 3 days",
+            "condition": { "type": "no_reading", "days": 3 },
+            "enabled": false
+        })).unwrap();
+        assert!(!request.enabled);
+        assert_eq!(request.condition, AlertRuleCondition::NoReading { days: 3 });
+    }
+    
+    #[test]
+    fn test_create_response_includes_warning_only_when_raised() {
+        use crate::entities::blood_pressure::{BloodPressureAlert, BloodPressureReading, CreateBloodPressureResponse};
+        
+        let now = Utc::now();
+        let reading = BloodPressureReading {
+            id: uuid::Uuid::new_v4(),
+            systolic: 190,
+            diastolic: 125,
+            pulse: None,
+            notes: None,
+            position: None,
+            arm: None,
+            device_id: None,
+            recorded_at: now,
+            created_at: now,
+            updated_at: now,
+        };
+        let quiet = serde_json::to_value(CreateBloodPressureResponse { reading: reading.clone(), warning: None }).unwrap();
+        assert_eq!(quiet["systolic"], 190);
+        assert!(quiet.get("warning").is_none());
+        
+        let warning = BloodPressureAlert {
+            id: uuid::Uuid::new_v4(),
+            kind: BloodPressureAlertKind::HypertensiveCrisis,
+            rule_id: None,
+            message: "Reading of 190/125 mmHg is in the hypertensive crisis range.".to_string(),
+            systolic: 190,
+            diastolic: 125,
+            reading_ids: vec![reading.id],
+            created_at: now,
+        };
+        let warned = serde_json::to_value(CreateBloodPressureResponse { reading, warning: Some(warning) }).unwrap();
+        assert_eq!(warned["id"], warned["warning"]["reading_ids"][0]);
+        assert_eq!(warned["warning"]["kind"], "hypertensive_crisis");
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod health_tests {
-    use MyHealthGuide_domain::health::{SystemStatus, ComponentStatus, HealthServiceTrait, SystemHealth, HealthComponent};
+    use my_health_guide_domain::health::{SystemStatus, ComponentStatus, HealthServiceTrait, SystemHealth, HealthComponent};
     use std::sync::Arc;
     use std::collections::HashMap;
     use async_trait::async_trait;
@@ -0,0 +1,449 @@
+use std::sync::Arc;
+use axum::{
+    extract::{Json, Query, Path},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde::Deserialize;
+use tracing::{error, info, instrument, warn};
+use chrono::Utc;
+use uuid::Uuid;
+use utoipa::{IntoParams, ToSchema};
+
+// Import domain entities and services
+use my_health_guide_domain::auth::UserInfo;
+use my_health_guide_domain::services::{WeightServiceTrait, create_default_weight_service};
+use my_health_guide_domain::services::weight::WeightServiceError;
+use my_health_guide_domain::entities::weight::{
+    CreateWeightRequest as DomainCreateWeightRequest, WeightInsights as DomainWeightInsights,
+    WeightReading as DomainWeightReading,
+};
+
+// Import our entities
+use crate::entities::weight::{PublicCreateWeightRequest, PublicWeightInsights, PublicWeightReading};
+use super::blood_pressure::{generate_pagination_links, ErrorResponse, HistoryQueryParams, PaginatedResponse};
+
+/// Query parameters for retrieving weight insights
+#[derive(Debug, Deserialize, IntoParams, ToSchema)]
+pub struct WeightInsightsQueryParams {
+    /// Height in centimetres, needed to report BMI (50-272)
+    pub height_cm: Option<f32>,
+}
+
+/// Service type for dependency injection
+pub type WeightService = Arc<dyn WeightServiceTrait + Send + Sync>;
+
+/// Create a default service for the handlers to use
+pub fn create_service() -> WeightService {
+    Arc::new(create_default_weight_service())
+}
+
+/// Get a single weight reading by ID
+#[utoipa::path(
+    get,
+    path = "/api/v1/weight/{id}",
+    params(
+        ("id" = String, Path, description = "Weight reading ID")
+    ),
+    responses(
+        (status = 200, description = "Weight reading found", body = PublicWeightReading),
+        (status = 404, description = "Weight reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_weight(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Fetching weight reading with ID: {}", id);
+
+    match service.get_reading_by_id(&user_info.user_id, &id.to_string()).await {
+        Ok(reading) => Ok((StatusCode::OK, Json(convert_to_public_reading(reading)))),
+        Err(e) => Err(service_error_response(e, "fetching")),
+    }
+}
+
+/// Create a new weight reading
+#[utoipa::path(
+    post,
+    path = "/api/v1/weight",
+    request_body = PublicCreateWeightRequest,
+    responses(
+        (status = 201, description = "Weight reading created", body = PublicWeightReading),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn create_weight(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Json(request): Json<PublicCreateWeightRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Creating new weight reading");
+
+    match service.create_reading(&user_info.user_id, convert_to_domain_request(request)).await {
+        Ok(reading) => {
+            info!("Weight reading created with ID: {}", reading.id);
+            Ok((StatusCode::CREATED, Json(convert_to_public_reading(reading))))
+        },
+        Err(e) => Err(service_error_response(e, "creating")),
+    }
+}
+
+/// Replace an existing weight reading
+#[utoipa::path(
+    put,
+    path = "/api/v1/weight/{id}",
+    params(
+        ("id" = String, Path, description = "Weight reading ID")
+    ),
+    request_body = PublicCreateWeightRequest,
+    responses(
+        (status = 200, description = "Weight reading updated", body = PublicWeightReading),
+        (status = 400, description = "Invalid request", body = PublicErrorResponse),
+        (status = 404, description = "Weight reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info, request))]
+pub async fn update_weight(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PublicCreateWeightRequest>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Updating weight reading with ID: {}", id);
+
+    match service.update_reading(&user_info.user_id, &id.to_string(), convert_to_domain_request(request)).await {
+        Ok(reading) => Ok((StatusCode::OK, Json(convert_to_public_reading(reading)))),
+        Err(e) => Err(service_error_response(e, "updating")),
+    }
+}
+
+/// Delete a weight reading
+///
+/// Readings are soft deleted and can be brought back with the restore endpoint.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/weight/{id}",
+    params(
+        ("id" = String, Path, description = "Weight reading ID")
+    ),
+    responses(
+        (status = 204, description = "Weight reading deleted"),
+        (status = 404, description = "Weight reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn delete_weight(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Deleting weight reading with ID: {}", id);
+
+    match service.delete_reading(&user_info.user_id, &id.to_string()).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(service_error_response(e, "deleting")),
+    }
+}
+
+/// Restore a deleted weight reading
+#[utoipa::path(
+    post,
+    path = "/api/v1/weight/{id}/restore",
+    params(
+        ("id" = String, Path, description = "Weight reading ID")
+    ),
+    responses(
+        (status = 200, description = "Weight reading restored", body = PublicWeightReading),
+        (status = 404, description = "Weight reading not found", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn restore_weight(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Restoring weight reading with ID: {}", id);
+
+    match service.restore_reading(&user_info.user_id, &id.to_string()).await {
+        Ok(reading) => Ok((StatusCode::OK, Json(convert_to_public_reading(reading)))),
+        Err(e) => Err(service_error_response(e, "restoring")),
+    }
+}
+
+/// Get paginated weight history
+#[utoipa::path(
+    get,
+    path = "/api/v1/weight",
+    params(
+        HistoryQueryParams
+    ),
+    responses(
+        (status = 200, description = "Weight history retrieved", body = WeightPaginatedResponse),
+        (status = 400, description = "Invalid date range or blood pressure only filter", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_weight_history(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Query(params): Query<HistoryQueryParams>,
+) -> Result<impl IntoResponse, Response> {
+    let limit = params.limit.unwrap_or(100).min(1000);
+    let offset = params.offset.unwrap_or(0);
+    let sort_desc = params.sort.as_deref() != Some("asc");
+
+    // The reading filters only exist for blood pressure
+    if params.category.is_some() || params.position.is_some() || params.arm.is_some() || params.device_id.is_some() {
+        return Err(ErrorResponse::bad_request(
+            "category, position, arm and device_id only filter blood pressure history"
+        ).into_response());
+    }
+
+    // Default to the last 30 days, like the blood pressure history
+    let now = Utc::now();
+    let start_date = match params.start_date.as_deref() {
+        Some(date_str) => parse_date(date_str, "start_date").map_err(IntoResponse::into_response)?,
+        None => now - chrono::Duration::days(30),
+    };
+    let end_date = match params.end_date.as_deref() {
+        Some(date_str) => parse_date(date_str, "end_date").map_err(IntoResponse::into_response)?,
+        None => now,
+    };
+
+    match service.get_filtered_readings(
+        &user_info.user_id,
+        Some(start_date.to_rfc3339()),
+        Some(end_date.to_rfc3339()),
+        Some(limit),
+        Some(offset),
+        Some(sort_desc),
+    ).await {
+        Ok((domain_readings, total_count)) => {
+            let (next, previous) = generate_pagination_links(
+                total_count,
+                limit,
+                offset,
+                "/api/v1/weight",
+                &params,
+            );
+
+            let response = PaginatedResponse {
+                total_count,
+                offset,
+                limit,
+                next,
+                previous,
+                data: domain_readings.into_iter().map(convert_to_public_reading).collect(),
+            };
+
+            Ok((StatusCode::OK, Json(response)))
+        },
+        Err(e) => Err(service_error_response(e, "listing")),
+    }
+}
+
+/// Get weight insights and analysis
+///
+/// Covers the last 90 days. BMI is only reported when `height_cm` is given.
+#[utoipa::path(
+    get,
+    path = "/api/v1/weight/insights",
+    params(
+        WeightInsightsQueryParams
+    ),
+    responses(
+        (status = 200, description = "Weight insights generated", body = PublicWeightInsights),
+        (status = 400, description = "Invalid height", body = PublicErrorResponse),
+        (status = 404, description = "Not enough readings to generate insights", body = PublicErrorResponse),
+        (status = 500, description = "Internal server error", body = PublicErrorResponse),
+        (status = 503, description = "Storage unavailable", body = PublicErrorResponse),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    tag = "weight"
+)]
+#[instrument(skip(service, user_info))]
+pub async fn get_weight_insights(
+    Extension(service): Extension<WeightService>,
+    Extension(user_info): Extension<UserInfo>,
+    Query(params): Query<WeightInsightsQueryParams>,
+) -> Result<impl IntoResponse, Response> {
+    info!("Generating weight insights");
+
+    match service.get_insights(&user_info.user_id, params.height_cm).await {
+        Ok(insights) => Ok((StatusCode::OK, Json(convert_to_public_insights(insights)))),
+        Err(WeightServiceError::InsufficientData(_)) => {
+            info!("Insufficient data for weight insights");
+            Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "insufficient_data".to_string(),
+                    message: "Not enough data to generate insights".to_string(),
+                    details: None,
+                }),
+            ).into_response())
+        },
+        Err(e) => Err(service_error_response(e, "analysing")),
+    }
+}
+
+/// Parse an RFC 3339 query parameter, rejecting the request if it is malformed
+fn parse_date(value: &str, name: &str) -> Result<chrono::DateTime<Utc>, ErrorResponse> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| ErrorResponse::bad_request(&format!(
+            "Invalid {} format. Use ISO 8601 (e.g. 2023-03-15T08:30:00Z)", name
+        )))
+}
+
+/// Map a service error to the matching HTTP error response
+fn service_error_response(error: WeightServiceError, action: &str) -> Response {
+    match error {
+        WeightServiceError::NotFound(_) => {
+            info!("Weight reading not found while {}", action);
+            ErrorResponse::not_found("weight reading").into_response()
+        },
+        WeightServiceError::ValidationError(message) => {
+            warn!("Invalid weight reading data: {}", message);
+            ErrorResponse::validation_error(&message, None).into_response()
+        },
+        WeightServiceError::StorageUnavailable(message) => {
+            warn!("Storage unavailable while {} weight reading: {}", action, message);
+            ErrorResponse::service_unavailable().into_response()
+        },
+        e => {
+            error!("Error {} weight reading: {}", action, e);
+            ErrorResponse::internal_error().into_response()
+        }
+    }
+}
+
+// Convert public request to domain request
+fn convert_to_domain_request(request: PublicCreateWeightRequest) -> DomainCreateWeightRequest {
+    let timestamp = request.timestamp
+        .map_or_else(|| Utc::now().to_rfc3339(), |dt| dt.to_rfc3339());
+
+    DomainCreateWeightRequest {
+        weight_kg: request.weight_kg,
+        body_fat_percentage: request.body_fat_percentage,
+        muscle_mass_kg: request.muscle_mass_kg,
+        notes: request.notes,
+        timestamp,
+    }
+}
+
+// Convert domain reading to public reading
+fn convert_to_public_reading(reading: DomainWeightReading) -> PublicWeightReading {
+    let timestamp = match chrono::DateTime::parse_from_rfc3339(&reading.timestamp) {
+        Ok(dt) => dt.with_timezone(&Utc),
+        Err(_) => Utc::now(), // Fallback to current time if parsing fails
+    };
+
+    PublicWeightReading {
+        id: Uuid::parse_str(&reading.id).unwrap_or_else(|_| Uuid::new_v4()),
+        weight_kg: reading.weight_kg,
+        body_fat_percentage: reading.body_fat_percentage,
+        muscle_mass_kg: reading.muscle_mass_kg,
+        notes: reading.notes,
+        recorded_at: timestamp,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+// Convert domain insights to public insights
+fn convert_to_public_insights(insights: DomainWeightInsights) -> PublicWeightInsights {
+    PublicWeightInsights {
+        current_weight_kg: insights.current_weight_kg,
+        change_30d_kg: insights.change_30d_kg,
+        change_90d_kg: insights.change_90d_kg,
+        trend: insights.trend.to_string(),
+        body_fat_percentage: insights.body_fat_percentage,
+        muscle_mass_kg: insights.muscle_mass_kg,
+        bmi: insights.bmi,
+        bmi_category: insights.bmi_category.map(|category| category.to_string()),
+        generated_at: insights.generated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use my_health_guide_domain::entities::weight::{BmiCategory, WeightTrend};
+
+    #[test]
+    fn test_public_insights_use_lowercase_labels() {
+        let insights = DomainWeightInsights {
+            current_weight_kg: 80.0,
+            change_30d_kg: 1.2,
+            change_90d_kg: 2.0,
+            trend: WeightTrend::Gaining,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            bmi: Some(26.1),
+            bmi_category: Some(BmiCategory::Overweight),
+            reading_count: 4,
+            generated_at: Utc::now(),
+        };
+
+        let public = convert_to_public_insights(insights);
+        assert_eq!(public.trend, "gaining");
+        assert_eq!(public.bmi_category.as_deref(), Some("overweight"));
+    }
+
+    #[test]
+    fn test_missing_timestamp_defaults_to_now() {
+        let request = PublicCreateWeightRequest {
+            weight_kg: 72.0,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: None,
+        };
+
+        let domain = convert_to_domain_request(request);
+        assert!(chrono::DateTime::parse_from_rfc3339(&domain.timestamp).is_ok());
+    }
+}
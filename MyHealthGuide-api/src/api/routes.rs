@@ -9,7 +9,7 @@ use tracing::debug;
 use std::sync::Arc;
 
 use my_health_guide_domain::auth::{auth_middleware, configure_auth, oidc::OidcClient, routes::oidc_routes, authorize};
-use crate::api::handlers::{health, blood_pressure};
+use crate::api::handlers::{health, blood_pressure, weight, goals, profile, auth};
 use crate::openapi::configure_swagger_routes;
 
 type AppState = blood_pressure::BloodPressureService;
@@ -18,8 +18,24 @@ type AppState = blood_pressure::BloodPressureService;
 pub async fn create_app() -> Router {
     debug!("Creating application router");
 
-    // Create blood pressure service using factory function
-    let blood_pressure_service = blood_pressure::create_service();
+    // Create profile service, which holds the time zone and guideline set a user's readings follow
+    let profile_service = profile::create_service();
+
+    // Create blood pressure service, which categorizes readings with the user's guideline set
+    let blood_pressure_service = blood_pressure::create_service(profile_service.clone());
+
+    // Evaluate alert rules on a schedule too, so rules about missing readings fire
+    #[cfg(not(test))]
+    my_health_guide_domain::services::alert_rules::start_alert_rule_task(blood_pressure_service.clone());
+
+    // Create weight service using factory function
+    let weight_service = weight::create_service();
+
+    // Create goal service, which reads the blood pressure and weight readings
+    let goal_service = goals::create_service(blood_pressure_service.clone(), weight_service.clone());
+
+    // Create user service using factory function
+    let user_service = auth::create_service();
 
     // Create health service using factory function
     let health_service = health::create_health_service();
@@ -37,7 +53,12 @@ pub async fn create_app() -> Router {
             let oidc_config = my_health_guide_domain::auth::oidc::OidcConfig::default();
 
             match OidcClient::new(oidc_config).await {
-                Ok(client) => Arc::new(client),
+                Ok(client) => {
+                    let client = Arc::new(client);
+                    // Periodically drop logins that were started but never completed
+                    my_health_guide_domain::auth::oidc::start_session_cleanup_task(client.clone());
+                    client
+                },
                 Err(err) => {
                     // Log error but don't crash the application
                     tracing::error!("Failed to initialize OIDC client: {:?}. OIDC auth will not be available.", err);
@@ -59,9 +80,41 @@ pub async fn create_app() -> Router {
     let api_routes = Router::new()
         // Define specific routes before parametrized routes to avoid conflicts
         .route("/bloodpressure/insights", get(blood_pressure::get_blood_pressure_insights))
+        .route("/bloodpressure/insights/alerts", get(blood_pressure::get_blood_pressure_alerts))
+        .route("/bloodpressure/patterns", get(blood_pressure::get_blood_pressure_patterns))
+        .route("/bloodpressure/hbpm", get(blood_pressure::get_blood_pressure_hbpm_report))
+        .route("/bloodpressure/alerts", get(blood_pressure::get_blood_pressure_alert_history))
+        .route("/bloodpressure/alert-rules", get(blood_pressure::get_blood_pressure_alert_rules)
+                                           .post(blood_pressure::create_blood_pressure_alert_rule))
+        .route("/bloodpressure/alert-rules/:id", get(blood_pressure::get_blood_pressure_alert_rule)
+                                               .put(blood_pressure::update_blood_pressure_alert_rule)
+                                               .delete(blood_pressure::delete_blood_pressure_alert_rule))
+        .route("/bloodpressure/sessions", get(blood_pressure::get_blood_pressure_sessions)
+                                        .post(blood_pressure::create_blood_pressure_session))
+        .route("/bloodpressure/sessions/:id", get(blood_pressure::get_blood_pressure_session)
+                                            .delete(blood_pressure::delete_blood_pressure_session))
         .route("/bloodpressure", get(blood_pressure::get_blood_pressure_history)
                                .post(blood_pressure::create_blood_pressure))
-        .route("/bloodpressure/:id", get(blood_pressure::get_blood_pressure))
+        .route("/bloodpressure/:id", get(blood_pressure::get_blood_pressure)
+                                   .put(blood_pressure::update_blood_pressure)
+                                   .patch(blood_pressure::patch_blood_pressure)
+                                   .delete(blood_pressure::delete_blood_pressure))
+        .route("/bloodpressure/:id/restore", post(blood_pressure::restore_blood_pressure))
+        .route("/weight/insights", get(weight::get_weight_insights))
+        .route("/weight", get(weight::get_weight_history)
+                        .post(weight::create_weight))
+        .route("/weight/:id", get(weight::get_weight)
+                            .put(weight::update_weight)
+                            .delete(weight::delete_weight))
+        .route("/weight/:id/restore", post(weight::restore_weight))
+        .route("/goals", get(goals::get_goals)
+                       .put(goals::set_goals)
+                       .delete(goals::delete_goals))
+        .route("/profile", get(profile::get_profile)
+                         .put(profile::set_profile))
+        .layer(Extension(weight_service))
+        .layer(Extension(goal_service))
+        .layer(Extension(profile_service))
         .layer(middleware::from_fn_with_state(
             blood_pressure_service.clone(),
             auth_middleware::<AppState>
@@ -111,9 +164,11 @@ pub async fn create_app() -> Router {
     let public_routes = Router::new()
         .route("/health", get(health::health_check))
         .route("/test", get(test_handler))
+        .route("/auth/register", post(auth::register))
         .route("/auth/login", post(my_health_guide_domain::auth::login))
         .route("/auth/refresh", post(my_health_guide_domain::auth::refresh_token))
-        .layer(Extension(health_service));
+        .layer(Extension(health_service))
+        .layer(Extension(user_service));
 
     debug!("Public routes configured");
 
@@ -121,6 +176,7 @@ pub async fn create_app() -> Router {
     let auth_routes = Router::new()
         .route("/auth/info", get(my_health_guide_domain::auth::auth_info))
         .route("/auth/logout", post(my_health_guide_domain::auth::logout))
+        .route("/auth/logout-all", post(my_health_guide_domain::auth::logout_all))
         .layer(middleware::from_fn_with_state(
             blood_pressure_service.clone(),
             auth_middleware::<AppState>
@@ -158,6 +214,15 @@ pub async fn create_app() -> Router {
     app
 }
 
+/// Add Swagger UI to the router
+pub fn add_swagger_ui(app: Router) -> Router {
+    // Get Swagger UI routes
+    let swagger = configure_swagger_routes();
+
+    // Merge Swagger UI with the app router
+    app.merge(swagger)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -167,12 +232,3 @@ pub mod tests {
         super::create_app().await
     }
 }
-
-/// Add Swagger UI to the router
-pub fn add_swagger_ui(app: Router) -> Router {
-    // Get Swagger UI routes
-    let swagger = configure_swagger_routes();
-
-    // Merge Swagger UI with the app router
-    app.merge(swagger)
-}
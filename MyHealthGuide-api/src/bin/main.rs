@@ -10,6 +10,7 @@ use tracing_subscriber::{
     EnvFilter,
 };
 use my_health_guide_api::api::create_application;
+use my_health_guide_data::repository::{outbox, storage_strategy, StorageStrategy};
 
 /// Application error type for the main function
 ///
@@ -124,12 +125,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     match my_health_guide_domain::database::initialize_database_pool() {
         Ok(_) => info!("Database pool initialized successfully"),
         Err(e) => {
-            error!("Failed to initialize database pool: {}", e);
-            // Continue running even if the database initialization fails
-            // The application will fall back to in-memory storage
+            error!("Failed to initialize database pool, data will not survive a restart: {}", e);
+            // Continue running even if the database initialization fails.
+            // Without a pool the repositories keep everything in memory
         }
     }
 
+    // Replay writes the outbox recorded while the database was failing
+    if storage_strategy() == StorageStrategy::Outbox {
+        info!("Storage outbox at {}", outbox().path().display());
+        my_health_guide_domain::services::storage::start_outbox_replay_task();
+    }
+
+    // Choose where token revocations live and start purging expired ones
+    my_health_guide_domain::auth::revocation::configure_revocation_store_from_env();
+    my_health_guide_domain::auth::token_blacklist::start_cleanup_task();
+
     // Database initialization is now handled by the domain layer factory functions
     // Let's just log what database we're using
     let db_type = std::env::var("DB_TYPE")
@@ -143,6 +154,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "postgres" => {
             info!("Using PostgreSQL database (connection details managed by domain layer)");
         }
+        #[cfg(feature = "mysql")]
+        "mysql" => {
+            info!("Using MySQL database (connection details managed by domain layer)");
+        }
         _ => {
             error!("Unsupported database type: {}", db_type);
             std::process::exit(1);
@@ -116,4 +116,16 @@ pub struct PublicTokenClaims {
     
     /// User roles
     pub roles: Vec<String>,
+    
+    /// Optional scopes limiting what the token may be used for
+    pub scopes: Option<Vec<String>>,
+    
+    /// Unique token identifier
+    pub jti: String,
+    
+    /// Token type, "access" or "refresh"
+    pub typ: Option<String>,
+    
+    /// Token family shared by the tokens issued by rotating one refresh token
+    pub fam: Option<String>,
 } 
\ No newline at end of file
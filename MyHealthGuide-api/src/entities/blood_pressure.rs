@@ -3,6 +3,9 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use validator::Validate;
 use utoipa::ToSchema;
+use my_health_guide_domain::entities::blood_pressure::{
+    AlertRuleCondition, BloodPressureAlertKind, MeasurementArm, MeasurementPosition, SessionAverage,
+};
 
 /// Public representation of a blood pressure reading
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -24,6 +27,18 @@ pub struct BloodPressureReading {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
     
+    /// Optional position during measurement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<MeasurementPosition>,
+    
+    /// Optional arm used for measurement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arm: Option<MeasurementArm>,
+    
+    /// Optional ID of the device used for measurement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    
     /// When the reading was taken
     pub recorded_at: DateTime<Utc>,
     
@@ -34,6 +49,47 @@ pub struct BloodPressureReading {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Public representation of an alert raised by a user's readings
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BloodPressureAlert {
+    /// Unique identifier for the alert
+    pub id: Uuid,
+    
+    /// What raised the alert
+    pub kind: BloodPressureAlertKind,
+    
+    /// Alert rule that fired the alert, for rule alerts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<Uuid>,
+    
+    /// What happened and what to do about it
+    pub message: String,
+    
+    /// Systolic pressure of the latest reading behind the alert, or their average for an average rule
+    pub systolic: i32,
+    
+    /// Diastolic pressure of the latest reading behind the alert, or their average for an average rule
+    pub diastolic: i32,
+    
+    /// Readings behind the alert, oldest first, ending with the one that raised it
+    pub reading_ids: Vec<Uuid>,
+    
+    /// When the alert was raised
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response to creating a blood pressure reading
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateBloodPressureResponse {
+    /// The created reading
+    #[serde(flatten)]
+    pub reading: BloodPressureReading,
+    
+    /// Alert raised by the reading, present when it needs attention
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<BloodPressureAlert>,
+}
+
 /// Request payload for creating a new blood pressure reading
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateBloodPressureRequest {
@@ -55,6 +111,16 @@ pub struct CreateBloodPressureRequest {
     
     /// When the reading was taken. Defaults to current time if not provided.
     pub timestamp: Option<DateTime<Utc>>,
+    
+    /// Optional position during measurement (sitting, standing or lying)
+    pub position: Option<MeasurementPosition>,
+    
+    /// Optional arm used for measurement (left or right)
+    pub arm: Option<MeasurementArm>,
+    
+    /// Optional ID of the device used for measurement
+    #[validate(length(min = 1, max = 100, message = "Device ID must be between 1 and 100 characters"))]
+    pub device_id: Option<String>,
 }
 
 /// Request payload for updating an existing blood pressure reading
@@ -78,4 +144,93 @@ pub struct UpdateBloodPressureRequest {
     
     /// When the reading was taken
     pub timestamp: Option<DateTime<Utc>>,
-} 
\ No newline at end of file
+    
+    /// Optional position during measurement (sitting, standing or lying)
+    pub position: Option<MeasurementPosition>,
+    
+    /// Optional arm used for measurement (left or right)
+    pub arm: Option<MeasurementArm>,
+    
+    /// Optional ID of the device used for measurement
+    #[validate(length(min = 1, max = 100, message = "Device ID must be between 1 and 100 characters"))]
+    pub device_id: Option<String>,
+}
+
+/// Public representation of a measurement session
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BloodPressureSession {
+    /// Unique identifier for the session
+    pub id: Uuid,
+    
+    /// Whether the first reading is left out of the average
+    pub discard_first: bool,
+    
+    /// Optional notes about the session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    
+    /// Readings in the session, oldest first
+    pub readings: Vec<BloodPressureReading>,
+    
+    /// Average of the session's readings, absent once they have all been deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average: Option<SessionAverage>,
+    
+    /// When the session was created in the system
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request payload for recording a measurement session
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateBloodPressureSessionRequest {
+    /// Readings taken during the session, 2 to 6 of them
+    pub readings: Vec<CreateBloodPressureRequest>,
+    
+    /// Leave the first reading out of the average (default: false)
+    #[serde(default)]
+    pub discard_first: bool,
+    
+    /// Optional notes about the session
+    pub notes: Option<String>,
+}
+
+/// Public representation of a user-defined alert rule
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AlertRule {
+    /// Unique identifier for the rule
+    pub id: Uuid,
+    
+    /// Name of the rule, shown in the alerts it fires
+    pub name: String,
+    
+    /// What makes the rule fire
+    pub condition: AlertRuleCondition,
+    
+    /// Whether the rule is evaluated
+    pub enabled: bool,
+    
+    /// When the rule was created
+    pub created_at: DateTime<Utc>,
+    
+    /// When the rule was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request payload for creating or replacing an alert rule
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateAlertRuleRequest {
+    /// Name of the rule, e.g. "Three high readings in a row"
+    pub name: String,
+    
+    /// What makes the rule fire
+    pub condition: AlertRuleCondition,
+    
+    /// Whether the rule is evaluated (default: true)
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Rules are enabled unless the request says otherwise
+fn default_enabled() -> bool {
+    true
+}
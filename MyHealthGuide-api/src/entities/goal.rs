@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use utoipa::ToSchema;
+
+use my_health_guide_domain::entities::goal::GoalProgress;
+
+/// Public representation of a user's goals
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublicGoals {
+    /// Systolic pressure to stay below, in mmHg
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_systolic: Option<u16>,
+
+    /// Diastolic pressure to stay below, in mmHg
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_diastolic: Option<u16>,
+
+    /// Blood pressure readings to take each week
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readings_per_week: Option<u32>,
+
+    /// Weight to reach, in kilograms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_weight_kg: Option<f32>,
+
+    /// When the goals were first set
+    pub created_at: DateTime<Utc>,
+
+    /// When the goals were last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request payload for setting goals, replacing any set before
+///
+/// Goals left out are cleared. The blood pressure targets are set together
+/// and at least one goal has to be set.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublicSetGoalsRequest {
+    /// Systolic pressure to stay below, in mmHg (90-180)
+    pub target_systolic: Option<u16>,
+
+    /// Diastolic pressure to stay below, in mmHg (50-120)
+    pub target_diastolic: Option<u16>,
+
+    /// Blood pressure readings to take each week (1-70)
+    pub readings_per_week: Option<u32>,
+
+    /// Weight to reach, in kilograms (20-500)
+    pub target_weight_kg: Option<f32>,
+}
+
+/// Goals response with the progress made towards them
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublicGoalsResponse {
+    /// The goals
+    pub goals: PublicGoals,
+
+    /// Progress towards each goal that is set, computed from the readings
+    pub progress: GoalProgress,
+}
@@ -11,4 +11,10 @@ pub mod common;
 pub mod auth;
 
 // Weight entities
-pub mod weight; 
\ No newline at end of file
+pub mod weight;
+
+// Goal entities
+pub mod goal;
+
+// Profile entities
+pub mod profile;
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use utoipa::ToSchema;
+
+use my_health_guide_domain::entities::blood_pressure::Sex;
+
+/// Public representation of a user's profile
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublicProfile {
+    /// IANA time zone name local times are taken in, e.g. "Europe/Amsterdam"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+
+    /// Guideline set readings are categorized and alerted with: acc_aha_2017, esc_esh_2018 or ish_2020
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guideline: Option<String>,
+
+    /// Date of birth; under 18 the AAP 2017 pediatric thresholds are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_of_birth: Option<NaiveDate>,
+
+    /// Sex, male or female, selecting the pediatric reference values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sex: Option<Sex>,
+
+    /// Latest height in centimeters, for the pediatric height percentile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height_cm: Option<f64>,
+
+    /// When the profile was first set
+    pub created_at: DateTime<Utc>,
+
+    /// When the profile was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request payload for setting the profile, replacing any set before
+///
+/// Fields left out are cleared.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PublicSetProfileRequest {
+    /// IANA time zone name local times are taken in, e.g. "Europe/Amsterdam"
+    pub time_zone: Option<String>,
+
+    /// Guideline set readings are categorized and alerted with: acc_aha_2017, esc_esh_2018 or ish_2020
+    ///
+    /// Without one the deployment's guideline set is used.
+    pub guideline: Option<String>,
+
+    /// Date of birth as YYYY-MM-DD; under 18 the AAP 2017 pediatric thresholds are used
+    pub date_of_birth: Option<NaiveDate>,
+
+    /// Sex, male or female, required with a date of birth for the pediatric thresholds
+    pub sex: Option<Sex>,
+
+    /// Latest height in centimeters (50-272), for the pediatric height percentile
+    pub height_cm: Option<f64>,
+}
@@ -19,11 +19,48 @@ pub fn configure_swagger_routes() -> SwaggerUi {
         crate::api::handlers::blood_pressure::create_blood_pressure,
         crate::api::handlers::blood_pressure::get_blood_pressure_history,
         crate::api::handlers::blood_pressure::get_blood_pressure_insights,
+        crate::api::handlers::blood_pressure::get_blood_pressure_patterns,
+        crate::api::handlers::blood_pressure::get_blood_pressure_alerts,
+        crate::api::handlers::blood_pressure::get_blood_pressure_hbpm_report,
+        crate::api::handlers::blood_pressure::update_blood_pressure,
+        crate::api::handlers::blood_pressure::patch_blood_pressure,
+        crate::api::handlers::blood_pressure::delete_blood_pressure,
+        crate::api::handlers::blood_pressure::restore_blood_pressure,
+        crate::api::handlers::blood_pressure::create_blood_pressure_session,
+        crate::api::handlers::blood_pressure::get_blood_pressure_sessions,
+        crate::api::handlers::blood_pressure::get_blood_pressure_session,
+        crate::api::handlers::blood_pressure::delete_blood_pressure_session,
+        crate::api::handlers::blood_pressure::get_blood_pressure_alert_history,
+        crate::api::handlers::blood_pressure::create_blood_pressure_alert_rule,
+        crate::api::handlers::blood_pressure::get_blood_pressure_alert_rules,
+        crate::api::handlers::blood_pressure::get_blood_pressure_alert_rule,
+        crate::api::handlers::blood_pressure::update_blood_pressure_alert_rule,
+        crate::api::handlers::blood_pressure::delete_blood_pressure_alert_rule,
+
+        // Weight endpoints
+        crate::api::handlers::weight::get_weight,
+        crate::api::handlers::weight::create_weight,
+        crate::api::handlers::weight::get_weight_history,
+        crate::api::handlers::weight::get_weight_insights,
+        crate::api::handlers::weight::update_weight,
+        crate::api::handlers::weight::delete_weight,
+        crate::api::handlers::weight::restore_weight,
+
+        // Goal endpoints
+        crate::api::handlers::goals::get_goals,
+        crate::api::handlers::goals::set_goals,
+        crate::api::handlers::goals::delete_goals,
+
+        // Profile endpoints
+        crate::api::handlers::profile::get_profile,
+        crate::api::handlers::profile::set_profile,
 
         // Auth endpoints
+        crate::api::handlers::auth::register,
         my_health_guide_domain::auth::auth_info,
         my_health_guide_domain::auth::refresh_token,
         my_health_guide_domain::auth::logout,
+        my_health_guide_domain::auth::logout_all,
         my_health_guide_domain::auth::login,
 
         // OIDC endpoints - note these are partially defined through the routes module
@@ -34,6 +71,48 @@ pub fn configure_swagger_routes() -> SwaggerUi {
             // Entities
             crate::entities::blood_pressure::BloodPressureReading,
             crate::entities::blood_pressure::CreateBloodPressureRequest,
+            crate::entities::blood_pressure::UpdateBloodPressureRequest,
+            crate::entities::blood_pressure::BloodPressureSession,
+            crate::entities::blood_pressure::CreateBloodPressureSessionRequest,
+            crate::entities::blood_pressure::CreateBloodPressureResponse,
+            crate::entities::blood_pressure::BloodPressureAlert,
+            crate::entities::blood_pressure::AlertRule,
+            crate::entities::blood_pressure::CreateAlertRuleRequest,
+            my_health_guide_domain::entities::blood_pressure::BloodPressureAlertKind,
+            my_health_guide_domain::entities::blood_pressure::AlertRuleCondition,
+            my_health_guide_domain::entities::blood_pressure::SessionAverage,
+            my_health_guide_domain::entities::blood_pressure::MeasurementPosition,
+            my_health_guide_domain::entities::blood_pressure::MeasurementArm,
+            my_health_guide_domain::entities::blood_pressure::BloodPressureCategory,
+            my_health_guide_domain::entities::blood_pressure::BloodPressureInsights,
+            my_health_guide_domain::entities::blood_pressure::BloodPressureTrend,
+            my_health_guide_domain::entities::blood_pressure::TimeOfDayPattern,
+            my_health_guide_domain::entities::blood_pressure::TimeOfDayAverage,
+            my_health_guide_domain::entities::blood_pressure::DippingPattern,
+            my_health_guide_domain::entities::blood_pressure::PairedReadingAlerts,
+            my_health_guide_domain::entities::blood_pressure::PairedReadingAlert,
+            my_health_guide_domain::entities::blood_pressure::PairedReadingAlertKind,
+            my_health_guide_domain::entities::blood_pressure::HbpmReport,
+            my_health_guide_domain::entities::blood_pressure::HbpmSlot,
+            my_health_guide_domain::entities::blood_pressure::HbpmPeriod,
+            my_health_guide_domain::entities::blood_pressure::PediatricClassification,
+            my_health_guide_domain::entities::blood_pressure::PediatricCategory,
+            my_health_guide_domain::entities::blood_pressure::PercentileBand,
+            my_health_guide_domain::entities::blood_pressure::Sex,
+            crate::entities::weight::PublicWeightReading,
+            crate::entities::weight::PublicCreateWeightRequest,
+            crate::entities::weight::PublicWeightInsights,
+            crate::entities::goal::PublicGoals,
+            crate::entities::goal::PublicSetGoalsRequest,
+            crate::entities::goal::PublicGoalsResponse,
+            my_health_guide_domain::entities::goal::GoalProgress,
+            my_health_guide_domain::entities::goal::BloodPressureGoalProgress,
+            my_health_guide_domain::entities::goal::ReadingFrequencyProgress,
+            my_health_guide_domain::entities::goal::WeightGoalProgress,
+            crate::entities::profile::PublicProfile,
+            crate::entities::profile::PublicSetProfileRequest,
+            crate::entities::auth::PublicRegistrationRequest,
+            crate::entities::auth::PublicUserInfo,
             crate::entities::common::PublicErrorResponse,
             crate::entities::common::PublicPaginationParams,
 
@@ -45,12 +124,22 @@ pub fn configure_swagger_routes() -> SwaggerUi {
             // Blood pressure handlers
             crate::api::handlers::blood_pressure::ErrorResponse,
             crate::api::handlers::blood_pressure::BloodPressurePaginatedResponse,
+            crate::api::handlers::blood_pressure::BloodPressureSessionPaginatedResponse,
+            crate::api::handlers::blood_pressure::SessionQueryParams,
             crate::api::handlers::blood_pressure::HistoryQueryParams,
             crate::api::handlers::blood_pressure::InsightsQueryParams,
+            crate::api::handlers::blood_pressure::PatternQueryParams,
+            crate::api::handlers::blood_pressure::AlertQueryParams,
+            crate::api::handlers::blood_pressure::HbpmQueryParams,
+
+            // Weight handlers
+            crate::api::handlers::blood_pressure::WeightPaginatedResponse,
+            crate::api::handlers::weight::WeightInsightsQueryParams,
 
             // Auth schemas
             my_health_guide_domain::auth::LoginRequest,
             my_health_guide_domain::auth::LoginResponse,
+            my_health_guide_domain::auth::LogoutRequest,
             my_health_guide_domain::auth::UserInfo,
             my_health_guide_domain::auth::Claims,
 
@@ -63,6 +152,9 @@ pub fn configure_swagger_routes() -> SwaggerUi {
     tags(
         (name = "health", description = "Health check endpoint"),
         (name = "blood_pressure", description = "Blood pressure management endpoints"),
+        (name = "weight", description = "Weight tracking endpoints"),
+        (name = "goals", description = "Personal goals and progress endpoints"),
+        (name = "profile", description = "User profile endpoints"),
         (name = "Authentication", description = "Authentication and authorization endpoints")
     ),
     info(
@@ -91,18 +183,18 @@ mod tests {
         let openapi = ApiDoc::openapi();
 
         // Verify basic info fields are set correctly
-        assert_eq!(openapi.info.title, "MyHealthGuide API");
+        assert_eq!(openapi.info.title, "My Health Guide API");
         assert_eq!(openapi.info.version, "0.1.0");
 
         // Verify tags are defined
         let tags = &openapi.tags;
         assert!(tags.is_some());
         let tags = tags.as_ref().unwrap();
-        assert!(tags.iter().any(|tag| tag.name == "System"));
-        assert!(tags.iter().any(|tag| tag.name == "Blood Pressure"));
+        assert!(tags.iter().any(|tag| tag.name == "health"));
+        assert!(tags.iter().any(|tag| tag.name == "blood_pressure"));
 
         // Verify servers are defined - checking if the servers exist
-        assert!(openapi.servers.is_some() || !openapi.servers.as_ref().map_or(true, |s| s.is_empty()));
+        assert!(openapi.servers.is_some() || openapi.servers.as_ref().is_some_and(|s| !s.is_empty()));
 
         // Debug print all available paths
         println!("Available paths in OpenAPI schema:");
@@ -125,6 +217,11 @@ mod tests {
         assert!(
             openapi.paths.paths.contains_key("/api/v1/bloodpressure/insights")
         );
+
+        assert!(openapi.paths.paths.contains_key("/api/v1/weight"));
+        assert!(openapi.paths.paths.contains_key("/api/v1/weight/{id}"));
+        assert!(openapi.paths.paths.contains_key("/api/v1/weight/insights"));
+        assert!(openapi.paths.paths.contains_key("/auth/register"));
     }
 
     #[test]
@@ -133,7 +230,7 @@ mod tests {
         let app: Router = Router::new();
 
         // Add Swagger UI routes
-        let app_with_swagger = configure_swagger_routes();
+        let app_with_swagger: Router = app.clone().merge(configure_swagger_routes());
 
         // We can't easily test the app directly, but we can verify that the router
         // changes after applying the swagger configuration
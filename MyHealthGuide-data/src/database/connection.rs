@@ -339,22 +339,7 @@ fn initialize_in_memory_sqlite_pool(config: &DatabaseConfig) -> Result<DatabaseP
     
     // Initialize schema for in-memory database
     let conn = pool.get()?;
-    rusqlite::Connection::execute_batch(&conn, 
-        "CREATE TABLE IF NOT EXISTS blood_pressure_readings (
-            id TEXT PRIMARY KEY,
-            systolic INTEGER NOT NULL,
-            diastolic INTEGER NOT NULL,
-            pulse INTEGER,
-            timestamp TEXT NOT NULL,
-            notes TEXT,
-            position TEXT,
-            arm TEXT,
-            device_id TEXT,
-            category TEXT
-        );
-        CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_timestamp 
-        ON blood_pressure_readings (timestamp DESC);"
-    )?;
+    run_sqlite_migrations(&conn)?;
     
     info!("In-memory SQLite database initialized successfully");
     Ok(DatabasePool::SQLite(Arc::new(pool)))
@@ -427,9 +412,12 @@ fn run_migrations() -> Result<(), DatabaseError> {
             run_sqlite_migrations(&conn)?;
         },
         #[cfg(feature = "mysql_db")]
-        DatabasePool::MySQL(_) => {
-            // MySQL migrations here
-            // ...
+        DatabasePool::MySQL(ref pool) => {
+            let mut conn = pool.get()
+                .map_err(DatabaseError::SqlitePoolError)?;
+            
+            super::migrations::run_mysql_migrations(&mut conn)
+                .map_err(DatabaseError::MigrationError)?;
         },
         #[cfg(feature = "postgres")]
         DatabasePool::PostgreSQL(_) => {
@@ -445,23 +433,78 @@ fn run_migrations() -> Result<(), DatabaseError> {
 
 /// Run SQLite migrations
 fn run_sqlite_migrations(conn: &rusqlite::Connection) -> Result<(), DatabaseError> {
-    // Create blood pressure readings table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS blood_pressure_readings (
-            id TEXT PRIMARY KEY,
-            systolic INTEGER NOT NULL,
-            diastolic INTEGER NOT NULL,
-            pulse INTEGER,
-            timestamp TEXT NOT NULL,
-            notes TEXT,
-            position TEXT,
-            arm TEXT,
-            device_id TEXT,
-            category TEXT
-        )",
-        [],
-    ).map_err(DatabaseError::SqliteError)?;
-    
+    super::migrations::run_sqlite_migrations(conn)
+        .map_err(DatabaseError::MigrationError)
+}
+
+/// Report which schema migrations are applied to the database and which are pending
+pub async fn migration_status() -> Result<Vec<super::migrations::MigrationStatus>, DatabaseError> {
+    let pool = get_db_pool()?;
+
+    match pool {
+        DatabasePool::SQLite(ref pool) => {
+            let conn = pool.get()
+                .map_err(DatabaseError::SqlitePoolError)?;
+
+            super::migrations::sqlite_migration_status(&conn)
+                .map_err(DatabaseError::MigrationError)
+        },
+        #[cfg(feature = "mysql_db")]
+        DatabasePool::MySQL(ref pool) => {
+            let mut conn = pool.get()
+                .map_err(DatabaseError::SqlitePoolError)?;
+
+            super::migrations::mysql_migration_status(&mut conn)
+                .map_err(DatabaseError::MigrationError)
+        },
+        #[cfg(feature = "postgres")]
+        DatabasePool::PostgreSQL(ref pool) => {
+            let mut client = pool.get().await
+                .map_err(|e| DatabaseError::GenericError(e.to_string()))?;
+
+            super::migrations::postgres_migration_status(&mut client).await
+                .map_err(DatabaseError::MigrationError)
+        },
+    }
+}
+
+/// Roll the database schema back to `target_version`
+///
+/// Reverts every applied migration newer than the target, newest first.
+/// Data held in the dropped tables is lost.
+pub async fn rollback_migrations(target_version: i64) -> Result<(), DatabaseError> {
+    let pool = get_db_pool()?;
+
+    warn!("Rolling back database migrations to version {}", target_version);
+
+    match pool {
+        DatabasePool::SQLite(ref pool) => {
+            let conn = pool.get()
+                .map_err(DatabaseError::SqlitePoolError)?;
+
+            super::migrations::rollback_sqlite_migrations(&conn, target_version)
+                .map_err(DatabaseError::MigrationError)?;
+        },
+        #[cfg(feature = "mysql_db")]
+        DatabasePool::MySQL(ref pool) => {
+            let mut conn = pool.get()
+                .map_err(DatabaseError::SqlitePoolError)?;
+
+            super::migrations::rollback_mysql_migrations(&mut conn, target_version)
+                .map_err(DatabaseError::MigrationError)?;
+        },
+        #[cfg(feature = "postgres")]
+        DatabasePool::PostgreSQL(ref pool) => {
+            let mut client = pool.get().await
+                .map_err(|e| DatabaseError::GenericError(e.to_string()))?;
+
+            super::migrations::rollback_postgres_migrations(&mut client, target_version).await
+                .map_err(DatabaseError::MigrationError)?;
+        },
+    }
+
+    info!("Database migrations rolled back to version {}", target_version);
+
     Ok(())
 }
 
@@ -1,16 +1,263 @@
-// Database migrations module
-// This will be implemented properly in the future
+//! Versioned schema migrations
+//!
+//! Each backend lists its numbered migrations in order. Applied migrations are
+//! recorded in the `schema_migrations` table together with a checksum of their
+//! statements, so a migration that is edited after it shipped is reported
+//! instead of silently leaving databases with diverging schemas.
+//!
+//! Migrations are never edited once released: to change the schema, append a
+//! new migration with the next version number and a matching `down`.
+
+use std::collections::HashMap;
+use sha2::{Digest, Sha256};
 
 // Import specific functions from each module instead of using glob imports
 mod sqlite;
 pub use sqlite::run_migrations as run_sqlite_migrations;
+pub use sqlite::rollback_migrations as rollback_sqlite_migrations;
+pub use sqlite::migration_status as sqlite_migration_status;
 
 #[cfg(feature = "mysql_db")]
 mod mysql;
 #[cfg(feature = "mysql_db")]
 pub use mysql::run_migrations as run_mysql_migrations;
+#[cfg(feature = "mysql_db")]
+pub use mysql::rollback_migrations as rollback_mysql_migrations;
+#[cfg(feature = "mysql_db")]
+pub use mysql::migration_status as mysql_migration_status;
 
 #[cfg(feature = "postgres")]
 mod postgres;
 #[cfg(feature = "postgres")]
-pub use postgres::run_migrations as run_postgres_migrations; 
\ No newline at end of file
+pub use postgres::run_migrations as run_postgres_migrations;
+#[cfg(feature = "postgres")]
+pub use postgres::rollback_migrations as rollback_postgres_migrations;
+#[cfg(feature = "postgres")]
+pub use postgres::migration_status as postgres_migration_status;
+
+/// A numbered schema change with the statements to apply and revert it
+#[derive(Debug)]
+pub struct Migration {
+    /// Version number, migrations are applied in ascending order
+    pub version: i64,
+
+    /// Short description of the change
+    pub name: &'static str,
+
+    /// Statements that apply the change
+    pub up: &'static [&'static str],
+
+    /// Statements that revert the change
+    pub down: &'static [&'static str],
+}
+
+impl Migration {
+    /// Checksum of the statements applied by this migration, as lowercase hex
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        for statement in self.up {
+            hasher.update(statement.as_bytes());
+            hasher.update(b"\n");
+        }
+
+        hasher.finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// A migration as recorded in the `schema_migrations` table
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    /// Version number of the migration
+    pub version: i64,
+
+    /// Name of the migration when it was applied
+    pub name: String,
+
+    /// Checksum of the statements that were applied
+    pub checksum: String,
+
+    /// When the migration was applied, as an RFC 3339 timestamp
+    pub applied_at: String,
+}
+
+/// State of a migration in a database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    /// Applied and unchanged since
+    Applied,
+
+    /// Known to this build but not applied yet
+    Pending,
+
+    /// Applied, but the migration has been edited since
+    ChecksumMismatch,
+
+    /// Applied by a newer build that this one does not know about
+    Unknown,
+}
+
+/// Report line for a single migration
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    /// Version number of the migration
+    pub version: i64,
+
+    /// Name of the migration
+    pub name: String,
+
+    /// Whether and how the migration is applied
+    pub state: MigrationState,
+
+    /// When the migration was applied, if it was
+    pub applied_at: Option<String>,
+}
+
+/// Compare the migrations known to this build with the ones applied to a database
+///
+/// The report is ordered by version and includes applied migrations this
+/// build does not know about.
+pub fn build_status(known: &[Migration], applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let recorded: HashMap<i64, &AppliedMigration> = applied.iter()
+        .map(|migration| (migration.version, migration))
+        .collect();
+
+    let mut report: Vec<MigrationStatus> = known.iter()
+        .map(|migration| match recorded.get(&migration.version) {
+            Some(record) => MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                state: if record.checksum == migration.checksum() {
+                    MigrationState::Applied
+                } else {
+                    MigrationState::ChecksumMismatch
+                },
+                applied_at: Some(record.applied_at.clone()),
+            },
+            None => MigrationStatus {
+                version: migration.version,
+                name: migration.name.to_string(),
+                state: MigrationState::Pending,
+                applied_at: None,
+            },
+        })
+        .collect();
+
+    report.extend(applied.iter()
+        .filter(|record| !known.iter().any(|migration| migration.version == record.version))
+        .map(|record| MigrationStatus {
+            version: record.version,
+            name: record.name.clone(),
+            state: MigrationState::Unknown,
+            applied_at: Some(record.applied_at.clone()),
+        }));
+
+    report.sort_by_key(|status| status.version);
+    report
+}
+
+/// Fail if any applied migration was edited after it was applied
+pub(crate) fn verify_checksums(known: &[Migration], applied: &[AppliedMigration]) -> Result<(), String> {
+    let mismatched: Vec<String> = build_status(known, applied)
+        .into_iter()
+        .filter(|status| status.state == MigrationState::ChecksumMismatch)
+        .map(|status| format!("{} ({})", status.version, status.name))
+        .collect();
+
+    if mismatched.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Applied migrations no longer match their definitions: {}",
+            mismatched.join(", ")
+        ))
+    }
+}
+
+/// Migrations that still have to be applied, in order
+pub(crate) fn pending<'a>(known: &'a [Migration], applied: &[AppliedMigration]) -> Vec<&'a Migration> {
+    known.iter()
+        .filter(|migration| !applied.iter().any(|record| record.version == migration.version))
+        .collect()
+}
+
+/// Applied migrations above `target_version`, newest first, as they must be rolled back
+pub(crate) fn to_roll_back<'a>(
+    known: &'a [Migration],
+    applied: &[AppliedMigration],
+    target_version: i64,
+) -> Result<Vec<&'a Migration>, String> {
+    let mut migrations = Vec::new();
+
+    for record in applied.iter().filter(|record| record.version > target_version) {
+        let migration = known.iter()
+            .find(|migration| migration.version == record.version)
+            .ok_or_else(|| format!(
+                "Cannot roll back migration {} ({}): it is not known to this build",
+                record.version, record.name
+            ))?;
+        migrations.push(migration);
+    }
+
+    migrations.sort_by_key(|migration| std::cmp::Reverse(migration.version));
+    Ok(migrations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration { version: 1, name: "first", up: &["CREATE TABLE a (id INTEGER)"], down: &["DROP TABLE a"] },
+        Migration { version: 2, name: "second", up: &["CREATE TABLE b (id INTEGER)"], down: &["DROP TABLE b"] },
+    ];
+
+    fn applied(version: i64, checksum: String) -> AppliedMigration {
+        AppliedMigration {
+            version,
+            name: format!("migration {}", version),
+            checksum,
+            applied_at: "2024-01-01T00:00:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_status_reports_every_state() {
+        let records = vec![
+            applied(1, "edited".to_string()),
+            applied(3, "from a newer build".to_string()),
+        ];
+
+        let states: Vec<(i64, MigrationState)> = build_status(MIGRATIONS, &records)
+            .into_iter()
+            .map(|status| (status.version, status.state))
+            .collect();
+        assert_eq!(states, vec![
+            (1, MigrationState::ChecksumMismatch),
+            (2, MigrationState::Pending),
+            (3, MigrationState::Unknown),
+        ]);
+
+        assert!(verify_checksums(MIGRATIONS, &records).is_err());
+        assert!(to_roll_back(MIGRATIONS, &records, 0).is_err());
+    }
+
+    #[test]
+    fn test_rollback_order_is_newest_first() {
+        let records = vec![
+            applied(1, MIGRATIONS[0].checksum()),
+            applied(2, MIGRATIONS[1].checksum()),
+        ];
+
+        assert!(verify_checksums(MIGRATIONS, &records).is_ok());
+        assert!(pending(MIGRATIONS, &records).is_empty());
+
+        let versions: Vec<i64> = to_roll_back(MIGRATIONS, &records, 0).unwrap()
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect();
+        assert_eq!(versions, vec![2, 1]);
+    }
+}
@@ -1,49 +1,503 @@
 use tokio_postgres::Client;
-use tracing::info;
+use tracing::{info, warn};
+
+use super::{AppliedMigration, Migration, MigrationStatus};
+
+/// PostgreSQL schema migrations, in the order they are applied
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_blood_pressure_readings",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_readings (
+                id VARCHAR(36) PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL DEFAULT '',
+                systolic INTEGER NOT NULL,
+                diastolic INTEGER NOT NULL,
+                pulse INTEGER,
+                timestamp VARCHAR(30) NOT NULL,
+                notes TEXT,
+                position VARCHAR(20),
+                arm VARCHAR(10),
+                device_id VARCHAR(50),
+                category VARCHAR(30),
+                deleted_at VARCHAR(30)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_timestamp
+            ON blood_pressure_readings (timestamp DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_user_timestamp
+            ON blood_pressure_readings (user_id, timestamp DESC)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_blood_pressure_readings_user_timestamp",
+            "DROP INDEX IF EXISTS idx_blood_pressure_readings_timestamp",
+            "DROP TABLE IF EXISTS blood_pressure_readings",
+        ],
+    },
+    Migration {
+        version: 2,
+        name: "create_weight_readings",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS weight_readings (
+                id VARCHAR(36) PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL,
+                weight_kg REAL NOT NULL,
+                body_fat_percentage REAL,
+                muscle_mass_kg REAL,
+                notes TEXT,
+                timestamp VARCHAR(30) NOT NULL,
+                deleted_at VARCHAR(30)
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_weight_readings_user_timestamp
+            ON weight_readings (user_id, timestamp DESC)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_weight_readings_user_timestamp",
+            "DROP TABLE IF EXISTS weight_readings",
+        ],
+    },
+    Migration {
+        version: 3,
+        name: "create_users",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS users (
+                id VARCHAR(36) PRIMARY KEY,
+                email VARCHAR(255) NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                name TEXT,
+                roles TEXT NOT NULL,
+                created_at VARCHAR(30) NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS users",
+        ],
+    },
+    // Times are unix timestamps in seconds so expired rows can be cleaned up
+    // with a plain comparison
+    Migration {
+        version: 4,
+        name: "create_token_revocations",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS revoked_tokens (
+                jti VARCHAR(255) PRIMARY KEY,
+                expires_at BIGINT NOT NULL,
+                revoked_at BIGINT NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS user_token_watermarks (
+                user_id VARCHAR(255) PRIMARY KEY,
+                not_before BIGINT NOT NULL,
+                expires_at BIGINT NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS user_token_watermarks",
+            "DROP TABLE IF EXISTS revoked_tokens",
+        ],
+    },
+    // Holds the PKCE verifier, CSRF state and nonce of in-progress logins so
+    // the callback can be handled by any instance
+    Migration {
+        version: 5,
+        name: "create_oidc_sessions",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS oidc_sessions (
+                csrf_token VARCHAR(255) PRIMARY KEY,
+                id VARCHAR(255) NOT NULL,
+                pkce_verifier VARCHAR(255) NOT NULL,
+                nonce VARCHAR(255) NOT NULL,
+                created_at BIGINT NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS oidc_sessions",
+        ],
+    },
+    // Readings are categorized when they are written; this fills in rows
+    // stored before that, using the same thresholds as the domain layer
+    Migration {
+        version: 6,
+        name: "backfill_blood_pressure_categories",
+        up: &[
+            "UPDATE blood_pressure_readings SET category = CASE
+                WHEN systolic >= 180 OR diastolic >= 120 THEN 'HypertensiveCrisis'
+                WHEN systolic >= 140 OR diastolic >= 90 THEN 'Hypertension2'
+                WHEN systolic >= 130 OR diastolic >= 80 THEN 'Hypertension1'
+                WHEN systolic >= 120 THEN 'Elevated'
+                ELSE 'Normal'
+            END
+            WHERE category IS NULL",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_user_category
+            ON blood_pressure_readings (user_id, category)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_blood_pressure_readings_user_category",
+        ],
+    },
+    // Positions and arms are stored in lowercase, as the API serializes them;
+    // earlier free-text values in other cases would not match the filters.
+    // There is nothing to undo, the original casing carries no meaning.
+    Migration {
+        version: 7,
+        name: "normalize_measurement_position_and_arm",
+        up: &[
+            "UPDATE blood_pressure_readings
+            SET position = LOWER(TRIM(position)), arm = LOWER(TRIM(arm))
+            WHERE position IS NOT NULL OR arm IS NOT NULL",
+        ],
+        down: &[],
+    },
+    // Reading times become TIMESTAMPTZ columns, and readings stored before
+    // created_at and updated_at existed take their measurement time for both.
+    // A timestamp that does not parse fails the migration.
+    Migration {
+        version: 8,
+        name: "typed_blood_pressure_timestamps",
+        up: &[
+            "ALTER TABLE blood_pressure_readings
+            ALTER COLUMN timestamp TYPE TIMESTAMPTZ USING timestamp::timestamptz",
+            "ALTER TABLE blood_pressure_readings
+            ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ,
+            ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ",
+            "UPDATE blood_pressure_readings SET created_at = timestamp, updated_at = timestamp
+            WHERE created_at IS NULL",
+            "ALTER TABLE blood_pressure_readings
+            ALTER COLUMN created_at SET NOT NULL,
+            ALTER COLUMN updated_at SET NOT NULL",
+        ],
+        down: &[
+            "ALTER TABLE blood_pressure_readings
+            DROP COLUMN IF EXISTS updated_at,
+            DROP COLUMN IF EXISTS created_at",
+            "ALTER TABLE blood_pressure_readings
+            ALTER COLUMN timestamp TYPE VARCHAR(30)
+            USING to_char(timestamp AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.MS\"Z\"')",
+        ],
+    },
+    // Categories gained a hypotension level, and a hypertensive crisis under
+    // ACC/AHA 2017, the default guideline set, is strictly above 180/120.
+    // Readings written before then are brought in line with it.
+    Migration {
+        version: 9,
+        name: "recategorize_hypotension_and_crisis",
+        up: &[
+            "UPDATE blood_pressure_readings SET category = 'Hypotension'
+            WHERE category = 'Normal' AND (systolic < 90 OR diastolic < 60)",
+            "UPDATE blood_pressure_readings SET category = 'Hypertension2'
+            WHERE category = 'HypertensiveCrisis' AND systolic <= 180 AND diastolic <= 120",
+        ],
+        down: &[
+            "UPDATE blood_pressure_readings SET category = 'HypertensiveCrisis'
+            WHERE category = 'Hypertension2' AND (systolic >= 180 OR diastolic >= 120)",
+            "UPDATE blood_pressure_readings SET category = 'Normal'
+            WHERE category = 'Hypotension'",
+        ],
+    },
+    // Measurement sessions group readings taken a minute or so apart so they
+    // can be averaged. A reading belongs to at most one session, and removing
+    // a session leaves its readings in place.
+    Migration {
+        version: 10,
+        name: "create_blood_pressure_sessions",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_sessions (
+                id VARCHAR(36) PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL,
+                discard_first BOOLEAN NOT NULL DEFAULT FALSE,
+                notes TEXT,
+                created_at TIMESTAMPTZ NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_sessions_user
+            ON blood_pressure_sessions (user_id, created_at DESC)",
+            "CREATE TABLE IF NOT EXISTS blood_pressure_session_readings (
+                reading_id VARCHAR(36) PRIMARY KEY,
+                session_id VARCHAR(36) NOT NULL REFERENCES blood_pressure_sessions (id) ON DELETE CASCADE
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_session_readings_session
+            ON blood_pressure_session_readings (session_id)",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS blood_pressure_session_readings",
+            "DROP TABLE IF EXISTS blood_pressure_sessions",
+        ],
+    },
+    // Alerts raised when a reading is in the hypertensive crisis range or
+    // stage 2 keeps coming back. The readings behind an alert are linked so
+    // an alert can point at more than one of them.
+    Migration {
+        version: 11,
+        name: "create_blood_pressure_alerts",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_alerts (
+                id VARCHAR(36) PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL,
+                kind VARCHAR(50) NOT NULL,
+                message TEXT NOT NULL,
+                systolic INTEGER NOT NULL,
+                diastolic INTEGER NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_alerts_user
+            ON blood_pressure_alerts (user_id, created_at DESC)",
+            "CREATE TABLE IF NOT EXISTS blood_pressure_alert_readings (
+                alert_id VARCHAR(36) NOT NULL REFERENCES blood_pressure_alerts (id) ON DELETE CASCADE,
+                reading_id VARCHAR(36) NOT NULL,
+                PRIMARY KEY (alert_id, reading_id)
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS blood_pressure_alert_readings",
+            "DROP TABLE IF EXISTS blood_pressure_alerts",
+        ],
+    },
+    // User-defined alert rules, such as "three readings in a row above 150"
+    // or "no reading in three days". The condition is kept as JSON so new
+    // kinds of rules need no schema change. Alerts fired by a rule point
+    // back at it and keep doing so after the rule is deleted.
+    Migration {
+        version: 12,
+        name: "create_blood_pressure_alert_rules",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_alert_rules (
+                id VARCHAR(36) PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL,
+                name VARCHAR(100) NOT NULL,
+                definition TEXT NOT NULL,
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                created_at TIMESTAMPTZ NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_alert_rules_user
+            ON blood_pressure_alert_rules (user_id)",
+            "ALTER TABLE blood_pressure_alerts ADD COLUMN IF NOT EXISTS rule_id VARCHAR(36)",
+        ],
+        down: &[
+            "ALTER TABLE blood_pressure_alerts DROP COLUMN IF EXISTS rule_id",
+            "DROP TABLE IF EXISTS blood_pressure_alert_rules",
+        ],
+    },
+    // Each user has at most one set of goals, so the table is keyed by
+    // user. Every goal is optional; progress is computed from the readings
+    // when requested rather than stored.
+    Migration {
+        version: 13,
+        name: "create_user_goals",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS user_goals (
+                user_id VARCHAR(255) PRIMARY KEY,
+                target_systolic INTEGER,
+                target_diastolic INTEGER,
+                readings_per_week INTEGER,
+                target_weight_kg REAL,
+                created_at TIMESTAMPTZ NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS user_goals",
+        ],
+    },
+    // Log out everywhere compares watermarks against millisecond issue
+    // times, so a login in the same second as the logout stays valid.
+    // Existing watermarks in seconds are converted in place.
+    Migration {
+        version: 14,
+        name: "token_watermarks_in_milliseconds",
+        up: &[
+            "UPDATE user_token_watermarks SET not_before = not_before * 1000",
+        ],
+        down: &[
+            "UPDATE user_token_watermarks SET not_before = not_before / 1000",
+        ],
+    },
+    // Each user has at most one profile, keyed by user like their goals.
+    // The time zone is an IANA name, so local times follow daylight saving.
+    Migration {
+        version: 15,
+        name: "create_user_profiles",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS user_profiles (
+                user_id VARCHAR(255) PRIMARY KEY,
+                time_zone VARCHAR(64),
+                created_at TIMESTAMPTZ NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS user_profiles",
+        ],
+    },
+    // Users may categorize their readings with another guideline set than
+    // the deployment's. Existing profiles keep using the deployment's.
+    Migration {
+        version: 16,
+        name: "add_user_profile_guideline",
+        up: &[
+            "ALTER TABLE user_profiles ADD COLUMN guideline VARCHAR(32)",
+        ],
+        down: &[
+            "ALTER TABLE user_profiles DROP COLUMN guideline",
+        ],
+    },
+    // What the pediatric thresholds need to know about a user, so it is not
+    // asked on every request. The date of birth is stored as YYYY-MM-DD text.
+    Migration {
+        version: 17,
+        name: "add_user_profile_patient_details",
+        up: &[
+            "ALTER TABLE user_profiles ADD COLUMN date_of_birth VARCHAR(10)",
+            "ALTER TABLE user_profiles ADD COLUMN sex VARCHAR(16)",
+            "ALTER TABLE user_profiles ADD COLUMN height_cm DOUBLE PRECISION",
+        ],
+        down: &[
+            "ALTER TABLE user_profiles DROP COLUMN height_cm",
+            "ALTER TABLE user_profiles DROP COLUMN sex",
+            "ALTER TABLE user_profiles DROP COLUMN date_of_birth",
+        ],
+    },
+];
 
 /// Run PostgreSQL database migrations
-pub async fn run_migrations(client: &Client) -> Result<(), String> {
+///
+/// Applies every pending migration, each in its own transaction. Fails
+/// without changing anything if an applied migration was edited since.
+pub async fn run_migrations(client: &mut Client) -> Result<(), String> {
     info!("Running PostgreSQL migrations");
-    
-    create_blood_pressure_table(client).await?;
-    create_blood_pressure_index(client).await?;
-    
+
+    create_migrations_table(client).await?;
+    let applied = applied_migrations(client).await?;
+    super::verify_checksums(MIGRATIONS, &applied)?;
+
+    if applied.is_empty() {
+        upgrade_unversioned_schema(client).await?;
+    }
+
+    for migration in super::pending(MIGRATIONS, &applied) {
+        apply_migration(client, migration).await?;
+    }
+
     info!("PostgreSQL migrations completed successfully");
     Ok(())
 }
 
-/// Create the blood pressure readings table
-async fn create_blood_pressure_table(client: &Client) -> Result<(), String> {
-    info!("Creating blood_pressure_readings table if not exists");
-    
+/// Roll back applied migrations until the schema is at `target_version`
+///
+/// Each migration is reverted in its own transaction, newest first. Use a
+/// target of 0 to revert every migration.
+pub async fn rollback_migrations(client: &mut Client, target_version: i64) -> Result<(), String> {
+    info!("Rolling back PostgreSQL migrations to version {}", target_version);
+
+    create_migrations_table(client).await?;
+    let applied = applied_migrations(client).await?;
+
+    for migration in super::to_roll_back(MIGRATIONS, &applied, target_version)? {
+        revert_migration(client, migration).await?;
+    }
+
+    Ok(())
+}
+
+/// Report which migrations are applied and which are pending
+pub async fn migration_status(client: &mut Client) -> Result<Vec<MigrationStatus>, String> {
+    create_migrations_table(client).await?;
+    let applied = applied_migrations(client).await?;
+
+    Ok(super::build_status(MIGRATIONS, &applied))
+}
+
+/// Create the table recording applied migrations
+async fn create_migrations_table(client: &Client) -> Result<(), String> {
     client.execute(
-        "CREATE TABLE IF NOT EXISTS blood_pressure_readings (
-            id VARCHAR(36) PRIMARY KEY,
-            systolic INTEGER NOT NULL,
-            diastolic INTEGER NOT NULL,
-            pulse INTEGER,
-            timestamp VARCHAR(30) NOT NULL,
-            notes TEXT,
-            position VARCHAR(20),
-            arm VARCHAR(10),
-            device_id VARCHAR(50),
-            category VARCHAR(30)
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            name VARCHAR(255) NOT NULL,
+            checksum VARCHAR(64) NOT NULL,
+            applied_at VARCHAR(40) NOT NULL
         )",
         &[],
-    ).await.map_err(|e| e.to_string())?;
-    
+    ).await.map_err(|e| format!("Failed to create schema_migrations table: {}", e))?;
+
     Ok(())
 }
 
-/// Create index on timestamp for efficient filtering
-async fn create_blood_pressure_index(client: &Client) -> Result<(), String> {
-    info!("Creating index on timestamp");
-    
-    client.execute(
-        "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_timestamp 
-        ON blood_pressure_readings (timestamp DESC)",
+/// Load the applied migrations, oldest first
+async fn applied_migrations(client: &Client) -> Result<Vec<AppliedMigration>, String> {
+    let rows = client.query(
+        "SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version",
+        &[],
+    ).await.map_err(|e| e.to_string())?;
+
+    Ok(rows.iter()
+        .map(|row| AppliedMigration {
+            version: row.get(0),
+            name: row.get(1),
+            checksum: row.get(2),
+            applied_at: row.get(3),
+        })
+        .collect())
+}
+
+/// Apply a migration and record it, all in one transaction
+async fn apply_migration(client: &mut Client, migration: &Migration) -> Result<(), String> {
+    info!("Applying migration {} ({})", migration.version, migration.name);
+
+    let tx = client.transaction().await.map_err(|e| e.to_string())?;
+
+    for statement in migration.up {
+        tx.batch_execute(statement).await
+            .map_err(|e| format!("Migration {} ({}) failed: {}", migration.version, migration.name, e))?;
+    }
+
+    tx.execute(
+        "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES ($1, $2, $3, $4)",
+        &[&migration.version, &migration.name, &migration.checksum(), &chrono::Utc::now().to_rfc3339()],
+    ).await.map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Revert a migration and forget it, all in one transaction
+async fn revert_migration(client: &mut Client, migration: &Migration) -> Result<(), String> {
+    warn!("Reverting migration {} ({})", migration.version, migration.name);
+
+    let tx = client.transaction().await.map_err(|e| e.to_string())?;
+
+    for statement in migration.down {
+        tx.batch_execute(statement).await
+            .map_err(|e| format!("Reverting migration {} ({}) failed: {}", migration.version, migration.name, e))?;
+    }
+
+    tx.execute("DELETE FROM schema_migrations WHERE version = $1", &[&migration.version])
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Bring a database created before migrations were versioned up to the first version
+///
+/// Old readings tables may be missing columns that migration 1 would have
+/// created; the `CREATE TABLE IF NOT EXISTS` there leaves them untouched.
+async fn upgrade_unversioned_schema(client: &Client) -> Result<(), String> {
+    let row = client.query_one(
+        "SELECT to_regclass('blood_pressure_readings') IS NOT NULL",
         &[],
-    ).await.map_err(|e| format!("Failed to create index: {}", e))?;
-    
+    ).await.map_err(|e| e.to_string())?;
+
+    if !row.get::<_, bool>(0) {
+        return Ok(());
+    }
+
+    info!("Upgrading unversioned blood_pressure_readings table");
+
+    // Rows written before the upgrade keep an empty owner and are therefore
+    // not visible to any user until they are reassigned
+    client.batch_execute(
+        "ALTER TABLE blood_pressure_readings
+        ADD COLUMN IF NOT EXISTS user_id VARCHAR(255) NOT NULL DEFAULT '';
+        ALTER TABLE blood_pressure_readings
+        ADD COLUMN IF NOT EXISTS deleted_at VARCHAR(30)"
+    ).await.map_err(|e| format!("Failed to upgrade blood_pressure_readings: {}", e))?;
+
     Ok(())
-} 
\ No newline at end of file
+}
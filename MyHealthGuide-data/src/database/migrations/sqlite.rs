@@ -1,49 +1,643 @@
 use rusqlite::Connection;
-use tracing::{info};
+use tracing::{info, warn};
+
+use super::{AppliedMigration, Migration, MigrationStatus};
+
+/// SQLite schema migrations, in the order they are applied
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_blood_pressure_readings",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_readings (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL DEFAULT '',
+                systolic INTEGER NOT NULL,
+                diastolic INTEGER NOT NULL,
+                pulse INTEGER,
+                timestamp TEXT NOT NULL,
+                notes TEXT,
+                position TEXT,
+                arm TEXT,
+                device_id TEXT,
+                category TEXT,
+                deleted_at TEXT
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_timestamp
+            ON blood_pressure_readings (timestamp DESC)",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_user_timestamp
+            ON blood_pressure_readings (user_id, timestamp DESC)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_blood_pressure_readings_user_timestamp",
+            "DROP INDEX IF EXISTS idx_blood_pressure_readings_timestamp",
+            "DROP TABLE IF EXISTS blood_pressure_readings",
+        ],
+    },
+    Migration {
+        version: 2,
+        name: "create_weight_readings",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS weight_readings (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                weight_kg REAL NOT NULL,
+                body_fat_percentage REAL,
+                muscle_mass_kg REAL,
+                notes TEXT,
+                timestamp TEXT NOT NULL,
+                deleted_at TEXT
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_weight_readings_user_timestamp
+            ON weight_readings (user_id, timestamp DESC)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_weight_readings_user_timestamp",
+            "DROP TABLE IF EXISTS weight_readings",
+        ],
+    },
+    Migration {
+        version: 3,
+        name: "create_users",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS users (
+                id TEXT PRIMARY KEY,
+                email TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                name TEXT,
+                roles TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS users",
+        ],
+    },
+    // Times are unix timestamps in seconds so expired rows can be cleaned up
+    // with a plain comparison
+    Migration {
+        version: 4,
+        name: "create_token_revocations",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS revoked_tokens (
+                jti TEXT PRIMARY KEY,
+                expires_at INTEGER NOT NULL,
+                revoked_at INTEGER NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS user_token_watermarks (
+                user_id TEXT PRIMARY KEY,
+                not_before INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS user_token_watermarks",
+            "DROP TABLE IF EXISTS revoked_tokens",
+        ],
+    },
+    // Holds the PKCE verifier, CSRF state and nonce of in-progress logins so
+    // the callback can be handled by any instance
+    Migration {
+        version: 5,
+        name: "create_oidc_sessions",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS oidc_sessions (
+                csrf_token TEXT PRIMARY KEY,
+                id TEXT NOT NULL,
+                pkce_verifier TEXT NOT NULL,
+                nonce TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS oidc_sessions",
+        ],
+    },
+    // Readings are categorized when they are written; this fills in rows
+    // stored before that, using the same thresholds as the domain layer
+    Migration {
+        version: 6,
+        name: "backfill_blood_pressure_categories",
+        up: &[
+            "UPDATE blood_pressure_readings SET category = CASE
+                WHEN systolic >= 180 OR diastolic >= 120 THEN 'HypertensiveCrisis'
+                WHEN systolic >= 140 OR diastolic >= 90 THEN 'Hypertension2'
+                WHEN systolic >= 130 OR diastolic >= 80 THEN 'Hypertension1'
+                WHEN systolic >= 120 THEN 'Elevated'
+                ELSE 'Normal'
+            END
+            WHERE category IS NULL",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_user_category
+            ON blood_pressure_readings (user_id, category)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_blood_pressure_readings_user_category",
+        ],
+    },
+    // Positions and arms are stored in lowercase, as the API serializes them;
+    // earlier free-text values in other cases would not match the filters.
+    // There is nothing to undo, the original casing carries no meaning.
+    Migration {
+        version: 7,
+        name: "normalize_measurement_position_and_arm",
+        up: &[
+            "UPDATE blood_pressure_readings
+            SET position = LOWER(TRIM(position)), arm = LOWER(TRIM(arm))
+            WHERE position IS NOT NULL OR arm IS NOT NULL",
+        ],
+        down: &[],
+    },
+    // Reading times become typed in the storage model. SQLite has no datetime
+    // type, so they stay text but are rewritten as fixed width UTC RFC 3339,
+    // which sorts in time order. A timestamp that does not parse fails the
+    // migration rather than being guessed.
+    Migration {
+        version: 8,
+        name: "typed_blood_pressure_timestamps",
+        up: &[
+            "UPDATE blood_pressure_readings SET timestamp = strftime('%Y-%m-%dT%H:%M:%fZ', timestamp)",
+            "ALTER TABLE blood_pressure_readings ADD COLUMN created_at TEXT",
+            "ALTER TABLE blood_pressure_readings ADD COLUMN updated_at TEXT",
+            "UPDATE blood_pressure_readings SET created_at = timestamp, updated_at = timestamp",
+        ],
+        down: &[
+            "ALTER TABLE blood_pressure_readings DROP COLUMN updated_at",
+            "ALTER TABLE blood_pressure_readings DROP COLUMN created_at",
+        ],
+    },
+    // Categories gained a hypotension level, and a hypertensive crisis under
+    // ACC/AHA 2017, the default guideline set, is strictly above 180/120.
+    // Readings written before then are brought in line with it.
+    Migration {
+        version: 9,
+        name: "recategorize_hypotension_and_crisis",
+        up: &[
+            "UPDATE blood_pressure_readings SET category = 'Hypotension'
+            WHERE category = 'Normal' AND (systolic < 90 OR diastolic < 60)",
+            "UPDATE blood_pressure_readings SET category = 'Hypertension2'
+            WHERE category = 'HypertensiveCrisis' AND systolic <= 180 AND diastolic <= 120",
+        ],
+        down: &[
+            "UPDATE blood_pressure_readings SET category = 'HypertensiveCrisis'
+            WHERE category = 'Hypertension2' AND (systolic >= 180 OR diastolic >= 120)",
+            "UPDATE blood_pressure_readings SET category = 'Normal'
+            WHERE category = 'Hypotension'",
+        ],
+    },
+    // Measurement sessions group readings taken a minute or so apart so they
+    // can be averaged. A reading belongs to at most one session, and removing
+    // a session leaves its readings in place.
+    Migration {
+        version: 10,
+        name: "create_blood_pressure_sessions",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_sessions (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                discard_first INTEGER NOT NULL DEFAULT 0,
+                notes TEXT,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_sessions_user
+            ON blood_pressure_sessions (user_id, created_at DESC)",
+            "CREATE TABLE IF NOT EXISTS blood_pressure_session_readings (
+                reading_id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL REFERENCES blood_pressure_sessions (id) ON DELETE CASCADE
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_session_readings_session
+            ON blood_pressure_session_readings (session_id)",
+        ],
+        down: &[
+            "DROP INDEX IF EXISTS idx_blood_pressure_session_readings_session",
+            "DROP TABLE IF EXISTS blood_pressure_session_readings",
+            "DROP INDEX IF EXISTS idx_blood_pressure_sessions_user",
+            "DROP TABLE IF EXISTS blood_pressure_sessions",
+        ],
+    },
+    // Alerts raised when a reading is in the hypertensive crisis range or
+    // stage 2 keeps coming back. The readings behind an alert are linked so
+    // an alert can point at more than one of them.
+    Migration {
+        version: 11,
+        name: "create_blood_pressure_alerts",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_alerts (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                message TEXT NOT NULL,
+                systolic INTEGER NOT NULL,
+                diastolic INTEGER NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_alerts_user
+            ON blood_pressure_alerts (user_id, created_at DESC)",
+            "CREATE TABLE IF NOT EXISTS blood_pressure_alert_readings (
+                alert_id TEXT NOT NULL REFERENCES blood_pressure_alerts (id) ON DELETE CASCADE,
+                reading_id TEXT NOT NULL,
+                PRIMARY KEY (alert_id, reading_id)
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS blood_pressure_alert_readings",
+            "DROP INDEX IF EXISTS idx_blood_pressure_alerts_user",
+            "DROP TABLE IF EXISTS blood_pressure_alerts",
+        ],
+    },
+    // User-defined alert rules, such as "three readings in a row above 150"
+    // or "no reading in three days". The condition is kept as JSON so new
+    // kinds of rules need no schema change. Alerts fired by a rule point
+    // back at it and keep doing so after the rule is deleted.
+    Migration {
+        version: 12,
+        name: "create_blood_pressure_alert_rules",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS blood_pressure_alert_rules (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                definition TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_blood_pressure_alert_rules_user
+            ON blood_pressure_alert_rules (user_id)",
+            "ALTER TABLE blood_pressure_alerts ADD COLUMN rule_id TEXT",
+        ],
+        down: &[
+            "ALTER TABLE blood_pressure_alerts DROP COLUMN rule_id",
+            "DROP INDEX IF EXISTS idx_blood_pressure_alert_rules_user",
+            "DROP TABLE IF EXISTS blood_pressure_alert_rules",
+        ],
+    },
+    // Each user has at most one set of goals, so the table is keyed by
+    // user. Every goal is optional; progress is computed from the readings
+    // when requested rather than stored.
+    Migration {
+        version: 13,
+        name: "create_user_goals",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS user_goals (
+                user_id TEXT PRIMARY KEY,
+                target_systolic INTEGER,
+                target_diastolic INTEGER,
+                readings_per_week INTEGER,
+                target_weight_kg REAL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS user_goals",
+        ],
+    },
+    // Log out everywhere compares watermarks against millisecond issue
+    // times, so a login in the same second as the logout stays valid.
+    // Existing watermarks in seconds are converted in place.
+    Migration {
+        version: 14,
+        name: "token_watermarks_in_milliseconds",
+        up: &[
+            "UPDATE user_token_watermarks SET not_before = not_before * 1000",
+        ],
+        down: &[
+            "UPDATE user_token_watermarks SET not_before = not_before / 1000",
+        ],
+    },
+    // Each user has at most one profile, keyed by user like their goals.
+    // The time zone is an IANA name, so local times follow daylight saving.
+    Migration {
+        version: 15,
+        name: "create_user_profiles",
+        up: &[
+            "CREATE TABLE IF NOT EXISTS user_profiles (
+                user_id TEXT PRIMARY KEY,
+                time_zone TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+        ],
+        down: &[
+            "DROP TABLE IF EXISTS user_profiles",
+        ],
+    },
+    // Users may categorize their readings with another guideline set than
+    // the deployment's. Existing profiles keep using the deployment's.
+    Migration {
+        version: 16,
+        name: "add_user_profile_guideline",
+        up: &[
+            "ALTER TABLE user_profiles ADD COLUMN guideline TEXT",
+        ],
+        down: &[
+            "ALTER TABLE user_profiles DROP COLUMN guideline",
+        ],
+    },
+    // What the pediatric thresholds need to know about a user, so it is not
+    // asked on every request. The date of birth is stored as YYYY-MM-DD text.
+    Migration {
+        version: 17,
+        name: "add_user_profile_patient_details",
+        up: &[
+            "ALTER TABLE user_profiles ADD COLUMN date_of_birth TEXT",
+            "ALTER TABLE user_profiles ADD COLUMN sex TEXT",
+            "ALTER TABLE user_profiles ADD COLUMN height_cm REAL",
+        ],
+        down: &[
+            "ALTER TABLE user_profiles DROP COLUMN height_cm",
+            "ALTER TABLE user_profiles DROP COLUMN sex",
+            "ALTER TABLE user_profiles DROP COLUMN date_of_birth",
+        ],
+    },
+];
 
 /// Run SQLite migrations
+///
+/// Applies every pending migration, each in its own transaction. Fails
+/// without changing anything if an applied migration was edited since.
 pub fn run_migrations(conn: &Connection) -> Result<(), String> {
     info!("Running SQLite migrations");
-    
-    create_blood_pressure_table(conn)?;
-    create_blood_pressure_index(conn)?;
-    
+
+    create_migrations_table(conn)?;
+    let applied = applied_migrations(conn)?;
+    super::verify_checksums(MIGRATIONS, &applied)?;
+
+    if applied.is_empty() {
+        upgrade_unversioned_schema(conn)?;
+    }
+
+    for migration in super::pending(MIGRATIONS, &applied) {
+        apply_migration(conn, migration)?;
+    }
+
     info!("SQLite migrations completed successfully");
     Ok(())
 }
 
-/// Create the blood pressure readings table
-fn create_blood_pressure_table(conn: &Connection) -> Result<(), String> {
-    info!("Creating blood_pressure_readings table if not exists");
-    
+/// Roll back applied migrations until the schema is at `target_version`
+///
+/// Each migration is reverted in its own transaction, newest first. Use a
+/// target of 0 to revert every migration.
+pub fn rollback_migrations(conn: &Connection, target_version: i64) -> Result<(), String> {
+    info!("Rolling back SQLite migrations to version {}", target_version);
+
+    create_migrations_table(conn)?;
+    let applied = applied_migrations(conn)?;
+
+    for migration in super::to_roll_back(MIGRATIONS, &applied, target_version)? {
+        revert_migration(conn, migration)?;
+    }
+
+    Ok(())
+}
+
+/// Report which migrations are applied and which are pending
+pub fn migration_status(conn: &Connection) -> Result<Vec<MigrationStatus>, String> {
+    create_migrations_table(conn)?;
+    let applied = applied_migrations(conn)?;
+
+    Ok(super::build_status(MIGRATIONS, &applied))
+}
+
+/// Create the table recording applied migrations
+fn create_migrations_table(conn: &Connection) -> Result<(), String> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS blood_pressure_readings (
-            id TEXT PRIMARY KEY,
-            systolic INTEGER NOT NULL,
-            diastolic INTEGER NOT NULL,
-            pulse INTEGER,
-            timestamp TEXT NOT NULL,
-            notes TEXT,
-            position TEXT,
-            arm TEXT,
-            device_id TEXT,
-            category TEXT
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TEXT NOT NULL
         )",
         [],
+    ).map_err(|e| format!("Failed to create schema_migrations table: {}", e))?;
+
+    Ok(())
+}
+
+/// Load the applied migrations, oldest first
+fn applied_migrations(conn: &Connection) -> Result<Vec<AppliedMigration>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT version, name, checksum, applied_at FROM schema_migrations ORDER BY version"
     ).map_err(|e| e.to_string())?;
-    
+
+    let applied = stmt.query_map([], |row| {
+        Ok(AppliedMigration {
+            version: row.get(0)?,
+            name: row.get(1)?,
+            checksum: row.get(2)?,
+            applied_at: row.get(3)?,
+        })
+    })
+    .map_err(|e| e.to_string())?
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|e| e.to_string())?;
+
+    Ok(applied)
+}
+
+/// Apply a migration and record it, all in one transaction
+fn apply_migration(conn: &Connection, migration: &Migration) -> Result<(), String> {
+    info!("Applying migration {} ({})", migration.version, migration.name);
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    for statement in migration.up {
+        tx.execute_batch(statement)
+            .map_err(|e| format!("Migration {} ({}) failed: {}", migration.version, migration.name, e))?;
+    }
+
+    tx.execute(
+        "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (?1, ?2, ?3, ?4)",
+        (migration.version, migration.name, migration.checksum(), chrono::Utc::now().to_rfc3339()),
+    ).map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Revert a migration and forget it, all in one transaction
+fn revert_migration(conn: &Connection, migration: &Migration) -> Result<(), String> {
+    warn!("Reverting migration {} ({})", migration.version, migration.name);
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    for statement in migration.down {
+        tx.execute_batch(statement)
+            .map_err(|e| format!("Reverting migration {} ({}) failed: {}", migration.version, migration.name, e))?;
+    }
+
+    tx.execute("DELETE FROM schema_migrations WHERE version = ?", [migration.version])
+        .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Bring a database created before migrations were versioned up to the first version
+///
+/// Old readings tables may be missing columns that migration 1 would have
+/// created; the `CREATE TABLE IF NOT EXISTS` there leaves them untouched.
+fn upgrade_unversioned_schema(conn: &Connection) -> Result<(), String> {
+    if !table_exists(conn, "blood_pressure_readings")? {
+        return Ok(());
+    }
+
+    info!("Upgrading unversioned blood_pressure_readings table");
+
+    // Rows written before the upgrade keep an empty owner and are therefore
+    // not visible to any user until they are reassigned
+    add_column_if_missing(conn, "user_id", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(conn, "deleted_at", "TEXT")?;
+
     Ok(())
 }
 
-/// Create index on timestamp for efficient filtering
-fn create_blood_pressure_index(conn: &Connection) -> Result<(), String> {
-    info!("Creating index on timestamp");
-    
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_blood_pressure_readings_timestamp 
-        ON blood_pressure_readings (timestamp DESC)",
-        [],
-    ).map_err(|e| format!("Failed to create index: {}", e))?;
-    
+/// Check whether a table exists
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, String> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+        [table],
+        |row| row.get(0),
+    ).map_err(|e| e.to_string())?;
+
+    Ok(count > 0)
+}
+
+/// Add a column to the readings table unless it already exists
+fn add_column_if_missing(conn: &Connection, column: &str, definition: &str) -> Result<(), String> {
+    let mut stmt = conn.prepare("PRAGMA table_info(blood_pressure_readings)")
+        .map_err(|e| e.to_string())?;
+    let exists = stmt.query_map([], |row| row.get::<_, String>(1))
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+
+    if !exists {
+        info!("Adding {} column to blood_pressure_readings", column);
+        conn.execute(
+            &format!("ALTER TABLE blood_pressure_readings ADD COLUMN {} {}", column, definition),
+            [],
+        ).map_err(|e| format!("Failed to add {} column: {}", column, e))?;
+    }
+
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_add_user_id_to_existing_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE blood_pressure_readings (
+                id TEXT PRIMARY KEY,
+                systolic INTEGER NOT NULL,
+                diastolic INTEGER NOT NULL,
+                pulse INTEGER,
+                timestamp TEXT NOT NULL,
+                notes TEXT,
+                position TEXT,
+                arm TEXT,
+                device_id TEXT,
+                category TEXT
+            )",
+            [],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO blood_pressure_readings (id, systolic, diastolic, timestamp, position, arm) 
+            VALUES ('legacy', 120, 80, '2024-01-01T08:00:00Z', 'Sitting', 'Left ')",
+            [],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO blood_pressure_readings (id, systolic, diastolic, timestamp) 
+            VALUES ('low', 85, 55, '2024-01-02T08:00:00Z'), ('boundary', 180, 100, '2024-01-03T08:00:00Z')",
+            [],
+        ).unwrap();
+
+        run_migrations(&conn).unwrap();
+        // Running again must be a no-op
+        run_migrations(&conn).unwrap();
+
+        let owner: String = conn.query_row(
+            "SELECT user_id FROM blood_pressure_readings WHERE id = 'legacy'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(owner, "");
+        
+        let deleted_at: Option<String> = conn.query_row(
+            "SELECT deleted_at FROM blood_pressure_readings WHERE id = 'legacy'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert!(deleted_at.is_none());
+
+        // 120/80 is stage 1 hypertension
+        let category: Option<String> = conn.query_row(
+            "SELECT category FROM blood_pressure_readings WHERE id = 'legacy'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(category.as_deref(), Some("Hypertension1"));
+
+        // Backfilled categories are realigned with the default guideline set
+        let categories: Vec<(String, String)> = conn.prepare(
+            "SELECT id, category FROM blood_pressure_readings WHERE id IN ('low', 'boundary') ORDER BY id",
+        ).unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(categories, vec![
+            ("boundary".to_string(), "Hypertension2".to_string()),
+            ("low".to_string(), "Hypotension".to_string()),
+        ]);
+
+        let (position, arm): (Option<String>, Option<String>) = conn.query_row(
+            "SELECT position, arm FROM blood_pressure_readings WHERE id = 'legacy'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(position.as_deref(), Some("sitting"));
+        assert_eq!(arm.as_deref(), Some("left"));
+
+        // Times are rewritten in the fixed width UTC form
+        let (timestamp, created_at, updated_at): (String, String, String) = conn.query_row(
+            "SELECT timestamp, created_at, updated_at FROM blood_pressure_readings WHERE id = 'legacy'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).unwrap();
+        assert_eq!(timestamp, "2024-01-01T08:00:00.000Z");
+        assert_eq!(created_at, timestamp);
+        assert_eq!(updated_at, timestamp);
+    }
+
+    #[test]
+    fn test_migrations_are_recorded_and_can_be_rolled_back() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        run_migrations(&conn).unwrap();
+        let status = migration_status(&conn).unwrap();
+        assert_eq!(status.len(), MIGRATIONS.len());
+        assert!(status.iter().all(|migration| migration.state == super::super::MigrationState::Applied));
+
+        rollback_migrations(&conn, 3).unwrap();
+        assert!(!table_exists(&conn, "oidc_sessions").unwrap());
+        assert!(table_exists(&conn, "users").unwrap());
+        let pending: Vec<i64> = migration_status(&conn).unwrap()
+            .into_iter()
+            .filter(|migration| migration.state == super::super::MigrationState::Pending)
+            .map(|migration| migration.version)
+            .collect();
+        assert_eq!(pending, vec![4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17]);
+
+        // Re-applying picks up where the rollback left off
+        run_migrations(&conn).unwrap();
+        assert!(table_exists(&conn, "oidc_sessions").unwrap());
+
+        // An edited migration is refused instead of silently diverging
+        conn.execute("UPDATE schema_migrations SET checksum = 'edited' WHERE version = 1", []).unwrap();
+        assert!(run_migrations(&conn).is_err());
+    }
+}
@@ -1,4 +1,3 @@
-use tracing::error;
 use thiserror::Error;
 
 // Database modules
@@ -7,6 +7,9 @@ pub struct BloodPressureReading {
     /// Unique identifier for the reading
     pub id: String,
     
+    /// Identifier of the user who owns the reading
+    pub user_id: String,
+    
     /// Systolic blood pressure (the higher number)
     pub systolic: u16,
     
@@ -20,7 +23,7 @@ pub struct BloodPressureReading {
     pub notes: Option<String>,
     
     /// When the reading was taken
-    pub timestamp: String,
+    pub timestamp: DateTime<Utc>,
     
     /// Optional position (e.g., sitting, standing)
     pub position: Option<String>,
@@ -30,6 +33,18 @@ pub struct BloodPressureReading {
     
     /// Optional device ID used for measurement
     pub device_id: Option<String>,
+
+    /// Blood pressure category computed from the measured values
+    #[serde(default)]
+    pub category: Option<String>,
+
+    /// When the reading was stored
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+
+    /// When the reading was last changed
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Input data for creating a new blood pressure reading
@@ -47,8 +62,8 @@ pub struct CreateBloodPressureRequest {
     /// Optional notes about the reading
     pub notes: Option<String>,
     
-    /// When the reading was taken. Defaults to current time if not provided.
-    pub timestamp: String,
+    /// When the reading was taken
+    pub timestamp: DateTime<Utc>,
     
     /// Optional position during measurement (e.g., sitting, standing)
     pub position: Option<String>,
@@ -58,23 +73,258 @@ pub struct CreateBloodPressureRequest {
     
     /// Optional device ID used for measurement
     pub device_id: Option<String>,
+
+    /// Blood pressure category computed from the measured values
+    pub category: Option<String>,
+}
+
+/// Filters for listing a user's blood pressure readings
+///
+/// Every filter is optional and a reading has to match all of the ones that
+/// are set. Dates are compared against the reading timestamp.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BloodPressureFilter {
+    /// Earliest timestamp to include
+    pub start_date: Option<DateTime<Utc>>,
+
+    /// Latest timestamp to include
+    pub end_date: Option<DateTime<Utc>>,
+
+    /// Only readings in this category
+    pub category: Option<String>,
+
+    /// Only readings taken in this position
+    pub position: Option<String>,
+
+    /// Only readings taken on this arm
+    pub arm: Option<String>,
+
+    /// Only readings taken with this device
+    pub device_id: Option<String>,
+}
+
+impl BloodPressureFilter {
+    /// Filter on a date range only
+    pub fn date_range(start_date: Option<DateTime<Utc>>, end_date: Option<DateTime<Utc>>) -> Self {
+        Self {
+            start_date,
+            end_date,
+            ..Default::default()
+        }
+    }
+
+    /// Whether a reading matches every filter that is set
+    pub fn matches(&self, reading: &BloodPressureReading) -> bool {
+        fn equals(filter: &Option<String>, value: &Option<String>) -> bool {
+            filter.is_none() || filter == value
+        }
+
+        self.start_date.is_none_or(|start| reading.timestamp >= start)
+            && self.end_date.is_none_or(|end| reading.timestamp <= end)
+            && equals(&self.category, &reading.category)
+            && equals(&self.position, &reading.position)
+            && equals(&self.arm, &reading.arm)
+            && equals(&self.device_id, &reading.device_id)
+    }
+}
+
+/// Storage model for a measurement session, a few readings taken together and averaged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloodPressureSession {
+    /// Unique identifier for the session
+    pub id: String,
+
+    /// Identifier of the user who owns the session
+    pub user_id: String,
+
+    /// Whether the first reading is left out of the average
+    pub discard_first: bool,
+
+    /// Optional notes about the session
+    pub notes: Option<String>,
+
+    /// Identifiers of the readings in the session
+    pub reading_ids: Vec<String>,
+
+    /// When the session was stored
+    pub created_at: DateTime<Utc>,
+}
+
+/// Input data for creating a measurement session together with its readings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBloodPressureSessionRequest {
+    /// Whether the first reading is left out of the average
+    pub discard_first: bool,
+
+    /// Optional notes about the session
+    pub notes: Option<String>,
+
+    /// The readings taken during the session, in the order they were taken
+    pub readings: Vec<CreateBloodPressureRequest>,
+}
+
+/// An entry of a user's readings listed with every session in place of its readings
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SessionAveragedEntry {
+    /// A reading outside any session, by ID
+    Reading(String),
+
+    /// A session standing in for all of its readings, by ID
+    Session(String),
+}
+
+impl SessionAveragedEntry {
+    /// ID of the reading or session
+    pub fn id(&self) -> &str {
+        match self {
+            SessionAveragedEntry::Reading(id) | SessionAveragedEntry::Session(id) => id,
+        }
+    }
+}
+
+/// Storage model for an alert raised by a user's readings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloodPressureAlert {
+    /// Unique identifier for the alert
+    pub id: String,
+
+    /// Identifier of the user whose readings raised the alert
+    pub user_id: String,
+
+    /// What raised the alert, e.g. "hypertensive_crisis"
+    pub kind: String,
+
+    /// Identifier of the user-defined rule that fired, if one did
+    pub rule_id: Option<String>,
+
+    /// Human-readable description of the alert
+    pub message: String,
+
+    /// Systolic pressure of the reading that raised the alert
+    pub systolic: u16,
+
+    /// Diastolic pressure of the reading that raised the alert
+    pub diastolic: u16,
+
+    /// Identifiers of the readings behind the alert
+    pub reading_ids: Vec<String>,
+
+    /// When the alert was raised
+    pub created_at: DateTime<Utc>,
+}
+
+/// Input data for recording an alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBloodPressureAlertRequest {
+    /// What raised the alert
+    pub kind: String,
+
+    /// Identifier of the user-defined rule that fired, if one did
+    pub rule_id: Option<String>,
+
+    /// Human-readable description of the alert
+    pub message: String,
+
+    /// Systolic pressure of the reading that raised the alert
+    pub systolic: u16,
+
+    /// Diastolic pressure of the reading that raised the alert
+    pub diastolic: u16,
+
+    /// Identifiers of the readings behind the alert
+    pub reading_ids: Vec<String>,
+}
+
+/// Filter for looking up a user's alerts
+///
+/// Every filter is optional and an alert has to match all of the ones that
+/// are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BloodPressureAlertFilter {
+    /// Only alerts of this kind
+    pub kind: Option<String>,
+
+    /// Only alerts fired by this rule
+    pub rule_id: Option<String>,
+
+    /// Only alerts raised at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl BloodPressureAlertFilter {
+    /// Whether an alert matches every filter that is set
+    pub fn matches(&self, alert: &BloodPressureAlert) -> bool {
+        self.kind.as_ref().is_none_or(|kind| &alert.kind == kind)
+            && self.rule_id.as_ref().is_none_or(|rule_id| alert.rule_id.as_ref() == Some(rule_id))
+            && self.since.is_none_or(|since| alert.created_at >= since)
+    }
+}
+
+/// Storage model for a user-defined alert rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloodPressureAlertRule {
+    /// Unique identifier for the rule
+    pub id: String,
+
+    /// Identifier of the user the rule watches
+    pub user_id: String,
+
+    /// Name of the rule, shown with the alerts it fires
+    pub name: String,
+
+    /// The rule's condition as JSON, interpreted by the domain layer
+    pub definition: String,
+
+    /// Whether the rule is evaluated
+    pub enabled: bool,
+
+    /// When the rule was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the rule was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input data for creating or replacing an alert rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBloodPressureAlertRuleRequest {
+    /// Name of the rule
+    pub name: String,
+
+    /// The rule's condition as JSON
+    pub definition: String,
+
+    /// Whether the rule is evaluated
+    pub enabled: bool,
 }
 
 /// Blood pressure category based on measurements
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum BloodPressureCategory {
+    /// Hypotension (systolic < 90 or diastolic < 60)
+    Hypotension,
+    
+    /// Optimal blood pressure (ESC/ESH: systolic < 120 and diastolic < 80)
+    Optimal,
+    
     /// Normal blood pressure (systolic < 120 and diastolic < 80)
     Normal,
     
     /// Elevated blood pressure (systolic 120-129 and diastolic < 80)
     Elevated,
     
-    /// Stage 1 Hypertension (systolic 130-139 or diastolic 80-89)
+    /// High-normal blood pressure (ESC/ESH: systolic 130-139 or diastolic 85-89)
+    HighNormal,
+    
+    /// Stage 1 or grade 1 hypertension (systolic 130-139 or diastolic 80-89)
     Hypertension1,
     
-    /// Stage 2 Hypertension (systolic ≥ 140 or diastolic ≥ 90)
+    /// Stage 2 or grade 2 hypertension (systolic ≥ 140 or diastolic ≥ 90)
     Hypertension2,
     
+    /// Grade 3 hypertension (ESC/ESH: systolic ≥ 180 or diastolic ≥ 110)
+    Hypertension3,
+    
     /// Hypertensive crisis (systolic > 180 and/or diastolic > 120)
     HypertensiveCrisis,
 }
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Storage model for a user's personal targets, one set per user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserGoals {
+    /// Identifier of the user the goals belong to
+    pub user_id: String,
+
+    /// Systolic pressure to stay below, in mmHg
+    pub target_systolic: Option<u16>,
+
+    /// Diastolic pressure to stay below, in mmHg
+    pub target_diastolic: Option<u16>,
+
+    /// Blood pressure readings to take each week
+    pub readings_per_week: Option<u32>,
+
+    /// Weight to reach, in kilograms
+    pub target_weight_kg: Option<f32>,
+
+    /// When the goals were first set
+    pub created_at: DateTime<Utc>,
+
+    /// When the goals were last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input data for setting a user's goals, replacing any set before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetUserGoalsRequest {
+    /// Systolic pressure to stay below, in mmHg
+    pub target_systolic: Option<u16>,
+
+    /// Diastolic pressure to stay below, in mmHg
+    pub target_diastolic: Option<u16>,
+
+    /// Blood pressure readings to take each week
+    pub readings_per_week: Option<u32>,
+
+    /// Weight to reach, in kilograms
+    pub target_weight_kg: Option<f32>,
+}
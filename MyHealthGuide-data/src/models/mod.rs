@@ -1,2 +1,7 @@
 // Data models for storage
-pub mod blood_pressure; 
\ No newline at end of file
+pub mod blood_pressure;
+pub mod weight;
+pub mod user;
+pub mod oidc_session;
+pub mod goal;
+pub mod profile;
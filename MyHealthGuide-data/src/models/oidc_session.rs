@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Storage model for an in-progress OIDC login
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcSession {
+    /// Unique identifier for the session
+    pub id: String,
+    
+    /// CSRF token sent as the `state` parameter, used to look the session up
+    pub csrf_token: String,
+    
+    /// PKCE verifier for the authorization code exchange
+    pub pkce_verifier: String,
+    
+    /// Nonce expected in the ID token
+    pub nonce: String,
+    
+    /// When the session was created, as a unix timestamp in seconds
+    pub created_at: i64,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Storage model for a user's profile, one per user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserProfile {
+    /// Identifier of the user the profile belongs to
+    pub user_id: String,
+
+    /// IANA time zone name, e.g. "Europe/Amsterdam"
+    pub time_zone: Option<String>,
+
+    /// Guideline set to categorize the user's readings with, e.g. "esc_esh_2018"
+    pub guideline: Option<String>,
+
+    /// Date of birth as YYYY-MM-DD
+    pub date_of_birth: Option<String>,
+
+    /// Sex for pediatric reference values, "male" or "female"
+    pub sex: Option<String>,
+
+    /// Latest height in centimeters
+    pub height_cm: Option<f64>,
+
+    /// When the profile was first set
+    pub created_at: DateTime<Utc>,
+
+    /// When the profile was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Input data for setting a user's profile, replacing any set before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetUserProfileRequest {
+    /// IANA time zone name, e.g. "Europe/Amsterdam"
+    pub time_zone: Option<String>,
+
+    /// Guideline set to categorize the user's readings with, e.g. "esc_esh_2018"
+    pub guideline: Option<String>,
+
+    /// Date of birth as YYYY-MM-DD
+    pub date_of_birth: Option<String>,
+
+    /// Sex for pediatric reference values, "male" or "female"
+    pub sex: Option<String>,
+
+    /// Latest height in centimeters
+    pub height_cm: Option<f64>,
+}
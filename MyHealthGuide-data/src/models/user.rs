@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Storage model for a registered user account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    /// Unique identifier for the user
+    pub id: String,
+    
+    /// Email address used to log in, stored lowercased
+    pub email: String,
+    
+    /// Argon2id hash of the user's password in PHC string format
+    pub password_hash: String,
+    
+    /// Optional display name
+    pub name: Option<String>,
+    
+    /// Roles granted to the user
+    pub roles: Vec<String>,
+    
+    /// When the account was created
+    pub created_at: String,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Storage model for a weight reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightReading {
+    /// Unique identifier for the reading
+    pub id: String,
+    
+    /// Identifier of the user who owns the reading
+    pub user_id: String,
+    
+    /// Weight in kilograms
+    pub weight_kg: f32,
+    
+    /// Optional body fat percentage
+    pub body_fat_percentage: Option<f32>,
+    
+    /// Optional muscle mass in kilograms
+    pub muscle_mass_kg: Option<f32>,
+    
+    /// Optional notes about the reading
+    pub notes: Option<String>,
+    
+    /// When the reading was taken
+    pub timestamp: String,
+}
+
+/// Input data for creating a new weight reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWeightRequest {
+    /// Weight in kilograms
+    pub weight_kg: f32,
+    
+    /// Optional body fat percentage
+    pub body_fat_percentage: Option<f32>,
+    
+    /// Optional muscle mass in kilograms
+    pub muscle_mass_kg: Option<f32>,
+    
+    /// Optional notes about the reading
+    pub notes: Option<String>,
+    
+    /// When the reading was taken. Defaults to current time if not provided.
+    pub timestamp: String,
+}
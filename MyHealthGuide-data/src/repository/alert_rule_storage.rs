@@ -0,0 +1,446 @@
+use chrono::{DateTime, Utc};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::models::blood_pressure::BloodPressureAlertRule;
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+#[cfg(feature = "sqlite")]
+use super::storage::{sqlite_time, sqlite_time_column};
+#[cfg(feature = "mysql_db")]
+use super::storage::{mysql_column, mysql_time, mysql_time_column};
+
+/// Alert rule columns
+const RULE_QUERY: &str =
+    "SELECT id, user_id, name, definition, enabled, created_at, updated_at FROM blood_pressure_alert_rules";
+
+/// Build a rule from its columns
+fn rule(
+    id: String,
+    user_id: String,
+    name: String,
+    definition: String,
+    enabled: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+) -> BloodPressureAlertRule {
+    BloodPressureAlertRule { id, user_id, name, definition, enabled, created_at, updated_at }
+}
+
+/// Map a SQLite row from `RULE_QUERY`
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_rule(row: &rusqlite::Row<'_>) -> rusqlite::Result<BloodPressureAlertRule> {
+    Ok(rule(
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        sqlite_time_column(row, 5)?,
+        sqlite_time_column(row, 6)?,
+    ))
+}
+
+/// Map a MySQL row from `RULE_QUERY`
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_rule(mut row: mysql::Row) -> Result<BloodPressureAlertRule, RepositoryError> {
+    Ok(rule(
+        mysql_column(&mut row, 0)?,
+        mysql_column(&mut row, 1)?,
+        mysql_column(&mut row, 2)?,
+        mysql_column(&mut row, 3)?,
+        mysql_column(&mut row, 4)?,
+        mysql_time_column(&mut row, 5)?,
+        mysql_time_column(&mut row, 6)?,
+    ))
+}
+
+/// Map a PostgreSQL row from `RULE_QUERY`
+#[cfg(feature = "postgres")]
+fn postgres_row_to_rule(row: &tokio_postgres::Row) -> BloodPressureAlertRule {
+    rule(row.get(0), row.get(1), row.get(2), row.get(3), row.get(4), row.get(5), row.get(6))
+}
+
+/// Database storage operations for user-defined alert rules
+///
+/// The rule's condition is stored as opaque JSON. Every query but the one
+/// the scheduled evaluation uses is scoped to the owning user.
+pub struct AlertRuleDatabaseStorage;
+
+impl AlertRuleDatabaseStorage {
+    /// Store a new alert rule
+    pub async fn store_rule(pool: &DatabasePool, rule: &BloodPressureAlertRule) -> Result<(), RepositoryError> {
+        debug!("Storing blood pressure alert rule in database: id={}", rule.id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT INTO blood_pressure_alert_rules (id, user_id, name, definition, enabled, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    (
+                        &rule.id,
+                        &rule.user_id,
+                        &rule.name,
+                        &rule.definition,
+                        rule.enabled,
+                        sqlite_time(&rule.created_at),
+                        sqlite_time(&rule.updated_at),
+                    ),
+                )?;
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT INTO blood_pressure_alert_rules (id, user_id, name, definition, enabled, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    vec![
+                        mysql::Value::from(&rule.id),
+                        mysql::Value::from(&rule.user_id),
+                        mysql::Value::from(&rule.name),
+                        mysql::Value::from(&rule.definition),
+                        mysql::Value::from(rule.enabled),
+                        mysql_time(&rule.created_at),
+                        mysql_time(&rule.updated_at),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO blood_pressure_alert_rules (id, user_id, name, definition, enabled, created_at, updated_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                    &[
+                        &rule.id,
+                        &rule.user_id,
+                        &rule.name,
+                        &rule.definition,
+                        &rule.enabled,
+                        &rule.created_at,
+                        &rule.updated_at,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a user's alert rules, oldest first, or only the one with the given ID
+    pub async fn get_rules(
+        pool: &DatabasePool,
+        user_id: &str,
+        id: Option<&Uuid>,
+    ) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+        debug!("Getting blood pressure alert rules from database: user={}, id={:?}", user_id, id);
+
+        let id = id.map(|id| id.to_string());
+        let order = "ORDER BY created_at, id";
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut params: Vec<&dyn rusqlite::ToSql> = vec![&user_id];
+                let mut query = format!("{} WHERE user_id = ?", RULE_QUERY);
+                if let Some(ref id) = id {
+                    query.push_str(" AND id = ?");
+                    params.push(id as &dyn rusqlite::ToSql);
+                }
+                query = format!("{} {}", query, order);
+
+                let mut stmt = conn.prepare(&query)?;
+                let rules = stmt.query_map(rusqlite::params_from_iter(params.iter()), sqlite_row_to_rule)?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(rules)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let mut params: Vec<mysql::Value> = vec![user_id.into()];
+                let mut query = format!("{} WHERE user_id = ?", RULE_QUERY);
+                if let Some(id) = id {
+                    query.push_str(" AND id = ?");
+                    params.push(id.into());
+                }
+                query = format!("{} {}", query, order);
+
+                let rows: Vec<mysql::Row> = conn.exec(&query, params)
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                rows.into_iter().map(mysql_row_to_rule).collect()
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = match id {
+                    Some(id) => client.query(
+                        &format!("{} WHERE user_id = $1 AND id = $2 {}", RULE_QUERY, order),
+                        &[&user_id, &id],
+                    ).await,
+                    None => client.query(
+                        &format!("{} WHERE user_id = $1 {}", RULE_QUERY, order),
+                        &[&user_id],
+                    ).await,
+                }.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.iter().map(postgres_row_to_rule).collect())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get the enabled alert rules of every user, grouped by user
+    pub async fn get_enabled_rules(pool: &DatabasePool) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+        debug!("Getting enabled blood pressure alert rules from database");
+
+        let query = format!("{} WHERE enabled = TRUE ORDER BY user_id, created_at, id", RULE_QUERY);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut stmt = conn.prepare(&query)?;
+                let rules = stmt.query_map([], sqlite_row_to_rule)?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(rules)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let rows: Vec<mysql::Row> = conn.query(&query)
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                rows.into_iter().map(mysql_row_to_rule).collect()
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(&query, &[]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.iter().map(postgres_row_to_rule).collect())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Replace the name, condition and state of one of a user's alert rules
+    ///
+    /// Returns `false` when the user owns no rule with that ID.
+    pub async fn update_rule(pool: &DatabasePool, rule: &BloodPressureAlertRule) -> Result<bool, RepositoryError> {
+        debug!("Updating blood pressure alert rule in database: id={}", rule.id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE blood_pressure_alert_rules SET name = ?1, definition = ?2, enabled = ?3, updated_at = ?4
+                     WHERE id = ?5 AND user_id = ?6",
+                    (
+                        &rule.name,
+                        &rule.definition,
+                        rule.enabled,
+                        sqlite_time(&rule.updated_at),
+                        &rule.id,
+                        &rule.user_id,
+                    ),
+                )?;
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                // MySQL reports changed rows rather than matched rows, so an
+                // update that repeats the stored values must still count
+                let exists: Option<u64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM blood_pressure_alert_rules WHERE id = ? AND user_id = ?",
+                    (&rule.id, &rule.user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                conn.exec_drop(
+                    "UPDATE blood_pressure_alert_rules SET name = ?, definition = ?, enabled = ?, updated_at = ?
+                     WHERE id = ? AND user_id = ?",
+                    vec![
+                        mysql::Value::from(&rule.name),
+                        mysql::Value::from(&rule.definition),
+                        mysql::Value::from(rule.enabled),
+                        mysql_time(&rule.updated_at),
+                        mysql::Value::from(&rule.id),
+                        mysql::Value::from(&rule.user_id),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(exists.unwrap_or(0) > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE blood_pressure_alert_rules SET name = $1, definition = $2, enabled = $3, updated_at = $4
+                     WHERE id = $5 AND user_id = $6",
+                    &[&rule.name, &rule.definition, &rule.enabled, &rule.updated_at, &rule.id, &rule.user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Delete one of a user's alert rules, keeping the alerts it fired
+    ///
+    /// Returns `false` when the user owns no rule with that ID.
+    pub async fn delete_rule(pool: &DatabasePool, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        debug!("Deleting blood pressure alert rule from database: id={}, user={}", id, user_id);
+
+        let id = id.to_string();
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let deleted = conn.execute(
+                    "DELETE FROM blood_pressure_alert_rules WHERE id = ?1 AND user_id = ?2",
+                    (&id, user_id),
+                )?;
+                Ok(deleted > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "DELETE FROM blood_pressure_alert_rules WHERE id = ? AND user_id = ?",
+                    (&id, user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(conn.affected_rows() > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let deleted = client.execute(
+                    "DELETE FROM blood_pressure_alert_rules WHERE id = $1 AND user_id = $2",
+                    &[&id, &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(deleted > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    /// A rule created `minutes_ago`, so rules sort by when they were created
+    fn rule(user_id: &str, name: &str, enabled: bool, minutes_ago: i64) -> BloodPressureAlertRule {
+        let created_at = Utc::now() - chrono::Duration::minutes(minutes_ago);
+        BloodPressureAlertRule {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            definition: r#"{"type":"no_reading","days":3}"#.to_string(),
+            enabled,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alert_rules_round_trip() {
+        let pool = sqlite_pool();
+        let mut reminder = rule("alice", "Reminder", true, 3);
+        let paused = rule("alice", "Paused", false, 2);
+        let other = rule("bob", "Reminder", true, 1);
+        for stored in [&reminder, &paused, &other] {
+            AlertRuleDatabaseStorage::store_rule(&pool, stored).await.unwrap();
+        }
+
+        let rules = AlertRuleDatabaseStorage::get_rules(&pool, "alice", None).await.unwrap();
+        assert_eq!(rules.iter().map(|rule| rule.name.as_str()).collect::<Vec<_>>(), vec!["Reminder", "Paused"]);
+        assert_eq!(rules[0].definition, reminder.definition);
+        assert!(!rules[1].enabled);
+        let enabled = AlertRuleDatabaseStorage::get_enabled_rules(&pool).await.unwrap();
+        assert_eq!(enabled.iter().map(|rule| rule.id.clone()).collect::<Vec<_>>(), vec![reminder.id.clone(), other.id.clone()]);
+
+        // Updates and deletes stay within the owner's rules
+        reminder.name = "Weekly reminder".to_string();
+        reminder.enabled = false;
+        assert!(AlertRuleDatabaseStorage::update_rule(&pool, &reminder).await.unwrap());
+        assert!(!AlertRuleDatabaseStorage::update_rule(&pool, &BloodPressureAlertRule {
+            user_id: "bob".to_string(),
+            ..reminder.clone()
+        }).await.unwrap());
+        let id = Uuid::parse_str(&reminder.id).unwrap();
+        let found = AlertRuleDatabaseStorage::get_rules(&pool, "alice", Some(&id)).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Weekly reminder");
+        assert!(!found[0].enabled);
+
+        assert!(!AlertRuleDatabaseStorage::delete_rule(&pool, "bob", &id).await.unwrap());
+        assert!(AlertRuleDatabaseStorage::delete_rule(&pool, "alice", &id).await.unwrap());
+        assert!(AlertRuleDatabaseStorage::get_rules(&pool, "alice", Some(&id)).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,403 @@
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::models::blood_pressure::{BloodPressureAlert, BloodPressureAlertFilter};
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+use super::storage::FilterValue;
+#[cfg(feature = "sqlite")]
+use super::storage::{sqlite_time, sqlite_time_column};
+#[cfg(feature = "mysql_db")]
+use super::storage::{mysql_column, mysql_time, mysql_time_column};
+
+/// Alert columns joined with the reading links, one row per reading
+const ALERT_QUERY: &str =
+    "SELECT a.id, a.user_id, a.kind, a.rule_id, a.message, a.systolic, a.diastolic, a.created_at, l.reading_id
+     FROM blood_pressure_alerts a
+     LEFT JOIN blood_pressure_alert_readings l ON l.alert_id = a.id";
+
+/// Newest alerts first, with each alert's rows kept together
+const ALERT_ORDER: &str = "ORDER BY a.created_at DESC, a.id, l.reading_id";
+
+/// An alert row from `ALERT_QUERY`, with the reading it was joined to
+type AlertRow = (BloodPressureAlert, Option<String>);
+
+/// Fold joined rows into alerts, keeping the order the alerts came in
+fn group_alert_rows(rows: impl IntoIterator<Item = AlertRow>) -> Vec<BloodPressureAlert> {
+    let mut alerts: Vec<BloodPressureAlert> = Vec::new();
+    for (alert, reading_id) in rows {
+        if alerts.last().is_none_or(|last| last.id != alert.id) {
+            alerts.push(alert);
+        }
+        if let (Some(last), Some(reading_id)) = (alerts.last_mut(), reading_id) {
+            last.reading_ids.push(reading_id);
+        }
+    }
+    alerts
+}
+
+/// Build an alert without readings from its columns
+fn alert(
+    id: String,
+    user_id: String,
+    kind: String,
+    rule_id: Option<String>,
+    message: String,
+    (systolic, diastolic): (i32, i32),
+    created_at: DateTime<Utc>,
+) -> BloodPressureAlert {
+    BloodPressureAlert {
+        id,
+        user_id,
+        kind,
+        rule_id,
+        message,
+        systolic: systolic as u16,
+        diastolic: diastolic as u16,
+        reading_ids: Vec::new(),
+        created_at,
+    }
+}
+
+/// Map a SQLite row from `ALERT_QUERY`
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_alert(row: &rusqlite::Row<'_>) -> rusqlite::Result<AlertRow> {
+    Ok((
+        alert(
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+            (row.get(5)?, row.get(6)?),
+            sqlite_time_column(row, 7)?,
+        ),
+        row.get(8)?,
+    ))
+}
+
+/// Map a MySQL row from `ALERT_QUERY`
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_alert(mut row: mysql::Row) -> Result<AlertRow, RepositoryError> {
+    Ok((
+        alert(
+            mysql_column(&mut row, 0)?,
+            mysql_column(&mut row, 1)?,
+            mysql_column(&mut row, 2)?,
+            mysql_column(&mut row, 3)?,
+            mysql_column(&mut row, 4)?,
+            (mysql_column(&mut row, 5)?, mysql_column(&mut row, 6)?),
+            mysql_time_column(&mut row, 7)?,
+        ),
+        mysql_column(&mut row, 8)?,
+    ))
+}
+
+/// Map a PostgreSQL row from `ALERT_QUERY`
+#[cfg(feature = "postgres")]
+fn postgres_row_to_alert(row: &tokio_postgres::Row) -> AlertRow {
+    (
+        alert(row.get(0), row.get(1), row.get(2), row.get(3), row.get(4), (row.get(5), row.get(6)), row.get(7)),
+        row.get(8),
+    )
+}
+
+/// SQL comparisons for the alert filters that are set, each paired with its value
+///
+/// The comparisons end where the backend specific placeholder goes.
+fn alert_filter_conditions(filter: &BloodPressureAlertFilter) -> Vec<(&'static str, FilterValue<'_>)> {
+    let values = [
+        ("kind =", &filter.kind),
+        ("rule_id =", &filter.rule_id),
+    ]
+    .into_iter()
+    .filter_map(|(condition, value)| value.as_deref().map(|value| (condition, FilterValue::Text(value))));
+
+    values
+        .chain(filter.since.map(|since| ("created_at >=", FilterValue::Time(since))))
+        .collect()
+}
+
+/// Database storage operations for alerts raised by readings
+///
+/// An alert row holds the alert itself and the reading links say which
+/// readings raised it. Every query is scoped to the owning user.
+pub struct AlertDatabaseStorage;
+
+impl AlertDatabaseStorage {
+    /// Store an alert and link its readings, all or nothing
+    pub async fn store_alert(pool: &DatabasePool, alert: &BloodPressureAlert) -> Result<(), RepositoryError> {
+        debug!("Storing blood pressure alert in database: id={}", alert.id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let mut conn = pool.get()?;
+                let tx = conn.transaction()?;
+
+                tx.execute(
+                    "INSERT INTO blood_pressure_alerts (id, user_id, kind, rule_id, message, systolic, diastolic, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    (
+                        &alert.id,
+                        &alert.user_id,
+                        &alert.kind,
+                        &alert.rule_id,
+                        &alert.message,
+                        alert.systolic,
+                        alert.diastolic,
+                        sqlite_time(&alert.created_at),
+                    ),
+                )?;
+                for reading_id in &alert.reading_ids {
+                    tx.execute(
+                        "INSERT INTO blood_pressure_alert_readings (alert_id, reading_id) VALUES (?1, ?2)",
+                        (&alert.id, reading_id),
+                    )?;
+                }
+
+                tx.commit()?;
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+                let mut tx = conn.start_transaction(mysql::TxOpts::default())
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                tx.exec_drop(
+                    "INSERT INTO blood_pressure_alerts (id, user_id, kind, rule_id, message, systolic, diastolic, created_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                    vec![
+                        mysql::Value::from(&alert.id),
+                        mysql::Value::from(&alert.user_id),
+                        mysql::Value::from(&alert.kind),
+                        mysql::Value::from(&alert.rule_id),
+                        mysql::Value::from(&alert.message),
+                        mysql::Value::from(alert.systolic),
+                        mysql::Value::from(alert.diastolic),
+                        mysql_time(&alert.created_at),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                for reading_id in &alert.reading_ids {
+                    tx.exec_drop(
+                        "INSERT INTO blood_pressure_alert_readings (alert_id, reading_id) VALUES (?, ?)",
+                        (&alert.id, reading_id),
+                    ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                }
+
+                tx.commit().map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let mut client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let tx = client.transaction().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                tx.execute(
+                    "INSERT INTO blood_pressure_alerts (id, user_id, kind, rule_id, message, systolic, diastolic, created_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                    &[
+                        &alert.id,
+                        &alert.user_id,
+                        &alert.kind,
+                        &alert.rule_id,
+                        &alert.message,
+                        &(alert.systolic as i32),
+                        &(alert.diastolic as i32),
+                        &alert.created_at,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                for reading_id in &alert.reading_ids {
+                    tx.execute(
+                        "INSERT INTO blood_pressure_alert_readings (alert_id, reading_id) VALUES ($1, $2)",
+                        &[&alert.id, reading_id],
+                    ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                }
+
+                tx.commit().await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get the alerts raised by a user's readings that match a filter, newest first
+    ///
+    /// The limit is taken from the alerts before they are joined to their
+    /// readings, so `limit` counts alerts.
+    pub async fn get_alerts(
+        pool: &DatabasePool,
+        user_id: &str,
+        filter: &BloodPressureAlertFilter,
+        limit: Option<usize>,
+    ) -> Result<Vec<BloodPressureAlert>, RepositoryError> {
+        debug!("Getting blood pressure alerts from database: user={}, filter={:?}, limit={:?}", user_id, filter, limit);
+
+        let conditions = alert_filter_conditions(filter);
+        // MySQL allows no LIMIT in an IN subquery, so the alerts are joined as a derived table
+        let limit_sql = limit.map(|limit| format!(" LIMIT {}", limit)).unwrap_or_default();
+        let alerts_query = |where_clauses: &[String]| {
+            format!(
+                "{} JOIN (SELECT id FROM blood_pressure_alerts WHERE {} ORDER BY created_at DESC, id{}) p ON p.id = a.id {}",
+                ALERT_QUERY, where_clauses.join(" AND "), limit_sql, ALERT_ORDER
+            )
+        };
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut where_clauses = vec!["user_id = ?".to_string()];
+                let mut params = vec![user_id.to_string()];
+                for (condition, value) in &conditions {
+                    where_clauses.push(format!("{} ?", condition));
+                    params.push(match value {
+                        FilterValue::Text(text) => text.to_string(),
+                        FilterValue::Time(time) => sqlite_time(time),
+                    });
+                }
+
+                let mut stmt = conn.prepare(&alerts_query(&where_clauses))?;
+                let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), sqlite_row_to_alert)?
+                    .collect::<Result<Vec<AlertRow>, _>>()?;
+
+                Ok(group_alert_rows(rows))
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let mut where_clauses = vec!["user_id = ?".to_string()];
+                let mut params: Vec<mysql::Value> = vec![user_id.into()];
+                for (condition, value) in &conditions {
+                    where_clauses.push(format!("{} ?", condition));
+                    params.push(match value {
+                        FilterValue::Text(text) => (*text).into(),
+                        FilterValue::Time(time) => mysql_time(time),
+                    });
+                }
+
+                let rows: Vec<mysql::Row> = conn.exec(alerts_query(&where_clauses), params)
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let rows = rows.into_iter()
+                    .map(mysql_row_to_alert)
+                    .collect::<Result<Vec<AlertRow>, _>>()?;
+
+                Ok(group_alert_rows(rows))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let mut where_clauses = vec!["user_id = $1".to_string()];
+                let mut params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = vec![Box::new(user_id.to_string())];
+                for (condition, value) in &conditions {
+                    params.push(match value {
+                        FilterValue::Text(text) => Box::new(text.to_string()),
+                        FilterValue::Time(time) => Box::new(*time),
+                    });
+                    where_clauses.push(format!("{} ${}", condition, params.len()));
+                }
+
+                let param_values: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+                    params.iter().map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync)).collect();
+                let rows = client.query(&alerts_query(&where_clauses), &param_values[..]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(group_alert_rows(rows.iter().map(postgres_row_to_alert)))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    fn alert(user_id: &str, reading_ids: &[&str], created_at: DateTime<Utc>) -> BloodPressureAlert {
+        BloodPressureAlert {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            kind: "repeated_stage_2".to_string(),
+            rule_id: None,
+            message: "Stage 2 readings keep coming back".to_string(),
+            systolic: 164,
+            diastolic: 102,
+            reading_ids: reading_ids.iter().map(|id| id.to_string()).collect(),
+            created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_alerts_round_trip() {
+        let pool = sqlite_pool();
+        let now = Utc::now();
+        let older = alert("alice", &["reading-1"], now - chrono::Duration::hours(1));
+        let newer = alert("alice", &["reading-2", "reading-3", "reading-4"], now);
+
+        for stored in [&older, &newer, &alert("bob", &["reading-5"], now)] {
+            AlertDatabaseStorage::store_alert(&pool, stored).await.unwrap();
+        }
+
+        let alerts = AlertDatabaseStorage::get_alerts(&pool, "alice", &BloodPressureAlertFilter::default(), None).await.unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].id, newer.id);
+        assert_eq!(alerts[0].reading_ids, newer.reading_ids);
+        assert_eq!((alerts[0].systolic, alerts[0].diastolic), (164, 102));
+        assert_eq!(alerts[0].kind, "repeated_stage_2");
+        assert_eq!(alerts[1].reading_ids, older.reading_ids);
+    }
+
+    #[tokio::test]
+    async fn test_alerts_are_filtered_and_limited_in_the_query() {
+        let pool = sqlite_pool();
+        let now = Utc::now();
+        let stale = alert("alice", &["reading-1"], now - chrono::Duration::days(8));
+        let older = alert("alice", &["reading-2", "reading-3"], now - chrono::Duration::hours(1));
+        let newer = alert("alice", &["reading-4", "reading-5"], now);
+        let crisis = BloodPressureAlert { kind: "hypertensive_crisis".to_string(), ..alert("alice", &["reading-6"], now) };
+
+        for stored in [&stale, &older, &newer, &crisis] {
+            AlertDatabaseStorage::store_alert(&pool, stored).await.unwrap();
+        }
+
+        let filter = BloodPressureAlertFilter {
+            kind: Some("repeated_stage_2".to_string()),
+            since: Some(now - chrono::Duration::days(7)),
+            ..Default::default()
+        };
+        let alerts = AlertDatabaseStorage::get_alerts(&pool, "alice", &filter, None).await.unwrap();
+        assert_eq!(alerts.iter().map(|alert| &alert.id).collect::<Vec<_>>(), [&newer.id, &older.id]);
+
+        let latest = AlertDatabaseStorage::get_alerts(&pool, "alice", &filter, Some(1)).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].reading_ids, newer.reading_ids);
+    }
+}
@@ -1,48 +1,204 @@
 use chrono::Utc;
-use tracing::{debug, error};
+use tracing::{debug, warn};
 use uuid::Uuid;
 use async_trait::async_trait;
 
-use crate::models::blood_pressure::{BloodPressureReading, CreateBloodPressureRequest, BloodPressureInsights};
+use crate::models::blood_pressure::{
+    BloodPressureReading, CreateBloodPressureRequest, BloodPressureFilter,
+    BloodPressureSession, CreateBloodPressureSessionRequest, BloodPressureAlert, BloodPressureAlertFilter, CreateBloodPressureAlertRequest,
+    BloodPressureAlertRule, CreateBloodPressureAlertRuleRequest, SessionAveragedEntry,
+};
 use crate::database::get_db_pool;
 use super::errors::RepositoryError;
 use super::in_memory::InMemoryStorage;
 use super::storage::DatabaseStorage;
+use super::session_storage::SessionDatabaseStorage;
+use super::alert_storage::AlertDatabaseStorage;
+use super::alert_rule_storage::AlertRuleDatabaseStorage;
+use super::strategy::{storage_strategy, unavailable, StorageStrategy};
+use super::outbox::{outbox, OutboxEntry};
 
 /// Repository trait for blood pressure readings
 #[async_trait]
 pub trait BloodPressureRepositoryTrait {
-    /// Create a new blood pressure reading from a request
-    async fn create(&self, request: CreateBloodPressureRequest) -> Result<BloodPressureReading, RepositoryError>;
+    /// Create a new blood pressure reading owned by the given user
+    async fn create(&self, user_id: &str, request: CreateBloodPressureRequest) -> Result<BloodPressureReading, RepositoryError>;
     
-    /// Get all blood pressure readings
-    async fn get_all(&self) -> Result<Vec<BloodPressureReading>, RepositoryError>;
+    /// Get all blood pressure readings owned by a user
+    async fn get_all(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, RepositoryError>;
     
-    /// Get the latest blood pressure reading
-    async fn get_latest(&self) -> Result<Option<BloodPressureReading>, RepositoryError>;
+    /// Get a user's latest blood pressure reading
+    async fn get_latest(&self, user_id: &str) -> Result<Option<BloodPressureReading>, RepositoryError>;
     
-    /// Get a blood pressure reading by ID
-    async fn get_by_id(&self, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError>;
+    /// Get a blood pressure reading by ID, provided it belongs to the user
+    async fn get_by_id(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError>;
     
-    /// Get filtered blood pressure readings
+    /// Get a user's blood pressure readings matching the filter
     async fn get_filtered(
         &self,
-        start_date: Option<String>,
-        end_date: Option<String>,
+        user_id: &str,
+        filter: BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
     ) -> Result<(Vec<BloodPressureReading>, usize), RepositoryError>;
     
-    /// Generate insights from blood pressure readings
-    async fn generate_insights(&self, timeframe_days: u32) -> Result<Option<BloodPressureInsights>, RepositoryError>;
+    /// Get the readings with the given IDs that a user owns, newest first
+    ///
+    /// IDs of deleted readings or of other users' readings are skipped.
+    async fn get_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureReading>, RepositoryError>;
+    
+    /// Page through a user's readings matching the filter, with every session in place of its readings
+    ///
+    /// A session is listed once when any of its readings matches, at the time
+    /// of its first reading. Returns the page and the total number of entries.
+    async fn get_session_averaged_entries(
+        &self,
+        user_id: &str,
+        filter: BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<SessionAveragedEntry>, usize), RepositoryError>;
+    
+    /// Replace the values of one of a user's readings, returning `None` if it does not exist
+    async fn update(&self, user_id: &str, id: Uuid, request: CreateBloodPressureRequest) -> Result<Option<BloodPressureReading>, RepositoryError>;
+    
+    /// Soft delete one of a user's readings, returning `false` if there was nothing to delete
+    async fn delete(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError>;
+    
+    /// Restore a soft deleted reading, returning `None` if the user owns no such reading
+    async fn restore(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError>;
+    
+    /// Store a new measurement session together with its readings, all or nothing
+    ///
+    /// Returns the session and its readings in the order they were given.
+    async fn create_session(&self, user_id: &str, request: CreateBloodPressureSessionRequest)
+        -> Result<(BloodPressureSession, Vec<BloodPressureReading>), RepositoryError>;
+    
+    /// Get a measurement session by ID, provided it belongs to the user
+    async fn get_session(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureSession>, RepositoryError>;
+    
+    /// Get a page of the measurement sessions owned by a user, newest first, with their total number
+    async fn get_sessions(&self, user_id: &str, limit: Option<usize>, offset: Option<usize>)
+        -> Result<(Vec<BloodPressureSession>, usize), RepositoryError>;
+    
+    /// Get the measurement sessions with the given IDs that a user owns, newest first
+    async fn get_sessions_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureSession>, RepositoryError>;
+    
+    /// Delete one of a user's sessions, keeping its readings, returning `false` if there was nothing to delete
+    async fn delete_session(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError>;
+    
+    /// Record an alert raised by some of a user's readings
+    async fn create_alert(&self, user_id: &str, request: CreateBloodPressureAlertRequest) -> Result<BloodPressureAlert, RepositoryError>;
+    
+    /// Get up to `limit` of the alerts raised by a user's readings that match a filter, newest first
+    async fn get_alerts(&self, user_id: &str, filter: BloodPressureAlertFilter, limit: Option<usize>) -> Result<Vec<BloodPressureAlert>, RepositoryError>;
+    
+    /// Create a new alert rule owned by the given user
+    async fn create_alert_rule(&self, user_id: &str, request: CreateBloodPressureAlertRuleRequest) -> Result<BloodPressureAlertRule, RepositoryError>;
+    
+    /// Get an alert rule by ID, provided it belongs to the user
+    async fn get_alert_rule(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureAlertRule>, RepositoryError>;
+    
+    /// Get all alert rules owned by a user, oldest first
+    async fn get_alert_rules(&self, user_id: &str) -> Result<Vec<BloodPressureAlertRule>, RepositoryError>;
+    
+    /// Get the enabled alert rules of every user, grouped by user
+    async fn get_enabled_alert_rules(&self) -> Result<Vec<BloodPressureAlertRule>, RepositoryError>;
+    
+    /// Replace one of a user's alert rules, returning `None` if it does not exist
+    async fn update_alert_rule(&self, user_id: &str, id: Uuid, request: CreateBloodPressureAlertRuleRequest) -> Result<Option<BloodPressureAlertRule>, RepositoryError>;
+    
+    /// Delete one of a user's alert rules, returning `false` if there was nothing to delete
+    async fn delete_alert_rule(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError>;
+}
+
+/// Build the stored form of a blood pressure reading
+///
+/// Both audit times are set to now; when the reading replaces an existing
+/// one, storage keeps the original `created_at`.
+fn build_reading(id: Uuid, user_id: &str, request: CreateBloodPressureRequest) -> BloodPressureReading {
+    let now = Utc::now();
+
+    BloodPressureReading {
+        id: id.to_string(),
+        user_id: user_id.to_string(),
+        systolic: request.systolic,
+        diastolic: request.diastolic,
+        pulse: request.pulse,
+        notes: request.notes,
+        timestamp: request.timestamp,
+        position: request.position,
+        arm: request.arm,
+        device_id: request.device_id,
+        category: request.category,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Build the stored form of a measurement session and of its readings
+fn build_session(id: Uuid, user_id: &str, request: CreateBloodPressureSessionRequest)
+    -> (BloodPressureSession, Vec<BloodPressureReading>)
+{
+    let readings: Vec<BloodPressureReading> = request.readings.into_iter()
+        .map(|reading| build_reading(Uuid::new_v4(), user_id, reading))
+        .collect();
+    let session = BloodPressureSession {
+        id: id.to_string(),
+        user_id: user_id.to_string(),
+        discard_first: request.discard_first,
+        notes: request.notes,
+        reading_ids: readings.iter().map(|reading| reading.id.clone()).collect(),
+        created_at: Utc::now(),
+    };
+
+    (session, readings)
+}
+
+/// Build the stored form of an alert
+fn build_alert(id: Uuid, user_id: &str, request: CreateBloodPressureAlertRequest) -> BloodPressureAlert {
+    BloodPressureAlert {
+        id: id.to_string(),
+        user_id: user_id.to_string(),
+        kind: request.kind,
+        rule_id: request.rule_id,
+        message: request.message,
+        systolic: request.systolic,
+        diastolic: request.diastolic,
+        reading_ids: request.reading_ids,
+        created_at: Utc::now(),
+    }
+}
+
+/// Build the stored form of an alert rule
+///
+/// Both audit times are set to now; when the rule replaces an existing one,
+/// the repository keeps the original `created_at`.
+fn build_alert_rule(id: Uuid, user_id: &str, request: CreateBloodPressureAlertRuleRequest) -> BloodPressureAlertRule {
+    let now = Utc::now();
+
+    BloodPressureAlertRule {
+        id: id.to_string(),
+        user_id: user_id.to_string(),
+        name: request.name,
+        definition: request.definition,
+        enabled: request.enabled,
+        created_at: now,
+        updated_at: now,
+    }
 }
 
 /// Repository for blood pressure readings.
 /// This implementation can use different database backends with SQLite as the default.
+///
+/// When a database is configured every operation goes to it, and failures are
+/// handled according to the configured [`StorageStrategy`]. The in-memory
+/// storage is only used when the application runs without a database.
 #[derive(Debug, Clone, Default)]
 pub struct BloodPressureRepository {
-    /// In-memory storage for when database is not available
+    /// In-memory storage for when no database is configured
     storage: InMemoryStorage,
 }
 
@@ -57,156 +213,106 @@ impl BloodPressureRepository {
 
 #[async_trait]
 impl BloodPressureRepositoryTrait for BloodPressureRepository {
-    /// Create a new blood pressure reading from a request
-    async fn create(&self, request: CreateBloodPressureRequest) -> Result<BloodPressureReading, RepositoryError> {
-        // Generate a unique ID
-        let id = Uuid::new_v4();
-        
-        // Create the reading object
-        let reading = BloodPressureReading {
-            id: id.to_string(),
-            systolic: request.systolic,
-            diastolic: request.diastolic,
-            pulse: request.pulse,
-            notes: request.notes,
-            timestamp: request.timestamp,
-            position: request.position,
-            arm: request.arm,
-            device_id: request.device_id,
-        };
-        
-        // Try to store in database first
-        match get_db_pool() {
-            Ok(pool) => {
-                debug!("Storing blood pressure reading in database: {}", reading.id);
-                match DatabaseStorage::store_reading(&pool, &reading).await {
-                    Ok(_) => Ok(reading),
-                    Err(e) => {
-                        error!("Failed to store reading in database: {}", e);
-                        // Fall back to in-memory storage
-                        self.storage.store_reading(&reading).await
-                    }
-                }
-            },
+    /// Create a new blood pressure reading owned by the given user
+    async fn create(&self, user_id: &str, request: CreateBloodPressureRequest) -> Result<BloodPressureReading, RepositoryError> {
+        let reading = build_reading(Uuid::new_v4(), user_id, request);
+
+        let pool = match get_db_pool() {
+            Ok(pool) => pool,
             Err(e) => {
-                // Database not available, use in-memory storage
+                // No database configured, use in-memory storage
                 debug!("Database not available ({}), using in-memory storage", e);
-                self.storage.store_reading(&reading).await
+                return self.storage.store_reading(&reading).await;
             }
+        };
+
+        debug!("Storing blood pressure reading in database: {}", reading.id);
+        match DatabaseStorage::store_reading(&pool, &reading).await {
+            Ok(_) => Ok(reading),
+            Err(e) if storage_strategy() == StorageStrategy::Outbox => {
+                warn!("Failed to store reading in database, recording it in the outbox: {}", e);
+                outbox().record(&OutboxEntry::BloodPressure(reading.clone())).await?;
+                Ok(reading)
+            },
+            Err(e) => Err(unavailable("store blood pressure reading", e)),
         }
     }
 
-    /// Get all blood pressure readings
-    async fn get_all(&self) -> Result<Vec<BloodPressureReading>, RepositoryError> {
-        // Try to get from database first
+    /// Get all blood pressure readings owned by a user
+    async fn get_all(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, RepositoryError> {
         match get_db_pool() {
             Ok(pool) => {
                 debug!("Getting all blood pressure readings from database");
-                match DatabaseStorage::get_all(&pool).await {
-                    Ok(readings) => Ok(readings),
-                    Err(e) => {
-                        error!("Failed to get readings from database: {}", e);
-                        // Fall back to in-memory storage
-                        self.storage.get_all().await
-                    }
-                }
+                DatabaseStorage::get_all(&pool, user_id).await
+                    .map_err(|e| unavailable("get blood pressure readings", e))
             },
             Err(e) => {
-                // Database not available or error occurred, use in-memory storage
+                // No database configured, use in-memory storage
                 debug!("Database not available ({}), using in-memory storage for get_all", e);
-                self.storage.get_all().await
+                self.storage.get_all(user_id).await
             }
         }
     }
-    
-    /// Get the latest blood pressure reading
-    async fn get_latest(&self) -> Result<Option<BloodPressureReading>, RepositoryError> {
-        // Try to get from database first
+
+    /// Get a user's latest blood pressure reading
+    async fn get_latest(&self, user_id: &str) -> Result<Option<BloodPressureReading>, RepositoryError> {
         match get_db_pool() {
             Ok(pool) => {
                 debug!("Getting latest blood pressure reading from database");
-                match DatabaseStorage::get_latest(&pool).await {
-                    Ok(reading) => Ok(reading),
-                    Err(e) => {
-                        error!("Failed to get latest reading from database: {}", e);
-                        // Fall back to in-memory storage
-                        self.storage.get_latest().await
-                    }
-                }
+                DatabaseStorage::get_latest(&pool, user_id).await
+                    .map_err(|e| unavailable("get latest blood pressure reading", e))
             },
             Err(e) => {
-                // Database not available or error occurred, use in-memory storage
+                // No database configured, use in-memory storage
                 debug!("Database not available ({}), using in-memory storage for get_latest", e);
-                self.storage.get_latest().await
+                self.storage.get_latest(user_id).await
             }
         }
     }
 
-    /// Get a blood pressure reading by ID
-    async fn get_by_id(&self, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
-        // Try to get from database first
+    /// Get a blood pressure reading by ID, provided it belongs to the user
+    async fn get_by_id(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
         match get_db_pool() {
             Ok(pool) => {
                 debug!("Getting blood pressure reading by ID from database: {}", id);
-                match DatabaseStorage::get_by_id(&pool, &id).await {
-                    Ok(reading) => Ok(reading),
-                    Err(e) => {
-                        error!("Failed to get reading by ID from database: {}", e);
-                        // Fall back to in-memory storage
-                        self.storage.get_by_id(&id).await
-                    }
-                }
+                DatabaseStorage::get_by_id(&pool, user_id, &id).await
+                    .map_err(|e| unavailable("get blood pressure reading", e))
             },
             Err(e) => {
-                // Database not available or error occurred, use in-memory storage
+                // No database configured, use in-memory storage
                 debug!("Database not available ({}), using in-memory storage for get_by_id", e);
-                self.storage.get_by_id(&id).await
+                self.storage.get_by_id(user_id, &id).await
             }
         }
     }
-    
-    /// Get filtered blood pressure readings
+
+    /// Get a user's blood pressure readings matching the filter
     async fn get_filtered(
         &self,
-        start_date: Option<String>,
-        end_date: Option<String>,
+        user_id: &str,
+        filter: BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
     ) -> Result<(Vec<BloodPressureReading>, usize), RepositoryError> {
-        // Try to get from database first
         match get_db_pool() {
             Ok(pool) => {
                 debug!("Getting filtered blood pressure readings from database");
-                match DatabaseStorage::get_filtered(
+                DatabaseStorage::get_filtered(
                     &pool,
-                    start_date.as_deref(),
-                    end_date.as_deref(),
+                    user_id,
+                    &filter,
                     limit,
                     offset,
                     sort_desc,
-                ).await {
-                    Ok(result) => Ok(result),
-                    Err(e) => {
-                        error!("Failed to get filtered readings from database: {}", e);
-                        // Fall back to in-memory storage
-                        self.storage.get_filtered(
-                            start_date.as_deref(),
-                            end_date.as_deref(),
-                            limit,
-                            offset,
-                            sort_desc,
-                        ).await
-                    }
-                }
+                ).await.map_err(|e| unavailable("get filtered blood pressure readings", e))
             },
             Err(e) => {
-                // Database not available or error occurred, use in-memory storage
+                // No database configured, use in-memory storage
                 debug!("Database not available ({}), using in-memory storage for get_filtered", e);
-                // Convert String to str for in-memory storage
                 self.storage.get_filtered(
-                    start_date.as_deref(),
-                    end_date.as_deref(),
+                    user_id,
+                    &filter,
                     limit,
                     offset,
                     sort_desc,
@@ -214,89 +320,349 @@ impl BloodPressureRepositoryTrait for BloodPressureRepository {
             }
         }
     }
-    
-    /// Generate insights from blood pressure readings
-    async fn generate_insights(&self, timeframe_days: u32) -> Result<Option<BloodPressureInsights>, RepositoryError> {
-        // Get readings within the timeframe
-        let start_date = chrono::Utc::now()
-            .checked_sub_signed(chrono::Duration::days(timeframe_days as i64))
-            .map(|dt| dt.to_rfc3339());
-            
-        let (readings, _) = self.get_filtered(
-            start_date,
-            None,
-            None,
-            None,
-            Some(false) // oldest first
-        ).await?;
-        
-        if readings.is_empty() {
+
+    /// Get the readings with the given IDs that a user owns, newest first
+    async fn get_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting blood pressure readings by ID from database");
+                DatabaseStorage::get_by_ids(&pool, user_id, ids).await
+                    .map_err(|e| unavailable("get blood pressure readings", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_by_ids", e);
+                self.storage.get_by_ids(user_id, ids).await
+            }
+        }
+    }
+
+    /// Page through a user's readings matching the filter, with every session in place of its readings
+    async fn get_session_averaged_entries(
+        &self,
+        user_id: &str,
+        filter: BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<SessionAveragedEntry>, usize), RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting session averaged blood pressure entries from database");
+                DatabaseStorage::get_session_averaged_entries(&pool, user_id, &filter, limit, offset, sort_desc).await
+                    .map_err(|e| unavailable("get session averaged blood pressure readings", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_session_averaged_entries", e);
+                self.storage.get_session_averaged_entries(user_id, &filter, limit, offset, sort_desc).await
+            }
+        }
+    }
+
+    /// Replace the values of one of a user's readings
+    async fn update(&self, user_id: &str, id: Uuid, request: CreateBloodPressureRequest) -> Result<Option<BloodPressureReading>, RepositoryError> {
+        let reading = build_reading(id, user_id, request);
+
+        let updated = match get_db_pool() {
+            Ok(pool) => {
+                debug!("Updating blood pressure reading in database: {}", id);
+                DatabaseStorage::update_reading(&pool, &reading).await
+                    .map_err(|e| unavailable("update blood pressure reading", e))?
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for update", e);
+                self.storage.update_reading(&reading).await?
+            }
+        };
+
+        if !updated {
             return Ok(None);
         }
-        
-        // Calculate averages
-        let count = readings.len();
-        let mut sum_systolic = 0.0;
-        let mut sum_diastolic = 0.0;
-        let mut sum_pulse = 0.0;
-        let mut pulse_count = 0;
-        
-        let mut max_systolic = i32::MIN;
-        let mut max_diastolic = i32::MIN;
-        let mut min_systolic = i32::MAX;
-        let mut min_diastolic = i32::MAX;
-        
-        for reading in &readings {
-            sum_systolic += reading.systolic as f64;
-            sum_diastolic += reading.diastolic as f64;
-            
-            if let Some(pulse) = reading.pulse {
-                sum_pulse += pulse as f64;
-                pulse_count += 1;
+
+        // Read it back for the created_at kept by storage
+        self.get_by_id(user_id, id).await
+    }
+
+    /// Soft delete one of a user's readings
+    async fn delete(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+        let deleted_at = Utc::now();
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Soft deleting blood pressure reading in database: {}", id);
+                DatabaseStorage::soft_delete_reading(&pool, user_id, &id, &deleted_at).await
+                    .map_err(|e| unavailable("delete blood pressure reading", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for delete", e);
+                self.storage.soft_delete_reading(user_id, &id).await
             }
-            
-            max_systolic = max_systolic.max(reading.systolic as i32);
-            max_diastolic = max_diastolic.max(reading.diastolic as i32);
-            min_systolic = min_systolic.min(reading.systolic as i32);
-            min_diastolic = min_diastolic.min(reading.diastolic as i32);
         }
-        
-        let avg_systolic = sum_systolic / count as f64;
-        let avg_diastolic = sum_diastolic / count as f64;
-        let avg_pulse = if pulse_count > 0 {
-            Some(sum_pulse / pulse_count as f64)
-        } else {
-            None
+    }
+
+    /// Restore a soft deleted reading
+    async fn restore(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
+        let restored = match get_db_pool() {
+            Ok(pool) => {
+                debug!("Restoring blood pressure reading in database: {}", id);
+                DatabaseStorage::restore_reading(&pool, user_id, &id).await
+                    .map_err(|e| unavailable("restore blood pressure reading", e))?
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for restore", e);
+                self.storage.restore_reading(user_id, &id).await?
+            }
         };
-        
-        // Determine category based on average readings
-        let category_str = if avg_systolic >= 180.0 || avg_diastolic >= 120.0 {
-            "HypertensiveCrisis"
-        } else if avg_systolic >= 140.0 || avg_diastolic >= 90.0 {
-            "Hypertension2"
-        } else if avg_systolic >= 130.0 || avg_diastolic >= 80.0 {
-            "Hypertension1"
-        } else if avg_systolic >= 120.0 && avg_diastolic < 80.0 {
-            "Elevated"
-        } else {
-            "Normal"
+
+        if !restored {
+            return Ok(None);
+        }
+
+        self.get_by_id(user_id, id).await
+    }
+
+    /// Store a new measurement session together with its readings, all or nothing
+    ///
+    /// Sessions are not recorded in the outbox: with the database down the
+    /// session fails and none of its readings are stored.
+    async fn create_session(&self, user_id: &str, request: CreateBloodPressureSessionRequest)
+        -> Result<(BloodPressureSession, Vec<BloodPressureReading>), RepositoryError>
+    {
+        let (session, readings) = build_session(Uuid::new_v4(), user_id, request);
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Storing blood pressure session in database: {}", session.id);
+                SessionDatabaseStorage::store_session(&pool, &session, &readings).await
+                    .map_err(|e| unavailable("store blood pressure session", e))?;
+                Ok((session, readings))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for create_session", e);
+                let session = self.storage.store_session(&session, &readings).await?;
+                Ok((session, readings))
+            }
+        }
+    }
+
+    /// Get a measurement session by ID, provided it belongs to the user
+    async fn get_session(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureSession>, RepositoryError> {
+        let sessions = self.get_sessions_by_ids(user_id, &[id.to_string()]).await?;
+        Ok(sessions.into_iter().next())
+    }
+
+    /// Get a page of the measurement sessions owned by a user, newest first, with their total number
+    async fn get_sessions(&self, user_id: &str, limit: Option<usize>, offset: Option<usize>)
+        -> Result<(Vec<BloodPressureSession>, usize), RepositoryError>
+    {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting blood pressure sessions from database");
+                let page = async {
+                    let sessions = SessionDatabaseStorage::get_sessions(&pool, user_id, None, limit, offset).await?;
+                    let total = SessionDatabaseStorage::count_sessions(&pool, user_id).await?;
+                    Ok((sessions, total))
+                };
+                page.await.map_err(|e| unavailable("get blood pressure sessions", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_sessions", e);
+                let sessions = self.storage.get_sessions(user_id, None).await?;
+                let total = sessions.len();
+                let page = sessions.into_iter()
+                    .skip(offset.unwrap_or(0))
+                    .take(limit.unwrap_or(total))
+                    .collect();
+                Ok((page, total))
+            }
+        }
+    }
+
+    /// Get the measurement sessions with the given IDs that a user owns, newest first
+    async fn get_sessions_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureSession>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting blood pressure sessions by ID from database");
+                SessionDatabaseStorage::get_sessions(&pool, user_id, Some(ids), None, None).await
+                    .map_err(|e| unavailable("get blood pressure sessions", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_sessions_by_ids", e);
+                self.storage.get_sessions(user_id, Some(ids)).await
+            }
+        }
+    }
+
+    /// Delete one of a user's sessions, keeping its readings
+    async fn delete_session(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Deleting blood pressure session from database: {}", id);
+                SessionDatabaseStorage::delete_session(&pool, user_id, &id).await
+                    .map_err(|e| unavailable("delete blood pressure session", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for delete_session", e);
+                self.storage.delete_session(user_id, &id).await
+            }
+        }
+    }
+
+    /// Record an alert raised by some of a user's readings
+    ///
+    /// Like sessions, alerts are not recorded in the outbox.
+    async fn create_alert(&self, user_id: &str, request: CreateBloodPressureAlertRequest) -> Result<BloodPressureAlert, RepositoryError> {
+        let alert = build_alert(Uuid::new_v4(), user_id, request);
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Storing blood pressure alert in database: {}", alert.id);
+                AlertDatabaseStorage::store_alert(&pool, &alert).await
+                    .map_err(|e| unavailable("store blood pressure alert", e))?;
+                Ok(alert)
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for create_alert", e);
+                self.storage.store_alert(&alert).await
+            }
+        }
+    }
+
+    /// Get up to `limit` of the alerts raised by a user's readings that match a filter, newest first
+    async fn get_alerts(&self, user_id: &str, filter: BloodPressureAlertFilter, limit: Option<usize>) -> Result<Vec<BloodPressureAlert>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting blood pressure alerts from database");
+                AlertDatabaseStorage::get_alerts(&pool, user_id, &filter, limit).await
+                    .map_err(|e| unavailable("get blood pressure alerts", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_alerts", e);
+                self.storage.get_alerts(user_id, &filter, limit).await
+            }
+        }
+    }
+
+    /// Create a new alert rule owned by the given user
+    ///
+    /// Like sessions, alert rules are not recorded in the outbox.
+    async fn create_alert_rule(&self, user_id: &str, request: CreateBloodPressureAlertRuleRequest) -> Result<BloodPressureAlertRule, RepositoryError> {
+        let rule = build_alert_rule(Uuid::new_v4(), user_id, request);
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Storing blood pressure alert rule in database: {}", rule.id);
+                AlertRuleDatabaseStorage::store_rule(&pool, &rule).await
+                    .map_err(|e| unavailable("store blood pressure alert rule", e))?;
+                Ok(rule)
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for create_alert_rule", e);
+                self.storage.store_alert_rule(&rule).await
+            }
+        }
+    }
+
+    /// Get an alert rule by ID, provided it belongs to the user
+    async fn get_alert_rule(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureAlertRule>, RepositoryError> {
+        let rules = match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting blood pressure alert rule by ID from database: {}", id);
+                AlertRuleDatabaseStorage::get_rules(&pool, user_id, Some(&id)).await
+                    .map_err(|e| unavailable("get blood pressure alert rule", e))?
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_alert_rule", e);
+                self.storage.get_alert_rules(user_id, Some(&id)).await?
+            }
         };
-        
-        let insights = BloodPressureInsights {
-            avg_systolic,
-            avg_diastolic,
-            avg_pulse,
-            max_systolic,
-            max_diastolic,
-            min_systolic,
-            min_diastolic,
-            category: category_str.to_string(),
-            reading_count: count,
-            period_days: timeframe_days,
-            generated_at: Utc::now(),
+
+        Ok(rules.into_iter().next())
+    }
+
+    /// Get all alert rules owned by a user, oldest first
+    async fn get_alert_rules(&self, user_id: &str) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting blood pressure alert rules from database");
+                AlertRuleDatabaseStorage::get_rules(&pool, user_id, None).await
+                    .map_err(|e| unavailable("get blood pressure alert rules", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_alert_rules", e);
+                self.storage.get_alert_rules(user_id, None).await
+            }
+        }
+    }
+
+    /// Get the enabled alert rules of every user, grouped by user
+    async fn get_enabled_alert_rules(&self) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting enabled blood pressure alert rules from database");
+                AlertRuleDatabaseStorage::get_enabled_rules(&pool).await
+                    .map_err(|e| unavailable("get enabled blood pressure alert rules", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_enabled_alert_rules", e);
+                self.storage.get_enabled_alert_rules().await
+            }
+        }
+    }
+
+    /// Replace one of a user's alert rules
+    async fn update_alert_rule(&self, user_id: &str, id: Uuid, request: CreateBloodPressureAlertRuleRequest) -> Result<Option<BloodPressureAlertRule>, RepositoryError> {
+        let Some(existing) = self.get_alert_rule(user_id, id).await? else {
+            return Ok(None);
         };
-        
-        Ok(Some(insights))
+        let rule = BloodPressureAlertRule {
+            created_at: existing.created_at,
+            ..build_alert_rule(id, user_id, request)
+        };
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Updating blood pressure alert rule in database: {}", id);
+                let updated = AlertRuleDatabaseStorage::update_rule(&pool, &rule).await
+                    .map_err(|e| unavailable("update blood pressure alert rule", e))?;
+                Ok(updated.then_some(rule))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for update_alert_rule", e);
+                self.storage.store_alert_rule(&rule).await.map(Some)
+            }
+        }
+    }
+
+    /// Delete one of a user's alert rules, keeping the alerts it fired
+    async fn delete_alert_rule(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Deleting blood pressure alert rule from database: {}", id);
+                AlertRuleDatabaseStorage::delete_rule(&pool, user_id, &id).await
+                    .map_err(|e| unavailable("delete blood pressure alert rule", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for delete_alert_rule", e);
+                self.storage.delete_alert_rule(user_id, &id).await
+            }
+        }
     }
 }
 
@@ -304,10 +670,13 @@ impl BloodPressureRepositoryTrait for BloodPressureRepository {
 #[cfg(any(test, feature = "mock"))]
 pub mod tests {
     use super::*;
+    use crate::repository::in_memory::session_averaged_entries;
     
     /// Mock implementation of BloodPressureRepository for testing
     pub struct MockBloodPressureRepository {
         readings: Vec<BloodPressureReading>,
+        sessions: Vec<BloodPressureSession>,
+        alert_rules: Vec<BloodPressureAlertRule>,
     }
     
     impl Default for MockBloodPressureRepository {
@@ -319,47 +688,52 @@ pub mod tests {
     impl MockBloodPressureRepository {
         /// Create a new empty mock repository
         pub fn new() -> Self {
-            Self { readings: Vec::new() }
+            Self { readings: Vec::new(), sessions: Vec::new(), alert_rules: Vec::new() }
         }
         
         /// Create a mock repository with predefined readings
         pub fn with_readings(readings: Vec<BloodPressureReading>) -> Self {
-            Self { readings }
+            Self { readings, sessions: Vec::new(), alert_rules: Vec::new() }
+        }
+        
+        /// Add predefined sessions to the mock repository
+        pub fn with_sessions(mut self, sessions: Vec<BloodPressureSession>) -> Self {
+            self.sessions = sessions;
+            self
+        }
+        
+        /// Add predefined alert rules to the mock repository
+        pub fn with_alert_rules(mut self, alert_rules: Vec<BloodPressureAlertRule>) -> Self {
+            self.alert_rules = alert_rules;
+            self
+        }
+        
+        /// Iterate over the predefined readings owned by a user
+        fn readings_for<'a>(&'a self, user_id: &'a str) -> impl Iterator<Item = &'a BloodPressureReading> + 'a {
+            self.readings.iter().filter(move |reading| reading.user_id == user_id)
         }
     }
     
     #[async_trait]
     impl BloodPressureRepositoryTrait for MockBloodPressureRepository {
-        async fn create(&self, request: CreateBloodPressureRequest) -> Result<BloodPressureReading, RepositoryError> {
-            let reading = BloodPressureReading {
-                id: Uuid::new_v4().to_string(),
-                systolic: request.systolic,
-                diastolic: request.diastolic,
-                pulse: request.pulse,
-                notes: request.notes,
-                timestamp: request.timestamp,
-                position: request.position,
-                arm: request.arm,
-                device_id: request.device_id,
-            };
-            
-            Ok(reading)
+        async fn create(&self, user_id: &str, request: CreateBloodPressureRequest) -> Result<BloodPressureReading, RepositoryError> {
+            Ok(build_reading(Uuid::new_v4(), user_id, request))
         }
         
-        async fn get_all(&self) -> Result<Vec<BloodPressureReading>, RepositoryError> {
-            Ok(self.readings.clone())
+        async fn get_all(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+            Ok(self.readings_for(user_id).cloned().collect())
         }
         
-        async fn get_latest(&self) -> Result<Option<BloodPressureReading>, RepositoryError> {
-            let latest = self.readings.iter()
+        async fn get_latest(&self, user_id: &str) -> Result<Option<BloodPressureReading>, RepositoryError> {
+            let latest = self.readings_for(user_id)
                 .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
                 .cloned();
                 
             Ok(latest)
         }
         
-        async fn get_by_id(&self, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
-            let reading = self.readings.iter()
+        async fn get_by_id(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
+            let reading = self.readings_for(user_id)
                 .find(|r| r.id == id.to_string())
                 .cloned();
                 
@@ -368,8 +742,8 @@ pub mod tests {
         
         async fn get_filtered(
             &self,
-            start_date: Option<String>,
-            end_date: Option<String>,
+            user_id: &str,
+            filter: BloodPressureFilter,
             limit: Option<usize>,
             offset: Option<usize>,
             sort_desc: Option<bool>,
@@ -378,22 +752,8 @@ pub mod tests {
             let limit = limit.unwrap_or(usize::MAX);
             let sort_desc = sort_desc.unwrap_or(true);
             
-            let mut filtered: Vec<BloodPressureReading> = self.readings.iter()
-                .filter(|reading| {
-                    if let Some(start) = &start_date {
-                        if reading.timestamp < *start {
-                            return false;
-                        }
-                    }
-                    
-                    if let Some(end) = &end_date {
-                        if reading.timestamp > *end {
-                            return false;
-                        }
-                    }
-                    
-                    true
-                })
+            let mut filtered: Vec<BloodPressureReading> = self.readings_for(user_id)
+                .filter(|reading| filter.matches(reading))
                 .cloned()
                 .collect();
                 
@@ -419,25 +779,131 @@ pub mod tests {
             Ok((paged, total))
         }
         
-        async fn generate_insights(&self, timeframe_days: u32) -> Result<Option<BloodPressureInsights>, RepositoryError> {
-            if self.readings.is_empty() {
+        async fn get_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+            Ok(self.readings_for(user_id)
+                .filter(|reading| ids.contains(&reading.id))
+                .cloned()
+                .collect())
+        }
+        
+        async fn get_session_averaged_entries(
+            &self,
+            user_id: &str,
+            filter: BloodPressureFilter,
+            limit: Option<usize>,
+            offset: Option<usize>,
+            sort_desc: Option<bool>,
+        ) -> Result<(Vec<SessionAveragedEntry>, usize), RepositoryError> {
+            let readings: Vec<BloodPressureReading> = self.readings_for(user_id).cloned().collect();
+            let (sessions, _) = self.get_sessions(user_id, None, None).await?;
+            Ok(session_averaged_entries(&readings, &sessions, &filter, limit, offset, sort_desc))
+        }
+        
+        async fn update(&self, user_id: &str, id: Uuid, request: CreateBloodPressureRequest) -> Result<Option<BloodPressureReading>, RepositoryError> {
+            let Some(existing) = self.readings_for(user_id).find(|r| r.id == id.to_string()) else {
                 return Ok(None);
-            }
+            };
+            
+            Ok(Some(BloodPressureReading {
+                created_at: existing.created_at,
+                ..build_reading(id, user_id, request)
+            }))
+        }
+        
+        async fn delete(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+            let id = id.to_string();
+            Ok(self.readings_for(user_id).any(|r| r.id == id))
+        }
+        
+        async fn restore(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
+            self.get_by_id(user_id, id).await
+        }
+        
+        async fn create_session(&self, user_id: &str, request: CreateBloodPressureSessionRequest)
+            -> Result<(BloodPressureSession, Vec<BloodPressureReading>), RepositoryError>
+        {
+            Ok(build_session(Uuid::new_v4(), user_id, request))
+        }
+        
+        async fn get_session(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureSession>, RepositoryError> {
+            let id = id.to_string();
+            Ok(self.sessions.iter()
+                .find(|session| session.user_id == user_id && session.id == id)
+                .cloned())
+        }
+        
+        async fn get_sessions(&self, user_id: &str, limit: Option<usize>, offset: Option<usize>)
+            -> Result<(Vec<BloodPressureSession>, usize), RepositoryError>
+        {
+            let sessions: Vec<BloodPressureSession> = self.sessions.iter()
+                .filter(|session| session.user_id == user_id)
+                .cloned()
+                .collect();
+            let total = sessions.len();
+            let page = sessions.into_iter()
+                .skip(offset.unwrap_or(0))
+                .take(limit.unwrap_or(total))
+                .collect();
+            Ok((page, total))
+        }
+        
+        async fn get_sessions_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureSession>, RepositoryError> {
+            Ok(self.sessions.iter()
+                .filter(|session| session.user_id == user_id && ids.contains(&session.id))
+                .cloned()
+                .collect())
+        }
+        
+        async fn delete_session(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+            Ok(self.get_session(user_id, id).await?.is_some())
+        }
+        
+        async fn create_alert(&self, user_id: &str, request: CreateBloodPressureAlertRequest) -> Result<BloodPressureAlert, RepositoryError> {
+            Ok(build_alert(Uuid::new_v4(), user_id, request))
+        }
+        
+        async fn get_alerts(&self, _user_id: &str, _filter: BloodPressureAlertFilter, _limit: Option<usize>) -> Result<Vec<BloodPressureAlert>, RepositoryError> {
+            Ok(Vec::new())
+        }
+        
+        async fn create_alert_rule(&self, user_id: &str, request: CreateBloodPressureAlertRuleRequest) -> Result<BloodPressureAlertRule, RepositoryError> {
+            Ok(build_alert_rule(Uuid::new_v4(), user_id, request))
+        }
+        
+        async fn get_alert_rule(&self, user_id: &str, id: Uuid) -> Result<Option<BloodPressureAlertRule>, RepositoryError> {
+            let id = id.to_string();
+            Ok(self.alert_rules.iter()
+                .find(|rule| rule.user_id == user_id && rule.id == id)
+                .cloned())
+        }
+        
+        async fn get_alert_rules(&self, user_id: &str) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+            Ok(self.alert_rules.iter()
+                .filter(|rule| rule.user_id == user_id)
+                .cloned()
+                .collect())
+        }
+        
+        async fn get_enabled_alert_rules(&self) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+            Ok(self.alert_rules.iter()
+                .filter(|rule| rule.enabled)
+                .cloned()
+                .collect())
+        }
+        
+        async fn update_alert_rule(&self, user_id: &str, id: Uuid, request: CreateBloodPressureAlertRuleRequest) -> Result<Option<BloodPressureAlertRule>, RepositoryError> {
+            let Some(existing) = self.get_alert_rule(user_id, id).await? else {
+                return Ok(None);
+            };
             
-            // Simple mock implementation
-            Ok(Some(BloodPressureInsights {
-                avg_systolic: 120.0,
-                avg_diastolic: 80.0,
-                avg_pulse: Some(72.0),
-                max_systolic: 130,
-                max_diastolic: 85,
-                min_systolic: 110,
-                min_diastolic: 75,
-                category: "Normal".to_string(),
-                reading_count: self.readings.len(),
-                period_days: timeframe_days,
-                generated_at: Utc::now(),
+            Ok(Some(BloodPressureAlertRule {
+                created_at: existing.created_at,
+                ..build_alert_rule(id, user_id, request)
             }))
         }
+        
+        async fn delete_alert_rule(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+            Ok(self.get_alert_rule(user_id, id).await?.is_some())
+        }
     }
 } 
\ No newline at end of file
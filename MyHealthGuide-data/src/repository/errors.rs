@@ -40,6 +40,14 @@ pub enum RepositoryError {
     #[error("Reading not found: {0}")]
     NotFound(String),
     
+    /// Conflict with existing data, such as a duplicate unique key
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    
+    /// The configured database failed and the operation could not be completed
+    #[error("Storage unavailable: {0}")]
+    Unavailable(String),
+    
     /// Pagination error
     #[error("Pagination error: {0}")]
     Pagination(String),
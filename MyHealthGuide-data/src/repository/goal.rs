@@ -0,0 +1,144 @@
+use tracing::debug;
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::models::goal::{SetUserGoalsRequest, UserGoals};
+use crate::database::get_db_pool;
+use super::errors::RepositoryError;
+use super::in_memory::InMemoryGoalStorage;
+use super::goal_storage::GoalDatabaseStorage;
+use super::strategy::unavailable;
+
+/// Repository trait for users' goals
+#[async_trait]
+pub trait GoalRepositoryTrait {
+    /// Get a user's goals, if they have set any
+    async fn get_goals(&self, user_id: &str) -> Result<Option<UserGoals>, RepositoryError>;
+
+    /// Set a user's goals, replacing any set before
+    async fn set_goals(&self, user_id: &str, request: SetUserGoalsRequest) -> Result<UserGoals, RepositoryError>;
+
+    /// Clear a user's goals, returning `false` if they had none
+    async fn delete_goals(&self, user_id: &str) -> Result<bool, RepositoryError>;
+}
+
+/// Build the goals to store for a request, keeping when goals were first set
+fn goals_for(user_id: &str, request: SetUserGoalsRequest, existing: Option<UserGoals>) -> UserGoals {
+    let now = Utc::now();
+    UserGoals {
+        user_id: user_id.to_string(),
+        target_systolic: request.target_systolic,
+        target_diastolic: request.target_diastolic,
+        readings_per_week: request.readings_per_week,
+        target_weight_kg: request.target_weight_kg,
+        created_at: existing.map(|goals| goals.created_at).unwrap_or(now),
+        updated_at: now,
+    }
+}
+
+/// Repository for users' goals.
+/// Uses the configured database, or in-memory storage when none is
+/// configured. Goals are never written to the outbox: they are replaced as a
+/// whole, so a failed write is surfaced and can simply be retried.
+#[derive(Debug, Clone, Default)]
+pub struct GoalRepository {
+    /// In-memory storage for when no database is configured
+    storage: InMemoryGoalStorage,
+}
+
+impl GoalRepository {
+    /// Create a new repository
+    pub fn new() -> Self {
+        Self {
+            storage: InMemoryGoalStorage::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl GoalRepositoryTrait for GoalRepository {
+    /// Get a user's goals
+    async fn get_goals(&self, user_id: &str) -> Result<Option<UserGoals>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                GoalDatabaseStorage::get_goals(&pool, user_id).await
+                    .map_err(|e| unavailable("get goals", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_goals", e);
+                self.storage.get_goals(user_id).await
+            }
+        }
+    }
+
+    /// Set a user's goals
+    async fn set_goals(&self, user_id: &str, request: SetUserGoalsRequest) -> Result<UserGoals, RepositoryError> {
+        let goals = goals_for(user_id, request, self.get_goals(user_id).await?);
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Storing goals in database: user={}", user_id);
+                GoalDatabaseStorage::store_goals(&pool, &goals).await
+                    .map_err(|e| unavailable("store goals", e))?;
+                Ok(goals)
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for set_goals", e);
+                self.storage.store_goals(&goals).await
+            }
+        }
+    }
+
+    /// Clear a user's goals
+    async fn delete_goals(&self, user_id: &str) -> Result<bool, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                GoalDatabaseStorage::delete_goals(&pool, user_id).await
+                    .map_err(|e| unavailable("delete goals", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for delete_goals", e);
+                self.storage.delete_goals(user_id).await
+            }
+        }
+    }
+}
+
+/// Mock goal repository for testing
+#[cfg(any(test, feature = "mock"))]
+pub mod tests {
+    use super::*;
+
+    /// Mock implementation of GoalRepository for testing.
+    /// Keeps goals in memory, whatever database is configured.
+    #[derive(Default)]
+    pub struct MockGoalRepository {
+        storage: InMemoryGoalStorage,
+    }
+
+    impl MockGoalRepository {
+        /// Create a new empty mock repository
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    #[async_trait]
+    impl GoalRepositoryTrait for MockGoalRepository {
+        async fn get_goals(&self, user_id: &str) -> Result<Option<UserGoals>, RepositoryError> {
+            self.storage.get_goals(user_id).await
+        }
+
+        async fn set_goals(&self, user_id: &str, request: SetUserGoalsRequest) -> Result<UserGoals, RepositoryError> {
+            let existing = self.storage.get_goals(user_id).await?;
+            self.storage.store_goals(&goals_for(user_id, request, existing)).await
+        }
+
+        async fn delete_goals(&self, user_id: &str) -> Result<bool, RepositoryError> {
+            self.storage.delete_goals(user_id).await
+        }
+    }
+}
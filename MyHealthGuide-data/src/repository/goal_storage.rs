@@ -0,0 +1,315 @@
+use chrono::{DateTime, Utc};
+use tracing::debug;
+
+use crate::models::goal::UserGoals;
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+#[cfg(feature = "sqlite")]
+use super::storage::{sqlite_time, sqlite_time_column};
+#[cfg(feature = "mysql_db")]
+use super::storage::{mysql_column, mysql_time, mysql_time_column};
+
+/// Goal columns
+const GOAL_QUERY: &str =
+    "SELECT user_id, target_systolic, target_diastolic, readings_per_week, target_weight_kg, created_at, updated_at
+     FROM user_goals";
+
+/// Build goals from their columns
+fn goals(
+    user_id: String,
+    (target_systolic, target_diastolic): (Option<i32>, Option<i32>),
+    readings_per_week: Option<i32>,
+    target_weight_kg: Option<f64>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+) -> UserGoals {
+    UserGoals {
+        user_id,
+        target_systolic: target_systolic.map(|v| v as u16),
+        target_diastolic: target_diastolic.map(|v| v as u16),
+        readings_per_week: readings_per_week.map(|v| v as u32),
+        target_weight_kg: target_weight_kg.map(|v| v as f32),
+        created_at,
+        updated_at,
+    }
+}
+
+/// Map a SQLite row from `GOAL_QUERY`
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_goals(row: &rusqlite::Row<'_>) -> rusqlite::Result<UserGoals> {
+    Ok(goals(
+        row.get(0)?,
+        (row.get(1)?, row.get(2)?),
+        row.get(3)?,
+        row.get(4)?,
+        sqlite_time_column(row, 5)?,
+        sqlite_time_column(row, 6)?,
+    ))
+}
+
+/// Map a MySQL row from `GOAL_QUERY`
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_goals(mut row: mysql::Row) -> Result<UserGoals, RepositoryError> {
+    Ok(goals(
+        mysql_column(&mut row, 0)?,
+        (mysql_column(&mut row, 1)?, mysql_column(&mut row, 2)?),
+        mysql_column(&mut row, 3)?,
+        mysql_column(&mut row, 4)?,
+        mysql_time_column(&mut row, 5)?,
+        mysql_time_column(&mut row, 6)?,
+    ))
+}
+
+/// Map a PostgreSQL row from `GOAL_QUERY`
+#[cfg(feature = "postgres")]
+fn postgres_row_to_goals(row: &tokio_postgres::Row) -> UserGoals {
+    goals(
+        row.get(0),
+        (row.get(1), row.get(2)),
+        row.get(3),
+        row.get::<_, Option<f32>>(4).map(f64::from),
+        row.get(5),
+        row.get(6),
+    )
+}
+
+/// Database storage operations for users' goals
+///
+/// A user has at most one set of goals, keyed by their ID.
+pub struct GoalDatabaseStorage;
+
+impl GoalDatabaseStorage {
+    /// Store a user's goals, replacing any stored before but keeping when they were first set
+    pub async fn store_goals(pool: &DatabasePool, goals: &UserGoals) -> Result<(), RepositoryError> {
+        debug!("Storing goals in database: user={}", goals.user_id);
+
+        let target_systolic = goals.target_systolic.map(i32::from);
+        let target_diastolic = goals.target_diastolic.map(i32::from);
+        let readings_per_week = goals.readings_per_week.map(|v| v as i32);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT INTO user_goals
+                     (user_id, target_systolic, target_diastolic, readings_per_week, target_weight_kg, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                         target_systolic = excluded.target_systolic,
+                         target_diastolic = excluded.target_diastolic,
+                         readings_per_week = excluded.readings_per_week,
+                         target_weight_kg = excluded.target_weight_kg,
+                         updated_at = excluded.updated_at",
+                    (
+                        &goals.user_id,
+                        target_systolic,
+                        target_diastolic,
+                        readings_per_week,
+                        goals.target_weight_kg.map(f64::from),
+                        sqlite_time(&goals.created_at),
+                        sqlite_time(&goals.updated_at),
+                    ),
+                )?;
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT INTO user_goals
+                     (user_id, target_systolic, target_diastolic, readings_per_week, target_weight_kg, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)
+                     ON DUPLICATE KEY UPDATE
+                         target_systolic = VALUES(target_systolic),
+                         target_diastolic = VALUES(target_diastolic),
+                         readings_per_week = VALUES(readings_per_week),
+                         target_weight_kg = VALUES(target_weight_kg),
+                         updated_at = VALUES(updated_at)",
+                    vec![
+                        mysql::Value::from(&goals.user_id),
+                        mysql::Value::from(target_systolic),
+                        mysql::Value::from(target_diastolic),
+                        mysql::Value::from(readings_per_week),
+                        mysql::Value::from(goals.target_weight_kg.map(f64::from)),
+                        mysql_time(&goals.created_at),
+                        mysql_time(&goals.updated_at),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO user_goals
+                     (user_id, target_systolic, target_diastolic, readings_per_week, target_weight_kg, created_at, updated_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                         target_systolic = EXCLUDED.target_systolic,
+                         target_diastolic = EXCLUDED.target_diastolic,
+                         readings_per_week = EXCLUDED.readings_per_week,
+                         target_weight_kg = EXCLUDED.target_weight_kg,
+                         updated_at = EXCLUDED.updated_at",
+                    &[
+                        &goals.user_id,
+                        &target_systolic,
+                        &target_diastolic,
+                        &readings_per_week,
+                        &goals.target_weight_kg,
+                        &goals.created_at,
+                        &goals.updated_at,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a user's goals
+    pub async fn get_goals(pool: &DatabasePool, user_id: &str) -> Result<Option<UserGoals>, RepositoryError> {
+        debug!("Getting goals from database: user={}", user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut stmt = conn.prepare(&format!("{} WHERE user_id = ?", GOAL_QUERY))?;
+
+                match stmt.query_row([user_id], sqlite_row_to_goals) {
+                    Ok(goals) => Ok(Some(goals)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(RepositoryError::Sqlite(e)),
+                }
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let row: Option<mysql::Row> = conn.exec_first(format!("{} WHERE user_id = ?", GOAL_QUERY), (user_id,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                row.map(mysql_row_to_goals).transpose()
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(&format!("{} WHERE user_id = $1", GOAL_QUERY), &[&user_id]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.first().map(postgres_row_to_goals))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Delete a user's goals
+    ///
+    /// Returns `false` when the user had no goals set.
+    pub async fn delete_goals(pool: &DatabasePool, user_id: &str) -> Result<bool, RepositoryError> {
+        debug!("Deleting goals from database: user={}", user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let deleted = conn.execute("DELETE FROM user_goals WHERE user_id = ?1", [user_id])?;
+                Ok(deleted > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop("DELETE FROM user_goals WHERE user_id = ?", (user_id,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(conn.affected_rows() > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let deleted = client.execute("DELETE FROM user_goals WHERE user_id = $1", &[&user_id]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(deleted > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    #[tokio::test]
+    async fn test_goals_round_trip() {
+        let pool = sqlite_pool();
+        let set_at = Utc::now() - chrono::Duration::days(10);
+        let goals = UserGoals {
+            user_id: "alice".to_string(),
+            target_systolic: Some(130),
+            target_diastolic: Some(80),
+            readings_per_week: Some(7),
+            target_weight_kg: None,
+            created_at: set_at,
+            updated_at: set_at,
+        };
+        GoalDatabaseStorage::store_goals(&pool, &goals).await.unwrap();
+        assert_eq!(GoalDatabaseStorage::get_goals(&pool, "alice").await.unwrap().unwrap().readings_per_week, Some(7));
+        assert!(GoalDatabaseStorage::get_goals(&pool, "bob").await.unwrap().is_none());
+
+        // Replacing the goals keeps when they were first set
+        GoalDatabaseStorage::store_goals(&pool, &UserGoals {
+            target_systolic: None,
+            target_diastolic: None,
+            target_weight_kg: Some(72.5),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            ..goals.clone()
+        }).await.unwrap();
+        let stored = GoalDatabaseStorage::get_goals(&pool, "alice").await.unwrap().unwrap();
+        assert_eq!((stored.target_systolic, stored.target_weight_kg), (None, Some(72.5)));
+        assert_eq!(stored.created_at.timestamp_millis(), set_at.timestamp_millis());
+        assert!(stored.updated_at > set_at);
+
+        assert!(!GoalDatabaseStorage::delete_goals(&pool, "bob").await.unwrap());
+        assert!(GoalDatabaseStorage::delete_goals(&pool, "alice").await.unwrap());
+        assert!(GoalDatabaseStorage::get_goals(&pool, "alice").await.unwrap().is_none());
+    }
+}
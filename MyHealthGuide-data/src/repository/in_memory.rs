@@ -1,15 +1,84 @@
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::models::blood_pressure::BloodPressureReading;
+use crate::models::blood_pressure::{
+    BloodPressureAlert, BloodPressureAlertFilter, BloodPressureAlertRule, BloodPressureFilter, BloodPressureReading, BloodPressureSession,
+    SessionAveragedEntry,
+};
+use crate::models::weight::WeightReading;
+use crate::models::user::User;
+use crate::models::goal::UserGoals;
+use crate::models::profile::UserProfile;
 use super::errors::RepositoryError;
 
+/// Page through readings matching the filter, with every session in place of its readings
+///
+/// `readings` are all of a user's active readings and `sessions` all of their
+/// sessions. Entries are listed and timed as by
+/// [`DatabaseStorage::get_session_averaged_entries`](super::storage::DatabaseStorage::get_session_averaged_entries).
+pub(super) fn session_averaged_entries(
+    readings: &[BloodPressureReading],
+    sessions: &[BloodPressureSession],
+    filter: &BloodPressureFilter,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    sort_desc: Option<bool>,
+) -> (Vec<SessionAveragedEntry>, usize) {
+    let session_of: HashMap<&str, &str> = sessions.iter()
+        .flat_map(|session| session.reading_ids.iter().map(|id| (id.as_str(), session.id.as_str())))
+        .collect();
+    let entry_of = |reading: &BloodPressureReading| match session_of.get(reading.id.as_str()) {
+        Some(session_id) => SessionAveragedEntry::Session(session_id.to_string()),
+        None => SessionAveragedEntry::Reading(reading.id.clone()),
+    };
+
+    // A session is timed by its first reading, matching or not
+    let mut times: HashMap<SessionAveragedEntry, DateTime<Utc>> = HashMap::new();
+    for reading in readings {
+        let time = times.entry(entry_of(reading)).or_insert(reading.timestamp);
+        *time = (*time).min(reading.timestamp);
+    }
+
+    let mut entries: Vec<(DateTime<Utc>, SessionAveragedEntry)> = readings.iter()
+        .filter(|reading| filter.matches(reading))
+        .map(entry_of)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|entry| (times[&entry], entry))
+        .collect();
+    entries.sort_by(|(a_time, a), (b_time, b)| {
+        let cmp = if sort_desc.unwrap_or(true) { b_time.cmp(a_time) } else { a_time.cmp(b_time) };
+        cmp.then_with(|| a.id().cmp(b.id()))
+    });
+
+    let total = entries.len();
+    let page = entries.into_iter()
+        .map(|(_, entry)| entry)
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(total))
+        .collect();
+    (page, total)
+}
+
 /// In-memory storage implementation for blood pressure readings
 #[derive(Debug, Clone)]
 pub struct InMemoryStorage {
     /// Storage for blood pressure readings
     readings: Arc<Mutex<HashMap<String, BloodPressureReading>>>,
+    
+    /// Soft deleted readings, kept aside so they can be restored
+    deleted: Arc<Mutex<HashMap<String, BloodPressureReading>>>,
+    
+    /// Storage for measurement sessions
+    sessions: Arc<Mutex<HashMap<String, BloodPressureSession>>>,
+    
+    /// Storage for alerts raised by readings
+    alerts: Arc<Mutex<HashMap<String, BloodPressureAlert>>>,
+    
+    /// Storage for user-defined alert rules
+    alert_rules: Arc<Mutex<HashMap<String, BloodPressureAlertRule>>>,
 }
 
 impl Default for InMemoryStorage {
@@ -23,7 +92,122 @@ impl InMemoryStorage {
     pub fn new() -> Self {
         Self {
             readings: Arc::new(Mutex::new(HashMap::new())),
+            deleted: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            alerts: Arc::new(Mutex::new(HashMap::new())),
+            alert_rules: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Store a session together with its new readings in memory
+    ///
+    /// Both locks are taken before anything is stored, so either all of it
+    /// is stored or none of it.
+    pub async fn store_session(
+        &self,
+        session: &BloodPressureSession,
+        readings: &[BloodPressureReading],
+    ) -> Result<BloodPressureSession, RepositoryError> {
+        let mut reading_store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let mut store = self.sessions.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        for reading in readings {
+            reading_store.insert(reading.id.clone(), reading.clone());
+        }
+        store.insert(session.id.clone(), session.clone());
+        Ok(session.clone())
+    }
+
+    /// Get a user's sessions from memory, newest first, optionally only those with the given IDs
+    pub async fn get_sessions(&self, user_id: &str, ids: Option<&[String]>) -> Result<Vec<BloodPressureSession>, RepositoryError> {
+        let store = self.sessions.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let mut sessions: Vec<BloodPressureSession> = store.values()
+            .filter(|session| session.user_id == user_id)
+            .filter(|session| ids.is_none_or(|ids| ids.contains(&session.id)))
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+        Ok(sessions)
+    }
+
+    /// Delete one of a user's sessions from memory, leaving its readings
+    ///
+    /// Returns `false` when the user owns no session with that ID.
+    pub async fn delete_session(&self, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        let mut store = self.sessions.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let key = id.to_string();
+        if store.get(&key).is_none_or(|session| session.user_id != user_id) {
+            return Ok(false);
         }
+        store.remove(&key);
+        Ok(true)
+    }
+
+    /// Store an alert in memory
+    pub async fn store_alert(&self, alert: &BloodPressureAlert) -> Result<BloodPressureAlert, RepositoryError> {
+        let mut store = self.alerts.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        store.insert(alert.id.clone(), alert.clone());
+        Ok(alert.clone())
+    }
+
+    /// Get the alerts raised by a user's readings that match a filter from memory, newest first
+    pub async fn get_alerts(
+        &self,
+        user_id: &str,
+        filter: &BloodPressureAlertFilter,
+        limit: Option<usize>,
+    ) -> Result<Vec<BloodPressureAlert>, RepositoryError> {
+        let store = self.alerts.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let mut alerts: Vec<BloodPressureAlert> = store.values()
+            .filter(|alert| alert.user_id == user_id && filter.matches(alert))
+            .cloned()
+            .collect();
+        alerts.sort_by_key(|alert| std::cmp::Reverse(alert.created_at));
+        alerts.truncate(limit.unwrap_or(alerts.len()));
+        Ok(alerts)
+    }
+
+    /// Store an alert rule in memory, replacing any with the same ID
+    pub async fn store_alert_rule(&self, rule: &BloodPressureAlertRule) -> Result<BloodPressureAlertRule, RepositoryError> {
+        let mut store = self.alert_rules.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        store.insert(rule.id.clone(), rule.clone());
+        Ok(rule.clone())
+    }
+
+    /// Get a user's alert rules from memory, oldest first, or only the one with the given ID
+    pub async fn get_alert_rules(&self, user_id: &str, id: Option<&Uuid>) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+        let store = self.alert_rules.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let id = id.map(|id| id.to_string());
+        let mut rules: Vec<BloodPressureAlertRule> = store.values()
+            .filter(|rule| rule.user_id == user_id)
+            .filter(|rule| id.as_ref().is_none_or(|id| &rule.id == id))
+            .cloned()
+            .collect();
+        rules.sort_by_key(|rule| rule.created_at);
+        Ok(rules)
+    }
+
+    /// Get the enabled alert rules of every user from memory, grouped by user
+    pub async fn get_enabled_alert_rules(&self) -> Result<Vec<BloodPressureAlertRule>, RepositoryError> {
+        let store = self.alert_rules.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let mut rules: Vec<BloodPressureAlertRule> = store.values()
+            .filter(|rule| rule.enabled)
+            .cloned()
+            .collect();
+        rules.sort_by(|a, b| a.user_id.cmp(&b.user_id).then(a.created_at.cmp(&b.created_at)));
+        Ok(rules)
+    }
+
+    /// Delete one of a user's alert rules from memory
+    ///
+    /// Returns `false` when the user owns no rule with that ID.
+    pub async fn delete_alert_rule(&self, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        let mut store = self.alert_rules.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let key = id.to_string();
+        if store.get(&key).is_none_or(|rule| rule.user_id != user_id) {
+            return Ok(false);
+        }
+        store.remove(&key);
+        Ok(true)
     }
 
     /// Store a reading in memory
@@ -33,35 +217,123 @@ impl InMemoryStorage {
         Ok(reading.clone())
     }
 
-    /// Get all readings from memory
-    pub async fn get_all(&self) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+    /// Get all readings owned by a user from memory
+    pub async fn get_all(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, RepositoryError> {
         let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
-        let readings: Vec<BloodPressureReading> = store.values().cloned().collect();
+        let readings: Vec<BloodPressureReading> = store.values()
+            .filter(|reading| reading.user_id == user_id)
+            .cloned()
+            .collect();
         Ok(readings)
     }
 
-    /// Get the latest reading from memory
-    pub async fn get_latest(&self) -> Result<Option<BloodPressureReading>, RepositoryError> {
+    /// Get the readings with the given IDs that a user owns from memory, newest first
+    pub async fn get_by_ids(&self, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+        let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let mut readings: Vec<BloodPressureReading> = ids.iter()
+            .filter_map(|id| store.get(id))
+            .filter(|reading| reading.user_id == user_id)
+            .cloned()
+            .collect();
+        readings.sort_by_key(|reading| std::cmp::Reverse(reading.timestamp));
+        Ok(readings)
+    }
+
+    /// Page through a user's readings in memory, with every session in place of its readings
+    pub async fn get_session_averaged_entries(
+        &self,
+        user_id: &str,
+        filter: &BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<SessionAveragedEntry>, usize), RepositoryError> {
+        let readings = self.get_all(user_id).await?;
+        let sessions = self.get_sessions(user_id, None).await?;
+        Ok(session_averaged_entries(&readings, &sessions, filter, limit, offset, sort_desc))
+    }
+
+    /// Get a user's latest reading from memory
+    pub async fn get_latest(&self, user_id: &str) -> Result<Option<BloodPressureReading>, RepositoryError> {
         let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
         
         // Sort by timestamp and get the latest
-        let mut readings: Vec<BloodPressureReading> = store.values().cloned().collect();
-        readings.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        let mut readings: Vec<BloodPressureReading> = store.values()
+            .filter(|reading| reading.user_id == user_id)
+            .cloned()
+            .collect();
+        readings.sort_by_key(|reading| std::cmp::Reverse(reading.timestamp));
         
         Ok(readings.first().cloned())
     }
 
-    /// Get a reading by ID from memory
-    pub async fn get_by_id(&self, id: &Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
+    /// Get a reading by ID from memory, provided it belongs to the user
+    pub async fn get_by_id(&self, user_id: &str, id: &Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
         let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
-        Ok(store.get(&id.to_string()).cloned())
+        Ok(store.get(&id.to_string())
+            .filter(|reading| reading.user_id == user_id)
+            .cloned())
     }
 
-    /// Get filtered readings from memory
+    /// Overwrite one of a user's readings in memory
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn update_reading(&self, reading: &BloodPressureReading) -> Result<bool, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        match store.get_mut(&reading.id) {
+            Some(existing) if existing.user_id == reading.user_id => {
+                *existing = BloodPressureReading {
+                    created_at: existing.created_at,
+                    ..reading.clone()
+                };
+                Ok(true)
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Move one of a user's readings out of the active set
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn soft_delete_reading(&self, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let key = id.to_string();
+        if store.get(&key).is_none_or(|reading| reading.user_id != user_id) {
+            return Ok(false);
+        }
+        
+        let mut deleted = self.deleted.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        if let Some(reading) = store.remove(&key) {
+            deleted.insert(key, reading);
+        }
+        Ok(true)
+    }
+
+    /// Move a soft deleted reading back into the active set
+    ///
+    /// Returns `false` when the user owns no reading with that ID, deleted or not.
+    pub async fn restore_reading(&self, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let key = id.to_string();
+        if store.get(&key).is_some_and(|reading| reading.user_id == user_id) {
+            return Ok(true);
+        }
+        
+        let mut deleted = self.deleted.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        if deleted.get(&key).is_none_or(|reading| reading.user_id != user_id) {
+            return Ok(false);
+        }
+        if let Some(reading) = deleted.remove(&key) {
+            store.insert(key, reading);
+        }
+        Ok(true)
+    }
+
+    /// Get a user's filtered readings from memory
     pub async fn get_filtered(
         &self,
-        start_date: Option<&str>,
-        end_date: Option<&str>,
+        user_id: &str,
+        filter: &BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
@@ -69,23 +341,10 @@ impl InMemoryStorage {
         let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
         let sort_desc = sort_desc.unwrap_or(true);
         
-        // First collect and filter all readings
-        let mut readings: Vec<BloodPressureReading> = store.values().filter(|&reading| {
-                // Filter by date range if specified
-                if let Some(start_date) = start_date {
-                    if reading.timestamp.as_str() < start_date {
-                        return false;
-                    }
-                }
-                
-                if let Some(end_date) = end_date {
-                    if reading.timestamp.as_str() > end_date {
-                        return false;
-                    }
-                }
-                
-                true
-            }).cloned()
+        // Only ever return the caller's own readings
+        let mut readings: Vec<BloodPressureReading> = store.values()
+            .filter(|reading| reading.user_id == user_id && filter.matches(reading))
+            .cloned()
             .collect();
         
         // Sort by timestamp
@@ -111,4 +370,364 @@ impl InMemoryStorage {
             
         Ok((page, total))
     }
-} 
\ No newline at end of file
+}
+
+/// In-memory storage implementation for weight readings
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryWeightStorage {
+    /// Storage for weight readings
+    readings: Arc<Mutex<HashMap<String, WeightReading>>>,
+    
+    /// Soft deleted readings, kept aside so they can be restored
+    deleted: Arc<Mutex<HashMap<String, WeightReading>>>,
+}
+
+impl InMemoryWeightStorage {
+    /// Create a new in-memory weight storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a weight reading in memory
+    pub async fn store_reading(&self, reading: &WeightReading) -> Result<WeightReading, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        store.insert(reading.id.clone(), reading.clone());
+        Ok(reading.clone())
+    }
+
+    /// Get a weight reading by ID from memory, provided it belongs to the user
+    pub async fn get_by_id(&self, user_id: &str, id: &Uuid) -> Result<Option<WeightReading>, RepositoryError> {
+        let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        Ok(store.get(&id.to_string())
+            .filter(|reading| reading.user_id == user_id)
+            .cloned())
+    }
+
+    /// Overwrite one of a user's weight readings in memory
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn update_reading(&self, reading: &WeightReading) -> Result<bool, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        match store.get_mut(&reading.id) {
+            Some(existing) if existing.user_id == reading.user_id => {
+                *existing = reading.clone();
+                Ok(true)
+            },
+            _ => Ok(false),
+        }
+    }
+
+    /// Move one of a user's weight readings out of the active set
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn soft_delete_reading(&self, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let key = id.to_string();
+        if store.get(&key).is_none_or(|reading| reading.user_id != user_id) {
+            return Ok(false);
+        }
+        
+        let mut deleted = self.deleted.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        if let Some(reading) = store.remove(&key) {
+            deleted.insert(key, reading);
+        }
+        Ok(true)
+    }
+
+    /// Move a soft deleted weight reading back into the active set
+    ///
+    /// Returns `false` when the user owns no reading with that ID, deleted or not.
+    pub async fn restore_reading(&self, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        let mut store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        let key = id.to_string();
+        if store.get(&key).is_some_and(|reading| reading.user_id == user_id) {
+            return Ok(true);
+        }
+        
+        let mut deleted = self.deleted.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        if deleted.get(&key).is_none_or(|reading| reading.user_id != user_id) {
+            return Ok(false);
+        }
+        if let Some(reading) = deleted.remove(&key) {
+            store.insert(key, reading);
+        }
+        Ok(true)
+    }
+
+    /// Get a user's filtered weight readings from memory
+    pub async fn get_filtered(
+        &self,
+        user_id: &str,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<WeightReading>, usize), RepositoryError> {
+        let store = self.readings.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        
+        let mut readings: Vec<WeightReading> = store.values()
+            .filter(|reading| reading.user_id == user_id)
+            .filter(|reading| start_date.is_none_or(|start| reading.timestamp.as_str() >= start))
+            .filter(|reading| end_date.is_none_or(|end| reading.timestamp.as_str() <= end))
+            .cloned()
+            .collect();
+        
+        readings.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        if sort_desc.unwrap_or(true) {
+            readings.reverse();
+        }
+        
+        let total = readings.len();
+        let page = readings
+            .into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(total))
+            .collect();
+            
+        Ok((page, total))
+    }
+}
+
+/// In-memory storage implementation for user accounts
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryUserStorage {
+    /// Storage for users, keyed by ID
+    users: Arc<Mutex<HashMap<String, User>>>,
+}
+
+impl InMemoryUserStorage {
+    /// Create a new in-memory user storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a user in memory, failing with a conflict if the email is taken
+    pub async fn store_user(&self, user: &User) -> Result<User, RepositoryError> {
+        let mut store = self.users.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        if store.values().any(|existing| existing.email == user.email) {
+            return Err(RepositoryError::Conflict(format!("Email {} is already registered", user.email)));
+        }
+        store.insert(user.id.clone(), user.clone());
+        Ok(user.clone())
+    }
+
+    /// Get a user by email from memory
+    pub async fn get_by_email(&self, email: &str) -> Result<Option<User>, RepositoryError> {
+        let store = self.users.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        Ok(store.values().find(|user| user.email == email).cloned())
+    }
+
+    /// Get a user by ID from memory
+    pub async fn get_by_id(&self, id: &str) -> Result<Option<User>, RepositoryError> {
+        let store = self.users.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        Ok(store.get(id).cloned())
+    }
+}
+
+/// In-memory storage implementation for users' goals
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryGoalStorage {
+    /// Storage for goals, keyed by user ID
+    goals: Arc<Mutex<HashMap<String, UserGoals>>>,
+}
+
+impl InMemoryGoalStorage {
+    /// Create a new in-memory goal storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a user's goals in memory, replacing any stored before
+    pub async fn store_goals(&self, goals: &UserGoals) -> Result<UserGoals, RepositoryError> {
+        let mut store = self.goals.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        store.insert(goals.user_id.clone(), goals.clone());
+        Ok(goals.clone())
+    }
+
+    /// Get a user's goals from memory
+    pub async fn get_goals(&self, user_id: &str) -> Result<Option<UserGoals>, RepositoryError> {
+        let store = self.goals.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        Ok(store.get(user_id).cloned())
+    }
+
+    /// Delete a user's goals from memory
+    pub async fn delete_goals(&self, user_id: &str) -> Result<bool, RepositoryError> {
+        let mut store = self.goals.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        Ok(store.remove(user_id).is_some())
+    }
+}
+
+/// In-memory storage implementation for users' profiles
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryProfileStorage {
+    /// Storage for profiles, keyed by user ID
+    profiles: Arc<Mutex<HashMap<String, UserProfile>>>,
+}
+
+impl InMemoryProfileStorage {
+    /// Create a new in-memory profile storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a user's profile in memory, replacing any stored before
+    pub async fn store_profile(&self, profile: &UserProfile) -> Result<UserProfile, RepositoryError> {
+        let mut store = self.profiles.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        store.insert(profile.user_id.clone(), profile.clone());
+        Ok(profile.clone())
+    }
+
+    /// Get a user's profile from memory
+    pub async fn get_profile(&self, user_id: &str) -> Result<Option<UserProfile>, RepositoryError> {
+        let store = self.profiles.lock().map_err(|e| RepositoryError::MutexLock(e.to_string()))?;
+        Ok(store.get(user_id).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn reading_for(user_id: &str, timestamp: &str) -> BloodPressureReading {
+        BloodPressureReading {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            systolic: 120,
+            diastolic: 80,
+            pulse: None,
+            notes: None,
+            timestamp: timestamp.parse().unwrap(),
+            position: None,
+            arm: None,
+            device_id: None,
+            category: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readings_are_scoped_to_owner() {
+        let storage = InMemoryStorage::new();
+        let alice = reading_for("alice", "2024-01-01T08:00:00Z");
+        let bob = reading_for("bob", "2024-01-02T08:00:00Z");
+        storage.store_reading(&alice).await.unwrap();
+        storage.store_reading(&bob).await.unwrap();
+
+        let all = storage.get_all("alice").await.unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, alice.id);
+
+        let latest = storage.get_latest("alice").await.unwrap().unwrap();
+        assert_eq!(latest.id, alice.id);
+
+        let (page, total) = storage.get_filtered("alice", &BloodPressureFilter::default(), None, None, None).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(page[0].id, alice.id);
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore() {
+        let storage = InMemoryStorage::new();
+        let reading = reading_for("alice", "2024-01-01T08:00:00Z");
+        storage.store_reading(&reading).await.unwrap();
+        let id = Uuid::parse_str(&reading.id).unwrap();
+
+        // Other users can neither delete nor restore the reading
+        assert!(!storage.soft_delete_reading("bob", &id).await.unwrap());
+
+        assert!(storage.soft_delete_reading("alice", &id).await.unwrap());
+        assert!(storage.get_by_id("alice", &id).await.unwrap().is_none());
+        assert!(storage.get_all("alice").await.unwrap().is_empty());
+        // Deleting twice reports nothing to delete
+        assert!(!storage.soft_delete_reading("alice", &id).await.unwrap());
+
+        assert!(!storage.restore_reading("bob", &id).await.unwrap());
+        assert!(storage.restore_reading("alice", &id).await.unwrap());
+        assert!(storage.get_by_id("alice", &id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_update_reading_requires_owner() {
+        let storage = InMemoryStorage::new();
+        let reading = reading_for("alice", "2024-01-01T08:00:00Z");
+        storage.store_reading(&reading).await.unwrap();
+
+        let mut hijacked = reading.clone();
+        hijacked.user_id = "bob".to_string();
+        hijacked.systolic = 200;
+        assert!(!storage.update_reading(&hijacked).await.unwrap());
+
+        let mut corrected = reading.clone();
+        corrected.systolic = 125;
+        corrected.created_at = Utc::now() + chrono::Duration::days(1);
+        assert!(storage.update_reading(&corrected).await.unwrap());
+
+        let id = Uuid::parse_str(&reading.id).unwrap();
+        let stored = storage.get_by_id("alice", &id).await.unwrap().unwrap();
+        assert_eq!(stored.systolic, 125);
+        // The original creation time is kept
+        assert_eq!(stored.created_at, reading.created_at);
+    }
+
+    #[tokio::test]
+    async fn test_weight_readings_are_scoped_and_restorable() {
+        let storage = InMemoryWeightStorage::new();
+        let reading = WeightReading {
+            id: Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            weight_kg: 72.5,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: "2024-01-01T08:00:00Z".to_string(),
+        };
+        storage.store_reading(&reading).await.unwrap();
+        let id = Uuid::parse_str(&reading.id).unwrap();
+
+        let (page, total) = storage.get_filtered("bob", None, None, None, None, None).await.unwrap();
+        assert!(page.is_empty());
+        assert_eq!(total, 0);
+
+        assert!(!storage.soft_delete_reading("bob", &id).await.unwrap());
+        assert!(storage.soft_delete_reading("alice", &id).await.unwrap());
+        assert!(storage.get_by_id("alice", &id).await.unwrap().is_none());
+
+        assert!(storage.restore_reading("alice", &id).await.unwrap());
+        let (page, total) = storage.get_filtered("alice", None, None, None, None, None).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(page[0].id, reading.id);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_id_hides_other_users_readings() {
+        let storage = InMemoryStorage::new();
+        let reading = reading_for("alice", "2024-01-01T08:00:00Z");
+        storage.store_reading(&reading).await.unwrap();
+        let id = Uuid::parse_str(&reading.id).unwrap();
+
+        assert!(storage.get_by_id("alice", &id).await.unwrap().is_some());
+        assert!(storage.get_by_id("bob", &id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_user_email_must_be_unique() {
+        let storage = InMemoryUserStorage::new();
+        let user = User {
+            id: Uuid::new_v4().to_string(),
+            email: "alice@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            name: None,
+            roles: vec!["user".to_string()],
+            created_at: "2024-01-01T08:00:00Z".to_string(),
+        };
+        storage.store_user(&user).await.unwrap();
+
+        let duplicate = User { id: Uuid::new_v4().to_string(), ..user.clone() };
+        assert!(matches!(storage.store_user(&duplicate).await, Err(RepositoryError::Conflict(_))));
+        assert_eq!(storage.get_by_email("alice@example.com").await.unwrap().unwrap().id, user.id);
+        assert!(storage.get_by_id(&duplicate.id).await.unwrap().is_none());
+    }
+}
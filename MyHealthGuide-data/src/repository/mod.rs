@@ -1,13 +1,50 @@
 // Repository module structure
 pub mod errors;
 mod blood_pressure;
+mod weight;
+mod user;
+mod goal;
+mod profile;
 mod in_memory;
 mod storage;
+mod session_storage;
+mod alert_storage;
+mod alert_rule_storage;
+mod weight_storage;
+mod user_storage;
+mod goal_storage;
+mod profile_storage;
+mod revocation_storage;
+mod oidc_session_storage;
+pub mod strategy;
+pub mod outbox;
 
 // Re-export commonly used types
 pub use errors::RepositoryError;
 pub use blood_pressure::{BloodPressureRepository, BloodPressureRepositoryTrait};
+pub use weight::{WeightRepository, WeightRepositoryTrait};
+pub use user::{UserRepository, UserRepositoryTrait};
+pub use goal::{GoalRepository, GoalRepositoryTrait};
+pub use profile::{ProfileRepository, ProfileRepositoryTrait};
+pub use storage::DatabaseStorage;
+pub use session_storage::SessionDatabaseStorage;
+pub use alert_storage::AlertDatabaseStorage;
+pub use alert_rule_storage::AlertRuleDatabaseStorage;
+pub use weight_storage::WeightDatabaseStorage;
+pub use user_storage::UserDatabaseStorage;
+pub use goal_storage::GoalDatabaseStorage;
+pub use profile_storage::ProfileDatabaseStorage;
+pub use revocation_storage::RevocationDatabaseStorage;
+pub use oidc_session_storage::OidcSessionDatabaseStorage;
+pub use strategy::{StorageStrategy, storage_strategy, set_storage_strategy};
+pub use outbox::{Outbox, OutboxEntry, OutboxMetrics, outbox};
 
 // Re-export test modules for both testing and when mock feature is enabled
 #[cfg(any(test, feature = "mock"))]
-pub use blood_pressure::tests; 
\ No newline at end of file
+pub mod tests {
+    pub use super::blood_pressure::tests::*;
+    pub use super::weight::tests::*;
+    pub use super::user::tests::*;
+    pub use super::goal::tests::*;
+    pub use super::profile::tests::*;
+}
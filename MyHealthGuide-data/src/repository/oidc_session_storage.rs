@@ -0,0 +1,333 @@
+use tracing::debug;
+
+use crate::models::oidc_session::OidcSession;
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+
+/// Columns selected for every session query, in the order the row mapping
+/// helpers below expect them
+const SESSION_COLUMNS: &str = "id, csrf_token, pkce_verifier, nonce, created_at";
+
+/// Map a SQLite row selected with `SESSION_COLUMNS` to a session
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_session(row: &rusqlite::Row<'_>) -> rusqlite::Result<OidcSession> {
+    Ok(OidcSession {
+        id: row.get(0)?,
+        csrf_token: row.get(1)?,
+        pkce_verifier: row.get(2)?,
+        nonce: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+/// Row shape returned by MySQL for a query selecting `SESSION_COLUMNS`
+#[cfg(feature = "mysql_db")]
+type MySqlSessionRow = (String, String, String, String, i64);
+
+/// Map a MySQL row selected with `SESSION_COLUMNS` to a session
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_session(row: MySqlSessionRow) -> OidcSession {
+    let (id, csrf_token, pkce_verifier, nonce, created_at) = row;
+    OidcSession {
+        id,
+        csrf_token,
+        pkce_verifier,
+        nonce,
+        created_at,
+    }
+}
+
+/// Map a PostgreSQL row selected with `SESSION_COLUMNS` to a session
+#[cfg(feature = "postgres")]
+fn postgres_row_to_session(row: &tokio_postgres::Row) -> OidcSession {
+    OidcSession {
+        id: row.get(0),
+        csrf_token: row.get(1),
+        pkce_verifier: row.get(2),
+        nonce: row.get(3),
+        created_at: row.get(4),
+    }
+}
+
+/// Database storage operations for in-progress OIDC logins
+///
+/// Sessions are keyed by their CSRF token, which comes back from the
+/// provider as the `state` parameter of the callback.
+pub struct OidcSessionDatabaseStorage;
+
+impl OidcSessionDatabaseStorage {
+    /// Store a session, replacing any session with the same CSRF token
+    pub async fn store_session(pool: &DatabasePool, session: &OidcSession) -> Result<(), RepositoryError> {
+        debug!("Storing OIDC session in database: id={}", session.id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT OR REPLACE INTO oidc_sessions (csrf_token, id, pkce_verifier, nonce, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    (&session.csrf_token, &session.id, &session.pkce_verifier, &session.nonce, session.created_at),
+                )?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "REPLACE INTO oidc_sessions (csrf_token, id, pkce_verifier, nonce, created_at)
+                     VALUES (?, ?, ?, ?, ?)",
+                    (&session.csrf_token, &session.id, &session.pkce_verifier, &session.nonce, session.created_at),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO oidc_sessions (csrf_token, id, pkce_verifier, nonce, created_at)
+                     VALUES ($1, $2, $3, $4, $5)
+                     ON CONFLICT (csrf_token) DO UPDATE SET
+                        id = EXCLUDED.id,
+                        pkce_verifier = EXCLUDED.pkce_verifier,
+                        nonce = EXCLUDED.nonce,
+                        created_at = EXCLUDED.created_at",
+                    &[&session.csrf_token, &session.id, &session.pkce_verifier, &session.nonce, &session.created_at],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a session by its CSRF token
+    pub async fn get_session(pool: &DatabasePool, csrf_token: &str) -> Result<Option<OidcSession>, RepositoryError> {
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let query = format!("SELECT {} FROM oidc_sessions WHERE csrf_token = ?", SESSION_COLUMNS);
+                match conn.query_row(&query, [csrf_token], sqlite_row_to_session) {
+                    Ok(session) => Ok(Some(session)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(RepositoryError::Sqlite(e)),
+                }
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let query = format!("SELECT {} FROM oidc_sessions WHERE csrf_token = ?", SESSION_COLUMNS);
+                let row: Option<MySqlSessionRow> = conn.exec_first(query, (csrf_token,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(row.map(mysql_row_to_session))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let query = format!("SELECT {} FROM oidc_sessions WHERE csrf_token = $1", SESSION_COLUMNS);
+                let rows = client.query(&query, &[&csrf_token]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.first().map(postgres_row_to_session))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get every stored session
+    pub async fn list_sessions(pool: &DatabasePool) -> Result<Vec<OidcSession>, RepositoryError> {
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let query = format!("SELECT {} FROM oidc_sessions", SESSION_COLUMNS);
+                let mut stmt = conn.prepare(&query)?;
+                let sessions = stmt.query_map([], sqlite_row_to_session)?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(sessions)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let query = format!("SELECT {} FROM oidc_sessions", SESSION_COLUMNS);
+                let rows: Vec<MySqlSessionRow> = conn.query(query)
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.into_iter().map(mysql_row_to_session).collect())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let query = format!("SELECT {} FROM oidc_sessions", SESSION_COLUMNS);
+                let rows = client.query(&query, &[]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.iter().map(postgres_row_to_session).collect())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Delete a session by its CSRF token
+    ///
+    /// Deleting a session that does not exist is not an error.
+    pub async fn delete_session(pool: &DatabasePool, csrf_token: &str) -> Result<(), RepositoryError> {
+        debug!("Deleting OIDC session from database");
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute("DELETE FROM oidc_sessions WHERE csrf_token = ?", [csrf_token])?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop("DELETE FROM oidc_sessions WHERE csrf_token = ?", (csrf_token,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute("DELETE FROM oidc_sessions WHERE csrf_token = $1", &[&csrf_token]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Remove sessions created before `cutoff`
+    ///
+    /// Returns the number of sessions removed.
+    pub async fn delete_created_before(pool: &DatabasePool, cutoff: i64) -> Result<usize, RepositoryError> {
+        debug!("Removing expired OIDC sessions from database");
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let removed = conn.execute("DELETE FROM oidc_sessions WHERE created_at < ?", [cutoff])?;
+
+                Ok(removed)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop("DELETE FROM oidc_sessions WHERE created_at < ?", (cutoff,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(conn.affected_rows() as usize)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let removed = client.execute("DELETE FROM oidc_sessions WHERE created_at < $1", &[&cutoff]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(removed as usize)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    fn session(csrf_token: &str, created_at: i64) -> OidcSession {
+        OidcSession {
+            id: format!("id-{}", csrf_token),
+            csrf_token: csrf_token.to_string(),
+            pkce_verifier: "verifier".to_string(),
+            nonce: "nonce".to_string(),
+            created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sessions_round_trip_and_expire() {
+        let pool = sqlite_pool();
+
+        OidcSessionDatabaseStorage::store_session(&pool, &session("old", 100)).await.unwrap();
+        OidcSessionDatabaseStorage::store_session(&pool, &session("new", 500)).await.unwrap();
+
+        let stored = OidcSessionDatabaseStorage::get_session(&pool, "new").await.unwrap().unwrap();
+        assert_eq!(stored.id, "id-new");
+        assert_eq!(stored.created_at, 500);
+        assert!(OidcSessionDatabaseStorage::get_session(&pool, "missing").await.unwrap().is_none());
+
+        assert_eq!(OidcSessionDatabaseStorage::delete_created_before(&pool, 200).await.unwrap(), 1);
+        assert!(OidcSessionDatabaseStorage::get_session(&pool, "old").await.unwrap().is_none());
+
+        OidcSessionDatabaseStorage::delete_session(&pool, "new").await.unwrap();
+        assert!(OidcSessionDatabaseStorage::list_sessions(&pool).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+//! Durable outbox for readings the database could not store
+//!
+//! With the `outbox` storage strategy a new reading that fails to reach the
+//! database is appended to a JSON lines file instead of being rejected. The
+//! file is synced before the write is acknowledged, so the reading survives a
+//! restart, and [`Outbox::replay`] moves pending readings into the database
+//! once it is reachable again. Pending readings are not visible to reads
+//! until they have been replayed.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::database::DatabasePool;
+use crate::models::blood_pressure::BloodPressureReading;
+use crate::models::weight::WeightReading;
+use super::errors::RepositoryError;
+use super::storage::DatabaseStorage;
+use super::weight_storage::WeightDatabaseStorage;
+
+/// A write waiting to be replayed into the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "reading", rename_all = "snake_case")]
+pub enum OutboxEntry {
+    /// A new blood pressure reading
+    BloodPressure(BloodPressureReading),
+
+    /// A new weight reading
+    Weight(WeightReading),
+}
+
+impl OutboxEntry {
+    /// Whether this entry records the given write
+    fn is_same_write(&self, other: &OutboxEntry) -> bool {
+        match (self, other) {
+            (OutboxEntry::BloodPressure(a), OutboxEntry::BloodPressure(b)) => a.id == b.id,
+            (OutboxEntry::Weight(a), OutboxEntry::Weight(b)) => a.id == b.id,
+            _ => false,
+        }
+    }
+}
+
+/// Counters describing the outbox
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutboxMetrics {
+    /// Writes waiting to be replayed
+    pub pending: usize,
+
+    /// Writes recorded since the process started
+    pub recorded_total: u64,
+
+    /// Writes replayed into the database since the process started
+    pub replayed_total: u64,
+
+    /// Replay attempts that failed since the process started
+    pub failed_replays_total: u64,
+}
+
+/// Append-only file of writes waiting for the database
+pub struct Outbox {
+    path: PathBuf,
+    file_lock: Arc<Mutex<()>>,
+    replaying: AtomicBool,
+    recorded: AtomicU64,
+    replayed: AtomicU64,
+    failed_replays: AtomicU64,
+}
+
+/// Map a file or serialization error to unavailable storage
+fn outbox_error(err: impl std::fmt::Display) -> RepositoryError {
+    RepositoryError::Unavailable(format!("Storage outbox error: {}", err))
+}
+
+impl Outbox {
+    /// Create an outbox backed by the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file_lock: Arc::new(Mutex::new(())),
+            replaying: AtomicBool::new(false),
+            recorded: AtomicU64::new(0),
+            replayed: AtomicU64::new(0),
+            failed_replays: AtomicU64::new(0),
+        }
+    }
+
+    /// Path of the file backing the outbox
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Durably record a write
+    pub async fn record(&self, entry: &OutboxEntry) -> Result<(), RepositoryError> {
+        let line = serde_json::to_string(entry).map_err(outbox_error)?;
+        self.with_file(move |path| append_line(path, &line)).await?;
+
+        self.recorded.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Writes waiting to be replayed, oldest first
+    pub fn pending(&self) -> Result<Vec<OutboxEntry>, RepositoryError> {
+        let _guard = self.file_lock.lock()?;
+        read_entries(&self.path)
+    }
+
+    /// Run file operations on the blocking thread pool while holding the file lock
+    ///
+    /// Syncing waits for the disk, which would otherwise stall the async
+    /// worker serving the request.
+    async fn with_file<T, F>(&self, operation: F) -> Result<T, RepositoryError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Path) -> Result<T, RepositoryError> + Send + 'static,
+    {
+        let path = self.path.clone();
+        let file_lock = Arc::clone(&self.file_lock);
+
+        tokio::task::spawn_blocking(move || {
+            let _guard = file_lock.lock()?;
+            operation(&path)
+        }).await.map_err(outbox_error)?
+    }
+
+    /// Current outbox counters
+    pub fn metrics(&self) -> OutboxMetrics {
+        OutboxMetrics {
+            pending: self.pending().map(|entries| entries.len()).unwrap_or(0),
+            recorded_total: self.recorded.load(Ordering::Relaxed),
+            replayed_total: self.replayed.load(Ordering::Relaxed),
+            failed_replays_total: self.failed_replays.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Replay pending writes into the database, oldest first
+    ///
+    /// Stops at the first write the database rejects and keeps it and every
+    /// later write for the next attempt. Returns how many writes were
+    /// replayed; concurrent calls return 0 while a replay is running.
+    pub async fn replay(&self, pool: &DatabasePool) -> Result<usize, RepositoryError> {
+        if self.replaying.swap(true, Ordering::SeqCst) {
+            return Ok(0);
+        }
+
+        let result = self.replay_pending(pool).await;
+        self.replaying.store(false, Ordering::SeqCst);
+
+        if result.is_err() {
+            self.failed_replays.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    async fn replay_pending(&self, pool: &DatabasePool) -> Result<usize, RepositoryError> {
+        let pending = self.with_file(read_entries).await?;
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        debug!("Replaying {} writes from the storage outbox", pending.len());
+
+        let mut replayed = Vec::new();
+        let mut failure = None;
+        for entry in pending {
+            match replay_entry(pool, &entry).await {
+                Ok(()) => replayed.push(entry),
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                },
+            }
+        }
+
+        if !replayed.is_empty() {
+            self.remove(replayed.clone()).await?;
+            self.replayed.fetch_add(replayed.len() as u64, Ordering::Relaxed);
+            info!("Replayed {} writes from the storage outbox", replayed.len());
+        }
+
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(replayed.len()),
+        }
+    }
+
+    /// Remove replayed writes, keeping anything recorded in the meantime
+    async fn remove(&self, replayed: Vec<OutboxEntry>) -> Result<(), RepositoryError> {
+        self.with_file(move |path| {
+            let remaining: Vec<OutboxEntry> = read_entries(path)?
+                .into_iter()
+                .filter(|entry| !replayed.iter().any(|done| done.is_same_write(entry)))
+                .collect();
+
+            let mut contents = String::new();
+            for entry in &remaining {
+                contents.push_str(&serde_json::to_string(entry).map_err(outbox_error)?);
+                contents.push('\n');
+            }
+
+            // Write a new file and move it into place so a crash never leaves a
+            // half written outbox behind
+            let temp_path = path.with_extension("tmp");
+            let mut file = fs::File::create(&temp_path).map_err(outbox_error)?;
+            file.write_all(contents.as_bytes()).map_err(outbox_error)?;
+            file.sync_data().map_err(outbox_error)?;
+            fs::rename(&temp_path, path).map_err(outbox_error)
+        }).await
+    }
+}
+
+/// Append a line to the outbox file and sync it; the caller must hold the file lock
+fn append_line(path: &Path, line: &str) -> Result<(), RepositoryError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(outbox_error)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(outbox_error)?;
+    writeln!(file, "{}", line).map_err(outbox_error)?;
+    file.sync_data().map_err(outbox_error)
+}
+
+/// Read every entry; the caller must hold the file lock
+fn read_entries(path: &Path) -> Result<Vec<OutboxEntry>, RepositoryError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(path).map_err(outbox_error)?;
+    let mut entries = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            // Only a crash in the middle of an append can leave a broken line
+            Err(e) => warn!("Skipping unreadable storage outbox entry: {}", e),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Store a single write, skipping it if an earlier replay already stored it
+async fn replay_entry(pool: &DatabasePool, entry: &OutboxEntry) -> Result<(), RepositoryError> {
+    match entry {
+        OutboxEntry::BloodPressure(reading) => {
+            if let Ok(id) = Uuid::parse_str(&reading.id) {
+                if DatabaseStorage::get_by_id(pool, &reading.user_id, &id).await?.is_some() {
+                    return Ok(());
+                }
+            }
+            DatabaseStorage::store_reading(pool, reading).await
+        },
+        OutboxEntry::Weight(reading) => {
+            if let Ok(id) = Uuid::parse_str(&reading.id) {
+                if WeightDatabaseStorage::get_by_id(pool, &reading.user_id, &id).await?.is_some() {
+                    return Ok(());
+                }
+            }
+            WeightDatabaseStorage::store_reading(pool, reading).await
+        },
+    }
+}
+
+/// The outbox used by the repositories
+///
+/// Lives at `STORAGE_OUTBOX_PATH`, by default `data/storage_outbox.jsonl`.
+static OUTBOX: Lazy<Outbox> = Lazy::new(|| {
+    let path = std::env::var("STORAGE_OUTBOX_PATH")
+        .unwrap_or_else(|_| "data/storage_outbox.jsonl".to_string());
+    Outbox::new(path)
+});
+
+/// Get the outbox used by the repositories
+pub fn outbox() -> &'static Outbox {
+    &OUTBOX
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use chrono::Utc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    fn reading(systolic: u16) -> BloodPressureReading {
+        BloodPressureReading {
+            id: Uuid::new_v4().to_string(),
+            user_id: "user-1".to_string(),
+            systolic,
+            diastolic: 80,
+            pulse: None,
+            notes: None,
+            timestamp: "2024-01-01T08:00:00Z".parse().unwrap(),
+            position: None,
+            arm: None,
+            device_id: None,
+            category: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_writes_are_replayed_once() {
+        let path = std::env::temp_dir().join(format!("outbox-test-{}.jsonl", Uuid::new_v4()));
+        let outbox = Outbox::new(&path);
+        let first = reading(120);
+        let second = reading(130);
+
+        outbox.record(&OutboxEntry::BloodPressure(first.clone())).await.unwrap();
+        outbox.record(&OutboxEntry::BloodPressure(second.clone())).await.unwrap();
+        assert_eq!(outbox.metrics().pending, 2);
+
+        // A write that already reached the database is not stored twice
+        let pool = sqlite_pool();
+        DatabaseStorage::store_reading(&pool, &first).await.unwrap();
+
+        assert_eq!(outbox.replay(&pool).await.unwrap(), 2);
+        assert_eq!(DatabaseStorage::get_all(&pool, "user-1").await.unwrap().len(), 2);
+
+        let metrics = outbox.metrics();
+        assert_eq!(metrics.pending, 0);
+        assert_eq!(metrics.recorded_total, 2);
+        assert_eq!(metrics.replayed_total, 2);
+        assert_eq!(outbox.replay(&pool).await.unwrap(), 0);
+
+        let _ = fs::remove_file(path);
+    }
+}
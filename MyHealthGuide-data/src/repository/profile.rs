@@ -0,0 +1,123 @@
+use tracing::debug;
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::models::profile::{SetUserProfileRequest, UserProfile};
+use crate::database::get_db_pool;
+use super::errors::RepositoryError;
+use super::in_memory::InMemoryProfileStorage;
+use super::profile_storage::ProfileDatabaseStorage;
+use super::strategy::unavailable;
+
+/// Repository trait for users' profiles
+#[async_trait]
+pub trait ProfileRepositoryTrait {
+    /// Get a user's profile, if they have set one
+    async fn get_profile(&self, user_id: &str) -> Result<Option<UserProfile>, RepositoryError>;
+
+    /// Set a user's profile, replacing any set before
+    async fn set_profile(&self, user_id: &str, request: SetUserProfileRequest) -> Result<UserProfile, RepositoryError>;
+}
+
+/// Build the profile to store for a request, keeping when it was first set
+fn profile_for(user_id: &str, request: SetUserProfileRequest, existing: Option<UserProfile>) -> UserProfile {
+    let now = Utc::now();
+    UserProfile {
+        user_id: user_id.to_string(),
+        time_zone: request.time_zone,
+        guideline: request.guideline,
+        date_of_birth: request.date_of_birth,
+        sex: request.sex,
+        height_cm: request.height_cm,
+        created_at: existing.map(|profile| profile.created_at).unwrap_or(now),
+        updated_at: now,
+    }
+}
+
+/// Repository for users' profiles.
+/// Uses the configured database, or in-memory storage when none is
+/// configured. Like goals, profiles are replaced as a whole and never
+/// written to the outbox.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileRepository {
+    /// In-memory storage for when no database is configured
+    storage: InMemoryProfileStorage,
+}
+
+impl ProfileRepository {
+    /// Create a new repository
+    pub fn new() -> Self {
+        Self {
+            storage: InMemoryProfileStorage::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl ProfileRepositoryTrait for ProfileRepository {
+    /// Get a user's profile
+    async fn get_profile(&self, user_id: &str) -> Result<Option<UserProfile>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                ProfileDatabaseStorage::get_profile(&pool, user_id).await
+                    .map_err(|e| unavailable("get profile", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_profile", e);
+                self.storage.get_profile(user_id).await
+            }
+        }
+    }
+
+    /// Set a user's profile
+    async fn set_profile(&self, user_id: &str, request: SetUserProfileRequest) -> Result<UserProfile, RepositoryError> {
+        let profile = profile_for(user_id, request, self.get_profile(user_id).await?);
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Storing profile in database: user={}", user_id);
+                ProfileDatabaseStorage::store_profile(&pool, &profile).await
+                    .map_err(|e| unavailable("store profile", e))?;
+                Ok(profile)
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for set_profile", e);
+                self.storage.store_profile(&profile).await
+            }
+        }
+    }
+}
+
+/// Mock profile repository for testing
+#[cfg(any(test, feature = "mock"))]
+pub mod tests {
+    use super::*;
+
+    /// Mock implementation of ProfileRepository for testing.
+    /// Keeps profiles in memory, whatever database is configured.
+    #[derive(Default)]
+    pub struct MockProfileRepository {
+        storage: InMemoryProfileStorage,
+    }
+
+    impl MockProfileRepository {
+        /// Create a new empty mock repository
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    #[async_trait]
+    impl ProfileRepositoryTrait for MockProfileRepository {
+        async fn get_profile(&self, user_id: &str) -> Result<Option<UserProfile>, RepositoryError> {
+            self.storage.get_profile(user_id).await
+        }
+
+        async fn set_profile(&self, user_id: &str, request: SetUserProfileRequest) -> Result<UserProfile, RepositoryError> {
+            let existing = self.storage.get_profile(user_id).await?;
+            self.storage.store_profile(&profile_for(user_id, request, existing)).await
+        }
+    }
+}
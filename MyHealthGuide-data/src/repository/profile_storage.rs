@@ -0,0 +1,263 @@
+use tracing::debug;
+
+use crate::models::profile::UserProfile;
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+#[cfg(feature = "sqlite")]
+use super::storage::{sqlite_time, sqlite_time_column};
+#[cfg(feature = "mysql_db")]
+use super::storage::{mysql_column, mysql_time, mysql_time_column};
+
+/// Profile columns
+const PROFILE_QUERY: &str =
+    "SELECT user_id, time_zone, guideline, date_of_birth, sex, height_cm, created_at, updated_at
+     FROM user_profiles";
+
+/// Map a SQLite row from `PROFILE_QUERY`
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_profile(row: &rusqlite::Row<'_>) -> rusqlite::Result<UserProfile> {
+    Ok(UserProfile {
+        user_id: row.get(0)?,
+        time_zone: row.get(1)?,
+        guideline: row.get(2)?,
+        date_of_birth: row.get(3)?,
+        sex: row.get(4)?,
+        height_cm: row.get(5)?,
+        created_at: sqlite_time_column(row, 6)?,
+        updated_at: sqlite_time_column(row, 7)?,
+    })
+}
+
+/// Map a MySQL row from `PROFILE_QUERY`
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_profile(mut row: mysql::Row) -> Result<UserProfile, RepositoryError> {
+    Ok(UserProfile {
+        user_id: mysql_column(&mut row, 0)?,
+        time_zone: mysql_column(&mut row, 1)?,
+        guideline: mysql_column(&mut row, 2)?,
+        date_of_birth: mysql_column(&mut row, 3)?,
+        sex: mysql_column(&mut row, 4)?,
+        height_cm: mysql_column(&mut row, 5)?,
+        created_at: mysql_time_column(&mut row, 6)?,
+        updated_at: mysql_time_column(&mut row, 7)?,
+    })
+}
+
+/// Map a PostgreSQL row from `PROFILE_QUERY`
+#[cfg(feature = "postgres")]
+fn postgres_row_to_profile(row: &tokio_postgres::Row) -> UserProfile {
+    UserProfile {
+        user_id: row.get(0),
+        time_zone: row.get(1),
+        guideline: row.get(2),
+        date_of_birth: row.get(3),
+        sex: row.get(4),
+        height_cm: row.get(5),
+        created_at: row.get(6),
+        updated_at: row.get(7),
+    }
+}
+
+/// Database storage operations for users' profiles
+///
+/// A user has at most one profile, keyed by their ID.
+pub struct ProfileDatabaseStorage;
+
+impl ProfileDatabaseStorage {
+    /// Store a user's profile, replacing any stored before but keeping when it was first set
+    pub async fn store_profile(pool: &DatabasePool, profile: &UserProfile) -> Result<(), RepositoryError> {
+        debug!("Storing profile in database: user={}", profile.user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT INTO user_profiles
+                     (user_id, time_zone, guideline, date_of_birth, sex, height_cm, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                         time_zone = excluded.time_zone,
+                         guideline = excluded.guideline,
+                         date_of_birth = excluded.date_of_birth,
+                         sex = excluded.sex,
+                         height_cm = excluded.height_cm,
+                         updated_at = excluded.updated_at",
+                    (
+                        &profile.user_id,
+                        &profile.time_zone,
+                        &profile.guideline,
+                        &profile.date_of_birth,
+                        &profile.sex,
+                        profile.height_cm,
+                        sqlite_time(&profile.created_at),
+                        sqlite_time(&profile.updated_at),
+                    ),
+                )?;
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT INTO user_profiles
+                     (user_id, time_zone, guideline, date_of_birth, sex, height_cm, created_at, updated_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                     ON DUPLICATE KEY UPDATE
+                         time_zone = VALUES(time_zone),
+                         guideline = VALUES(guideline),
+                         date_of_birth = VALUES(date_of_birth),
+                         sex = VALUES(sex),
+                         height_cm = VALUES(height_cm),
+                         updated_at = VALUES(updated_at)",
+                    vec![
+                        mysql::Value::from(&profile.user_id),
+                        mysql::Value::from(&profile.time_zone),
+                        mysql::Value::from(&profile.guideline),
+                        mysql::Value::from(&profile.date_of_birth),
+                        mysql::Value::from(&profile.sex),
+                        mysql::Value::from(profile.height_cm),
+                        mysql_time(&profile.created_at),
+                        mysql_time(&profile.updated_at),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO user_profiles
+                     (user_id, time_zone, guideline, date_of_birth, sex, height_cm, created_at, updated_at)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                         time_zone = EXCLUDED.time_zone,
+                         guideline = EXCLUDED.guideline,
+                         date_of_birth = EXCLUDED.date_of_birth,
+                         sex = EXCLUDED.sex,
+                         height_cm = EXCLUDED.height_cm,
+                         updated_at = EXCLUDED.updated_at",
+                    &[
+                        &profile.user_id,
+                        &profile.time_zone,
+                        &profile.guideline,
+                        &profile.date_of_birth,
+                        &profile.sex,
+                        &profile.height_cm,
+                        &profile.created_at,
+                        &profile.updated_at,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a user's profile
+    pub async fn get_profile(pool: &DatabasePool, user_id: &str) -> Result<Option<UserProfile>, RepositoryError> {
+        debug!("Getting profile from database: user={}", user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut stmt = conn.prepare(&format!("{} WHERE user_id = ?", PROFILE_QUERY))?;
+
+                match stmt.query_row([user_id], sqlite_row_to_profile) {
+                    Ok(profile) => Ok(Some(profile)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(RepositoryError::Sqlite(e)),
+                }
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let row: Option<mysql::Row> = conn.exec_first(format!("{} WHERE user_id = ?", PROFILE_QUERY), (user_id,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                row.map(mysql_row_to_profile).transpose()
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(&format!("{} WHERE user_id = $1", PROFILE_QUERY), &[&user_id]).await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.first().map(postgres_row_to_profile))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use chrono::Utc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    #[tokio::test]
+    async fn test_profile_round_trip() {
+        let pool = sqlite_pool();
+        let set_at = Utc::now() - chrono::Duration::days(10);
+        let profile = UserProfile {
+            user_id: "alice".to_string(),
+            time_zone: Some("Europe/Amsterdam".to_string()),
+            guideline: Some("esc_esh_2018".to_string()),
+            date_of_birth: Some("2012-05-14".to_string()),
+            sex: Some("female".to_string()),
+            height_cm: Some(151.5),
+            created_at: set_at,
+            updated_at: set_at,
+        };
+        ProfileDatabaseStorage::store_profile(&pool, &profile).await.unwrap();
+        let stored = ProfileDatabaseStorage::get_profile(&pool, "alice").await.unwrap().unwrap();
+        assert_eq!(stored.time_zone.as_deref(), Some("Europe/Amsterdam"));
+        assert_eq!(stored.guideline.as_deref(), Some("esc_esh_2018"));
+        assert_eq!(stored.date_of_birth.as_deref(), Some("2012-05-14"));
+        assert_eq!(stored.sex.as_deref(), Some("female"));
+        assert_eq!(stored.height_cm, Some(151.5));
+        assert!(ProfileDatabaseStorage::get_profile(&pool, "bob").await.unwrap().is_none());
+
+        // Replacing the profile keeps when it was first set
+        ProfileDatabaseStorage::store_profile(&pool, &UserProfile {
+            time_zone: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            ..profile
+        }).await.unwrap();
+        let stored = ProfileDatabaseStorage::get_profile(&pool, "alice").await.unwrap().unwrap();
+        assert_eq!(stored.time_zone, None);
+        assert_eq!(stored.created_at.timestamp_millis(), set_at.timestamp_millis());
+        assert!(stored.updated_at > set_at);
+    }
+}
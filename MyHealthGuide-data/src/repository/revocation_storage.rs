@@ -0,0 +1,322 @@
+use tracing::debug;
+
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+
+/// Database storage operations for revoked tokens
+///
+/// Backs a token revocation store that is shared by every API instance
+/// using the same database. All times are unix timestamps in seconds.
+pub struct RevocationDatabaseStorage;
+
+impl RevocationDatabaseStorage {
+    /// Record a revoked token, keeping it until `expires_at`
+    ///
+    /// Revoking the same token twice is not an error. Returns true if this
+    /// call revoked the token and false if it was already revoked, which lets
+    /// callers enforce single use of a token across instances.
+    pub async fn revoke_token(
+        pool: &DatabasePool,
+        jti: &str,
+        expires_at: i64,
+        revoked_at: i64,
+    ) -> Result<bool, RepositoryError> {
+        debug!("Storing revoked token in database: {}", jti);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let inserted = conn.execute(
+                    "INSERT INTO revoked_tokens (jti, expires_at, revoked_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(jti) DO NOTHING",
+                    (jti, expires_at, revoked_at),
+                )?;
+
+                Ok(inserted > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT IGNORE INTO revoked_tokens (jti, expires_at, revoked_at) VALUES (?, ?, ?)",
+                    (jti, expires_at, revoked_at),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(conn.affected_rows() > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let inserted = client.execute(
+                    "INSERT INTO revoked_tokens (jti, expires_at, revoked_at) VALUES ($1, $2, $3)
+                     ON CONFLICT (jti) DO NOTHING",
+                    &[&jti, &expires_at, &revoked_at],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(inserted > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Check whether a token has been revoked
+    pub async fn is_revoked(pool: &DatabasePool, jti: &str) -> Result<bool, RepositoryError> {
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let count: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM revoked_tokens WHERE jti = ?",
+                    [jti],
+                    |row| row.get(0),
+                )?;
+
+                Ok(count > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let count: Option<i64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM revoked_tokens WHERE jti = ?",
+                    (jti,),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(count.unwrap_or(0) > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let row = client.query_one(
+                    "SELECT COUNT(*) FROM revoked_tokens WHERE jti = $1",
+                    &[&jti],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(row.get::<_, i64>(0) > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Raise a user's "not before" watermark
+    ///
+    /// The watermark is a unix timestamp in milliseconds and the expiry one
+    /// in seconds; both only ever move forward.
+    pub async fn set_user_not_before(
+        pool: &DatabasePool,
+        user_id: &str,
+        not_before: i64,
+        expires_at: i64,
+    ) -> Result<(), RepositoryError> {
+        debug!("Storing token watermark in database for user: {}", user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT INTO user_token_watermarks (user_id, not_before, expires_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(user_id) DO UPDATE SET
+                        not_before = MAX(not_before, excluded.not_before),
+                        expires_at = MAX(expires_at, excluded.expires_at)",
+                    (user_id, not_before, expires_at),
+                )?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT INTO user_token_watermarks (user_id, not_before, expires_at) VALUES (?, ?, ?)
+                     ON DUPLICATE KEY UPDATE
+                        not_before = GREATEST(not_before, VALUES(not_before)),
+                        expires_at = GREATEST(expires_at, VALUES(expires_at))",
+                    (user_id, not_before, expires_at),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO user_token_watermarks (user_id, not_before, expires_at) VALUES ($1, $2, $3)
+                     ON CONFLICT (user_id) DO UPDATE SET
+                        not_before = GREATEST(user_token_watermarks.not_before, EXCLUDED.not_before),
+                        expires_at = GREATEST(user_token_watermarks.expires_at, EXCLUDED.expires_at)",
+                    &[&user_id, &not_before, &expires_at],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a user's "not before" watermark, if one is set
+    pub async fn get_user_not_before(pool: &DatabasePool, user_id: &str) -> Result<Option<i64>, RepositoryError> {
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                match conn.query_row(
+                    "SELECT not_before FROM user_token_watermarks WHERE user_id = ?",
+                    [user_id],
+                    |row| row.get(0),
+                ) {
+                    Ok(not_before) => Ok(Some(not_before)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(RepositoryError::Sqlite(e)),
+                }
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_first(
+                    "SELECT not_before FROM user_token_watermarks WHERE user_id = ?",
+                    (user_id,),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(
+                    "SELECT not_before FROM user_token_watermarks WHERE user_id = $1",
+                    &[&user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.first().map(|row| row.get(0)))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Remove revoked tokens and watermarks that expired before `now`
+    ///
+    /// Returns the number of rows removed.
+    pub async fn delete_expired(pool: &DatabasePool, now: i64) -> Result<usize, RepositoryError> {
+        debug!("Removing expired token revocations from database");
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let tokens = conn.execute("DELETE FROM revoked_tokens WHERE expires_at < ?", [now])?;
+                let watermarks = conn.execute("DELETE FROM user_token_watermarks WHERE expires_at < ?", [now])?;
+
+                Ok(tokens + watermarks)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop("DELETE FROM revoked_tokens WHERE expires_at < ?", (now,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let tokens = conn.affected_rows();
+                conn.exec_drop("DELETE FROM user_token_watermarks WHERE expires_at < ?", (now,))
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let watermarks = conn.affected_rows();
+
+                Ok((tokens + watermarks) as usize)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let tokens = client.execute(
+                    "DELETE FROM revoked_tokens WHERE expires_at < $1",
+                    &[&now],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let watermarks = client.execute(
+                    "DELETE FROM user_token_watermarks WHERE expires_at < $1",
+                    &[&now],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok((tokens + watermarks) as usize)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    #[tokio::test]
+    async fn test_revocations_round_trip_and_expire() {
+        let pool = sqlite_pool();
+
+        assert!(RevocationDatabaseStorage::revoke_token(&pool, "jti-1", 100, 50).await.unwrap());
+        assert!(!RevocationDatabaseStorage::revoke_token(&pool, "jti-1", 100, 60).await.unwrap());
+        assert!(RevocationDatabaseStorage::revoke_token(&pool, "jti-2", 300, 50).await.unwrap());
+        assert!(RevocationDatabaseStorage::is_revoked(&pool, "jti-1").await.unwrap());
+        assert!(!RevocationDatabaseStorage::is_revoked(&pool, "jti-3").await.unwrap());
+
+        RevocationDatabaseStorage::set_user_not_before(&pool, "user-1", 2000, 100).await.unwrap();
+        RevocationDatabaseStorage::set_user_not_before(&pool, "user-1", 1000, 400).await.unwrap();
+        assert_eq!(RevocationDatabaseStorage::get_user_not_before(&pool, "user-1").await.unwrap(), Some(2000));
+        assert_eq!(RevocationDatabaseStorage::get_user_not_before(&pool, "user-2").await.unwrap(), None);
+
+        // Only the first token has expired; the watermark expiry moved forward to 400
+        assert_eq!(RevocationDatabaseStorage::delete_expired(&pool, 200).await.unwrap(), 1);
+        assert!(!RevocationDatabaseStorage::is_revoked(&pool, "jti-1").await.unwrap());
+        assert!(RevocationDatabaseStorage::is_revoked(&pool, "jti-2").await.unwrap());
+        assert_eq!(RevocationDatabaseStorage::get_user_not_before(&pool, "user-1").await.unwrap(), Some(2000));
+    }
+}
@@ -0,0 +1,514 @@
+use chrono::{DateTime, Utc};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::models::blood_pressure::{BloodPressureReading, BloodPressureSession};
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+#[cfg(feature = "sqlite")]
+use super::storage::{insert_sqlite_reading, sqlite_time, sqlite_time_column};
+#[cfg(feature = "mysql_db")]
+use super::storage::{insert_mysql_reading, mysql_column, mysql_time, mysql_time_column};
+#[cfg(feature = "postgres")]
+use super::storage::insert_postgres_reading;
+
+/// Session columns joined with the reading links, one row per reading
+///
+/// A session without readings still yields one row, with a `NULL` reading.
+const SESSION_QUERY: &str =
+    "SELECT s.id, s.user_id, s.discard_first, s.notes, s.created_at, l.reading_id
+     FROM blood_pressure_sessions s
+     LEFT JOIN blood_pressure_session_readings l ON l.session_id = s.id";
+
+/// A session row from `SESSION_QUERY`, with the reading it was joined to
+type SessionRow = (BloodPressureSession, Option<String>);
+
+/// Fold joined rows into sessions, keeping the order the sessions came in
+fn group_session_rows(rows: impl IntoIterator<Item = SessionRow>) -> Vec<BloodPressureSession> {
+    let mut sessions: Vec<BloodPressureSession> = Vec::new();
+    for (session, reading_id) in rows {
+        if sessions.last().is_none_or(|last| last.id != session.id) {
+            sessions.push(session);
+        }
+        if let (Some(last), Some(reading_id)) = (sessions.last_mut(), reading_id) {
+            last.reading_ids.push(reading_id);
+        }
+    }
+    sessions
+}
+
+/// Build a session without readings from its columns
+fn session(
+    id: String,
+    user_id: String,
+    discard_first: bool,
+    notes: Option<String>,
+    created_at: DateTime<Utc>,
+) -> BloodPressureSession {
+    BloodPressureSession {
+        id,
+        user_id,
+        discard_first,
+        notes,
+        reading_ids: Vec::new(),
+        created_at,
+    }
+}
+
+/// Map a SQLite row from `SESSION_QUERY`
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_session(row: &rusqlite::Row<'_>) -> rusqlite::Result<SessionRow> {
+    Ok((
+        session(row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, sqlite_time_column(row, 4)?),
+        row.get(5)?,
+    ))
+}
+
+/// Map a MySQL row from `SESSION_QUERY`
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_session(mut row: mysql::Row) -> Result<SessionRow, RepositoryError> {
+    Ok((
+        session(
+            mysql_column(&mut row, 0)?,
+            mysql_column(&mut row, 1)?,
+            mysql_column(&mut row, 2)?,
+            mysql_column(&mut row, 3)?,
+            mysql_time_column(&mut row, 4)?,
+        ),
+        mysql_column(&mut row, 5)?,
+    ))
+}
+
+/// Map a PostgreSQL row from `SESSION_QUERY`
+#[cfg(feature = "postgres")]
+fn postgres_row_to_session(row: &tokio_postgres::Row) -> SessionRow {
+    (
+        session(row.get(0), row.get(1), row.get(2), row.get(3), row.get(4)),
+        row.get(5),
+    )
+}
+
+/// Database storage operations for measurement sessions
+///
+/// A session row holds the session itself and the reading links say which
+/// readings belong to it. Every query is scoped to the owning user.
+pub struct SessionDatabaseStorage;
+
+impl SessionDatabaseStorage {
+    /// Store a session together with its new readings and link them, all or nothing
+    ///
+    /// `readings` are inserted in the same transaction as the session, so a
+    /// failure leaves neither behind.
+    pub async fn store_session(
+        pool: &DatabasePool,
+        session: &BloodPressureSession,
+        readings: &[BloodPressureReading],
+    ) -> Result<(), RepositoryError> {
+        debug!("Storing blood pressure session in database: id={}, readings={}", session.id, readings.len());
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let mut conn = pool.get()?;
+                let tx = conn.transaction()?;
+
+                for reading in readings {
+                    insert_sqlite_reading(&tx, reading)?;
+                }
+                tx.execute(
+                    "INSERT INTO blood_pressure_sessions (id, user_id, discard_first, notes, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    (
+                        &session.id,
+                        &session.user_id,
+                        session.discard_first,
+                        &session.notes,
+                        sqlite_time(&session.created_at),
+                    ),
+                )?;
+                for reading_id in &session.reading_ids {
+                    tx.execute(
+                        "INSERT INTO blood_pressure_session_readings (reading_id, session_id) VALUES (?1, ?2)",
+                        (reading_id, &session.id),
+                    )?;
+                }
+
+                tx.commit()?;
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+                let mut tx = conn.start_transaction(mysql::TxOpts::default())
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                for reading in readings {
+                    insert_mysql_reading(&mut tx, reading)?;
+                }
+                tx.exec_drop(
+                    "INSERT INTO blood_pressure_sessions (id, user_id, discard_first, notes, created_at)
+                     VALUES (?, ?, ?, ?, ?)",
+                    vec![
+                        mysql::Value::from(&session.id),
+                        mysql::Value::from(&session.user_id),
+                        mysql::Value::from(session.discard_first),
+                        mysql::Value::from(&session.notes),
+                        mysql_time(&session.created_at),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                for reading_id in &session.reading_ids {
+                    tx.exec_drop(
+                        "INSERT INTO blood_pressure_session_readings (reading_id, session_id) VALUES (?, ?)",
+                        (reading_id, &session.id),
+                    ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                }
+
+                tx.commit().map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let mut client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let tx = client.transaction().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                for reading in readings {
+                    insert_postgres_reading(&*tx, reading).await?;
+                }
+                tx.execute(
+                    "INSERT INTO blood_pressure_sessions (id, user_id, discard_first, notes, created_at)
+                     VALUES ($1, $2, $3, $4, $5)",
+                    &[&session.id, &session.user_id, &session.discard_first, &session.notes, &session.created_at],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                for reading_id in &session.reading_ids {
+                    tx.execute(
+                        "INSERT INTO blood_pressure_session_readings (reading_id, session_id) VALUES ($1, $2)",
+                        &[reading_id, &session.id],
+                    ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                }
+
+                tx.commit().await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a page of a user's sessions, newest first, optionally only those with the given IDs
+    ///
+    /// The page is taken from the sessions before they are joined to their
+    /// readings, so `limit` counts sessions. `offset` only applies with a
+    /// `limit`.
+    pub async fn get_sessions(
+        pool: &DatabasePool,
+        user_id: &str,
+        ids: Option<&[String]>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<BloodPressureSession>, RepositoryError> {
+        debug!("Getting blood pressure sessions from database: user={}, ids={:?}, limit={:?}", user_id, ids, limit);
+
+        if ids.is_some_and(|ids| ids.is_empty()) {
+            return Ok(Vec::new());
+        }
+
+        // MySQL allows no LIMIT in an IN subquery, so the page is joined as a derived table
+        let pagination = limit
+            .map(|limit| format!(" LIMIT {} OFFSET {}", limit, offset.unwrap_or(0)))
+            .unwrap_or_default();
+        let page_query = |placeholders: &[String]| {
+            let mut where_sql = format!("user_id = {}", placeholders[0]);
+            if placeholders.len() > 1 {
+                where_sql.push_str(&format!(" AND id IN ({})", placeholders[1..].join(", ")));
+            }
+            format!(
+                "{} JOIN (SELECT id FROM blood_pressure_sessions WHERE {} ORDER BY created_at DESC, id{}) p ON p.id = s.id
+                 ORDER BY s.created_at DESC, s.id, l.reading_id",
+                SESSION_QUERY, where_sql, pagination
+            )
+        };
+        let ids = ids.unwrap_or_default();
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let placeholders = vec!["?".to_string(); ids.len() + 1];
+                let params = std::iter::once(user_id).chain(ids.iter().map(String::as_str));
+                let mut stmt = conn.prepare(&page_query(&placeholders))?;
+                let rows = stmt.query_map(rusqlite::params_from_iter(params), sqlite_row_to_session)?
+                    .collect::<Result<Vec<SessionRow>, _>>()?;
+
+                Ok(group_session_rows(rows))
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let placeholders = vec!["?".to_string(); ids.len() + 1];
+                let params: Vec<mysql::Value> = std::iter::once(user_id)
+                    .chain(ids.iter().map(String::as_str))
+                    .map(mysql::Value::from)
+                    .collect();
+                let rows: Vec<mysql::Row> = conn.exec(page_query(&placeholders), params)
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let rows = rows.into_iter()
+                    .map(mysql_row_to_session)
+                    .collect::<Result<Vec<SessionRow>, _>>()?;
+
+                Ok(group_session_rows(rows))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let placeholders: Vec<String> = (1..=ids.len() + 1).map(|index| format!("${}", index)).collect();
+                let params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = std::iter::once(&user_id as &(dyn tokio_postgres::types::ToSql + Sync))
+                    .chain(ids.iter().map(|id| id as &(dyn tokio_postgres::types::ToSql + Sync)))
+                    .collect();
+                let rows = client.query(&page_query(&placeholders), &params[..])
+                    .await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(group_session_rows(rows.iter().map(postgres_row_to_session)))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Count a user's sessions
+    pub async fn count_sessions(pool: &DatabasePool, user_id: &str) -> Result<usize, RepositoryError> {
+        debug!("Counting blood pressure sessions in database: user={}", user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+                let total: i64 = conn.query_row(
+                    "SELECT COUNT(*) FROM blood_pressure_sessions WHERE user_id = ?1",
+                    [user_id],
+                    |row| row.get(0),
+                )?;
+                Ok(total as usize)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+                let total: Option<u64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM blood_pressure_sessions WHERE user_id = ?",
+                    (user_id,),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(total.unwrap_or(0) as usize)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let row = client.query_one(
+                    "SELECT COUNT(*) FROM blood_pressure_sessions WHERE user_id = $1",
+                    &[&user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let total: i64 = row.get(0);
+                Ok(total as usize)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Delete one of a user's sessions and its reading links, leaving the readings
+    ///
+    /// Returns `false` when the user owns no session with that ID.
+    pub async fn delete_session(pool: &DatabasePool, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        debug!("Deleting blood pressure session from database: id={}, user={}", id, user_id);
+
+        // Links are removed explicitly, as SQLite only cascades with foreign keys enabled
+        let id = id.to_string();
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let mut conn = pool.get()?;
+                let tx = conn.transaction()?;
+
+                tx.execute(
+                    "DELETE FROM blood_pressure_session_readings WHERE session_id IN
+                     (SELECT id FROM blood_pressure_sessions WHERE id = ?1 AND user_id = ?2)",
+                    (&id, user_id),
+                )?;
+                let deleted = tx.execute(
+                    "DELETE FROM blood_pressure_sessions WHERE id = ?1 AND user_id = ?2",
+                    (&id, user_id),
+                )?;
+
+                tx.commit()?;
+                Ok(deleted > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+                let mut tx = conn.start_transaction(mysql::TxOpts::default())
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                tx.exec_drop(
+                    "DELETE FROM blood_pressure_session_readings WHERE session_id IN
+                     (SELECT id FROM blood_pressure_sessions WHERE id = ? AND user_id = ?)",
+                    (&id, user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                tx.exec_drop(
+                    "DELETE FROM blood_pressure_sessions WHERE id = ? AND user_id = ?",
+                    (&id, user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let deleted = tx.affected_rows();
+
+                tx.commit().map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(deleted > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let mut client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let tx = client.transaction().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                tx.execute(
+                    "DELETE FROM blood_pressure_session_readings WHERE session_id IN
+                     (SELECT id FROM blood_pressure_sessions WHERE id = $1 AND user_id = $2)",
+                    &[&id, &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let deleted = tx.execute(
+                    "DELETE FROM blood_pressure_sessions WHERE id = $1 AND user_id = $2",
+                    &[&id, &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                tx.commit().await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                Ok(deleted > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::storage::DatabaseStorage;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    fn session(user_id: &str, reading_ids: &[&str], created_at: DateTime<Utc>) -> BloodPressureSession {
+        BloodPressureSession {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            discard_first: true,
+            notes: Some("Morning".to_string()),
+            reading_ids: reading_ids.iter().map(|id| id.to_string()).collect(),
+            created_at,
+        }
+    }
+
+    fn reading(user_id: &str, id: &str) -> BloodPressureReading {
+        let now = Utc::now();
+        BloodPressureReading {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            systolic: 120,
+            diastolic: 80,
+            pulse: None,
+            timestamp: now,
+            notes: None,
+            position: None,
+            arm: None,
+            device_id: None,
+            category: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_session_stores_none_of_its_readings() {
+        let pool = sqlite_pool();
+        let readings = [reading("alice", "reading-1"), reading("alice", "reading-2")];
+        let stored = session("alice", &["reading-1", "reading-2"], Utc::now());
+        SessionDatabaseStorage::store_session(&pool, &stored, &readings).await.unwrap();
+        assert_eq!(DatabaseStorage::get_all(&pool, "alice").await.unwrap().len(), 2);
+
+        // The session ID is taken, so its new reading is rolled back with it
+        let retried = BloodPressureSession { reading_ids: vec!["reading-3".to_string()], ..stored.clone() };
+        assert!(SessionDatabaseStorage::store_session(&pool, &retried, &[reading("alice", "reading-3")]).await.is_err());
+        assert_eq!(DatabaseStorage::get_all(&pool, "alice").await.unwrap().len(), 2);
+        assert_eq!(SessionDatabaseStorage::get_sessions(&pool, "alice", None, None, None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_round_trip_and_delete() {
+        let pool = sqlite_pool();
+        let now = Utc::now();
+        let older = session("alice", &["reading-1", "reading-2"], now - chrono::Duration::hours(1));
+        let newer = session("alice", &["reading-3", "reading-4", "reading-5"], now);
+        let other = session("bob", &["reading-6", "reading-7"], now);
+
+        for stored in [&older, &newer, &other] {
+            SessionDatabaseStorage::store_session(&pool, stored, &[]).await.unwrap();
+        }
+
+        let sessions = SessionDatabaseStorage::get_sessions(&pool, "alice", None, None, None).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, newer.id);
+        assert_eq!(sessions[0].reading_ids, newer.reading_ids);
+        assert!(sessions[0].discard_first);
+        assert_eq!(sessions[0].notes.as_deref(), Some("Morning"));
+
+        let older_id = Uuid::parse_str(&older.id).unwrap();
+        let ids = [older.id.clone()];
+        let found = SessionDatabaseStorage::get_sessions(&pool, "alice", Some(&ids), None, None).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].reading_ids, older.reading_ids);
+        assert!(SessionDatabaseStorage::get_sessions(&pool, "bob", Some(&ids), None, None).await.unwrap().is_empty());
+
+        // A page counts sessions, not the readings they are joined to
+        let page = SessionDatabaseStorage::get_sessions(&pool, "alice", None, Some(1), Some(1)).await.unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, older.id);
+        assert_eq!(page[0].reading_ids, older.reading_ids);
+        assert_eq!(SessionDatabaseStorage::count_sessions(&pool, "alice").await.unwrap(), 2);
+
+        // Only the owner can delete, and a reading can then join a new session
+        assert!(!SessionDatabaseStorage::delete_session(&pool, "bob", &older_id).await.unwrap());
+        assert!(SessionDatabaseStorage::delete_session(&pool, "alice", &older_id).await.unwrap());
+        assert!(!SessionDatabaseStorage::delete_session(&pool, "alice", &older_id).await.unwrap());
+        SessionDatabaseStorage::store_session(&pool, &session("alice", &["reading-1"], now), &[]).await.unwrap();
+        assert_eq!(SessionDatabaseStorage::get_sessions(&pool, "alice", None, None, None).await.unwrap().len(), 2);
+    }
+}
@@ -1,11 +1,317 @@
+use chrono::{DateTime, SecondsFormat, Utc};
 use tracing::debug;
 use uuid::Uuid;
 
-use crate::models::blood_pressure::BloodPressureReading;
+use crate::models::blood_pressure::{BloodPressureFilter, BloodPressureReading, SessionAveragedEntry};
 use crate::database::DatabasePool;
 use super::errors::RepositoryError;
 
+/// Columns selected for every blood pressure reading query, in the order the
+/// row mapping helpers below expect them
+const READING_COLUMNS: &str =
+    "id, user_id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id, category, created_at, updated_at";
+
+/// Format a timestamp for SQLite, which has no datetime type
+///
+/// Timestamps are stored as fixed width RFC 3339 text in UTC, so they sort
+/// and compare in time order.
+#[cfg(feature = "sqlite")]
+pub(super) fn sqlite_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Format the soft delete time for the `deleted_at` column
+///
+/// Every backend keeps `deleted_at` as text, in the same fixed width RFC 3339
+/// form SQLite uses for its other timestamps.
+fn deleted_at_text(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Read a timestamp column written with `sqlite_time`
+#[cfg(feature = "sqlite")]
+pub(super) fn sqlite_time_column(row: &rusqlite::Row<'_>, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(index)?;
+    DateTime::parse_from_rfc3339(&value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+/// Map a SQLite row selected with `READING_COLUMNS` to a reading
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_reading(row: &rusqlite::Row<'_>) -> rusqlite::Result<BloodPressureReading> {
+    Ok(BloodPressureReading {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        systolic: row.get::<_, i32>(2)? as u16,
+        diastolic: row.get::<_, i32>(3)? as u16,
+        pulse: row.get::<_, Option<i32>>(4)?.map(|p| p as u16),
+        timestamp: sqlite_time_column(row, 5)?,
+        notes: row.get(6)?,
+        position: row.get(7)?,
+        arm: row.get(8)?,
+        device_id: row.get(9)?,
+        category: row.get(10)?,
+        created_at: sqlite_time_column(row, 11)?,
+        updated_at: sqlite_time_column(row, 12)?,
+    })
+}
+
+/// Convert a timestamp to a MySQL `DATETIME` value in UTC
+#[cfg(feature = "mysql_db")]
+pub(super) fn mysql_time(time: &DateTime<Utc>) -> mysql::Value {
+    use chrono::{Datelike, Timelike};
+
+    mysql::Value::Date(
+        time.year() as u16,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+        time.timestamp_subsec_micros(),
+    )
+}
+
+/// Take a column out of a MySQL row selected with `READING_COLUMNS`
+#[cfg(feature = "mysql_db")]
+pub(super) fn mysql_column<T: mysql::prelude::FromValue>(row: &mut mysql::Row, index: usize) -> Result<T, RepositoryError> {
+    row.take_opt(index)
+        .unwrap_or(Err(mysql::FromValueError(mysql::Value::NULL)))
+        .map_err(|e| RepositoryError::Database(format!("Invalid value in column {}: {:?}", index, e.0).into()))
+}
+
+/// Take a MySQL `DATETIME` column as a UTC timestamp
+///
+/// Prepared statements return dates as `Value::Date`, plain queries as text.
+#[cfg(feature = "mysql_db")]
+pub(super) fn mysql_time_column(row: &mut mysql::Row, index: usize) -> Result<DateTime<Utc>, RepositoryError> {
+    let time = match mysql_column(row, index)? {
+        mysql::Value::Date(year, month, day, hour, minute, second, micros) => {
+            chrono::NaiveDate::from_ymd_opt(year.into(), month.into(), day.into())
+                .and_then(|date| date.and_hms_micro_opt(hour.into(), minute.into(), second.into(), micros))
+        },
+        mysql::Value::Bytes(bytes) => std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|text| chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()),
+        _ => None,
+    };
+
+    time.map(|time| time.and_utc())
+        .ok_or_else(|| RepositoryError::Database(format!("Invalid timestamp in column {}", index).into()))
+}
+
+/// Map a MySQL row selected with `READING_COLUMNS` to a reading
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_reading(mut row: mysql::Row) -> Result<BloodPressureReading, RepositoryError> {
+    Ok(BloodPressureReading {
+        id: mysql_column(&mut row, 0)?,
+        user_id: mysql_column(&mut row, 1)?,
+        systolic: mysql_column::<i32>(&mut row, 2)? as u16,
+        diastolic: mysql_column::<i32>(&mut row, 3)? as u16,
+        pulse: mysql_column::<Option<i32>>(&mut row, 4)?.map(|p| p as u16),
+        timestamp: mysql_time_column(&mut row, 5)?,
+        notes: mysql_column(&mut row, 6)?,
+        position: mysql_column(&mut row, 7)?,
+        arm: mysql_column(&mut row, 8)?,
+        device_id: mysql_column(&mut row, 9)?,
+        category: mysql_column(&mut row, 10)?,
+        created_at: mysql_time_column(&mut row, 11)?,
+        updated_at: mysql_time_column(&mut row, 12)?,
+    })
+}
+
+/// Map a PostgreSQL row selected with `READING_COLUMNS` to a reading
+#[cfg(feature = "postgres")]
+fn postgres_row_to_reading(row: &tokio_postgres::Row) -> BloodPressureReading {
+    BloodPressureReading {
+        id: row.get(0),
+        user_id: row.get(1),
+        systolic: row.get::<_, i32>(2) as u16,
+        diastolic: row.get::<_, i32>(3) as u16,
+        pulse: row.get::<_, Option<i32>>(4).map(|p| p as u16),
+        timestamp: row.get(5),
+        notes: row.get(6),
+        position: row.get(7),
+        arm: row.get(8),
+        device_id: row.get(9),
+        category: row.get(10),
+        created_at: row.get(11),
+        updated_at: row.get(12),
+    }
+}
+
+/// Insert a reading through a SQLite connection or transaction
+#[cfg(feature = "sqlite")]
+pub(super) fn insert_sqlite_reading(conn: &rusqlite::Connection, reading: &BloodPressureReading) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO blood_pressure_readings
+         (id, user_id, systolic, diastolic, pulse, notes, timestamp, position, arm, device_id, category,
+          created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        (
+            &reading.id,
+            &reading.user_id,
+            reading.systolic,
+            reading.diastolic,
+            reading.pulse,
+            &reading.notes,
+            sqlite_time(&reading.timestamp),
+            &reading.position,
+            &reading.arm,
+            &reading.device_id,
+            &reading.category,
+            sqlite_time(&reading.created_at),
+            sqlite_time(&reading.updated_at),
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Insert a reading through a MySQL connection or transaction
+#[cfg(feature = "mysql_db")]
+pub(super) fn insert_mysql_reading(conn: &mut impl mysql::prelude::Queryable, reading: &BloodPressureReading) -> Result<(), RepositoryError> {
+    conn.exec_drop(
+        "INSERT INTO blood_pressure_readings
+         (id, user_id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id, category,
+          created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        vec![
+            mysql::Value::from(&reading.id),
+            mysql::Value::from(&reading.user_id),
+            mysql::Value::from(reading.systolic),
+            mysql::Value::from(reading.diastolic),
+            mysql::Value::from(reading.pulse),
+            mysql_time(&reading.timestamp),
+            mysql::Value::from(&reading.notes),
+            mysql::Value::from(&reading.position),
+            mysql::Value::from(&reading.arm),
+            mysql::Value::from(&reading.device_id),
+            mysql::Value::from(&reading.category),
+            mysql_time(&reading.created_at),
+            mysql_time(&reading.updated_at),
+        ],
+    ).map_err(|e| RepositoryError::Database(e.to_string().into()))
+}
+
+/// Insert a reading through a PostgreSQL client or transaction
+#[cfg(feature = "postgres")]
+pub(super) async fn insert_postgres_reading(
+    client: &impl tokio_postgres::GenericClient,
+    reading: &BloodPressureReading,
+) -> Result<(), RepositoryError> {
+    client.execute(
+        "INSERT INTO blood_pressure_readings
+         (id, user_id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id, category,
+          created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+        &[
+            &reading.id,
+            &reading.user_id,
+            &(reading.systolic as i32),
+            &(reading.diastolic as i32),
+            &reading.pulse.map(|p| p as i32),
+            &reading.timestamp,
+            &reading.notes,
+            &reading.position,
+            &reading.arm,
+            &reading.device_id,
+            &reading.category,
+            &reading.created_at,
+            &reading.updated_at,
+        ],
+    ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+    Ok(())
+}
+
+/// Readings matching the filter, each keyed by its session or else by itself
+///
+/// The conditions go at `{where}`, unqualified, as the session links share
+/// no column names with the readings.
+const SESSION_AVERAGED_MATCHES: &str =
+    "SELECT DISTINCT COALESCE(l.session_id, r.id) AS entry_id, l.session_id AS session_id
+     FROM blood_pressure_readings r
+     LEFT JOIN blood_pressure_session_readings l ON l.reading_id = r.id
+     WHERE {where}";
+
+/// Time of every entry: its own for a reading, the first reading's for a session
+///
+/// The owner placeholder goes at `{user}`.
+const SESSION_AVERAGED_TIMES: &str =
+    "SELECT COALESCE(l.session_id, r.id) AS entry_id, MIN(r.timestamp) AS entry_time
+     FROM blood_pressure_readings r
+     LEFT JOIN blood_pressure_session_readings l ON l.reading_id = r.id
+     WHERE r.user_id = {user} AND r.deleted_at IS NULL
+     GROUP BY COALESCE(l.session_id, r.id)";
+
+/// Page of entries for `get_session_averaged_entries`, one row per entry
+fn session_averaged_query(where_sql: &str, user_placeholder: &str, sort_direction: &str, pagination: &str) -> String {
+    format!(
+        "SELECT m.entry_id, m.session_id FROM ({}) m JOIN ({}) t ON t.entry_id = m.entry_id
+         ORDER BY t.entry_time {}, m.entry_id{}",
+        SESSION_AVERAGED_MATCHES.replace("{where}", where_sql),
+        SESSION_AVERAGED_TIMES.replace("{user}", user_placeholder),
+        sort_direction,
+        pagination,
+    )
+}
+
+/// Number of entries for `get_session_averaged_entries`
+fn session_averaged_count_query(where_sql: &str) -> String {
+    format!(
+        "SELECT COUNT(DISTINCT COALESCE(l.session_id, r.id))
+         FROM blood_pressure_readings r
+         LEFT JOIN blood_pressure_session_readings l ON l.reading_id = r.id
+         WHERE {}",
+        where_sql,
+    )
+}
+
+/// Entry for a row of `session_averaged_query`
+fn session_averaged_entry(entry_id: String, session_id: Option<String>) -> SessionAveragedEntry {
+    match session_id {
+        Some(_) => SessionAveragedEntry::Session(entry_id),
+        None => SessionAveragedEntry::Reading(entry_id),
+    }
+}
+
+/// Value a filter condition compares against
+pub(super) enum FilterValue<'a> {
+    Text(&'a str),
+    Time(DateTime<Utc>),
+}
+
+/// SQL comparisons for the filters that are set, each paired with its value
+///
+/// The comparisons end where the backend specific placeholder goes.
+fn filter_conditions(filter: &BloodPressureFilter) -> Vec<(&'static str, FilterValue<'_>)> {
+    let dates = [
+        ("timestamp >=", filter.start_date),
+        ("timestamp <=", filter.end_date),
+    ]
+    .into_iter()
+    .filter_map(|(condition, value)| value.map(|value| (condition, FilterValue::Time(value))));
+
+    let values = [
+        ("category =", &filter.category),
+        ("position =", &filter.position),
+        ("arm =", &filter.arm),
+        ("device_id =", &filter.device_id),
+    ]
+    .into_iter()
+    .filter_map(|(condition, value)| value.as_deref().map(|value| (condition, FilterValue::Text(value))));
+
+    dates.chain(values).collect()
+}
+
 /// Database storage operations for blood pressure readings
+///
+/// Every read is scoped to a single owner: callers pass the `user_id` of the
+/// authenticated user and only that user's readings are ever returned.
+/// Deleted readings are only marked with `deleted_at` and are skipped by all
+/// reads until they are restored.
 pub struct DatabaseStorage;
 
 impl DatabaseStorage {
@@ -13,475 +319,916 @@ impl DatabaseStorage {
     #[cfg(feature = "sqlite")]
     pub async fn store_reading(pool: &DatabasePool, reading: &BloodPressureReading) -> Result<(), RepositoryError> {
         debug!("Storing blood pressure reading in database: id={}", reading.id);
-        
+
         match pool {
             DatabasePool::SQLite(pool) => {
                 let conn = pool.get().map_err(RepositoryError::Pool)?;
-                
-                conn.execute(
-                    "INSERT INTO blood_pressure_readings 
-                     (id, systolic, diastolic, pulse, notes, timestamp, position, arm, device_id) 
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-                    (
-                        &reading.id,
-                        reading.systolic,
-                        reading.diastolic,
-                        reading.pulse,
-                        &reading.notes,
-                        &reading.timestamp,
-                        &reading.position,
-                        &reading.arm,
-                        &reading.device_id,
-                    ),
-                ).map_err(RepositoryError::Sqlite)?;
-                
-                Ok(())
+                insert_sqlite_reading(&conn, reading).map_err(RepositoryError::Sqlite)
             },
-            
+
             #[cfg(feature = "mysql_db")]
             DatabasePool::MySQL(pool) => {
-                use mysql::prelude::*;
-                
                 let mut conn = pool.get()
-                    .map_err(|e| RepositoryError::Pool(e))?;
-                
-                conn.exec_drop(
-                    "INSERT INTO blood_pressure_readings 
-                     (id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id) 
-                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                    (
-                        &reading.id,
-                        reading.systolic,
-                        reading.diastolic,
-                        reading.pulse,
-                        &reading.timestamp,
-                        &reading.notes,
-                        &reading.position,
-                        &reading.arm,
-                        &reading.device_id,
-                    ),
-                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
-                Ok(())
+                    .map_err(RepositoryError::Pool)?;
+                insert_mysql_reading(&mut *conn, reading)
             },
-            
+
             #[cfg(feature = "postgres")]
             DatabasePool::PostgreSQL(pool) => {
                 // Get a client from the pool with async/await
                 let client = pool.get().await
                     .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
-                // Execute the query with async/await
-                client.execute(
-                    "INSERT INTO blood_pressure_readings 
-                     (id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id) 
-                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
-                    &[
-                        &reading.id,
-                        &(reading.systolic as i32),
-                        &(reading.diastolic as i32),
-                        &reading.pulse.map(|p| p as i32),
-                        &reading.timestamp,
-                        &reading.notes,
-                        &reading.position,
-                        &reading.arm,
-                        &reading.device_id,
-                    ],
-                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
-                Ok(())
+                insert_postgres_reading(&**client, reading).await
             },
-            
+
             #[allow(unreachable_patterns)]
             _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
         }
     }
-    
-    /// Get all readings from the database
-    pub async fn get_all(pool: &DatabasePool) -> Result<Vec<BloodPressureReading>, RepositoryError> {
-        debug!("Getting all blood pressure readings from database");
-        
+
+    /// Get all readings owned by a user from the database
+    pub async fn get_all(pool: &DatabasePool, user_id: &str) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+        debug!("Getting all blood pressure readings from database for user {}", user_id);
+
         match pool {
             DatabasePool::SQLite(pool) => {
                 let conn = pool.get()?;
-                
-                let mut stmt = conn.prepare(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings ORDER BY timestamp DESC"
-                )?;
-                
-                let readings = stmt.query_map([], |row| {
-                    Ok(BloodPressureReading {
-                        id: row.get(0)?,
-                        systolic: row.get::<_, i32>(1)? as u16,
-                        diastolic: row.get::<_, i32>(2)? as u16,
-                        pulse: row.get::<_, Option<i32>>(3)?.map(|p| p as u16),
-                        timestamp: row.get(4)?,
-                        notes: row.get(5)?,
-                        position: row.get(6)?,
-                        arm: row.get(7)?,
-                        device_id: row.get(8)?,
-                    })
-                })?;
-                
+
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM blood_pressure_readings WHERE user_id = ? AND deleted_at IS NULL ORDER BY timestamp DESC",
+                    READING_COLUMNS
+                ))?;
+
+                let readings = stmt.query_map([user_id], sqlite_row_to_reading)?;
+
                 let mut result = Vec::new();
                 for reading in readings {
                     result.push(reading?);
                 }
-                
+
                 Ok(result)
             },
-            
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let rows: Vec<mysql::Row> = conn.exec(
+                    format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE user_id = ? AND deleted_at IS NULL ORDER BY timestamp DESC",
+                        READING_COLUMNS
+                    ),
+                    (user_id,),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                rows.into_iter().map(mysql_row_to_reading).collect()
+            },
+
             #[cfg(feature = "postgres")]
             DatabasePool::PostgreSQL(pool) => {
                 // Get a client from the pool
                 let client = pool.get().await
                     .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
+
                 // Execute the query
                 let rows = client.query(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings ORDER BY timestamp DESC",
-                    &[],
+                    &format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE user_id = $1 AND deleted_at IS NULL ORDER BY timestamp DESC",
+                        READING_COLUMNS
+                    ),
+                    &[&user_id],
                 ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
+
                 // Convert the rows to BloodPressureReading objects
-                let mut result = Vec::new();
-                for row in rows {
-                    let reading = BloodPressureReading {
-                        id: row.get(0),
-                        systolic: row.get::<_, i32>(1) as u16,
-                        diastolic: row.get::<_, i32>(2) as u16,
-                        pulse: row.get::<_, Option<i32>>(3).map(|p| p as u16),
-                        timestamp: row.get(4),
-                        notes: row.get(5),
-                        position: row.get(6),
-                        arm: row.get(7),
-                        device_id: row.get(8),
-                    };
-                    result.push(reading);
-                }
-                
-                Ok(result)
+                Ok(rows.iter().map(postgres_row_to_reading).collect())
             },
-            
+
             #[allow(unreachable_patterns)]
             _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
         }
     }
-    
-    /// Get a reading by ID from the database
-    pub async fn get_by_id(pool: &DatabasePool, id: &Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
-        debug!("Getting blood pressure reading by ID from database: id={}", id);
-        
+
+    /// Get a reading by ID from the database, provided it belongs to the user
+    pub async fn get_by_id(pool: &DatabasePool, user_id: &str, id: &Uuid) -> Result<Option<BloodPressureReading>, RepositoryError> {
+        debug!("Getting blood pressure reading by ID from database: id={}, user={}", id, user_id);
+
         match pool {
             DatabasePool::SQLite(pool) => {
                 let conn = pool.get()?;
-                
-                let mut stmt = conn.prepare(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings WHERE id = ?"
-                )?;
-                
-                let reading = stmt.query_row([&id.to_string()], |row| {
-                    Ok(BloodPressureReading {
-                        id: row.get(0)?,
-                        systolic: row.get::<_, i32>(1)? as u16,
-                        diastolic: row.get::<_, i32>(2)? as u16,
-                        pulse: row.get::<_, Option<i32>>(3)?.map(|p| p as u16),
-                        timestamp: row.get(4)?,
-                        notes: row.get(5)?,
-                        position: row.get(6)?,
-                        arm: row.get(7)?,
-                        device_id: row.get(8)?,
-                    })
-                });
-                
+
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM blood_pressure_readings WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    READING_COLUMNS
+                ))?;
+
+                let reading = stmt.query_row([id.to_string().as_str(), user_id], sqlite_row_to_reading);
+
                 match reading {
                     Ok(reading) => Ok(Some(reading)),
                     Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
                     Err(e) => Err(RepositoryError::Sqlite(e)),
                 }
             },
-            
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let row: Option<mysql::Row> = conn.exec_first(
+                    format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                        READING_COLUMNS
+                    ),
+                    (id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                row.map(mysql_row_to_reading).transpose()
+            },
+
             #[cfg(feature = "postgres")]
             DatabasePool::PostgreSQL(pool) => {
                 let client = pool.get().await
                     .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
+
                 let rows = client.query(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings WHERE id = $1",
-                    &[&id.to_string()],
+                    &format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+                        READING_COLUMNS
+                    ),
+                    &[&id.to_string(), &user_id],
                 ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
-                if rows.is_empty() {
-                    return Ok(None);
-                }
-                
-                let row = &rows[0];
-                let reading = BloodPressureReading {
-                    id: row.get(0),
-                    systolic: row.get::<_, i32>(1) as u16,
-                    diastolic: row.get::<_, i32>(2) as u16,
-                    pulse: row.get::<_, Option<i32>>(3).map(|p| p as u16),
-                    timestamp: row.get(4),
-                    notes: row.get(5),
-                    position: row.get(6),
-                    arm: row.get(7),
-                    device_id: row.get(8),
-                };
-                
-                Ok(Some(reading))
+
+                Ok(rows.first().map(postgres_row_to_reading))
             },
-            
+
             #[allow(unreachable_patterns)]
             _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
         }
     }
-    
-    /// Get the latest blood pressure reading from the database
-    pub async fn get_latest(pool: &DatabasePool) -> Result<Option<BloodPressureReading>, RepositoryError> {
-        debug!("Getting latest blood pressure reading from database");
-        
+
+    /// Get a user's latest blood pressure reading from the database
+    pub async fn get_latest(pool: &DatabasePool, user_id: &str) -> Result<Option<BloodPressureReading>, RepositoryError> {
+        debug!("Getting latest blood pressure reading from database for user {}", user_id);
+
         match pool {
             DatabasePool::SQLite(pool) => {
                 let conn = pool.get()?;
-                
-                let mut stmt = conn.prepare(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings ORDER BY timestamp DESC LIMIT 1"
-                )?;
-                
-                let reading = stmt.query_row([], |row| {
-                    Ok(BloodPressureReading {
-                        id: row.get(0)?,
-                        systolic: row.get::<_, i32>(1)? as u16,
-                        diastolic: row.get::<_, i32>(2)? as u16,
-                        pulse: row.get::<_, Option<i32>>(3)?.map(|p| p as u16),
-                        timestamp: row.get(4)?,
-                        notes: row.get(5)?,
-                        position: row.get(6)?,
-                        arm: row.get(7)?,
-                        device_id: row.get(8)?,
-                    })
-                });
-                
+
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM blood_pressure_readings WHERE user_id = ? AND deleted_at IS NULL ORDER BY timestamp DESC LIMIT 1",
+                    READING_COLUMNS
+                ))?;
+
+                let reading = stmt.query_row([user_id], sqlite_row_to_reading);
+
                 match reading {
                     Ok(reading) => Ok(Some(reading)),
                     Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
                     Err(e) => Err(RepositoryError::Sqlite(e)),
                 }
             },
-            
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let row: Option<mysql::Row> = conn.exec_first(
+                    format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE user_id = ? AND deleted_at IS NULL ORDER BY timestamp DESC LIMIT 1",
+                        READING_COLUMNS
+                    ),
+                    (user_id,),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                row.map(mysql_row_to_reading).transpose()
+            },
+
             #[cfg(feature = "postgres")]
             DatabasePool::PostgreSQL(pool) => {
                 let client = pool.get().await
                     .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
+
                 let rows = client.query(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings ORDER BY timestamp DESC LIMIT 1",
-                    &[],
+                    &format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE user_id = $1 AND deleted_at IS NULL ORDER BY timestamp DESC LIMIT 1",
+                        READING_COLUMNS
+                    ),
+                    &[&user_id],
                 ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
-                if rows.is_empty() {
-                    return Ok(None);
-                }
-                
-                let row = &rows[0];
-                let reading = BloodPressureReading {
-                    id: row.get(0),
-                    systolic: row.get::<_, i32>(1) as u16,
-                    diastolic: row.get::<_, i32>(2) as u16,
-                    pulse: row.get::<_, Option<i32>>(3).map(|p| p as u16),
-                    timestamp: row.get(4),
-                    notes: row.get(5),
-                    position: row.get(6),
-                    arm: row.get(7),
-                    device_id: row.get(8),
-                };
-                
-                Ok(Some(reading))
+
+                Ok(rows.first().map(postgres_row_to_reading))
             },
-            
+
             #[allow(unreachable_patterns)]
             _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
         }
     }
-    
-    /// Get filtered readings from the database
+
+    /// Get a user's filtered readings from the database
+    ///
+    /// Every filter that is set becomes part of the `WHERE` clause, so paging
+    /// and the total count only ever see matching readings. Without a `limit`
+    /// every matching reading is returned and `offset` is ignored.
     pub async fn get_filtered(
         pool: &DatabasePool,
-        start_date: Option<&str>,
-        end_date: Option<&str>,
+        user_id: &str,
+        filter: &BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
     ) -> Result<(Vec<BloodPressureReading>, usize), RepositoryError> {
-        debug!("Getting filtered blood pressure readings from database");
-        
+        debug!("Getting filtered blood pressure readings from database for user {}", user_id);
+
         let sort_direction = if sort_desc.unwrap_or(true) { "DESC" } else { "ASC" };
-        let limit_val = limit.unwrap_or(100);
-        let offset_val = offset.unwrap_or(0);
-        
+        let pagination = limit
+            .map(|limit| format!(" LIMIT {} OFFSET {}", limit, offset.unwrap_or(0)))
+            .unwrap_or_default();
+        let conditions = filter_conditions(filter);
+
         match pool {
             DatabasePool::SQLite(pool) => {
                 let conn = pool.get()?;
-                
-                // Build query with date filters if provided
-                let mut query = String::from(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings"
-                );
-                
-                let mut where_clauses = Vec::new();
-                let mut params: Vec<&dyn rusqlite::ToSql> = Vec::new();
-                
-                // Create owned copies of the date strings so they live long enough
-                let start_string: Option<String> = start_date.map(|s| s.to_string());
-                let end_string: Option<String> = end_date.map(|s| s.to_string());
-                
-                if let Some(ref start) = start_string {
-                    where_clauses.push("timestamp >= ?");
-                    params.push(start as &dyn rusqlite::ToSql);
-                }
-                
-                if let Some(ref end) = end_string {
-                    where_clauses.push("timestamp <= ?");
-                    params.push(end as &dyn rusqlite::ToSql);
-                }
-                
-                if !where_clauses.is_empty() {
-                    query.push_str(" WHERE ");
-                    query.push_str(&where_clauses.join(" AND "));
+
+                // Build query with owner and filter conditions
+                let mut query = format!("SELECT {} FROM blood_pressure_readings", READING_COLUMNS);
+
+                let mut where_clauses = vec!["user_id = ?".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params = vec![user_id.to_string()];
+
+                for (condition, value) in &conditions {
+                    where_clauses.push(format!("{} ?", condition));
+                    params.push(match value {
+                        FilterValue::Text(text) => text.to_string(),
+                        FilterValue::Time(time) => sqlite_time(time),
+                    });
                 }
-                
+
+                query.push_str(" WHERE ");
+                query.push_str(&where_clauses.join(" AND "));
+
                 // Add sorting
                 query.push_str(&format!(" ORDER BY timestamp {}", sort_direction));
-                
+
                 // Add pagination
-                query.push_str(&format!(" LIMIT {} OFFSET {}", limit_val, offset_val));
-                
+                query.push_str(&pagination);
+
                 // Execute query
                 let mut stmt = conn.prepare(&query)?;
-                
-                let readings = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-                    Ok(BloodPressureReading {
-                        id: row.get(0)?,
-                        systolic: row.get::<_, i32>(1)? as u16,
-                        diastolic: row.get::<_, i32>(2)? as u16,
-                        pulse: row.get::<_, Option<i32>>(3)?.map(|p| p as u16),
-                        timestamp: row.get(4)?,
-                        notes: row.get(5)?,
-                        position: row.get(6)?,
-                        arm: row.get(7)?,
-                        device_id: row.get(8)?,
-                    })
-                })?;
-                
+
+                let readings = stmt.query_map(rusqlite::params_from_iter(params.iter()), sqlite_row_to_reading)?;
+
                 let mut result = Vec::new();
                 for reading in readings {
                     result.push(reading?);
                 }
-                
+
                 // Get total count for pagination
                 let mut count_query = String::from("SELECT COUNT(*) FROM blood_pressure_readings");
-                
-                if !where_clauses.is_empty() {
-                    count_query.push_str(" WHERE ");
-                    count_query.push_str(&where_clauses.join(" AND "));
-                }
-                
+                count_query.push_str(" WHERE ");
+                count_query.push_str(&where_clauses.join(" AND "));
+
                 let mut count_stmt = conn.prepare(&count_query)?;
                 let total: i64 = count_stmt.query_row(
                     rusqlite::params_from_iter(params.iter()),
                     |row| row.get(0)
                 )?;
-                
+
                 Ok((result, total as usize))
             },
-            
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                // Build query with owner and filter conditions
+                let mut where_clauses = vec!["user_id = ?".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params: Vec<mysql::Value> = vec![user_id.into()];
+
+                for (condition, value) in &conditions {
+                    where_clauses.push(format!("{} ?", condition));
+                    params.push(match value {
+                        FilterValue::Text(text) => (*text).into(),
+                        FilterValue::Time(time) => mysql_time(time),
+                    });
+                }
+
+                let where_sql = where_clauses.join(" AND ");
+                let query = format!(
+                    "SELECT {} FROM blood_pressure_readings WHERE {} ORDER BY timestamp {}{}",
+                    READING_COLUMNS, where_sql, sort_direction, pagination
+                );
+
+                let rows: Vec<mysql::Row> = conn.exec(&query, params.clone())
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                // Get total count for pagination
+                let total: Option<u64> = conn.exec_first(
+                    format!("SELECT COUNT(*) FROM blood_pressure_readings WHERE {}", where_sql),
+                    params,
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let result = rows.into_iter().map(mysql_row_to_reading).collect::<Result<_, _>>()?;
+
+                Ok((result, total.unwrap_or(0) as usize))
+            },
+
             #[cfg(feature = "postgres")]
             DatabasePool::PostgreSQL(pool) => {
                 let client = pool.get().await
                     .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
-                // Build query with date filters
-                let mut query = String::from(
-                    "SELECT id, systolic, diastolic, pulse, timestamp, notes, position, arm, device_id
-                     FROM blood_pressure_readings"
-                );
-                
-                let mut where_clauses = Vec::new();
-                let mut params = Vec::new();
-                let mut param_index = 1;
-                
-                if let Some(start) = start_date {
-                    where_clauses.push(format!("timestamp >= ${}", param_index));
-                    params.push(start);
-                    param_index += 1;
-                }
-                
-                if let Some(end) = end_date {
-                    where_clauses.push(format!("timestamp <= ${}", param_index));
-                    params.push(end);
-                    param_index += 1;
-                }
-                
-                if !where_clauses.is_empty() {
-                    query.push_str(" WHERE ");
-                    query.push_str(&where_clauses.join(" AND "));
+
+                // Build query with owner and filter conditions
+                let mut query = format!("SELECT {} FROM blood_pressure_readings", READING_COLUMNS);
+
+                let mut where_clauses = vec!["user_id = $1".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = vec![Box::new(user_id.to_string())];
+
+                for (condition, value) in &conditions {
+                    params.push(match value {
+                        FilterValue::Text(text) => Box::new(text.to_string()),
+                        FilterValue::Time(time) => Box::new(*time),
+                    });
+                    where_clauses.push(format!("{} ${}", condition, params.len()));
                 }
-                
+
+                query.push_str(" WHERE ");
+                query.push_str(&where_clauses.join(" AND "));
+
                 // Add sorting
                 query.push_str(&format!(" ORDER BY timestamp {}", sort_direction));
-                
+
                 // Add pagination
-                query.push_str(&format!(" LIMIT {} OFFSET {}", limit_val, offset_val));
-                
+                query.push_str(&pagination);
+
                 // Execute query
-                let param_values: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = 
-                    params.iter().map(|p| p as &(dyn tokio_postgres::types::ToSql + Sync)).collect();
-                
+                let param_values: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+                    params.iter().map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync)).collect();
+
                 let rows = client.query(&query, &param_values[..])
                     .await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
+
                 // Convert rows to BloodPressureReading objects
-                let mut result = Vec::new();
-                for row in rows {
-                    let reading = BloodPressureReading {
-                        id: row.get(0),
-                        systolic: row.get::<_, i32>(1) as u16,
-                        diastolic: row.get::<_, i32>(2) as u16,
-                        pulse: row.get::<_, Option<i32>>(3).map(|p| p as u16),
-                        timestamp: row.get(4),
-                        notes: row.get(5),
-                        position: row.get(6),
-                        arm: row.get(7),
-                        device_id: row.get(8),
-                    };
-                    result.push(reading);
-                }
-                
+                let result = rows.iter().map(postgres_row_to_reading).collect();
+
                 // Get total count for pagination
                 let mut count_query = String::from("SELECT COUNT(*) FROM blood_pressure_readings");
-                
-                if !where_clauses.is_empty() {
-                    count_query.push_str(" WHERE ");
-                    count_query.push_str(&where_clauses.join(" AND "));
-                }
-                
+                count_query.push_str(" WHERE ");
+                count_query.push_str(&where_clauses.join(" AND "));
+
                 let count_row = client.query_one(&count_query, &param_values[..])
                     .await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
-                
+
                 let total: i64 = count_row.get(0);
-                
+
                 Ok((result, total as usize))
             },
-            
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get the readings with the given IDs that a user owns, newest first
+    ///
+    /// IDs of deleted readings or of other users' readings are skipped.
+    pub async fn get_by_ids(pool: &DatabasePool, user_id: &str, ids: &[String]) -> Result<Vec<BloodPressureReading>, RepositoryError> {
+        debug!("Getting {} blood pressure readings by ID from database for user {}", ids.len(), user_id);
+
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let placeholders = vec!["?"; ids.len()].join(", ");
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM blood_pressure_readings WHERE user_id = ? AND deleted_at IS NULL AND id IN ({})
+                     ORDER BY timestamp DESC",
+                    READING_COLUMNS, placeholders
+                ))?;
+
+                let params = std::iter::once(user_id).chain(ids.iter().map(String::as_str));
+                let readings = stmt.query_map(rusqlite::params_from_iter(params), sqlite_row_to_reading)?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(readings)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let placeholders = vec!["?"; ids.len()].join(", ");
+                let params: Vec<mysql::Value> = std::iter::once(user_id)
+                    .chain(ids.iter().map(String::as_str))
+                    .map(mysql::Value::from)
+                    .collect();
+                let rows: Vec<mysql::Row> = conn.exec(
+                    format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE user_id = ? AND deleted_at IS NULL AND id IN ({})
+                         ORDER BY timestamp DESC",
+                        READING_COLUMNS, placeholders
+                    ),
+                    params,
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                rows.into_iter().map(mysql_row_to_reading).collect()
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(
+                    &format!(
+                        "SELECT {} FROM blood_pressure_readings WHERE user_id = $1 AND deleted_at IS NULL AND id = ANY($2)
+                         ORDER BY timestamp DESC",
+                        READING_COLUMNS
+                    ),
+                    &[&user_id, &ids],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.iter().map(postgres_row_to_reading).collect())
+            },
+
             #[allow(unreachable_patterns)]
             _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
         }
     }
-} 
\ No newline at end of file
+
+    /// Page through a user's readings matching the filter, with every session in place of its readings
+    ///
+    /// A session is listed once when any of its readings matches, at the time
+    /// of its first reading. Returns the entries on the page and how many
+    /// there are in total.
+    pub async fn get_session_averaged_entries(
+        pool: &DatabasePool,
+        user_id: &str,
+        filter: &BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<SessionAveragedEntry>, usize), RepositoryError> {
+        debug!("Getting session averaged blood pressure entries from database for user {}", user_id);
+
+        let sort_direction = if sort_desc.unwrap_or(true) { "DESC" } else { "ASC" };
+        let pagination = limit
+            .map(|limit| format!(" LIMIT {} OFFSET {}", limit, offset.unwrap_or(0)))
+            .unwrap_or_default();
+        let conditions = filter_conditions(filter);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut where_clauses = vec!["user_id = ?".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params = vec![user_id.to_string()];
+                for (condition, value) in &conditions {
+                    where_clauses.push(format!("{} ?", condition));
+                    params.push(match value {
+                        FilterValue::Text(text) => text.to_string(),
+                        FilterValue::Time(time) => sqlite_time(time),
+                    });
+                }
+                let where_sql = where_clauses.join(" AND ");
+
+                let total: i64 = conn.query_row(
+                    &session_averaged_count_query(&where_sql),
+                    rusqlite::params_from_iter(params.iter()),
+                    |row| row.get(0),
+                )?;
+
+                // The owner comes once more, for the entry times
+                params.push(user_id.to_string());
+                let mut stmt = conn.prepare(&session_averaged_query(&where_sql, "?", sort_direction, &pagination))?;
+                let entries = stmt.query_map(
+                    rusqlite::params_from_iter(params.iter()),
+                    |row| Ok(session_averaged_entry(row.get(0)?, row.get(1)?)),
+                )?.collect::<Result<Vec<_>, _>>()?;
+
+                Ok((entries, total as usize))
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let mut where_clauses = vec!["user_id = ?".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params: Vec<mysql::Value> = vec![user_id.into()];
+                for (condition, value) in &conditions {
+                    where_clauses.push(format!("{} ?", condition));
+                    params.push(match value {
+                        FilterValue::Text(text) => (*text).into(),
+                        FilterValue::Time(time) => mysql_time(time),
+                    });
+                }
+                let where_sql = where_clauses.join(" AND ");
+
+                let total: Option<u64> = conn.exec_first(session_averaged_count_query(&where_sql), params.clone())
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                // The owner comes once more, for the entry times
+                params.push(user_id.into());
+                let rows: Vec<(String, Option<String>)> = conn.exec(
+                    session_averaged_query(&where_sql, "?", sort_direction, &pagination),
+                    params,
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let entries = rows.into_iter()
+                    .map(|(entry_id, session_id)| session_averaged_entry(entry_id, session_id))
+                    .collect();
+
+                Ok((entries, total.unwrap_or(0) as usize))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let mut where_clauses = vec!["user_id = $1".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params: Vec<Box<dyn tokio_postgres::types::ToSql + Sync + Send>> = vec![Box::new(user_id.to_string())];
+                for (condition, value) in &conditions {
+                    params.push(match value {
+                        FilterValue::Text(text) => Box::new(text.to_string()),
+                        FilterValue::Time(time) => Box::new(*time),
+                    });
+                    where_clauses.push(format!("{} ${}", condition, params.len()));
+                }
+                let where_sql = where_clauses.join(" AND ");
+                let param_values: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+                    params.iter().map(|p| p.as_ref() as &(dyn tokio_postgres::types::ToSql + Sync)).collect();
+
+                let count_row = client.query_one(&session_averaged_count_query(&where_sql), &param_values[..])
+                    .await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let total: i64 = count_row.get(0);
+
+                let rows = client.query(
+                    &session_averaged_query(&where_sql, "$1", sort_direction, &pagination),
+                    &param_values[..],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+                let entries = rows.iter()
+                    .map(|row| session_averaged_entry(row.get(0), row.get(1)))
+                    .collect();
+
+                Ok((entries, total as usize))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Overwrite the measured values of a user's reading
+    ///
+    /// The stored `created_at` is kept; `updated_at` is taken from the reading.
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn update_reading(pool: &DatabasePool, reading: &BloodPressureReading) -> Result<bool, RepositoryError> {
+        debug!("Updating blood pressure reading in database: id={}, user={}", reading.id, reading.user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE blood_pressure_readings
+                     SET systolic = ?1, diastolic = ?2, pulse = ?3, notes = ?4, timestamp = ?5,
+                         position = ?6, arm = ?7, device_id = ?8, category = ?9, updated_at = ?10
+                     WHERE id = ?11 AND user_id = ?12 AND deleted_at IS NULL",
+                    (
+                        reading.systolic,
+                        reading.diastolic,
+                        reading.pulse,
+                        &reading.notes,
+                        sqlite_time(&reading.timestamp),
+                        &reading.position,
+                        &reading.arm,
+                        &reading.device_id,
+                        &reading.category,
+                        sqlite_time(&reading.updated_at),
+                        &reading.id,
+                        &reading.user_id,
+                    ),
+                )?;
+
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                // MySQL reports changed rows rather than matched rows, so an
+                // update that repeats the stored values must still count
+                let exists: Option<u64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM blood_pressure_readings WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    (&reading.id, &reading.user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                conn.exec_drop(
+                    "UPDATE blood_pressure_readings
+                     SET systolic = ?, diastolic = ?, pulse = ?, notes = ?, timestamp = ?,
+                         position = ?, arm = ?, device_id = ?, category = ?, updated_at = ?
+                     WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    vec![
+                        mysql::Value::from(reading.systolic),
+                        mysql::Value::from(reading.diastolic),
+                        mysql::Value::from(reading.pulse),
+                        mysql::Value::from(&reading.notes),
+                        mysql_time(&reading.timestamp),
+                        mysql::Value::from(&reading.position),
+                        mysql::Value::from(&reading.arm),
+                        mysql::Value::from(&reading.device_id),
+                        mysql::Value::from(&reading.category),
+                        mysql_time(&reading.updated_at),
+                        mysql::Value::from(&reading.id),
+                        mysql::Value::from(&reading.user_id),
+                    ],
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(exists.unwrap_or(0) > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE blood_pressure_readings
+                     SET systolic = $1, diastolic = $2, pulse = $3, notes = $4, timestamp = $5,
+                         position = $6, arm = $7, device_id = $8, category = $9, updated_at = $10
+                     WHERE id = $11 AND user_id = $12 AND deleted_at IS NULL",
+                    &[
+                        &(reading.systolic as i32),
+                        &(reading.diastolic as i32),
+                        &reading.pulse.map(|p| p as i32),
+                        &reading.notes,
+                        &reading.timestamp,
+                        &reading.position,
+                        &reading.arm,
+                        &reading.device_id,
+                        &reading.category,
+                        &reading.updated_at,
+                        &reading.id,
+                        &reading.user_id,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Soft delete a user's reading by stamping `deleted_at`
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn soft_delete_reading(pool: &DatabasePool, user_id: &str, id: &Uuid, deleted_at: &DateTime<Utc>) -> Result<bool, RepositoryError> {
+        debug!("Soft deleting blood pressure reading in database: id={}, user={}", id, user_id);
+
+        let deleted_at = deleted_at_text(deleted_at);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE blood_pressure_readings SET deleted_at = ?1
+                     WHERE id = ?2 AND user_id = ?3 AND deleted_at IS NULL",
+                    (deleted_at, id.to_string(), user_id),
+                )?;
+
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "UPDATE blood_pressure_readings SET deleted_at = ?
+                     WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    (deleted_at, id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(conn.affected_rows() > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE blood_pressure_readings SET deleted_at = $1
+                     WHERE id = $2 AND user_id = $3 AND deleted_at IS NULL",
+                    &[&deleted_at, &id.to_string(), &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Clear the soft delete marker on a user's reading
+    ///
+    /// Restoring a reading that was never deleted is a no-op that still
+    /// reports success; `false` means the user owns no reading with that ID.
+    pub async fn restore_reading(pool: &DatabasePool, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        debug!("Restoring blood pressure reading in database: id={}, user={}", id, user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE blood_pressure_readings SET deleted_at = NULL
+                     WHERE id = ?1 AND user_id = ?2",
+                    (id.to_string(), user_id),
+                )?;
+
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                // MySQL reports changed rows rather than matched rows, so
+                // check for the reading explicitly
+                let exists: Option<u64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM blood_pressure_readings WHERE id = ? AND user_id = ?",
+                    (id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                conn.exec_drop(
+                    "UPDATE blood_pressure_readings SET deleted_at = NULL
+                     WHERE id = ? AND user_id = ?",
+                    (id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(exists.unwrap_or(0) > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE blood_pressure_readings SET deleted_at = NULL
+                     WHERE id = $1 AND user_id = $2",
+                    &[&id.to_string(), &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    fn reading(timestamp: &str, category: &str, arm: &str) -> BloodPressureReading {
+        BloodPressureReading {
+            id: Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            systolic: 120,
+            diastolic: 80,
+            pulse: None,
+            notes: None,
+            timestamp: timestamp.parse().unwrap(),
+            position: Some("sitting".to_string()),
+            arm: Some(arm.to_string()),
+            device_id: None,
+            category: Some(category.to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_filtered_applies_every_filter() {
+        let pool = sqlite_pool();
+        let readings = [
+            reading("2024-01-01T08:00:00Z", "Normal", "left"),
+            reading("2024-01-02T08:00:00Z", "Hypertension1", "left"),
+            reading("2024-01-03T08:00:00Z", "Hypertension1", "right"),
+            reading("2024-01-04T08:00:00Z", "Hypertension1", "left"),
+        ];
+        for reading in &readings {
+            DatabaseStorage::store_reading(&pool, reading).await.unwrap();
+        }
+
+        let filter = BloodPressureFilter {
+            end_date: Some("2024-01-03T23:59:59Z".parse().unwrap()),
+            category: Some("Hypertension1".to_string()),
+            arm: Some("left".to_string()),
+            ..Default::default()
+        };
+        let (page, total) = DatabaseStorage::get_filtered(&pool, "alice", &filter, None, None, None).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(page[0].id, readings[1].id);
+        assert_eq!(page[0].category.as_deref(), Some("Hypertension1"));
+        assert_eq!(page[0].timestamp, readings[1].timestamp);
+        assert_eq!(page[0].created_at.timestamp_millis(), readings[1].created_at.timestamp_millis());
+
+        // The total counts every match, not just the page
+        let filter = BloodPressureFilter {
+            category: Some("Hypertension1".to_string()),
+            ..Default::default()
+        };
+        let (page, total) = DatabaseStorage::get_filtered(&pool, "alice", &filter, Some(1), None, None).await.unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 1);
+
+        let (_, total) = DatabaseStorage::get_filtered(&pool, "bob", &filter, None, None, None).await.unwrap();
+        assert_eq!(total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_filtered_without_limit_returns_every_reading() {
+        let pool = sqlite_pool();
+        let start: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+        for hour in 0..150 {
+            let timestamp = (start + chrono::Duration::hours(hour)).to_rfc3339();
+            DatabaseStorage::store_reading(&pool, &reading(&timestamp, "Normal", "left")).await.unwrap();
+        }
+
+        let filter = BloodPressureFilter::default();
+        let (page, total) = DatabaseStorage::get_filtered(&pool, "alice", &filter, None, None, Some(false)).await.unwrap();
+        assert_eq!((page.len(), total), (150, 150));
+        assert_eq!(page.last().unwrap().timestamp, start + chrono::Duration::hours(149));
+
+        // An offset only applies together with a limit
+        let (page, _) = DatabaseStorage::get_filtered(&pool, "alice", &filter, None, Some(10), None).await.unwrap();
+        assert_eq!(page.len(), 150);
+        let (page, _) = DatabaseStorage::get_filtered(&pool, "alice", &filter, Some(100), Some(120), None).await.unwrap();
+        assert_eq!(page.len(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_stores_a_fixed_width_deleted_at() {
+        let pool = sqlite_pool();
+        let stored = reading("2024-01-01T08:00:00Z", "Normal", "left");
+        DatabaseStorage::store_reading(&pool, &stored).await.unwrap();
+
+        let id = Uuid::parse_str(&stored.id).unwrap();
+        let deleted_at: DateTime<Utc> = "2024-02-01T00:00:00Z".parse().unwrap();
+        assert!(DatabaseStorage::soft_delete_reading(&pool, "alice", &id, &deleted_at).await.unwrap());
+
+        let column: String = match &pool {
+            DatabasePool::SQLite(sqlite) => sqlite.get().unwrap().query_row(
+                "SELECT deleted_at FROM blood_pressure_readings WHERE id = ?1",
+                [&stored.id],
+                |row| row.get(0),
+            ).unwrap(),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        };
+        assert_eq!(column, "2024-02-01T00:00:00.000Z");
+    }
+}
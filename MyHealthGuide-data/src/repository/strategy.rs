@@ -0,0 +1,105 @@
+//! How repositories react when the database fails
+//!
+//! Repositories use the database whenever one is configured; the in-memory
+//! storage is only used, for reads and writes alike, when the application
+//! runs without a database. When a configured database fails, the
+//! [`StorageStrategy`] decides what happens instead of quietly serving a
+//! different dataset.
+
+use std::str::FromStr;
+use std::sync::RwLock;
+use once_cell::sync::Lazy;
+use tracing::{error, info, warn};
+
+use super::errors::RepositoryError;
+
+/// What to do when a configured database fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StorageStrategy {
+    /// Surface the failure as `RepositoryError::Unavailable`
+    #[default]
+    Strict,
+
+    /// Like `Strict`, but new readings are recorded in the durable outbox and
+    /// replayed into the database once it recovers
+    Outbox,
+}
+
+impl FromStr for StorageStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(StorageStrategy::Strict),
+            "outbox" => Ok(StorageStrategy::Outbox),
+            _ => Err(format!("Unknown storage strategy: {}", s)),
+        }
+    }
+}
+
+impl StorageStrategy {
+    /// Read the strategy from the `STORAGE_STRATEGY` variable, defaulting to strict
+    pub fn from_env() -> Self {
+        match std::env::var("STORAGE_STRATEGY") {
+            Ok(value) => value.parse().unwrap_or_else(|e| {
+                warn!("{}, using strict storage", e);
+                StorageStrategy::Strict
+            }),
+            Err(_) => StorageStrategy::Strict,
+        }
+    }
+}
+
+/// The strategy used by the repositories, read from the environment on first use
+static STORAGE_STRATEGY: Lazy<RwLock<StorageStrategy>> = Lazy::new(|| {
+    let strategy = StorageStrategy::from_env();
+    info!("Using {:?} storage strategy", strategy);
+    RwLock::new(strategy)
+});
+
+/// Get the configured storage strategy
+pub fn storage_strategy() -> StorageStrategy {
+    match STORAGE_STRATEGY.read() {
+        Ok(strategy) => *strategy,
+        Err(poisoned) => *poisoned.into_inner(),
+    }
+}
+
+/// Replace the configured storage strategy
+pub fn set_storage_strategy(strategy: StorageStrategy) {
+    match STORAGE_STRATEGY.write() {
+        Ok(mut current) => *current = strategy,
+        Err(poisoned) => *poisoned.into_inner() = strategy,
+    }
+}
+
+/// Report a failed database operation as unavailable storage
+///
+/// Errors that describe the request rather than the database, such as a
+/// conflict on a unique key, are passed through unchanged.
+pub(crate) fn unavailable(action: &str, err: RepositoryError) -> RepositoryError {
+    match err {
+        RepositoryError::Validation(_) | RepositoryError::NotFound(_) | RepositoryError::Conflict(_) => err,
+        err => {
+            error!("Failed to {} in database: {}", action, err);
+            RepositoryError::Unavailable(format!("Failed to {}: {}", action, err))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_strategy() {
+        assert_eq!("strict".parse::<StorageStrategy>().unwrap(), StorageStrategy::Strict);
+        assert_eq!("OUTBOX".parse::<StorageStrategy>().unwrap(), StorageStrategy::Outbox);
+        assert!("memory".parse::<StorageStrategy>().is_err());
+
+        let conflict = unavailable("store user", RepositoryError::Conflict("taken".to_string()));
+        assert!(matches!(conflict, RepositoryError::Conflict(_)));
+        let lock = unavailable("store user", RepositoryError::Lock("poisoned".to_string()));
+        assert!(matches!(lock, RepositoryError::Unavailable(_)));
+    }
+}
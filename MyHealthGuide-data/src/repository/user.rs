@@ -0,0 +1,132 @@
+use tracing::debug;
+use async_trait::async_trait;
+
+use crate::models::user::User;
+use crate::database::get_db_pool;
+use super::errors::RepositoryError;
+use super::in_memory::InMemoryUserStorage;
+use super::user_storage::UserDatabaseStorage;
+use super::strategy::unavailable;
+
+/// Repository trait for user accounts
+#[async_trait]
+pub trait UserRepositoryTrait {
+    /// Create a new user, failing with `RepositoryError::Conflict` if the email is taken
+    async fn create(&self, user: User) -> Result<User, RepositoryError>;
+
+    /// Get a user by their (normalized) email address
+    async fn get_by_email(&self, email: &str) -> Result<Option<User>, RepositoryError>;
+
+    /// Get a user by ID
+    async fn get_by_id(&self, id: &str) -> Result<Option<User>, RepositoryError>;
+}
+
+/// Repository for user accounts.
+/// Uses the configured database, or in-memory storage when none is
+/// configured. Accounts are never written to the outbox: a registration that
+/// cannot reach the database fails as unavailable whatever the storage
+/// strategy, since the email uniqueness check needs the database too.
+#[derive(Debug, Clone, Default)]
+pub struct UserRepository {
+    /// In-memory storage for when no database is configured
+    storage: InMemoryUserStorage,
+}
+
+impl UserRepository {
+    /// Create a new repository
+    pub fn new() -> Self {
+        Self {
+            storage: InMemoryUserStorage::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepositoryTrait for UserRepository {
+    /// Create a new user
+    async fn create(&self, user: User) -> Result<User, RepositoryError> {
+        if self.get_by_email(&user.email).await?.is_some() {
+            return Err(RepositoryError::Conflict(format!("Email {} is already registered", user.email)));
+        }
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Storing user in database: {}", user.id);
+                UserDatabaseStorage::store_user(&pool, &user).await
+                    .map_err(|e| unavailable("store user", e))?;
+                Ok(user)
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage", e);
+                self.storage.store_user(&user).await
+            }
+        }
+    }
+
+    /// Get a user by email
+    async fn get_by_email(&self, email: &str) -> Result<Option<User>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                UserDatabaseStorage::get_by_email(&pool, email).await
+                    .map_err(|e| unavailable("get user by email", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_by_email", e);
+                self.storage.get_by_email(email).await
+            }
+        }
+    }
+
+    /// Get a user by ID
+    async fn get_by_id(&self, id: &str) -> Result<Option<User>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                UserDatabaseStorage::get_by_id(&pool, id).await
+                    .map_err(|e| unavailable("get user by ID", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_by_id", e);
+                self.storage.get_by_id(id).await
+            }
+        }
+    }
+}
+
+/// Mock user repository for testing
+#[cfg(any(test, feature = "mock"))]
+pub mod tests {
+    use super::*;
+
+    /// Mock implementation of UserRepository for testing.
+    /// Keeps created users in memory so registration and login can be
+    /// exercised together.
+    #[derive(Default)]
+    pub struct MockUserRepository {
+        storage: InMemoryUserStorage,
+    }
+
+    impl MockUserRepository {
+        /// Create a new empty mock repository
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    #[async_trait]
+    impl UserRepositoryTrait for MockUserRepository {
+        async fn create(&self, user: User) -> Result<User, RepositoryError> {
+            self.storage.store_user(&user).await
+        }
+
+        async fn get_by_email(&self, email: &str) -> Result<Option<User>, RepositoryError> {
+            self.storage.get_by_email(email).await
+        }
+
+        async fn get_by_id(&self, id: &str) -> Result<Option<User>, RepositoryError> {
+            self.storage.get_by_id(id).await
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use tracing::debug;
+
+use crate::models::user::User;
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+
+/// Columns selected for every user query, in the order the row mapping
+/// helpers below expect them
+const USER_COLUMNS: &str = "id, email, password_hash, name, roles, created_at";
+
+/// Split the stored comma separated role list
+fn parse_roles(roles: &str) -> Vec<String> {
+    roles.split(',')
+        .map(str::trim)
+        .filter(|role| !role.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Map a SQLite row selected with `USER_COLUMNS` to a user
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_user(row: &rusqlite::Row<'_>) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        email: row.get(1)?,
+        password_hash: row.get(2)?,
+        name: row.get(3)?,
+        roles: parse_roles(&row.get::<_, String>(4)?),
+        created_at: row.get(5)?,
+    })
+}
+
+/// Row shape returned by MySQL for a query selecting `USER_COLUMNS`
+#[cfg(feature = "mysql_db")]
+type MySqlUserRow = (String, String, String, Option<String>, String, String);
+
+/// Map a MySQL row selected with `USER_COLUMNS` to a user
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_user(row: MySqlUserRow) -> User {
+    let (id, email, password_hash, name, roles, created_at) = row;
+    User {
+        id,
+        email,
+        password_hash,
+        name,
+        roles: parse_roles(&roles),
+        created_at,
+    }
+}
+
+/// Map a PostgreSQL row selected with `USER_COLUMNS` to a user
+#[cfg(feature = "postgres")]
+fn postgres_row_to_user(row: &tokio_postgres::Row) -> User {
+    User {
+        id: row.get(0),
+        email: row.get(1),
+        password_hash: row.get(2),
+        name: row.get(3),
+        roles: parse_roles(row.get(4)),
+        created_at: row.get(5),
+    }
+}
+
+/// Error for an insert that hit the unique email index
+fn email_taken(email: &str) -> RepositoryError {
+    RepositoryError::Conflict(format!("Email {} is already registered", email))
+}
+
+/// Whether a SQLite error is a unique constraint violation (SQLITE_CONSTRAINT_UNIQUE)
+#[cfg(feature = "sqlite")]
+fn is_sqlite_unique_violation(error: &rusqlite::Error) -> bool {
+    matches!(error, rusqlite::Error::SqliteFailure(e, _) if e.extended_code == 2067)
+}
+
+/// Whether a MySQL error is a duplicate key error (ER_DUP_ENTRY)
+#[cfg(feature = "mysql_db")]
+fn is_mysql_unique_violation(error: &mysql::Error) -> bool {
+    matches!(error, mysql::Error::MySqlError(e) if e.code == 1062)
+}
+
+/// Whether a PostgreSQL error is a unique violation (SQLSTATE 23505)
+#[cfg(feature = "postgres")]
+fn is_postgres_unique_violation(error: &tokio_postgres::Error) -> bool {
+    error.code() == Some(&tokio_postgres::error::SqlState::UNIQUE_VIOLATION)
+}
+
+/// Database storage operations for user accounts
+///
+/// Emails are unique; callers are expected to normalize them before they
+/// reach this layer.
+pub struct UserDatabaseStorage;
+
+impl UserDatabaseStorage {
+    /// Store a new user in the database
+    ///
+    /// A concurrent registration that already took the email fails with
+    /// `RepositoryError::Conflict` rather than a database error.
+    pub async fn store_user(pool: &DatabasePool, user: &User) -> Result<(), RepositoryError> {
+        debug!("Storing user in database: id={}", user.id);
+
+        let roles = user.roles.join(",");
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT INTO users (id, email, password_hash, name, roles, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    (&user.id, &user.email, &user.password_hash, &user.name, &roles, &user.created_at),
+                ).map_err(|e| if is_sqlite_unique_violation(&e) {
+                    email_taken(&user.email)
+                } else {
+                    RepositoryError::Sqlite(e)
+                })?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT INTO users (id, email, password_hash, name, roles, created_at)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                    (&user.id, &user.email, &user.password_hash, &user.name, &roles, &user.created_at),
+                ).map_err(|e| if is_mysql_unique_violation(&e) {
+                    email_taken(&user.email)
+                } else {
+                    RepositoryError::Database(e.to_string().into())
+                })?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO users (id, email, password_hash, name, roles, created_at)
+                     VALUES ($1, $2, $3, $4, $5, $6)",
+                    &[&user.id, &user.email, &user.password_hash, &user.name, &roles, &user.created_at],
+                ).await.map_err(|e| if is_postgres_unique_violation(&e) {
+                    email_taken(&user.email)
+                } else {
+                    RepositoryError::Database(e.to_string().into())
+                })?;
+
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Find a user by a unique column
+    async fn find_by(pool: &DatabasePool, column: &str, value: &str) -> Result<Option<User>, RepositoryError> {
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM users WHERE {} = ?",
+                    USER_COLUMNS, column
+                ))?;
+
+                match stmt.query_row([value], sqlite_row_to_user) {
+                    Ok(user) => Ok(Some(user)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(RepositoryError::Sqlite(e)),
+                }
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let row: Option<MySqlUserRow> = conn.exec_first(
+                    format!("SELECT {} FROM users WHERE {} = ?", USER_COLUMNS, column),
+                    (value,),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(row.map(mysql_row_to_user))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(
+                    &format!("SELECT {} FROM users WHERE {} = $1", USER_COLUMNS, column),
+                    &[&value],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.first().map(postgres_row_to_user))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a user by email from the database
+    pub async fn get_by_email(pool: &DatabasePool, email: &str) -> Result<Option<User>, RepositoryError> {
+        debug!("Getting user by email from database");
+        Self::find_by(pool, "email", email).await
+    }
+
+    /// Get a user by ID from the database
+    pub async fn get_by_id(pool: &DatabasePool, id: &str) -> Result<Option<User>, RepositoryError> {
+        debug!("Getting user by ID from database: {}", id);
+        Self::find_by(pool, "id", id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A single connection in-memory SQLite pool with the schema applied
+    fn sqlite_pool() -> DatabasePool {
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .unwrap();
+        crate::database::migrations::run_sqlite_migrations(&pool.get().unwrap()).unwrap();
+        DatabasePool::SQLite(Arc::new(pool))
+    }
+
+    fn user(id: &str, email: &str) -> User {
+        User {
+            id: id.to_string(),
+            email: email.to_string(),
+            password_hash: "hash".to_string(),
+            name: None,
+            roles: vec!["user".to_string()],
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_user_with_taken_email_is_a_conflict() {
+        let pool = sqlite_pool();
+        UserDatabaseStorage::store_user(&pool, &user("user-1", "alice@example.com")).await.unwrap();
+
+        let result = UserDatabaseStorage::store_user(&pool, &user("user-2", "alice@example.com")).await;
+        assert!(matches!(result, Err(RepositoryError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_parse_roles() {
+        assert_eq!(parse_roles("user,admin"), vec!["user", "admin"]);
+        assert_eq!(parse_roles(" user , "), vec!["user"]);
+        assert!(parse_roles("").is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+use chrono::Utc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+use async_trait::async_trait;
+
+use crate::models::weight::{WeightReading, CreateWeightRequest};
+use crate::database::get_db_pool;
+use super::errors::RepositoryError;
+use super::in_memory::InMemoryWeightStorage;
+use super::weight_storage::WeightDatabaseStorage;
+use super::strategy::{storage_strategy, unavailable, StorageStrategy};
+use super::outbox::{outbox, OutboxEntry};
+
+/// Repository trait for weight readings
+#[async_trait]
+pub trait WeightRepositoryTrait {
+    /// Create a new weight reading owned by the given user
+    async fn create(&self, user_id: &str, request: CreateWeightRequest) -> Result<WeightReading, RepositoryError>;
+
+    /// Get a weight reading by ID, provided it belongs to the user
+    async fn get_by_id(&self, user_id: &str, id: Uuid) -> Result<Option<WeightReading>, RepositoryError>;
+
+    /// Get a user's filtered weight readings
+    async fn get_filtered(
+        &self,
+        user_id: &str,
+        start_date: Option<String>,
+        end_date: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<WeightReading>, usize), RepositoryError>;
+
+    /// Replace the values of one of a user's readings, returning `None` if it does not exist
+    async fn update(&self, user_id: &str, id: Uuid, request: CreateWeightRequest) -> Result<Option<WeightReading>, RepositoryError>;
+
+    /// Soft delete one of a user's readings, returning `false` if there was nothing to delete
+    async fn delete(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError>;
+
+    /// Restore a soft deleted reading, returning `None` if the user owns no such reading
+    async fn restore(&self, user_id: &str, id: Uuid) -> Result<Option<WeightReading>, RepositoryError>;
+}
+
+/// Build the stored form of a weight reading
+fn build_reading(id: Uuid, user_id: &str, request: CreateWeightRequest) -> WeightReading {
+    WeightReading {
+        id: id.to_string(),
+        user_id: user_id.to_string(),
+        weight_kg: request.weight_kg,
+        body_fat_percentage: request.body_fat_percentage,
+        muscle_mass_kg: request.muscle_mass_kg,
+        notes: request.notes,
+        timestamp: request.timestamp,
+    }
+}
+
+/// Repository for weight readings.
+/// Uses the configured database and handles its failures according to the
+/// [`StorageStrategy`], like the blood pressure repository.
+#[derive(Debug, Clone, Default)]
+pub struct WeightRepository {
+    /// In-memory storage for when no database is configured
+    storage: InMemoryWeightStorage,
+}
+
+impl WeightRepository {
+    /// Create a new repository
+    pub fn new() -> Self {
+        Self {
+            storage: InMemoryWeightStorage::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl WeightRepositoryTrait for WeightRepository {
+    /// Create a new weight reading owned by the given user
+    async fn create(&self, user_id: &str, request: CreateWeightRequest) -> Result<WeightReading, RepositoryError> {
+        let reading = build_reading(Uuid::new_v4(), user_id, request);
+
+        let pool = match get_db_pool() {
+            Ok(pool) => pool,
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage", e);
+                return self.storage.store_reading(&reading).await;
+            }
+        };
+
+        debug!("Storing weight reading in database: {}", reading.id);
+        match WeightDatabaseStorage::store_reading(&pool, &reading).await {
+            Ok(_) => Ok(reading),
+            Err(e) if storage_strategy() == StorageStrategy::Outbox => {
+                warn!("Failed to store weight reading in database, recording it in the outbox: {}", e);
+                outbox().record(&OutboxEntry::Weight(reading.clone())).await?;
+                Ok(reading)
+            },
+            Err(e) => Err(unavailable("store weight reading", e)),
+        }
+    }
+
+    /// Get a weight reading by ID, provided it belongs to the user
+    async fn get_by_id(&self, user_id: &str, id: Uuid) -> Result<Option<WeightReading>, RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting weight reading by ID from database: {}", id);
+                WeightDatabaseStorage::get_by_id(&pool, user_id, &id).await
+                    .map_err(|e| unavailable("get weight reading", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_by_id", e);
+                self.storage.get_by_id(user_id, &id).await
+            }
+        }
+    }
+
+    /// Get a user's filtered weight readings
+    async fn get_filtered(
+        &self,
+        user_id: &str,
+        start_date: Option<String>,
+        end_date: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<WeightReading>, usize), RepositoryError> {
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Getting filtered weight readings from database");
+                WeightDatabaseStorage::get_filtered(
+                    &pool,
+                    user_id,
+                    start_date.as_deref(),
+                    end_date.as_deref(),
+                    limit,
+                    offset,
+                    sort_desc,
+                ).await.map_err(|e| unavailable("get filtered weight readings", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for get_filtered", e);
+                self.storage.get_filtered(
+                    user_id,
+                    start_date.as_deref(),
+                    end_date.as_deref(),
+                    limit,
+                    offset,
+                    sort_desc,
+                ).await
+            }
+        }
+    }
+
+    /// Replace the values of one of a user's readings
+    async fn update(&self, user_id: &str, id: Uuid, request: CreateWeightRequest) -> Result<Option<WeightReading>, RepositoryError> {
+        let reading = build_reading(id, user_id, request);
+
+        let updated = match get_db_pool() {
+            Ok(pool) => {
+                debug!("Updating weight reading in database: {}", id);
+                WeightDatabaseStorage::update_reading(&pool, &reading).await
+                    .map_err(|e| unavailable("update weight reading", e))?
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for update", e);
+                self.storage.update_reading(&reading).await?
+            }
+        };
+
+        Ok(updated.then_some(reading))
+    }
+
+    /// Soft delete one of a user's readings
+    async fn delete(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+        let deleted_at = Utc::now().to_rfc3339();
+
+        match get_db_pool() {
+            Ok(pool) => {
+                debug!("Soft deleting weight reading in database: {}", id);
+                WeightDatabaseStorage::soft_delete_reading(&pool, user_id, &id, &deleted_at).await
+                    .map_err(|e| unavailable("delete weight reading", e))
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for delete", e);
+                self.storage.soft_delete_reading(user_id, &id).await
+            }
+        }
+    }
+
+    /// Restore a soft deleted reading
+    async fn restore(&self, user_id: &str, id: Uuid) -> Result<Option<WeightReading>, RepositoryError> {
+        let restored = match get_db_pool() {
+            Ok(pool) => {
+                debug!("Restoring weight reading in database: {}", id);
+                WeightDatabaseStorage::restore_reading(&pool, user_id, &id).await
+                    .map_err(|e| unavailable("restore weight reading", e))?
+            },
+            Err(e) => {
+                // No database configured, use in-memory storage
+                debug!("Database not available ({}), using in-memory storage for restore", e);
+                self.storage.restore_reading(user_id, &id).await?
+            }
+        };
+
+        if !restored {
+            return Ok(None);
+        }
+
+        self.get_by_id(user_id, id).await
+    }
+}
+
+/// Mock weight repository for testing
+#[cfg(any(test, feature = "mock"))]
+pub mod tests {
+    use super::*;
+
+    /// Mock implementation of WeightRepository for testing
+    #[derive(Default)]
+    pub struct MockWeightRepository {
+        readings: Vec<WeightReading>,
+    }
+
+    impl MockWeightRepository {
+        /// Create a new empty mock repository
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Create a mock repository with predefined readings
+        pub fn with_readings(readings: Vec<WeightReading>) -> Self {
+            Self { readings }
+        }
+
+        /// Iterate over the predefined readings owned by a user
+        fn readings_for<'a>(&'a self, user_id: &'a str) -> impl Iterator<Item = &'a WeightReading> + 'a {
+            self.readings.iter().filter(move |reading| reading.user_id == user_id)
+        }
+    }
+
+    #[async_trait]
+    impl WeightRepositoryTrait for MockWeightRepository {
+        async fn create(&self, user_id: &str, request: CreateWeightRequest) -> Result<WeightReading, RepositoryError> {
+            Ok(build_reading(Uuid::new_v4(), user_id, request))
+        }
+
+        async fn get_by_id(&self, user_id: &str, id: Uuid) -> Result<Option<WeightReading>, RepositoryError> {
+            let id = id.to_string();
+            Ok(self.readings_for(user_id).find(|r| r.id == id).cloned())
+        }
+
+        async fn get_filtered(
+            &self,
+            user_id: &str,
+            start_date: Option<String>,
+            end_date: Option<String>,
+            limit: Option<usize>,
+            offset: Option<usize>,
+            sort_desc: Option<bool>,
+        ) -> Result<(Vec<WeightReading>, usize), RepositoryError> {
+            let mut filtered: Vec<WeightReading> = self.readings_for(user_id)
+                .filter(|reading| start_date.as_ref().is_none_or(|start| reading.timestamp >= *start))
+                .filter(|reading| end_date.as_ref().is_none_or(|end| reading.timestamp <= *end))
+                .cloned()
+                .collect();
+
+            filtered.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            if sort_desc.unwrap_or(true) {
+                filtered.reverse();
+            }
+
+            let total = filtered.len();
+            let paged = filtered
+                .into_iter()
+                .skip(offset.unwrap_or(0))
+                .take(limit.unwrap_or(usize::MAX))
+                .collect();
+
+            Ok((paged, total))
+        }
+
+        async fn update(&self, user_id: &str, id: Uuid, request: CreateWeightRequest) -> Result<Option<WeightReading>, RepositoryError> {
+            let key = id.to_string();
+            if self.readings_for(user_id).all(|r| r.id != key) {
+                return Ok(None);
+            }
+
+            Ok(Some(build_reading(id, user_id, request)))
+        }
+
+        async fn delete(&self, user_id: &str, id: Uuid) -> Result<bool, RepositoryError> {
+            let id = id.to_string();
+            Ok(self.readings_for(user_id).any(|r| r.id == id))
+        }
+
+        async fn restore(&self, user_id: &str, id: Uuid) -> Result<Option<WeightReading>, RepositoryError> {
+            self.get_by_id(user_id, id).await
+        }
+    }
+}
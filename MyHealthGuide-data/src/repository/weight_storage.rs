@@ -0,0 +1,545 @@
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::models::weight::WeightReading;
+use crate::database::DatabasePool;
+use super::errors::RepositoryError;
+
+/// Columns selected for every weight reading query, in the order the row
+/// mapping helpers below expect them
+const WEIGHT_COLUMNS: &str =
+    "id, user_id, weight_kg, body_fat_percentage, muscle_mass_kg, notes, timestamp";
+
+/// Map a SQLite row selected with `WEIGHT_COLUMNS` to a reading
+#[cfg(feature = "sqlite")]
+fn sqlite_row_to_weight(row: &rusqlite::Row<'_>) -> rusqlite::Result<WeightReading> {
+    Ok(WeightReading {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        weight_kg: row.get::<_, f64>(2)? as f32,
+        body_fat_percentage: row.get::<_, Option<f64>>(3)?.map(|v| v as f32),
+        muscle_mass_kg: row.get::<_, Option<f64>>(4)?.map(|v| v as f32),
+        notes: row.get(5)?,
+        timestamp: row.get(6)?,
+    })
+}
+
+/// Row shape returned by MySQL for a query selecting `WEIGHT_COLUMNS`
+#[cfg(feature = "mysql_db")]
+type MySqlWeightRow = (String, String, f64, Option<f64>, Option<f64>, Option<String>, String);
+
+/// Map a MySQL row selected with `WEIGHT_COLUMNS` to a reading
+#[cfg(feature = "mysql_db")]
+fn mysql_row_to_weight(row: MySqlWeightRow) -> WeightReading {
+    let (id, user_id, weight_kg, body_fat_percentage, muscle_mass_kg, notes, timestamp) = row;
+    WeightReading {
+        id,
+        user_id,
+        weight_kg: weight_kg as f32,
+        body_fat_percentage: body_fat_percentage.map(|v| v as f32),
+        muscle_mass_kg: muscle_mass_kg.map(|v| v as f32),
+        notes,
+        timestamp,
+    }
+}
+
+/// Map a PostgreSQL row selected with `WEIGHT_COLUMNS` to a reading
+#[cfg(feature = "postgres")]
+fn postgres_row_to_weight(row: &tokio_postgres::Row) -> WeightReading {
+    WeightReading {
+        id: row.get(0),
+        user_id: row.get(1),
+        weight_kg: row.get(2),
+        body_fat_percentage: row.get(3),
+        muscle_mass_kg: row.get(4),
+        notes: row.get(5),
+        timestamp: row.get(6),
+    }
+}
+
+/// Database storage operations for weight readings
+///
+/// Follows the same rules as the blood pressure storage: every query is
+/// scoped to the owning user and deleted readings are only marked with
+/// `deleted_at` until they are restored.
+pub struct WeightDatabaseStorage;
+
+impl WeightDatabaseStorage {
+    /// Store a weight reading in the database
+    pub async fn store_reading(pool: &DatabasePool, reading: &WeightReading) -> Result<(), RepositoryError> {
+        debug!("Storing weight reading in database: id={}", reading.id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                conn.execute(
+                    "INSERT INTO weight_readings
+                     (id, user_id, weight_kg, body_fat_percentage, muscle_mass_kg, notes, timestamp)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    (
+                        &reading.id,
+                        &reading.user_id,
+                        reading.weight_kg as f64,
+                        reading.body_fat_percentage.map(|v| v as f64),
+                        reading.muscle_mass_kg.map(|v| v as f64),
+                        &reading.notes,
+                        &reading.timestamp,
+                    ),
+                )?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "INSERT INTO weight_readings
+                     (id, user_id, weight_kg, body_fat_percentage, muscle_mass_kg, notes, timestamp)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    (
+                        &reading.id,
+                        &reading.user_id,
+                        reading.weight_kg as f64,
+                        reading.body_fat_percentage.map(|v| v as f64),
+                        reading.muscle_mass_kg.map(|v| v as f64),
+                        &reading.notes,
+                        &reading.timestamp,
+                    ),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                client.execute(
+                    "INSERT INTO weight_readings
+                     (id, user_id, weight_kg, body_fat_percentage, muscle_mass_kg, notes, timestamp)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                    &[
+                        &reading.id,
+                        &reading.user_id,
+                        &reading.weight_kg,
+                        &reading.body_fat_percentage,
+                        &reading.muscle_mass_kg,
+                        &reading.notes,
+                        &reading.timestamp,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(())
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a weight reading by ID from the database, provided it belongs to the user
+    pub async fn get_by_id(pool: &DatabasePool, user_id: &str, id: &Uuid) -> Result<Option<WeightReading>, RepositoryError> {
+        debug!("Getting weight reading by ID from database: id={}, user={}", id, user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM weight_readings WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    WEIGHT_COLUMNS
+                ))?;
+
+                match stmt.query_row([id.to_string().as_str(), user_id], sqlite_row_to_weight) {
+                    Ok(reading) => Ok(Some(reading)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                    Err(e) => Err(RepositoryError::Sqlite(e)),
+                }
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let row: Option<MySqlWeightRow> = conn.exec_first(
+                    format!(
+                        "SELECT {} FROM weight_readings WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                        WEIGHT_COLUMNS
+                    ),
+                    (id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(row.map(mysql_row_to_weight))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let rows = client.query(
+                    &format!(
+                        "SELECT {} FROM weight_readings WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL",
+                        WEIGHT_COLUMNS
+                    ),
+                    &[&id.to_string(), &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(rows.first().map(postgres_row_to_weight))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Get a user's filtered weight readings from the database
+    ///
+    /// Without a `limit` every matching reading is returned and `offset` is ignored.
+    pub async fn get_filtered(
+        pool: &DatabasePool,
+        user_id: &str,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<WeightReading>, usize), RepositoryError> {
+        debug!("Getting filtered weight readings from database for user {}", user_id);
+
+        let sort_direction = if sort_desc.unwrap_or(true) { "DESC" } else { "ASC" };
+        let pagination = limit
+            .map(|limit| format!(" LIMIT {} OFFSET {}", limit, offset.unwrap_or(0)))
+            .unwrap_or_default();
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let mut where_clauses = vec!["user_id = ?", "deleted_at IS NULL"];
+                let mut params: Vec<&dyn rusqlite::ToSql> = vec![&user_id];
+
+                let start_string: Option<String> = start_date.map(|s| s.to_string());
+                let end_string: Option<String> = end_date.map(|s| s.to_string());
+
+                if let Some(ref start) = start_string {
+                    where_clauses.push("timestamp >= ?");
+                    params.push(start as &dyn rusqlite::ToSql);
+                }
+
+                if let Some(ref end) = end_string {
+                    where_clauses.push("timestamp <= ?");
+                    params.push(end as &dyn rusqlite::ToSql);
+                }
+
+                let where_sql = where_clauses.join(" AND ");
+                let query = format!(
+                    "SELECT {} FROM weight_readings WHERE {} ORDER BY timestamp {}{}",
+                    WEIGHT_COLUMNS, where_sql, sort_direction, pagination
+                );
+
+                let mut stmt = conn.prepare(&query)?;
+                let readings = stmt.query_map(rusqlite::params_from_iter(params.iter()), sqlite_row_to_weight)?;
+
+                let mut result = Vec::new();
+                for reading in readings {
+                    result.push(reading?);
+                }
+
+                let total: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM weight_readings WHERE {}", where_sql),
+                    rusqlite::params_from_iter(params.iter()),
+                    |row| row.get(0),
+                )?;
+
+                Ok((result, total as usize))
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let mut where_clauses = vec!["user_id = ?", "deleted_at IS NULL"];
+                let mut params: Vec<mysql::Value> = vec![user_id.into()];
+
+                if let Some(start) = start_date {
+                    where_clauses.push("timestamp >= ?");
+                    params.push(start.into());
+                }
+
+                if let Some(end) = end_date {
+                    where_clauses.push("timestamp <= ?");
+                    params.push(end.into());
+                }
+
+                let where_sql = where_clauses.join(" AND ");
+                let query = format!(
+                    "SELECT {} FROM weight_readings WHERE {} ORDER BY timestamp {}{}",
+                    WEIGHT_COLUMNS, where_sql, sort_direction, pagination
+                );
+
+                let rows: Vec<MySqlWeightRow> = conn.exec(&query, params.clone())
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let total: Option<u64> = conn.exec_first(
+                    format!("SELECT COUNT(*) FROM weight_readings WHERE {}", where_sql),
+                    params,
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok((rows.into_iter().map(mysql_row_to_weight).collect(), total.unwrap_or(0) as usize))
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let mut where_clauses = vec!["user_id = $1".to_string(), "deleted_at IS NULL".to_string()];
+                let mut params = vec![user_id];
+
+                if let Some(start) = start_date {
+                    params.push(start);
+                    where_clauses.push(format!("timestamp >= ${}", params.len()));
+                }
+
+                if let Some(end) = end_date {
+                    params.push(end);
+                    where_clauses.push(format!("timestamp <= ${}", params.len()));
+                }
+
+                let where_sql = where_clauses.join(" AND ");
+                let query = format!(
+                    "SELECT {} FROM weight_readings WHERE {} ORDER BY timestamp {}{}",
+                    WEIGHT_COLUMNS, where_sql, sort_direction, pagination
+                );
+
+                let param_values: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
+                    params.iter().map(|p| p as &(dyn tokio_postgres::types::ToSql + Sync)).collect();
+
+                let rows = client.query(&query, &param_values[..])
+                    .await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let count_row = client.query_one(
+                    &format!("SELECT COUNT(*) FROM weight_readings WHERE {}", where_sql),
+                    &param_values[..],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let total: i64 = count_row.get(0);
+
+                Ok((rows.iter().map(postgres_row_to_weight).collect(), total as usize))
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Overwrite the measured values of a user's weight reading
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn update_reading(pool: &DatabasePool, reading: &WeightReading) -> Result<bool, RepositoryError> {
+        debug!("Updating weight reading in database: id={}, user={}", reading.id, reading.user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE weight_readings
+                     SET weight_kg = ?1, body_fat_percentage = ?2, muscle_mass_kg = ?3, notes = ?4, timestamp = ?5
+                     WHERE id = ?6 AND user_id = ?7 AND deleted_at IS NULL",
+                    (
+                        reading.weight_kg as f64,
+                        reading.body_fat_percentage.map(|v| v as f64),
+                        reading.muscle_mass_kg.map(|v| v as f64),
+                        &reading.notes,
+                        &reading.timestamp,
+                        &reading.id,
+                        &reading.user_id,
+                    ),
+                )?;
+
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                // MySQL reports changed rows rather than matched rows, so an
+                // update that repeats the stored values must still count
+                let exists: Option<u64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM weight_readings WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    (&reading.id, &reading.user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                conn.exec_drop(
+                    "UPDATE weight_readings
+                     SET weight_kg = ?, body_fat_percentage = ?, muscle_mass_kg = ?, notes = ?, timestamp = ?
+                     WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    (
+                        reading.weight_kg as f64,
+                        reading.body_fat_percentage.map(|v| v as f64),
+                        reading.muscle_mass_kg.map(|v| v as f64),
+                        &reading.notes,
+                        &reading.timestamp,
+                        &reading.id,
+                        &reading.user_id,
+                    ),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(exists.unwrap_or(0) > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE weight_readings
+                     SET weight_kg = $1, body_fat_percentage = $2, muscle_mass_kg = $3, notes = $4, timestamp = $5
+                     WHERE id = $6 AND user_id = $7 AND deleted_at IS NULL",
+                    &[
+                        &reading.weight_kg,
+                        &reading.body_fat_percentage,
+                        &reading.muscle_mass_kg,
+                        &reading.notes,
+                        &reading.timestamp,
+                        &reading.id,
+                        &reading.user_id,
+                    ],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Soft delete a user's weight reading by stamping `deleted_at`
+    ///
+    /// Returns `false` when no active reading with that ID belongs to the user.
+    pub async fn soft_delete_reading(pool: &DatabasePool, user_id: &str, id: &Uuid, deleted_at: &str) -> Result<bool, RepositoryError> {
+        debug!("Soft deleting weight reading in database: id={}, user={}", id, user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE weight_readings SET deleted_at = ?1
+                     WHERE id = ?2 AND user_id = ?3 AND deleted_at IS NULL",
+                    (deleted_at, id.to_string(), user_id),
+                )?;
+
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                conn.exec_drop(
+                    "UPDATE weight_readings SET deleted_at = ?
+                     WHERE id = ? AND user_id = ? AND deleted_at IS NULL",
+                    (deleted_at, id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(conn.affected_rows() > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE weight_readings SET deleted_at = $1
+                     WHERE id = $2 AND user_id = $3 AND deleted_at IS NULL",
+                    &[&deleted_at, &id.to_string(), &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+
+    /// Clear the soft delete marker on a user's weight reading
+    ///
+    /// Restoring a reading that was never deleted is a no-op that still
+    /// reports success; `false` means the user owns no reading with that ID.
+    pub async fn restore_reading(pool: &DatabasePool, user_id: &str, id: &Uuid) -> Result<bool, RepositoryError> {
+        debug!("Restoring weight reading in database: id={}, user={}", id, user_id);
+
+        match pool {
+            DatabasePool::SQLite(pool) => {
+                let conn = pool.get()?;
+
+                let updated = conn.execute(
+                    "UPDATE weight_readings SET deleted_at = NULL
+                     WHERE id = ?1 AND user_id = ?2",
+                    (id.to_string(), user_id),
+                )?;
+
+                Ok(updated > 0)
+            },
+
+            #[cfg(feature = "mysql_db")]
+            DatabasePool::MySQL(pool) => {
+                use mysql::prelude::*;
+
+                let mut conn = pool.get()?;
+
+                let exists: Option<u64> = conn.exec_first(
+                    "SELECT COUNT(*) FROM weight_readings WHERE id = ? AND user_id = ?",
+                    (id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                conn.exec_drop(
+                    "UPDATE weight_readings SET deleted_at = NULL
+                     WHERE id = ? AND user_id = ?",
+                    (id.to_string(), user_id),
+                ).map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(exists.unwrap_or(0) > 0)
+            },
+
+            #[cfg(feature = "postgres")]
+            DatabasePool::PostgreSQL(pool) => {
+                let client = pool.get().await
+                    .map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                let updated = client.execute(
+                    "UPDATE weight_readings SET deleted_at = NULL
+                     WHERE id = $1 AND user_id = $2",
+                    &[&id.to_string(), &user_id],
+                ).await.map_err(|e| RepositoryError::Database(e.to_string().into()))?;
+
+                Ok(updated > 0)
+            },
+
+            #[allow(unreachable_patterns)]
+            _ => Err(RepositoryError::Database("Unsupported database type or not implemented".to_string().into())),
+        }
+    }
+}
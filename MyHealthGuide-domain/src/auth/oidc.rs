@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
 
 use openidconnect::core::{
     CoreProviderMetadata, CoreClient, CoreResponseType,
@@ -12,9 +14,11 @@ use openidconnect::{
     ResponseTypes, EmptyAdditionalProviderMetadata
 };
 use openidconnect::reqwest::async_http_client;
-use tracing::{debug, error, warn};
+use tracing::{debug, error, info, warn};
 use thiserror::Error;
 
+use my_health_guide_data::database::{get_db_pool, DatabasePool};
+use my_health_guide_data::repository::OidcSessionDatabaseStorage;
 use crate::auth::UserInfo;
 
 /// Errors that can occur during OIDC authentication
@@ -243,8 +247,8 @@ impl OidcClient {
 
         debug!("OIDC client initialized successfully");
 
-        // Create a new in-memory session repository
-        let session_repository = Arc::new(InMemorySessionRepository::new());
+        // Keep sessions in memory or in the database, depending on OIDC_SESSION_STORE
+        let session_repository = session_repository_from_env();
 
         Ok(Self {
             _client: client,
@@ -293,8 +297,15 @@ impl OidcClient {
 
     /// Stub implementation for tests
     pub fn stub() -> Self {
-        // Create a minimal client for testing
-        let config = OidcConfig::default();
+        // Create a minimal client for testing. The configuration is fixed rather
+        // than read from the environment so the stub behaves the same everywhere.
+        let config = OidcConfig {
+            client_id: "stub-client-id".to_string(),
+            client_secret: "stub-client-secret".to_string(),
+            issuer_url: "https://stub-issuer.example.com".to_string(),
+            redirect_url: "http://localhost:3000/callback".to_string(),
+            session_timeout: Duration::from_secs(600),
+        };
         let issuer_url = IssuerUrl::new(config.issuer_url.clone()).unwrap();
         let client_id = ClientId::new(config.client_id.clone());
         let client_secret = ClientSecret::new(config.client_secret.clone());
@@ -377,6 +388,7 @@ impl OidcClient {
 
         // Store the session
         self.session_repository.store_session(session.clone())
+            .await
             .map_err(|e| {
                 error!("Failed to store OIDC session: {}", e);
                 OidcError::Generic(format!("Failed to store session: {}", e))
@@ -401,7 +413,7 @@ impl OidcClient {
         };
 
         // Store the session for later use in tests
-        self.session_repository.store_session(session.clone())?;
+        self.session_repository.store_session(session.clone()).await?;
 
         let auth_url = format!(
             "https://stub-issuer.example.com/auth?client_id={}&redirect_uri={}&state={}&scope=openid+email+profile&nonce={}",
@@ -418,7 +430,7 @@ impl OidcClient {
     #[cfg(not(any(test, feature = "mock")))]
     pub async fn handle_callback(&self, code: &str, state: &str) -> Result<UserInfo, OidcError> {
         // Lookup the session from the CSRF token (state parameter)
-        let session = self.session_repository.get_session(state)?;
+        let session = self.session_repository.get_session(state).await?;
 
         debug!("Retrieved session for state '{}': id={}, created_at={:?}, nonce={}",
             state, session.id, session.created_at, session.nonce);
@@ -489,7 +501,7 @@ impl OidcClient {
         }
 
         // Clean up the session
-        if let Err(e) = self.session_repository.delete_session(state) {
+        if let Err(e) = self.session_repository.delete_session(state).await {
             warn!("Failed to delete OIDC session: {}", e);
             // Continue anyway, not a critical error
         }
@@ -580,6 +592,11 @@ impl OidcClient {
         self.config.session_timeout
     }
 
+    /// Remove sessions older than the configured session timeout
+    pub async fn cleanup_expired_sessions(&self) -> Result<(), OidcError> {
+        self.session_repository.cleanup_expired_sessions(self.config.session_timeout).await
+    }
+
     /// Fetch user profile information from the userinfo endpoint
     #[cfg(not(any(test, feature = "mock")))]
     pub async fn fetch_user_profile(&self, access_token: &str) -> Result<UserProfile, OidcError> {
@@ -686,8 +703,8 @@ impl OidcClient {
 
     /// Debug utility to print information about all active sessions
     #[cfg(not(any(test, feature = "mock")))]
-    pub fn debug_sessions(&self) {
-        if let Ok(sessions) = self.session_repository.debug_sessions() {
+    pub async fn debug_sessions(&self) {
+        if let Ok(sessions) = self.session_repository.debug_sessions().await {
             debug!("Current OIDC sessions ({}):", sessions.len());
             for (token, session) in sessions {
                 debug!("  Session with token '{}': id={}, created={:?}, nonce={}",
@@ -700,21 +717,22 @@ impl OidcClient {
 }
 
 /// Session repository trait for storing OIDC sessions
+#[async_trait]
 pub trait SessionRepository: Send + Sync {
     /// Store a session
-    fn store_session(&self, session: OidcSession) -> Result<(), OidcError>;
+    async fn store_session(&self, session: OidcSession) -> Result<(), OidcError>;
 
     /// Get a session by CSRF token
-    fn get_session(&self, csrf_token: &str) -> Result<OidcSession, OidcError>;
+    async fn get_session(&self, csrf_token: &str) -> Result<OidcSession, OidcError>;
 
     /// Delete a session
-    fn delete_session(&self, csrf_token: &str) -> Result<(), OidcError>;
+    async fn delete_session(&self, csrf_token: &str) -> Result<(), OidcError>;
 
     /// Cleanup expired sessions
-    fn cleanup_expired_sessions(&self, timeout: Duration) -> Result<(), OidcError>;
+    async fn cleanup_expired_sessions(&self, timeout: Duration) -> Result<(), OidcError>;
 
     /// Debug utility to print information about all active sessions
-    fn debug_sessions(&self) -> Result<HashMap<String, OidcSession>, OidcError>;
+    async fn debug_sessions(&self) -> Result<HashMap<String, OidcSession>, OidcError>;
 }
 
 /// In-memory implementation of SessionRepository
@@ -737,8 +755,9 @@ impl InMemorySessionRepository {
     }
 }
 
+#[async_trait]
 impl SessionRepository for InMemorySessionRepository {
-    fn store_session(&self, session: OidcSession) -> Result<(), OidcError> {
+    async fn store_session(&self, session: OidcSession) -> Result<(), OidcError> {
         let mut sessions = self.sessions.lock().map_err(|e| {
             error!("Failed to acquire session lock: {}", e);
             OidcError::ClientInitError(format!("Session lock error: {}", e))
@@ -748,7 +767,7 @@ impl SessionRepository for InMemorySessionRepository {
         Ok(())
     }
 
-    fn get_session(&self, csrf_token: &str) -> Result<OidcSession, OidcError> {
+    async fn get_session(&self, csrf_token: &str) -> Result<OidcSession, OidcError> {
         let sessions = self.sessions.lock().map_err(|e| {
             error!("Failed to acquire session lock: {}", e);
             OidcError::UserInfoError(format!("Session lock error: {}", e))
@@ -760,7 +779,7 @@ impl SessionRepository for InMemorySessionRepository {
         })
     }
 
-    fn delete_session(&self, csrf_token: &str) -> Result<(), OidcError> {
+    async fn delete_session(&self, csrf_token: &str) -> Result<(), OidcError> {
         let mut sessions = self.sessions.lock().map_err(|e| {
             error!("Failed to acquire session lock: {}", e);
             OidcError::UserInfoError(format!("Session lock error: {}", e))
@@ -770,7 +789,7 @@ impl SessionRepository for InMemorySessionRepository {
         Ok(())
     }
 
-    fn cleanup_expired_sessions(&self, timeout: Duration) -> Result<(), OidcError> {
+    async fn cleanup_expired_sessions(&self, timeout: Duration) -> Result<(), OidcError> {
         let mut sessions = self.sessions.lock().map_err(|e| {
             error!("Failed to acquire session lock: {}", e);
             OidcError::UserInfoError(format!("Session lock error: {}", e))
@@ -793,7 +812,7 @@ impl SessionRepository for InMemorySessionRepository {
         Ok(())
     }
 
-    fn debug_sessions(&self) -> Result<HashMap<String, OidcSession>, OidcError> {
+    async fn debug_sessions(&self) -> Result<HashMap<String, OidcSession>, OidcError> {
         let sessions = self.sessions.lock().map_err(|e| {
             error!("Failed to acquire session lock: {}", e);
             OidcError::UserInfoError(format!("Session lock error: {}", e))
@@ -803,8 +822,150 @@ impl SessionRepository for InMemorySessionRepository {
     }
 }
 
+/// Database implementation of SessionRepository
+///
+/// Sessions live in the `oidc_sessions` table, so a callback can be handled
+/// by any instance sharing the database and survives a restart.
+pub struct DatabaseSessionRepository {
+    pool: DatabasePool,
+}
+
+impl DatabaseSessionRepository {
+    /// Create a repository on top of an existing connection pool
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Convert a system time to a unix timestamp in seconds
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(_) => 0,
+    }
+}
+
+/// Map repository errors to OIDC errors
+fn map_repo_error(err: my_health_guide_data::repository::RepositoryError) -> OidcError {
+    error!("OIDC session storage error: {}", err);
+    OidcError::Generic(format!("Session storage error: {}", err))
+}
+
+#[async_trait]
+impl SessionRepository for DatabaseSessionRepository {
+    async fn store_session(&self, session: OidcSession) -> Result<(), OidcError> {
+        let data_session = my_health_guide_data::models::oidc_session::OidcSession {
+            id: session.id,
+            csrf_token: session.csrf_token,
+            pkce_verifier: session.pkce_verifier,
+            nonce: session.nonce,
+            created_at: unix_seconds(session.created_at),
+        };
+
+        OidcSessionDatabaseStorage::store_session(&self.pool, &data_session)
+            .await
+            .map_err(map_repo_error)
+    }
+
+    async fn get_session(&self, csrf_token: &str) -> Result<OidcSession, OidcError> {
+        let data_session = OidcSessionDatabaseStorage::get_session(&self.pool, csrf_token)
+            .await
+            .map_err(map_repo_error)?;
+
+        data_session.map(convert_to_domain_session).ok_or_else(|| {
+            error!("Session not found for state token. This may be due to an expired session or invalid state parameter.");
+            OidcError::SessionNotFound
+        })
+    }
+
+    async fn delete_session(&self, csrf_token: &str) -> Result<(), OidcError> {
+        OidcSessionDatabaseStorage::delete_session(&self.pool, csrf_token)
+            .await
+            .map_err(map_repo_error)
+    }
+
+    async fn cleanup_expired_sessions(&self, timeout: Duration) -> Result<(), OidcError> {
+        let cutoff = unix_seconds(SystemTime::now()) - timeout.as_secs() as i64;
+
+        let removed = OidcSessionDatabaseStorage::delete_created_before(&self.pool, cutoff)
+            .await
+            .map_err(map_repo_error)?;
+        debug!("Removed {} expired OIDC sessions", removed);
+
+        Ok(())
+    }
+
+    async fn debug_sessions(&self) -> Result<HashMap<String, OidcSession>, OidcError> {
+        let sessions = OidcSessionDatabaseStorage::list_sessions(&self.pool)
+            .await
+            .map_err(map_repo_error)?;
+
+        Ok(sessions.into_iter()
+            .map(|session| (session.csrf_token.clone(), convert_to_domain_session(session)))
+            .collect())
+    }
+}
+
+/// Convert a stored session back to an OIDC session
+fn convert_to_domain_session(data_session: my_health_guide_data::models::oidc_session::OidcSession) -> OidcSession {
+    OidcSession {
+        id: data_session.id,
+        csrf_token: data_session.csrf_token,
+        pkce_verifier: data_session.pkce_verifier,
+        created_at: UNIX_EPOCH + Duration::from_secs(data_session.created_at.max(0) as u64),
+        nonce: data_session.nonce,
+    }
+}
+
+/// Create the session repository selected by the `OIDC_SESSION_STORE` variable
+///
+/// `database` keeps sessions in the application database so logins work
+/// across instances and restarts; anything else keeps them in memory. If the
+/// database is not available the in-memory repository is used.
+pub fn session_repository_from_env() -> Arc<dyn SessionRepository> {
+    let kind = std::env::var("OIDC_SESSION_STORE")
+        .unwrap_or_else(|_| "memory".to_string())
+        .to_lowercase();
+
+    if kind != "database" {
+        info!("Using in-memory OIDC session store");
+        return Arc::new(InMemorySessionRepository::new());
+    }
+
+    match get_db_pool() {
+        Ok(pool) => {
+            info!("Using database OIDC session store");
+            Arc::new(DatabaseSessionRepository::new(pool))
+        },
+        Err(e) => {
+            warn!("Database not available ({}), falling back to in-memory OIDC session store", e);
+            Arc::new(InMemorySessionRepository::new())
+        }
+    }
+}
+
+/// Start a background task that periodically removes expired OIDC sessions
+///
+/// Runs once per session timeout, so no session outlives twice the timeout.
+/// Should be called during application startup with the client the routes use.
+#[cfg(feature = "with-tokio")]
+pub fn start_session_cleanup_task(client: Arc<OidcClient>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(client.config.session_timeout.max(Duration::from_secs(60)));
+
+        loop {
+            interval.tick().await;
+            debug!("Running scheduled OIDC session cleanup");
+            if let Err(e) = client.cleanup_expired_sessions().await {
+                warn!("Failed to clean up OIDC sessions: {}", e);
+            }
+        }
+    });
+}
+
 // Tests for the OidcConfig
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -880,8 +1041,8 @@ mod tests {
         assert_eq!(config.session_timeout, Duration::from_secs(300));
     }
 
-    #[test]
-    fn test_inmemory_session_repository() {
+    #[tokio::test]
+    async fn test_inmemory_session_repository() {
         // Create a session repository
         let repo = InMemorySessionRepository::new();
 
@@ -895,11 +1056,11 @@ mod tests {
         };
 
         // Store the session
-        let result = repo.store_session(session.clone());
+        let result = repo.store_session(session.clone()).await;
         assert!(result.is_ok(), "Failed to store session: {:?}", result.err());
 
         // Fetch the session
-        let fetched = repo.get_session("test-csrf");
+        let fetched = repo.get_session("test-csrf").await;
         assert!(fetched.is_ok(), "Failed to get session: {:?}", fetched.err());
 
         // Compare the sessions
@@ -909,11 +1070,11 @@ mod tests {
         assert_eq!(fetched_session.pkce_verifier, session.pkce_verifier);
 
         // Delete the session
-        let delete_result = repo.delete_session("test-csrf");
+        let delete_result = repo.delete_session("test-csrf").await;
         assert!(delete_result.is_ok(), "Failed to delete session: {:?}", delete_result.err());
 
         // Try to fetch the deleted session
-        let not_found = repo.get_session("test-csrf");
+        let not_found = repo.get_session("test-csrf").await;
         assert!(not_found.is_err(), "Session should have been deleted");
         match not_found.err().unwrap() {
             OidcError::SessionNotFound => { /* expected */ },
@@ -921,8 +1082,8 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_session_expiration() {
+    #[tokio::test]
+    async fn test_session_expiration() {
         // Create a session repository
         let repo = InMemorySessionRepository::new();
 
@@ -939,7 +1100,7 @@ mod tests {
         };
 
         // Store the expired session
-        repo.store_session(session).unwrap();
+        repo.store_session(session).await.unwrap();
 
         // Create a non-expired session (created just now)
         let session2 = OidcSession {
@@ -951,18 +1112,18 @@ mod tests {
         };
 
         // Store the valid session
-        repo.store_session(session2).unwrap();
+        repo.store_session(session2).await.unwrap();
 
         // Cleanup expired sessions (using 10 minutes timeout)
-        let cleanup_result = repo.cleanup_expired_sessions(Duration::from_secs(10 * 60));
+        let cleanup_result = repo.cleanup_expired_sessions(Duration::from_secs(10 * 60)).await;
         assert!(cleanup_result.is_ok(), "Failed to cleanup expired sessions: {:?}", cleanup_result.err());
 
         // The expired session should be gone
-        let expired_result = repo.get_session("expired-csrf");
+        let expired_result = repo.get_session("expired-csrf").await;
         assert!(expired_result.is_err(), "Expired session should have been removed");
 
         // The valid session should still be there
-        let valid_result = repo.get_session("valid-csrf");
+        let valid_result = repo.get_session("valid-csrf").await;
         assert!(valid_result.is_ok(), "Valid session should still exist");
     }
 
@@ -0,0 +1,66 @@
+//! Password hashing for locally registered users
+//!
+//! Passwords are hashed with Argon2id using the crate's default parameters
+//! and stored as PHC strings, so the algorithm and parameters travel with
+//! the hash.
+
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use once_cell::sync::Lazy;
+
+/// Hash used to spend the same verification time when a login names an
+/// unknown user
+static DUMMY_HASH: Lazy<String> = Lazy::new(|| {
+    hash_password("dummy-password-for-timing").unwrap_or_default()
+});
+
+/// Hash a password with Argon2id and a random salt
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+        .map_err(|e| format!("Failed to generate salt: {}", e))?;
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash password: {}", e))
+}
+
+/// Check a password against a stored PHC hash
+///
+/// Malformed hashes are treated as a mismatch.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Run a verification that always fails, to keep response times for
+/// unknown users in line with those for wrong passwords
+pub fn verify_dummy_password(password: &str) {
+    let _ = verify_password(password, &DUMMY_HASH);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hash = hash_password("correct horse battery staple").unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse battery staple", &hash));
+        assert!(!verify_password("wrong password", &hash));
+        assert!(!verify_password("correct horse battery staple", "not-a-hash"));
+    }
+
+    #[test]
+    fn test_hashes_are_salted() {
+        assert_ne!(hash_password("secret123").unwrap(), hash_password("secret123").unwrap());
+    }
+}
@@ -0,0 +1,198 @@
+//! Token revocation stores
+//!
+//! `validate_token` checks revocations through the configured
+//! [`RevocationStore`]. The default is the process-local [`TokenBlacklist`];
+//! deployments running several replicas, or wanting revocations to survive a
+//! restart, should switch to the database-backed store by setting
+//! `TOKEN_REVOCATION_STORE=database`.
+
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use tracing::{info, warn};
+
+use my_health_guide_data::database::{get_db_pool, DatabasePool};
+use my_health_guide_data::repository::{RepositoryError, RevocationDatabaseStorage};
+use crate::auth::token::SecurityError;
+use crate::auth::token_blacklist::{self, TokenBlacklist};
+
+/// Storage for revoked tokens and per-user "not before" watermarks
+#[async_trait]
+pub trait RevocationStore: Send + Sync {
+    /// Revoke a single token until it would have expired anyway
+    async fn revoke_token(&self, jti: &str, expiration: SystemTime) -> Result<(), SecurityError>;
+
+    /// Revoke a single token unless it already is, returning whether this call revoked it
+    ///
+    /// Implementations must make the check and the revocation atomic so a
+    /// single-use token can only be used once, even by concurrent requests.
+    async fn revoke_token_once(&self, jti: &str, expiration: SystemTime) -> Result<bool, SecurityError>;
+
+    /// Check whether a single token has been revoked
+    async fn is_revoked(&self, jti: &str) -> Result<bool, SecurityError>;
+
+    /// Revoke every token issued to a user at or before `issued_before`, in unix milliseconds
+    async fn revoke_user_tokens(
+        &self,
+        user_id: &str,
+        issued_before: i64,
+        expiration: SystemTime,
+    ) -> Result<(), SecurityError>;
+
+    /// Get the unix timestamp in milliseconds at or before which the user's tokens are revoked
+    async fn user_not_before(&self, user_id: &str) -> Result<Option<i64>, SecurityError>;
+
+    /// Drop revocations whose tokens have expired, returning how many were removed
+    async fn cleanup_expired(&self) -> Result<usize, SecurityError>;
+}
+
+#[async_trait]
+impl RevocationStore for TokenBlacklist {
+    async fn revoke_token(&self, jti: &str, expiration: SystemTime) -> Result<(), SecurityError> {
+        TokenBlacklist::revoke_token(self, jti, expiration);
+        Ok(())
+    }
+
+    async fn revoke_token_once(&self, jti: &str, expiration: SystemTime) -> Result<bool, SecurityError> {
+        Ok(TokenBlacklist::revoke_token_once(self, jti, expiration))
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, SecurityError> {
+        Ok(TokenBlacklist::is_revoked(self, jti))
+    }
+
+    async fn revoke_user_tokens(
+        &self,
+        user_id: &str,
+        issued_before: i64,
+        expiration: SystemTime,
+    ) -> Result<(), SecurityError> {
+        TokenBlacklist::revoke_user_tokens(self, user_id, issued_before, expiration);
+        Ok(())
+    }
+
+    async fn user_not_before(&self, user_id: &str) -> Result<Option<i64>, SecurityError> {
+        Ok(TokenBlacklist::user_not_before(self, user_id))
+    }
+
+    async fn cleanup_expired(&self) -> Result<usize, SecurityError> {
+        Ok(self.cleanup_expired_tokens())
+    }
+}
+
+/// Revocation store shared through the application database
+pub struct DatabaseRevocationStore {
+    pool: DatabasePool,
+}
+
+impl DatabaseRevocationStore {
+    /// Create a store on top of an existing connection pool
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Convert a system time to a unix timestamp in seconds
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(_) => 0,
+    }
+}
+
+/// Map repository errors to security errors
+fn map_repo_error(err: RepositoryError) -> SecurityError {
+    SecurityError::Generic(format!("Revocation store error: {}", err))
+}
+
+#[async_trait]
+impl RevocationStore for DatabaseRevocationStore {
+    async fn revoke_token(&self, jti: &str, expiration: SystemTime) -> Result<(), SecurityError> {
+        self.revoke_token_once(jti, expiration).await.map(|_| ())
+    }
+
+    async fn revoke_token_once(&self, jti: &str, expiration: SystemTime) -> Result<bool, SecurityError> {
+        RevocationDatabaseStorage::revoke_token(&self.pool, jti, unix_seconds(expiration), Utc::now().timestamp())
+            .await
+            .map_err(map_repo_error)
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, SecurityError> {
+        RevocationDatabaseStorage::is_revoked(&self.pool, jti)
+            .await
+            .map_err(map_repo_error)
+    }
+
+    async fn revoke_user_tokens(
+        &self,
+        user_id: &str,
+        issued_before: i64,
+        expiration: SystemTime,
+    ) -> Result<(), SecurityError> {
+        RevocationDatabaseStorage::set_user_not_before(&self.pool, user_id, issued_before, unix_seconds(expiration))
+            .await
+            .map_err(map_repo_error)
+    }
+
+    async fn user_not_before(&self, user_id: &str) -> Result<Option<i64>, SecurityError> {
+        RevocationDatabaseStorage::get_user_not_before(&self.pool, user_id)
+            .await
+            .map_err(map_repo_error)
+    }
+
+    async fn cleanup_expired(&self) -> Result<usize, SecurityError> {
+        RevocationDatabaseStorage::delete_expired(&self.pool, Utc::now().timestamp())
+            .await
+            .map_err(map_repo_error)
+    }
+}
+
+/// The store consulted by `validate_token`, the in-memory blacklist until configured otherwise
+static REVOCATION_STORE: Lazy<RwLock<Arc<dyn RevocationStore>>> = Lazy::new(|| {
+    let store: Arc<dyn RevocationStore> = token_blacklist::shared_blacklist();
+    RwLock::new(store)
+});
+
+/// Get the configured revocation store
+pub fn revocation_store() -> Arc<dyn RevocationStore> {
+    match REVOCATION_STORE.read() {
+        Ok(store) => store.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Replace the configured revocation store
+pub fn set_revocation_store(store: Arc<dyn RevocationStore>) {
+    match REVOCATION_STORE.write() {
+        Ok(mut current) => *current = store,
+        Err(poisoned) => *poisoned.into_inner() = store,
+    }
+}
+
+/// Configure the revocation store from the `TOKEN_REVOCATION_STORE` variable
+///
+/// `database` selects the database-backed store, anything else keeps the
+/// in-memory blacklist. Should run after the database pool is initialized;
+/// if the database is not available the in-memory store is kept.
+pub fn configure_revocation_store_from_env() {
+    let kind = std::env::var("TOKEN_REVOCATION_STORE")
+        .unwrap_or_else(|_| "memory".to_string())
+        .to_lowercase();
+
+    if kind != "database" {
+        info!("Using in-memory token revocation store");
+        return;
+    }
+
+    match get_db_pool() {
+        Ok(pool) => {
+            info!("Using database token revocation store");
+            set_revocation_store(Arc::new(DatabaseRevocationStore::new(pool)));
+        },
+        Err(e) => {
+            warn!("Database not available ({}), falling back to in-memory token revocation store", e);
+        }
+    }
+}
@@ -191,12 +191,16 @@ async fn callback_handler(
     // Handle the callback
     match client.handle_callback(code, state).await {
         Ok(user_info) => {
-            // Generate tokens
-            let access_token = match token::generate_token(
+            // Generate tokens in one new family, so reusing the refresh token also revokes this access token
+            let family = uuid::Uuid::new_v4().to_string();
+            let generate = |token_type| token::generate_family_token(
                 &user_info.user_id,
-                token::TokenType::Access,
-                Some(user_info.roles.clone())
-            ) {
+                token_type,
+                Some(user_info.roles.clone()),
+                None,
+                Some(&family)
+            );
+            let access_token = match generate(token::TokenType::Access) {
                 Ok(token) => token,
                 Err(e) => {
                     error!("Failed to generate access token: {}", e);
@@ -217,11 +221,7 @@ async fn callback_handler(
                 }
             };
 
-            let refresh_token = match token::generate_token(
-                &user_info.user_id,
-                token::TokenType::Refresh,
-                Some(user_info.roles.clone())
-            ) {
+            let refresh_token = match generate(token::TokenType::Refresh) {
                 Ok(token) => token,
                 Err(e) => {
                     error!("Failed to generate refresh token: {}", e);
@@ -84,6 +84,11 @@ mod routes_tests {
     
     #[tokio::test]
     async fn test_oidc_callback_success() {
+        // Token generation after a successful callback needs a signing secret
+        if std::env::var("JWT_SECRET").is_err() {
+            std::env::set_var("JWT_SECRET", "test_secret_key_for_testing_only");
+        }
+
         // Create a router with the OIDC routes
         let client = Arc::new(OidcClient::stub());
         let app = oidc_routes().with_state(client);
@@ -107,8 +112,10 @@ mod routes_tests {
         
         println!("Response body: {}", body_str);
         
-        // Verify the response contains user info
-        let user_info: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+        // Verify the login response contains tokens and user info
+        let login_response: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+        assert!(login_response["access_token"].is_string());
+        let user_info = &login_response["user"];
         assert_eq!(user_info["user_id"].as_str().unwrap(), "test-user-123");
         assert_eq!(user_info["auth_source"].as_str().unwrap(), "oidc");
     }
@@ -3,8 +3,10 @@ use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Algorithm,
 use std::env;
 use tracing::{debug, error, info};
 use chrono::{Duration, Utc};
+use std::time::{Duration as StdDuration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
 use crate::auth::Claims;
-use crate::auth::token_blacklist;
+use crate::auth::revocation::revocation_store;
 
 /// Security errors for authentication and token operations
 #[derive(Debug, Error)]
@@ -33,6 +35,19 @@ pub enum SecurityError {
     #[error("Token has been revoked")]
     TokenRevoked,
 
+    /// Token is valid but of the wrong type for this use
+    #[error("Token is not of the expected type")]
+    InvalidTokenType,
+
+    /// Single-use refresh token presented a second time; its family has been revoked
+    #[error("Refresh token has already been used")]
+    TokenReused {
+        /// The user the token was issued to
+        user_id: String,
+        /// The revoked token family
+        family: String,
+    },
+
     /// Generic error
     #[error("Security error: {0}")]
     Generic(String),
@@ -58,8 +73,10 @@ pub enum SecurityError {
     InvalidAudience,
 }
 
-/// Token types for authentication
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+/// Token types for authentication, carried in the `typ` claim
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
 pub enum TokenType {
     /// Short-lived access token
     Access,
@@ -93,11 +110,40 @@ impl TokenType {
     }
 }
 
-/// Generate a new JWT token
+/// Generate a new JWT token carrying the user's roles
 pub fn generate_token(
     user_id: &str,
     token_type: TokenType,
-    _roles: Option<Vec<String>>,
+    roles: Option<Vec<String>>,
+) -> Result<String, SecurityError> {
+    generate_scoped_token(user_id, token_type, roles, None)
+}
+
+/// Generate a new JWT token carrying the user's roles and optional scopes
+///
+/// Every token gets a fresh `jti` so it can be told apart from other tokens
+/// issued to the same user. A refresh token generated this way starts a new
+/// token family.
+pub fn generate_scoped_token(
+    user_id: &str,
+    token_type: TokenType,
+    roles: Option<Vec<String>>,
+    scopes: Option<Vec<String>>,
+) -> Result<String, SecurityError> {
+    generate_family_token(user_id, token_type, roles, scopes, None)
+}
+
+/// Generate a new JWT token belonging to a token family
+///
+/// A family is the chain of tokens issued by rotating one login's refresh
+/// token. Without a family, refresh tokens start a new one named after their
+/// own `jti` and access tokens stay outside of any family.
+pub fn generate_family_token(
+    user_id: &str,
+    token_type: TokenType,
+    roles: Option<Vec<String>>,
+    scopes: Option<Vec<String>>,
+    family: Option<&str>,
 ) -> Result<String, SecurityError> {
     // Load JWT secret from environment
     let jwt_secret = env::var("JWT_SECRET").map_err(|e| {
@@ -115,12 +161,25 @@ pub fn generate_token(
     let now = Utc::now();
     let expiration = now + token_type.expiration();
 
+    let jti = uuid::Uuid::new_v4().to_string();
+    let family = match (family, token_type) {
+        (Some(family), _) => Some(family.to_string()),
+        (None, TokenType::Refresh) => Some(jti.clone()),
+        (None, TokenType::Access) => None,
+    };
+
     // Create claims
     let claims = Claims {
         sub: user_id.to_string(),
         iss: issuer,
         iat: now.timestamp(),
+        iat_ms: Some(now.timestamp_millis()),
         exp: expiration.timestamp(),
+        roles: roles.unwrap_or_default(),
+        scopes,
+        jti,
+        typ: Some(token_type),
+        fam: family,
     };
 
     // Encode the token
@@ -141,7 +200,36 @@ pub fn generate_token(
 }
 
 /// Validate a JWT token and return the decoded claims
-pub fn validate_token(token: &str) -> Result<Claims, SecurityError> {
+///
+/// Besides the signature and expiry this checks the configured revocation
+/// store, which is why it is async.
+pub async fn validate_token(token: &str) -> Result<Claims, SecurityError> {
+    let claims = decode_token(token)?;
+
+    // Check if token has been revoked
+    if is_token_revoked(&claims).await? {
+        return Err(SecurityError::TokenRevoked);
+    }
+
+    Ok(claims)
+}
+
+/// Validate a token presented to access the API
+///
+/// Refresh tokens are rejected so they can only be used to get new tokens.
+/// Tokens issued before the `typ` claim existed are accepted.
+pub async fn validate_access_token(token: &str) -> Result<Claims, SecurityError> {
+    let claims = validate_token(token).await?;
+
+    if claims.typ == Some(TokenType::Refresh) {
+        return Err(SecurityError::InvalidTokenType);
+    }
+
+    Ok(claims)
+}
+
+/// Check a token's signature, issuer and expiry without consulting the revocation store
+fn decode_token(token: &str) -> Result<Claims, SecurityError> {
     // Load JWT secret from environment
     let jwt_secret = env::var("JWT_SECRET").map_err(|e| {
         error!("JWT_SECRET environment variable not found: {}", e);
@@ -173,34 +261,117 @@ pub fn validate_token(token: &str) -> Result<Claims, SecurityError> {
         }
     })?;
 
-    // Check if token has been revoked
-    if is_token_revoked(&token_data.claims.sub)? {
+    Ok(token_data.claims)
+}
+
+/// Check if a token has been revoked, either on its own, with its family or
+/// by a "log out everywhere" for its user
+async fn is_token_revoked(claims: &Claims) -> Result<bool, SecurityError> {
+    let is_revoked = (!claims.jti.is_empty() && revocation_store().is_revoked(&claims.jti).await?)
+        || is_family_or_user_revoked(claims).await?;
+    debug!("Checking if token {} for user {} is revoked: {}", claims.jti, claims.sub, is_revoked);
+    Ok(is_revoked)
+}
+
+/// Check the revocations that cover more than a single token
+async fn is_family_or_user_revoked(claims: &Claims) -> Result<bool, SecurityError> {
+    let store = revocation_store();
+
+    if let Some(family) = &claims.fam {
+        if store.is_revoked(&family_revocation_key(family)).await? {
+            return Ok(true);
+        }
+    }
+
+    Ok(store.user_not_before(&claims.sub).await?.is_some_and(|not_before| issued_at_millis(claims) <= not_before))
+}
+
+/// When a token was issued, in milliseconds
+///
+/// Tokens without `iat_ms` count from the start of their second, so a log
+/// out everywhere in that second still revokes them.
+fn issued_at_millis(claims: &Claims) -> i64 {
+    claims.iat_ms.unwrap_or(claims.iat * 1000)
+}
+
+/// Key under which a revoked token family is stored alongside revoked `jti`s
+fn family_revocation_key(family: &str) -> String {
+    format!("family:{}", family)
+}
+
+/// Validate a refresh token and use it up
+///
+/// Refresh tokens are single use: the first call with a token revokes it and
+/// returns its claims so a new pair can be issued in the same family. Any
+/// later call with the same token means it leaked, so the whole family is
+/// revoked and `TokenReused` is returned.
+pub async fn use_refresh_token(token: &str) -> Result<Claims, SecurityError> {
+    let claims = decode_token(token)?;
+
+    if claims.typ != Some(TokenType::Refresh) || claims.jti.is_empty() {
+        return Err(SecurityError::InvalidTokenType);
+    }
+
+    if is_family_or_user_revoked(&claims).await? {
         return Err(SecurityError::TokenRevoked);
     }
 
-    Ok(token_data.claims)
+    // Revoking is atomic in every store, so only one caller can win the race
+    if !revocation_store().revoke_token_once(&claims.jti, expiration_of(&claims)).await? {
+        revoke_token_family(&claims).await?;
+        return Err(SecurityError::TokenReused {
+            user_id: claims.sub,
+            family: claims.fam.unwrap_or_default(),
+        });
+    }
+
+    Ok(claims)
 }
 
-/// Check if a token has been revoked
-fn is_token_revoked(user_id: &str) -> Result<bool, SecurityError> {
-    // Check the token blacklist
-    let is_revoked = token_blacklist::blacklist().is_revoked(user_id);
-    debug!("Checking if token for user {} is revoked: {}", user_id, is_revoked);
-    Ok(is_revoked)
+/// Revoke every token in the family the given token belongs to
+pub async fn revoke_token_family(claims: &Claims) -> Result<(), SecurityError> {
+    let Some(family) = &claims.fam else {
+        return Err(SecurityError::Generic("Token has no family to revoke".to_string()));
+    };
+
+    info!("Revoking token family {} for user {}", family, claims.sub);
+
+    // Later tokens in the family may have been issued up to now, so keep the
+    // revocation for a full refresh token lifetime
+    let longest_lifetime = TokenType::Refresh.expiration().to_std()
+        .map_err(|e| SecurityError::ConfigError(format!("Invalid refresh token lifetime: {}", e)))?;
+    revocation_store().revoke_token(&family_revocation_key(family), SystemTime::now() + longest_lifetime).await
+}
+
+/// When a token expires naturally
+fn expiration_of(claims: &Claims) -> SystemTime {
+    UNIX_EPOCH + StdDuration::from_secs(claims.exp.max(0) as u64)
 }
 
-/// Revoke a user's tokens
-pub fn revoke_token(user_id: &str) -> Result<(), SecurityError> {
-    // In a real application, this would add the token to a revocation list
-    info!("Revoking tokens for user {}", user_id);
+/// Revoke a single token
+///
+/// The token stays blacklisted until it would have expired anyway.
+pub async fn revoke_token(claims: &Claims) -> Result<(), SecurityError> {
+    if claims.jti.is_empty() {
+        return Err(SecurityError::Generic("Token has no jti to revoke".to_string()));
+    }
+
+    info!("Revoking token {} for user {}", claims.jti, claims.sub);
 
-    // Add token to blacklist with an expiration time
-    // We'll use a generous expiration time to ensure it's blacklisted long enough
-    // In a real app, you might want to use the actual token expiration time
-    let expiration = std::time::SystemTime::now() + std::time::Duration::from_secs(86400); // 24 hours
-    token_blacklist::blacklist().revoke_token(user_id, expiration);
+    revocation_store().revoke_token(&claims.jti, expiration_of(claims)).await
+}
 
-    Ok(())
+/// Revoke every token issued to a user so far
+///
+/// Tokens issued after this call are unaffected, so the user can log in again.
+pub async fn revoke_all_tokens(user_id: &str) -> Result<(), SecurityError> {
+    info!("Revoking all tokens for user {}", user_id);
+
+    // Refresh tokens live longest, so the watermark is needed until they expire
+    let longest_lifetime = TokenType::Refresh.expiration().to_std()
+        .map_err(|e| SecurityError::ConfigError(format!("Invalid refresh token lifetime: {}", e)))?;
+    let expiration = SystemTime::now() + longest_lifetime;
+    revocation_store().revoke_user_tokens(user_id, Utc::now().timestamp_millis(), expiration).await
 }
 
 #[cfg(test)]
@@ -213,8 +384,8 @@ mod tests {
         std::env::set_var("JWT_AUDIENCE", "test-audience");
     }
 
-    #[test]
-    fn test_generate_and_validate_token() {
+    #[tokio::test]
+    async fn test_generate_and_validate_token() {
         setup_test_env();
 
         let user_id = "test-user-123";
@@ -224,13 +395,130 @@ mod tests {
         assert!(!token.is_empty());
 
         // Should be able to validate the token
-        let claims = validate_token(&token).unwrap();
+        let claims = validate_token(&token).await.unwrap();
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.iss, "test-issuer");
     }
 
-    #[test]
-    fn test_token_expiration() {
+    #[tokio::test]
+    async fn test_token_carries_roles_scopes_and_jti() {
+        setup_test_env();
+
+        let roles = Some(vec!["user".to_string(), "admin".to_string()]);
+        let scopes = Some(vec!["readings:read".to_string()]);
+        let token = generate_scoped_token("test-user-789", TokenType::Access, roles, scopes).unwrap();
+
+        let claims = validate_token(&token).await.unwrap();
+        assert_eq!(claims.roles, vec!["user", "admin"]);
+        assert_eq!(claims.scopes, Some(vec!["readings:read".to_string()]));
+        assert!(!claims.jti.is_empty());
+
+        // Each token is issued with its own identifier
+        let other = validate_token(&generate_token("test-user-789", TokenType::Access, None).unwrap()).await.unwrap();
+        assert_ne!(other.jti, claims.jti);
+        assert!(other.roles.is_empty());
+        assert!(other.scopes.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_revoking_one_token_keeps_others_valid() {
+        setup_test_env();
+
+        let first = generate_token("test-user-revoke", TokenType::Access, None).unwrap();
+        let second = generate_token("test-user-revoke", TokenType::Access, None).unwrap();
+
+        revoke_token(&validate_token(&first).await.unwrap()).await.unwrap();
+
+        assert!(matches!(validate_token(&first).await, Err(SecurityError::TokenRevoked)));
+        assert!(validate_token(&second).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_all_tokens_uses_watermark() {
+        setup_test_env();
+
+        let access = generate_token("test-user-everywhere", TokenType::Access, None).unwrap();
+        let refresh = generate_token("test-user-everywhere", TokenType::Refresh, None).unwrap();
+        let other_user = generate_token("test-user-bystander", TokenType::Access, None).unwrap();
+
+        revoke_all_tokens("test-user-everywhere").await.unwrap();
+
+        assert!(matches!(validate_token(&access).await, Err(SecurityError::TokenRevoked)));
+        assert!(matches!(validate_token(&refresh).await, Err(SecurityError::TokenRevoked)));
+        assert!(validate_token(&other_user).await.is_ok());
+
+        // Logging in again right away works
+        tokio::time::sleep(StdDuration::from_millis(2)).await;
+        let fresh = generate_token("test-user-everywhere", TokenType::Access, None).unwrap();
+        assert!(validate_token(&fresh).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_all_tokens_tells_apart_tokens_issued_in_the_same_second() {
+        let user_id = "test-user-same-second";
+        let second = Utc::now().timestamp();
+        let logged_out_at = second * 1000 + 500;
+        revocation_store()
+            .revoke_user_tokens(user_id, logged_out_at, SystemTime::now() + StdDuration::from_secs(3600))
+            .await
+            .unwrap();
+
+        let claims = |iat_ms: Option<i64>| Claims {
+            sub: user_id.to_string(),
+            iss: "test-issuer".to_string(),
+            iat: second,
+            iat_ms,
+            exp: second + 3600,
+            roles: vec![],
+            scopes: None,
+            jti: uuid::Uuid::new_v4().to_string(),
+            typ: Some(TokenType::Access),
+            fam: None,
+        };
+
+        assert!(is_family_or_user_revoked(&claims(Some(logged_out_at - 1))).await.unwrap());
+        assert!(is_family_or_user_revoked(&claims(Some(logged_out_at))).await.unwrap());
+        assert!(!is_family_or_user_revoked(&claims(Some(logged_out_at + 1))).await.unwrap());
+
+        // Tokens issued without millisecond precision stay revoked for the whole second
+        assert!(is_family_or_user_revoked(&claims(None)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_tokens_rotate_and_detect_reuse() {
+        setup_test_env();
+
+        let login_family = uuid::Uuid::new_v4().to_string();
+        let access = generate_family_token("test-user-rotate", TokenType::Access, None, None, Some(&login_family)).unwrap();
+        let first = generate_family_token("test-user-rotate", TokenType::Refresh, None, None, Some(&login_family)).unwrap();
+
+        // Access and refresh tokens can't stand in for each other
+        assert!(matches!(use_refresh_token(&access).await, Err(SecurityError::InvalidTokenType)));
+        assert!(matches!(validate_access_token(&first).await, Err(SecurityError::InvalidTokenType)));
+
+        let claims = use_refresh_token(&first).await.unwrap();
+        let family = claims.fam.clone().unwrap();
+        assert_eq!(family, login_family);
+
+        let second = generate_family_token("test-user-rotate", TokenType::Refresh, None, None, Some(&family)).unwrap();
+        let rotated_access = generate_family_token("test-user-rotate", TokenType::Access, None, None, Some(&family)).unwrap();
+        assert!(validate_access_token(&rotated_access).await.is_ok());
+
+        // Replaying the first refresh token revokes everything in its family
+        match use_refresh_token(&first).await {
+            Err(SecurityError::TokenReused { user_id, family: revoked }) => {
+                assert_eq!(user_id, "test-user-rotate");
+                assert_eq!(revoked, family);
+            },
+            other => panic!("Expected TokenReused but got: {:?}", other),
+        }
+        assert!(matches!(use_refresh_token(&second).await, Err(SecurityError::TokenRevoked)));
+        assert!(matches!(validate_token(&rotated_access).await, Err(SecurityError::TokenRevoked)));
+        assert!(matches!(validate_token(&access).await, Err(SecurityError::TokenRevoked)));
+    }
+
+    #[tokio::test]
+    async fn test_token_expiration() {
         setup_test_env();
 
         // Generate a token with a fixed expiration time in the past
@@ -241,7 +529,13 @@ mod tests {
             sub: user_id.to_string(),
             iss: "test-issuer".to_string(),
             iat: Utc::now().timestamp(),
+            iat_ms: None,
             exp: Utc::now().timestamp() - 3600, // 1 hour in the past
+            roles: vec![],
+            scopes: None,
+            jti: uuid::Uuid::new_v4().to_string(),
+            typ: Some(TokenType::Access),
+            fam: None,
         };
 
         // Encode token directly with expired claim
@@ -253,7 +547,7 @@ mod tests {
         ).unwrap();
 
         // Validating this explicitly expired token should fail
-        let result = validate_token(&token);
+        let result = validate_token(&token).await;
         assert!(result.is_err(), "Token validation should fail for expired token");
 
         // Check that it's the right kind of error
@@ -263,12 +557,12 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_invalid_token() {
+    #[tokio::test]
+    async fn test_invalid_token() {
         setup_test_env();
 
         // Try to validate an invalid token
-        let result = validate_token("invalid.token.format");
+        let result = validate_token("invalid.token.format").await;
         assert!(result.is_err());
 
         match result {
@@ -12,33 +12,44 @@ use once_cell::sync::Lazy;
 ///
 /// # Example
 /// ```rust
-/// use MyHealthGuide_domain::auth::token_blacklist;
+/// use my_health_guide_domain::auth::token_blacklist;
 ///
 /// // Check if a token is revoked
 /// if token_blacklist::blacklist().is_revoked("some-token-id") {
 ///     println!("Token is revoked");
 /// }
 /// ```
-static TOKEN_BLACKLIST: Lazy<TokenBlacklist> = Lazy::new(|| {
-    TokenBlacklist::new()
+static TOKEN_BLACKLIST: Lazy<Arc<TokenBlacklist>> = Lazy::new(|| {
+    Arc::new(TokenBlacklist::new())
 });
 
 /// Token blacklist structure for tracking revoked tokens
 ///
+/// This is the in-memory [`RevocationStore`](crate::auth::revocation::RevocationStore).
+/// Revocations live only as long as the process and are not shared between
+/// replicas.
+///
 /// This structure maintains a thread-safe collection of revoked tokens
 /// and provides methods to:
 /// - Revoke tokens
 /// - Check if a token is revoked
+/// - Revoke every token a user was issued up to a point in time
 /// - Clean up expired tokens
 ///
 /// The blacklist has a maximum size limit to prevent unbounded growth, and
 /// it automatically removes expired tokens during cleanup operations.
 pub struct TokenBlacklist {
     /// Map of token identifiers to expiration times
-    /// Key: user_id or jti (JWT ID) if available
+    /// Key: jti (JWT ID)
     /// Value: (expiration timestamp, revocation timestamp)
     revoked_tokens: Arc<Mutex<HashMap<String, (SystemTime, SystemTime)>>>,
 
+    /// Per-user "not before" watermarks set when a user logs out everywhere
+    /// Key: user_id
+    /// Value: (tokens issued at or before this unix timestamp are revoked,
+    ///         when the watermark can be dropped because those tokens expired)
+    not_before: Arc<Mutex<HashMap<String, (i64, SystemTime)>>>,
+
     /// Maximum size of the blacklist before aggressive pruning
     max_size: usize,
 }
@@ -56,13 +67,14 @@ impl TokenBlacklist {
     ///
     /// # Example
     /// ```rust
-    /// use MyHealthGuide_domain::auth::token_blacklist::TokenBlacklist;
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
     ///
     /// let blacklist = TokenBlacklist::new();
     /// ```
     pub fn new() -> Self {
         Self {
             revoked_tokens: Arc::new(Mutex::new(HashMap::new())),
+            not_before: Arc::new(Mutex::new(HashMap::new())),
             max_size: 10000, // Default size limit
         }
     }
@@ -77,7 +89,7 @@ impl TokenBlacklist {
     ///
     /// # Example
     /// ```rust
-    /// use MyHealthGuide_domain::auth::token_blacklist::TokenBlacklist;
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
     ///
     /// // Create a blacklist that can store up to 5000 tokens
     /// let blacklist = TokenBlacklist::with_max_size(5000);
@@ -85,6 +97,7 @@ impl TokenBlacklist {
     pub fn with_max_size(max_size: usize) -> Self {
         Self {
             revoked_tokens: Arc::new(Mutex::new(HashMap::new())),
+            not_before: Arc::new(Mutex::new(HashMap::new())),
             max_size,
         }
     }
@@ -96,30 +109,57 @@ impl TokenBlacklist {
     /// tokens based on revocation time.
     ///
     /// # Arguments
-    /// * `token_id` - A unique identifier for the token (usually JTI or user ID)
+    /// * `token_id` - The token's JTI
     /// * `expiration` - When the token expires naturally
     ///
     /// # Example
     /// ```rust
     /// use std::time::{SystemTime, Duration};
-    /// use MyHealthGuide_domain::auth::token_blacklist::TokenBlacklist;
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
     ///
     /// let blacklist = TokenBlacklist::new();
     /// let expiration = SystemTime::now() + Duration::from_secs(3600); // 1 hour expiration
     /// blacklist.revoke_token("user123:session456", expiration);
     /// ```
     pub fn revoke_token(&self, token_id: &str, expiration: SystemTime) {
-        let revocation_time = SystemTime::now();
         let mut tokens = self.revoked_tokens.lock().unwrap();
+        self.insert_token(&mut tokens, token_id, expiration);
+    }
+
+    /// Add a token to the blacklist unless it is already there
+    ///
+    /// Checking and revoking happen under one lock, so when several callers
+    /// race to use the same single-use token exactly one of them gets `true`.
+    ///
+    /// # Returns
+    /// * `bool` - True if this call revoked the token, false if it already was
+    pub fn revoke_token_once(&self, token_id: &str, expiration: SystemTime) -> bool {
+        let mut tokens = self.revoked_tokens.lock().unwrap();
+        if tokens.contains_key(token_id) {
+            return false;
+        }
+
+        self.insert_token(&mut tokens, token_id, expiration);
+        true
+    }
+
+    /// Insert a token into the locked blacklist, making room if it is full
+    fn insert_token(
+        &self,
+        tokens: &mut HashMap<String, (SystemTime, SystemTime)>,
+        token_id: &str,
+        expiration: SystemTime,
+    ) {
+        let revocation_time = SystemTime::now();
 
         // Check size before adding
         if tokens.len() >= self.max_size {
             warn!("Token blacklist reached max size ({}), performing aggressive cleanup", self.max_size);
-            self.cleanup_expired_tokens_internal(&mut tokens);
+            self.cleanup_expired_tokens_internal(tokens);
 
             // If still at capacity, remove oldest entries
             if tokens.len() >= self.max_size {
-                self.remove_oldest_entries(&mut tokens, self.max_size / 2);
+                self.remove_oldest_entries(tokens, self.max_size / 2);
             }
         }
 
@@ -138,7 +178,7 @@ impl TokenBlacklist {
     ///
     /// # Example
     /// ```rust
-    /// use MyHealthGuide_domain::auth::token_blacklist::TokenBlacklist;
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
     ///
     /// let blacklist = TokenBlacklist::new();
     /// // ... revoke some tokens ...
@@ -154,6 +194,43 @@ impl TokenBlacklist {
         tokens.contains_key(token_id)
     }
 
+    /// Revoke every token issued to a user at or before `issued_before`
+    ///
+    /// The watermark only ever moves forward, so an older call can't
+    /// un-revoke tokens revoked by a newer one.
+    ///
+    /// # Arguments
+    /// * `user_id` - The user whose tokens are revoked
+    /// * `issued_before` - Unix timestamp in milliseconds; tokens issued at or before it are revoked
+    /// * `expiration` - When the last of the affected tokens expires naturally
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::{SystemTime, Duration};
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
+    ///
+    /// let blacklist = TokenBlacklist::new();
+    /// let expiration = SystemTime::now() + Duration::from_secs(7 * 86400);
+    /// blacklist.revoke_user_tokens("user123", chrono::Utc::now().timestamp_millis(), expiration);
+    /// ```
+    pub fn revoke_user_tokens(&self, user_id: &str, issued_before: i64, expiration: SystemTime) {
+        let mut watermarks = self.not_before.lock().unwrap();
+
+        let entry = watermarks.entry(user_id.to_string()).or_insert((issued_before, expiration));
+        entry.0 = entry.0.max(issued_before);
+        entry.1 = entry.1.max(expiration);
+        info!("All tokens revoked for user {} up to {}", user_id, entry.0);
+    }
+
+    /// Get the "not before" watermark for a user, if they logged out everywhere
+    ///
+    /// # Returns
+    /// The unix timestamp in milliseconds at or before which the user's tokens are revoked
+    pub fn user_not_before(&self, user_id: &str) -> Option<i64> {
+        let watermarks = self.not_before.lock().unwrap();
+        watermarks.get(user_id).map(|(issued_before, _)| *issued_before)
+    }
+
     /// Get the number of tokens in the blacklist
     ///
     /// # Returns
@@ -161,7 +238,7 @@ impl TokenBlacklist {
     ///
     /// # Example
     /// ```rust
-    /// use MyHealthGuide_domain::auth::token_blacklist::TokenBlacklist;
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
     ///
     /// let blacklist = TokenBlacklist::new();
     /// println!("Blacklist contains {} revoked tokens", blacklist.size());
@@ -181,7 +258,7 @@ impl TokenBlacklist {
     ///
     /// # Example
     /// ```rust
-    /// use MyHealthGuide_domain::auth::token_blacklist::TokenBlacklist;
+    /// use my_health_guide_domain::auth::token_blacklist::TokenBlacklist;
     ///
     /// let blacklist = TokenBlacklist::new();
     /// // ... revoke some tokens ...
@@ -190,8 +267,17 @@ impl TokenBlacklist {
     /// println!("Removed {} expired tokens", removed);
     /// ```
     pub fn cleanup_expired_tokens(&self) -> usize {
-        let mut tokens = self.revoked_tokens.lock().unwrap();
-        self.cleanup_expired_tokens_internal(&mut tokens)
+        let removed = {
+            let mut tokens = self.revoked_tokens.lock().unwrap();
+            self.cleanup_expired_tokens_internal(&mut tokens)
+        };
+
+        // Watermarks are only needed while the tokens they cover can still be used
+        let now = SystemTime::now();
+        let mut watermarks = self.not_before.lock().unwrap();
+        watermarks.retain(|_, (_, expiration)| now.duration_since(*expiration).is_err());
+
+        removed
     }
 
     /// Internal implementation of cleanup that works with an already-locked HashMap
@@ -228,7 +314,7 @@ impl TokenBlacklist {
 
         // Sort by revocation time (oldest first)
         let mut sorted_entries = entries_clone.clone();
-        sorted_entries.sort_by(|a, b| a.1.1.cmp(&b.1.1));
+        sorted_entries.sort_by_key(|(_, (_, revoked_at))| *revoked_at);
 
         // Take the oldest entries to remove (up to count)
         let to_remove: Vec<String> = sorted_entries.iter()
@@ -254,7 +340,7 @@ impl TokenBlacklist {
 ///
 /// # Example
 /// ```rust
-/// use MyHealthGuide_domain::auth::token_blacklist;
+/// use my_health_guide_domain::auth::token_blacklist;
 /// use std::time::{SystemTime, Duration};
 ///
 /// // Revoke a token
@@ -270,10 +356,17 @@ pub fn blacklist() -> &'static TokenBlacklist {
     &TOKEN_BLACKLIST
 }
 
-/// Start a background task to periodically clean up the token blacklist
+/// Get a shared handle to the global token blacklist
+pub(crate) fn shared_blacklist() -> Arc<TokenBlacklist> {
+    TOKEN_BLACKLIST.clone()
+}
+
+/// Start a background task to periodically clean up revoked tokens
 ///
-/// This function starts a Tokio task that runs every hour to remove expired tokens
-/// from the blacklist. It should be called during application startup.
+/// This function starts a Tokio task that runs every hour to remove expired
+/// revocations from the configured revocation store, whether that is this
+/// in-memory blacklist or the database. It should be called during
+/// application startup, after the store has been configured.
 ///
 /// # Example
 /// ```rust
@@ -281,7 +374,7 @@ pub fn blacklist() -> &'static TokenBlacklist {
 /// #[tokio::main]
 /// async fn main() {
 ///     // ... other initialization ...
-///     MyHealthGuide_domain::auth::token_blacklist::start_cleanup_task();
+///     my_health_guide_domain::auth::token_blacklist::start_cleanup_task();
 ///     // ... continue with startup ...
 /// }
 /// ```
@@ -289,6 +382,7 @@ pub fn blacklist() -> &'static TokenBlacklist {
 pub fn start_cleanup_task() {
     use tokio::time;
     use std::time::Duration;
+    use crate::auth::revocation::revocation_store;
 
     tokio::spawn(async move {
         let cleanup_interval = Duration::from_secs(3600); // 1 hour
@@ -296,10 +390,11 @@ pub fn start_cleanup_task() {
 
         loop {
             interval.tick().await;
-            debug!("Running scheduled token blacklist cleanup");
-            let removed = blacklist().cleanup_expired_tokens();
-            debug!("Removed {} expired tokens, {} remain in blacklist",
-                  removed, blacklist().size());
+            debug!("Running scheduled token revocation cleanup");
+            match revocation_store().cleanup_expired().await {
+                Ok(removed) => debug!("Removed {} expired token revocations", removed),
+                Err(e) => warn!("Failed to clean up token revocations: {}", e),
+            }
         }
     });
 }
@@ -308,6 +403,7 @@ pub fn start_cleanup_task() {
 mod tests {
     use super::*;
     use std::thread::sleep;
+    use std::time::Duration;
 
     #[test]
     fn test_revoke_and_check_token() {
@@ -325,6 +421,25 @@ mod tests {
         assert!(!blacklist.is_revoked("unknown-token"));
     }
 
+    #[test]
+    fn test_user_watermark_only_moves_forward() {
+        let blacklist = TokenBlacklist::new();
+        let expiration = SystemTime::now() + Duration::from_secs(60);
+
+        assert_eq!(blacklist.user_not_before("user-1"), None);
+
+        blacklist.revoke_user_tokens("user-1", 2000, expiration);
+        blacklist.revoke_user_tokens("user-1", 1000, expiration);
+        assert_eq!(blacklist.user_not_before("user-1"), Some(2000));
+        assert_eq!(blacklist.user_not_before("user-2"), None);
+
+        // Expired watermarks are dropped during cleanup
+        blacklist.revoke_user_tokens("user-2", 1000, SystemTime::now() - Duration::from_secs(1));
+        blacklist.cleanup_expired_tokens();
+        assert_eq!(blacklist.user_not_before("user-2"), None);
+        assert_eq!(blacklist.user_not_before("user-1"), Some(2000));
+    }
+
     #[test]
     fn test_cleanup_expired_tokens() {
         let blacklist = TokenBlacklist::new();
@@ -373,11 +488,13 @@ mod tests {
         let expiration = SystemTime::now() + Duration::from_secs(300);
         blacklist.revoke_token("new-token", expiration);
 
-        // We should still have max size tokens
-        assert_eq!(blacklist.size(), 5);
+        // Half of the max size was evicted to make room for the new token
+        assert_eq!(blacklist.size(), 4);
 
-        // The oldest token should be gone
+        // The oldest tokens should be gone
         assert!(!blacklist.is_revoked("token-0"));
+        assert!(!blacklist.is_revoked("token-1"));
+        assert!(blacklist.is_revoked("token-2"));
 
         // The new token should be there
         assert!(blacklist.is_revoked("new-token"));
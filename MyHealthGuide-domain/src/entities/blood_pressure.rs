@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
 use validator::{Validate, ValidationError};
 
 #[cfg(feature = "with-api")]
@@ -12,6 +12,9 @@ pub struct BloodPressureReading {
     /// Unique identifier for the reading
     pub id: String,
     
+    /// Identifier of the user who owns the reading
+    pub user_id: String,
+    
     /// Systolic blood pressure (the higher number)
     pub systolic: u16,
     
@@ -25,20 +28,26 @@ pub struct BloodPressureReading {
     pub notes: Option<String>,
     
     /// When the reading was taken
-    pub timestamp: String,
+    pub timestamp: DateTime<Utc>,
     
-    /// Optional position (e.g., sitting, standing)
-    pub position: Option<String>,
+    /// Optional position during measurement
+    pub position: Option<MeasurementPosition>,
     
-    /// Optional arm used (left or right)
-    pub arm: Option<String>,
+    /// Optional arm used for measurement
+    pub arm: Option<MeasurementArm>,
     
     /// Optional device ID used for measurement
     pub device_id: Option<String>,
+    
+    /// When the reading was stored
+    pub created_at: DateTime<Utc>,
+    
+    /// When the reading was last changed
+    pub updated_at: DateTime<Utc>,
 }
 
 /// Custom validator for RFC3339 timestamp format
-fn validate_timestamp(timestamp: &str) -> Result<(), ValidationError> {
+pub(crate) fn validate_timestamp(timestamp: &str) -> Result<(), ValidationError> {
     match DateTime::parse_from_rfc3339(timestamp) {
         Ok(dt) => {
             // Check if timestamp is in the future
@@ -75,44 +84,241 @@ pub struct CreateBloodPressureRequest {
     #[validate(custom = "validate_timestamp")]
     pub timestamp: String,
     
-    /// Optional position during measurement (e.g., sitting, standing)
-    pub position: Option<String>,
+    /// Optional position during measurement
+    pub position: Option<MeasurementPosition>,
     
-    /// Optional arm used for measurement (left or right)
-    pub arm: Option<String>,
+    /// Optional arm used for measurement
+    pub arm: Option<MeasurementArm>,
     
     /// Optional device ID used for measurement
+    #[validate(length(min = 1, max = 100, message = "Device ID must be between 1 and 100 characters"))]
+    pub device_id: Option<String>,
+}
+
+/// Request payload for partially updating a blood pressure reading
+///
+/// Fields left as `None` keep their current value. The merged result is
+/// validated with the same rules as a new reading.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct UpdateBloodPressureRequest {
+    /// Systolic blood pressure (the higher number)
+    pub systolic: Option<u16>,
+    
+    /// Diastolic blood pressure (the lower number)
+    pub diastolic: Option<u16>,
+    
+    /// Pulse rate in beats per minute
+    pub pulse: Option<u16>,
+    
+    /// Notes about the reading
+    pub notes: Option<String>,
+    
+    /// When the reading was taken
+    pub timestamp: Option<String>,
+    
+    /// Position during measurement
+    pub position: Option<MeasurementPosition>,
+    
+    /// Arm used for measurement
+    pub arm: Option<MeasurementArm>,
+    
+    /// Device ID used for measurement
     pub device_id: Option<String>,
 }
 
+impl UpdateBloodPressureRequest {
+    /// Apply the changes to an existing reading, producing a full request
+    pub fn merge_into(self, reading: BloodPressureReading) -> CreateBloodPressureRequest {
+        CreateBloodPressureRequest {
+            systolic: self.systolic.unwrap_or(reading.systolic),
+            diastolic: self.diastolic.unwrap_or(reading.diastolic),
+            pulse: self.pulse.or(reading.pulse),
+            notes: self.notes.or(reading.notes),
+            timestamp: self.timestamp
+                .unwrap_or_else(|| reading.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            position: self.position.or(reading.position),
+            arm: self.arm.or(reading.arm),
+            device_id: self.device_id.or(reading.device_id),
+        }
+    }
+}
+
+/// Filters for listing a user's blood pressure readings
+///
+/// Every filter is optional and a reading has to match all of the ones that
+/// are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BloodPressureFilter {
+    /// Earliest timestamp to include
+    pub start_date: Option<DateTime<Utc>>,
+
+    /// Latest timestamp to include
+    pub end_date: Option<DateTime<Utc>>,
+
+    /// Only readings in this category
+    pub category: Option<BloodPressureCategory>,
+
+    /// Only readings taken in this position
+    pub position: Option<MeasurementPosition>,
+
+    /// Only readings taken on this arm
+    pub arm: Option<MeasurementArm>,
+
+    /// Only readings taken with this device
+    pub device_id: Option<String>,
+}
+
+impl BloodPressureFilter {
+    /// Filter on a date range only
+    pub fn date_range(start_date: Option<DateTime<Utc>>, end_date: Option<DateTime<Utc>>) -> Self {
+        Self {
+            start_date,
+            end_date,
+            ..Default::default()
+        }
+    }
+}
+
+/// Body position during a blood pressure measurement
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementPosition {
+    /// Seated, the position guidelines recommend for routine readings
+    Sitting,
+
+    /// Standing, used for orthostatic checks
+    Standing,
+
+    /// Lying down
+    Lying,
+}
+
+impl MeasurementPosition {
+    /// Stable identifier of the position, the same as its serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MeasurementPosition::Sitting => "sitting",
+            MeasurementPosition::Standing => "standing",
+            MeasurementPosition::Lying => "lying",
+        }
+    }
+
+    /// Parse a stored position, ignoring case
+    ///
+    /// Returns `None` for values that are not a known position.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "sitting" => Some(MeasurementPosition::Sitting),
+            "standing" => Some(MeasurementPosition::Standing),
+            "lying" => Some(MeasurementPosition::Lying),
+            _ => None,
+        }
+    }
+}
+
+/// Arm a blood pressure measurement was taken on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum MeasurementArm {
+    /// Left arm
+    Left,
+
+    /// Right arm
+    Right,
+}
+
+impl MeasurementArm {
+    /// Stable identifier of the arm, the same as its serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MeasurementArm::Left => "left",
+            MeasurementArm::Right => "right",
+        }
+    }
+
+    /// Parse a stored arm, ignoring case
+    ///
+    /// Returns `None` for values that are not a known arm.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "left" => Some(MeasurementArm::Left),
+            "right" => Some(MeasurementArm::Right),
+            _ => None,
+        }
+    }
+}
+
 /// Blood pressure category based on measurements
+///
+/// Which categories are used, and their thresholds, depend on the
+/// [`GuidelineSet`](crate::services::guidelines::GuidelineSet). The ranges
+/// below are those of ACC/AHA 2017 or, for categories it does not have,
+/// ESC/ESH 2018.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "with-api", derive(ToSchema))]
 pub enum BloodPressureCategory {
+    /// Hypotension (systolic < 90 or diastolic < 60)
+    Hypotension,
+    
+    /// Optimal blood pressure (ESC/ESH: systolic < 120 and diastolic < 80)
+    Optimal,
+    
     /// Normal blood pressure (systolic < 120 and diastolic < 80)
     Normal,
     
     /// Elevated blood pressure (systolic 120-129 and diastolic < 80)
     Elevated,
     
-    /// Stage 1 Hypertension (systolic 130-139 or diastolic 80-89)
+    /// High-normal blood pressure (ESC/ESH: systolic 130-139 or diastolic 85-89)
+    HighNormal,
+    
+    /// Stage 1 or grade 1 hypertension (systolic 130-139 or diastolic 80-89)
     Hypertension1,
     
-    /// Stage 2 Hypertension (systolic ≥ 140 or diastolic ≥ 90)
+    /// Stage 2 or grade 2 hypertension (systolic ≥ 140 or diastolic ≥ 90)
     Hypertension2,
     
+    /// Grade 3 hypertension (ESC/ESH: systolic ≥ 180 or diastolic ≥ 110)
+    Hypertension3,
+    
     /// Hypertensive crisis (systolic > 180 and/or diastolic > 120)
     HypertensiveCrisis,
 }
 
-impl ToString for BloodPressureCategory {
-    fn to_string(&self) -> String {
+impl BloodPressureCategory {
+    /// Stable identifier of the category, the same as its serialized form
+    ///
+    /// This is the value stored with each reading and used to filter by category.
+    pub fn as_str(&self) -> &'static str {
         match self {
-            BloodPressureCategory::Normal => "Normal".to_string(),
-            BloodPressureCategory::Elevated => "Elevated".to_string(),
-            BloodPressureCategory::Hypertension1 => "Hypertension Stage 1".to_string(),
-            BloodPressureCategory::Hypertension2 => "Hypertension Stage 2".to_string(),
-            BloodPressureCategory::HypertensiveCrisis => "Hypertensive Crisis".to_string(),
+            BloodPressureCategory::Hypotension => "Hypotension",
+            BloodPressureCategory::Optimal => "Optimal",
+            BloodPressureCategory::Normal => "Normal",
+            BloodPressureCategory::Elevated => "Elevated",
+            BloodPressureCategory::HighNormal => "HighNormal",
+            BloodPressureCategory::Hypertension1 => "Hypertension1",
+            BloodPressureCategory::Hypertension2 => "Hypertension2",
+            BloodPressureCategory::Hypertension3 => "Hypertension3",
+            BloodPressureCategory::HypertensiveCrisis => "HypertensiveCrisis",
+        }
+    }
+}
+
+impl std::fmt::Display for BloodPressureCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BloodPressureCategory::Hypotension => write!(f, "Hypotension"),
+            BloodPressureCategory::Optimal => write!(f, "Optimal"),
+            BloodPressureCategory::Normal => write!(f, "Normal"),
+            BloodPressureCategory::Elevated => write!(f, "Elevated"),
+            BloodPressureCategory::HighNormal => write!(f, "High Normal"),
+            BloodPressureCategory::Hypertension1 => write!(f, "Hypertension Stage 1"),
+            BloodPressureCategory::Hypertension2 => write!(f, "Hypertension Stage 2"),
+            BloodPressureCategory::Hypertension3 => write!(f, "Hypertension Grade 3"),
+            BloodPressureCategory::HypertensiveCrisis => write!(f, "Hypertensive Crisis"),
         }
     }
 }
@@ -145,6 +351,45 @@ pub struct BloodPressureInsights {
     /// Blood pressure category based on average readings
     pub category: BloodPressureCategory,
     
+    /// Guideline set the category follows, e.g. "acc_aha_2017"
+    pub guideline: String,
+    
+    /// Pediatric classification of the averages, for patients under 18
+    pub pediatric: Option<PediatricClassification>,
+    
+    /// Sample standard deviation of systolic readings (needs two readings)
+    pub systolic_sd: Option<f64>,
+    
+    /// Sample standard deviation of diastolic readings (needs two readings)
+    pub diastolic_sd: Option<f64>,
+    
+    /// Average real variability of systolic readings, the mean change between consecutive readings
+    pub systolic_arv: Option<f64>,
+    
+    /// Average real variability of diastolic readings
+    pub diastolic_arv: Option<f64>,
+    
+    /// Coefficient of variation of systolic readings, in percent
+    pub systolic_cv: Option<f64>,
+    
+    /// Coefficient of variation of diastolic readings, in percent
+    pub diastolic_cv: Option<f64>,
+    
+    /// Average pulse pressure (systolic minus diastolic)
+    pub avg_pulse_pressure: f64,
+    
+    /// Average mean arterial pressure
+    pub avg_mean_arterial_pressure: f64,
+    
+    /// Percentage of readings within 90-129 systolic and 60-79 diastolic
+    pub time_in_target_range: Option<f64>,
+    
+    /// Linear trend of systolic readings (needs three readings on different times)
+    pub systolic_trend: Option<BloodPressureTrend>,
+    
+    /// Linear trend of diastolic readings
+    pub diastolic_trend: Option<BloodPressureTrend>,
+    
     /// Number of readings analyzed
     pub reading_count: usize,
     
@@ -155,12 +400,660 @@ pub struct BloodPressureInsights {
     pub generated_at: DateTime<Utc>,
 }
 
+/// Linear-regression trend of readings over time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct BloodPressureTrend {
+    /// Estimated change in mmHg per week
+    pub slope_per_week: f64,
+    
+    /// Lower bound of the 95% confidence interval of the slope
+    pub ci_lower: f64,
+    
+    /// Upper bound of the 95% confidence interval of the slope
+    pub ci_upper: f64,
+}
+
+/// Nocturnal dipping classification, from the night-to-day systolic ratio
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum DippingPattern {
+    /// Night systolic at least 10% below the day (ratio up to 0.9)
+    Dipper,
+    
+    /// Night systolic less than 10% below the day (ratio above 0.9 up to 1.0)
+    NonDipper,
+    
+    /// Night systolic above the day (ratio above 1.0)
+    ReverseDipper,
+}
+
+/// Averages of the readings taken in one part of the day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct TimeOfDayAverage {
+    /// Average systolic reading
+    pub avg_systolic: f64,
+    
+    /// Average diastolic reading
+    pub avg_diastolic: f64,
+    
+    /// Number of readings taken in this part of the day
+    pub reading_count: usize,
+}
+
+/// Blood pressure broken down by the local time of day the readings were taken
+///
+/// A part of the day without readings is left out, as is anything derived
+/// from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct TimeOfDayPattern {
+    /// IANA time zone used for local times, e.g. "Europe/Amsterdam"
+    pub time_zone: String,
+    
+    /// Readings taken from 04:00 until noon
+    pub morning: Option<TimeOfDayAverage>,
+    
+    /// Readings taken from 18:00 until midnight
+    pub evening: Option<TimeOfDayAverage>,
+    
+    /// Readings taken from 09:00 until 21:00, the day used for dipping
+    pub daytime: Option<TimeOfDayAverage>,
+    
+    /// Readings taken from 01:00 until 06:00, the night used for dipping
+    pub nighttime: Option<TimeOfDayAverage>,
+    
+    /// Morning minus night average systolic, in mmHg
+    pub morning_surge: Option<f64>,
+    
+    /// Night average systolic divided by the day average systolic
+    pub dipping_ratio: Option<f64>,
+    
+    /// Classification of the dipping ratio
+    pub dipping_pattern: Option<DippingPattern>,
+    
+    /// Number of readings analyzed
+    pub reading_count: usize,
+    
+    /// Analysis period in days
+    pub period_days: u32,
+    
+    /// Timestamp of the analysis
+    pub generated_at: DateTime<Utc>,
+}
+
+/// What a paired reading alert found
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum PairedReadingAlertKind {
+    /// Standing dropped at least 20 systolic or 10 diastolic below lying or sitting
+    OrthostaticHypotension,
+
+    /// Left and right arm systolic differ by at least 10
+    InterArmDifference,
+}
+
+/// An alert raised by comparing two readings taken minutes apart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct PairedReadingAlert {
+    /// What was found
+    pub kind: PairedReadingAlertKind,
+
+    /// Reading compared against: lying or sitting for orthostatic, the left arm for inter-arm
+    pub baseline_reading_id: String,
+
+    /// Reading compared: standing for orthostatic, the right arm for inter-arm
+    pub compared_reading_id: String,
+
+    /// Baseline minus compared systolic, in mmHg
+    pub systolic_difference: i32,
+
+    /// Baseline minus compared diastolic, in mmHg
+    pub diastolic_difference: i32,
+
+    /// When the later reading of the pair was taken
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Alerts from the position and arm of paired readings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct PairedReadingAlerts {
+    /// Alerts found, oldest first
+    pub alerts: Vec<PairedReadingAlert>,
+
+    /// Number of readings analyzed
+    pub reading_count: usize,
+
+    /// Analysis period in days
+    pub period_days: u32,
+
+    /// Timestamp of the analysis
+    pub generated_at: DateTime<Utc>,
+}
+
+/// What raised an alert on a new reading
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum BloodPressureAlertKind {
+    /// The reading is in the hypertensive crisis range
+    HypertensiveCrisis,
+
+    /// The reading is the latest of several stage 2 readings in a short time
+    #[serde(rename = "repeated_stage_2")]
+    RepeatedStage2,
+
+    /// One of the user's own alert rules fired
+    Rule,
+}
+
+impl BloodPressureAlertKind {
+    /// The kind as stored and serialized
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BloodPressureAlertKind::HypertensiveCrisis => "hypertensive_crisis",
+            BloodPressureAlertKind::RepeatedStage2 => "repeated_stage_2",
+            BloodPressureAlertKind::Rule => "rule",
+        }
+    }
+
+    /// Parse a stored kind
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hypertensive_crisis" => Some(BloodPressureAlertKind::HypertensiveCrisis),
+            "repeated_stage_2" => Some(BloodPressureAlertKind::RepeatedStage2),
+            "rule" => Some(BloodPressureAlertKind::Rule),
+            _ => None,
+        }
+    }
+}
+
+/// Domain event raised when a user's readings need attention
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct BloodPressureAlert {
+    /// Unique identifier for the alert
+    pub id: String,
+
+    /// Identifier of the user whose readings raised the alert
+    pub user_id: String,
+
+    /// What raised the alert
+    pub kind: BloodPressureAlertKind,
+
+    /// Identifier of the alert rule that fired, for rule alerts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+
+    /// What happened and what to do about it
+    pub message: String,
+
+    /// Systolic pressure of the latest reading behind the alert, or their average for an average rule
+    pub systolic: u16,
+
+    /// Diastolic pressure of the latest reading behind the alert, or their average for an average rule
+    pub diastolic: u16,
+
+    /// Readings behind the alert, oldest first, ending with the one that raised it
+    pub reading_ids: Vec<String>,
+
+    /// When the alert was raised
+    pub created_at: DateTime<Utc>,
+}
+
+/// Highest sensible threshold for an alert rule, in mmHg
+const MAX_RULE_THRESHOLD: u16 = 300;
+
+/// Longest period an alert rule may look back over, in days
+const MAX_RULE_DAYS: u32 = 365;
+
+/// Most readings an alert rule may require in a row
+const MAX_RULE_COUNT: usize = 50;
+
+/// What makes a user-defined alert rule fire
+///
+/// A reading is above the thresholds when its systolic pressure is above
+/// `systolic` or its diastolic pressure is above `diastolic`; a threshold
+/// that is left out is not checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum AlertRuleCondition {
+    /// The latest `count` readings are all above the thresholds
+    ConsecutiveAbove {
+        /// Readings in a row that must be above the thresholds
+        count: usize,
+        /// Systolic threshold in mmHg
+        systolic: Option<u16>,
+        /// Diastolic threshold in mmHg
+        diastolic: Option<u16>,
+    },
+
+    /// The average of the readings taken in the last `days` days is above the thresholds
+    AverageAbove {
+        /// Days to average over, 7 for a weekly average
+        days: u32,
+        /// Systolic threshold in mmHg
+        systolic: Option<u16>,
+        /// Diastolic threshold in mmHg
+        diastolic: Option<u16>,
+    },
+
+    /// The latest reading is more than `days` days old
+    NoReading {
+        /// Days without a reading before the rule fires
+        days: u32,
+    },
+}
+
+impl AlertRuleCondition {
+    /// Whether a reading's pressures are above the condition's thresholds
+    ///
+    /// Always false for conditions without thresholds.
+    pub fn is_above(&self, systolic: f64, diastolic: f64) -> bool {
+        match self {
+            AlertRuleCondition::ConsecutiveAbove { systolic: systolic_limit, diastolic: diastolic_limit, .. }
+            | AlertRuleCondition::AverageAbove { systolic: systolic_limit, diastolic: diastolic_limit, .. } => {
+                systolic_limit.is_some_and(|limit| systolic > limit as f64)
+                    || diastolic_limit.is_some_and(|limit| diastolic > limit as f64)
+            },
+            AlertRuleCondition::NoReading { .. } => false,
+        }
+    }
+}
+
+/// Check the limits of an alert rule condition
+fn validate_rule_condition(condition: &AlertRuleCondition) -> Result<(), ValidationError> {
+    let error = |message: &'static str| {
+        let mut error = ValidationError::new("invalid_condition");
+        error.message = Some(message.into());
+        Err(error)
+    };
+    let thresholds_valid = |systolic: &Option<u16>, diastolic: &Option<u16>| {
+        (systolic.is_some() || diastolic.is_some())
+            && [systolic, diastolic].iter().all(|limit| limit.is_none_or(|limit| limit <= MAX_RULE_THRESHOLD))
+    };
+
+    match condition {
+        AlertRuleCondition::ConsecutiveAbove { count, systolic, diastolic } => {
+            if !(1..=MAX_RULE_COUNT).contains(count) {
+                return error("count must be between 1 and 50");
+            }
+            if !thresholds_valid(systolic, diastolic) {
+                return error("Set a systolic or diastolic threshold of at most 300 mmHg");
+            }
+        },
+        AlertRuleCondition::AverageAbove { days, systolic, diastolic } => {
+            if !(1..=MAX_RULE_DAYS).contains(days) {
+                return error("days must be between 1 and 365");
+            }
+            if !thresholds_valid(systolic, diastolic) {
+                return error("Set a systolic or diastolic threshold of at most 300 mmHg");
+            }
+        },
+        AlertRuleCondition::NoReading { days } => {
+            if !(1..=MAX_RULE_DAYS).contains(days) {
+                return error("days must be between 1 and 365");
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Whether a new rule is enabled when the request does not say
+fn default_rule_enabled() -> bool {
+    true
+}
+
+/// A user-defined rule that raises alerts on the user's readings
+///
+/// Rules are evaluated whenever the user records a reading and on a
+/// schedule, so rules about missing readings fire too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct AlertRule {
+    /// Unique identifier for the rule
+    pub id: String,
+
+    /// Identifier of the user the rule watches
+    pub user_id: String,
+
+    /// Name of the rule, shown in the alerts it fires
+    pub name: String,
+
+    /// What makes the rule fire
+    pub condition: AlertRuleCondition,
+
+    /// Whether the rule is evaluated
+    pub enabled: bool,
+
+    /// When the rule was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the rule was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create or replace an alert rule
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct CreateAlertRuleRequest {
+    /// Name of the rule, e.g. "Three high readings in a row"
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+
+    /// What makes the rule fire
+    #[validate(custom = "validate_rule_condition")]
+    pub condition: AlertRuleCondition,
+
+    /// Whether the rule is evaluated (default: true)
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+/// Part of the day a home monitoring reading is taken in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum HbpmPeriod {
+    /// From 04:00 until noon, local time
+    Morning,
+
+    /// From 18:00 until midnight, local time
+    Evening,
+}
+
+/// A morning or evening of the home monitoring week without its duplicate readings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct HbpmSlot {
+    /// Local date of the slot
+    pub date: NaiveDate,
+
+    /// Morning or evening
+    pub period: HbpmPeriod,
+
+    /// Readings taken in the slot, fewer than the two required
+    pub reading_count: usize,
+}
+
+/// Result of a standard 7-day home blood pressure monitoring (HBPM) week
+///
+/// The protocol asks for two readings each morning and evening for 7 days.
+/// Day 1 is discarded and the readings of days 2 to 7 are averaged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct HbpmReport {
+    /// IANA time zone used for local times, e.g. "Europe/Amsterdam"
+    pub time_zone: String,
+
+    /// Local date of day 1
+    pub start_date: NaiveDate,
+
+    /// Local date of day 7
+    pub end_date: NaiveDate,
+
+    /// Whether every slot of days 2 to 7 has its duplicate readings
+    pub complete: bool,
+
+    /// Average systolic of the readings used, if there are any
+    pub avg_systolic: Option<f64>,
+
+    /// Average diastolic of the readings used, if there are any
+    pub avg_diastolic: Option<f64>,
+
+    /// Category of the rounded averages
+    pub category: Option<BloodPressureCategory>,
+
+    /// Number of readings averaged
+    pub reading_count: usize,
+
+    /// Slots of days 2 to 7 still missing readings, in order
+    pub missing_slots: Vec<HbpmSlot>,
+
+    /// Timestamp of the analysis
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Request payload for recording a measurement session
+///
+/// Guidelines recommend taking two or three readings a minute or two apart
+/// and averaging them, so a session is created together with its readings.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct CreateBloodPressureSessionRequest {
+    /// Readings taken during the session
+    #[validate(length(min = 2, max = 6, message = "A session must have between 2 and 6 readings"))]
+    pub readings: Vec<CreateBloodPressureRequest>,
+
+    /// Leave the first reading out of the average, as it tends to read high
+    #[serde(default)]
+    pub discard_first: bool,
+
+    /// Optional notes about the session
+    #[validate(length(max = 1000, message = "Notes cannot exceed 1000 characters"))]
+    pub notes: Option<String>,
+}
+
+/// Average of the readings in a measurement session
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct SessionAverage {
+    /// Average systolic pressure
+    pub systolic: f64,
+
+    /// Average diastolic pressure
+    pub diastolic: f64,
+
+    /// Average pulse rate, if any averaged reading had one
+    pub pulse: Option<f64>,
+
+    /// Category of the rounded averages
+    pub category: BloodPressureCategory,
+
+    /// Number of readings averaged
+    pub reading_count: usize,
+}
+
+/// Domain entity for a measurement session: readings taken together and averaged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct BloodPressureSession {
+    /// Unique identifier for the session
+    pub id: String,
+
+    /// Identifier of the user who owns the session
+    pub user_id: String,
+
+    /// Whether the first reading is left out of the average
+    pub discard_first: bool,
+
+    /// Optional notes about the session
+    pub notes: Option<String>,
+
+    /// Readings in the session, oldest first; deleted readings are left out
+    pub readings: Vec<BloodPressureReading>,
+
+    /// Average of the session's readings, if it has any left
+    pub average: Option<SessionAverage>,
+
+    /// When the session was stored
+    pub created_at: DateTime<Utc>,
+}
+
+/// Sex used to look up pediatric reference values
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Sex {
+    /// Male, the AAP tables for boys
+    Male,
+
+    /// Female, the AAP tables for girls
+    Female,
+}
+
+impl Sex {
+    /// Stable identifier of the sex, the same as its serialized form
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+        }
+    }
+
+    /// Parse a sex, ignoring case
+    ///
+    /// Returns `None` for values that are not a known sex.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "male" | "m" => Some(Sex::Male),
+            "female" | "f" => Some(Sex::Female),
+            _ => None,
+        }
+    }
+}
+
+/// What the pediatric classifier needs to know about a patient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PediatricPatient {
+    /// Date of birth, for the age in completed years
+    pub date_of_birth: NaiveDate,
+
+    /// Sex, selecting the table for boys or girls
+    pub sex: Sex,
+
+    /// Latest height in centimeters, if known
+    pub height_cm: Option<f64>,
+}
+
+impl PediatricPatient {
+    /// Age in completed years on the given date
+    pub fn age_on(&self, date: NaiveDate) -> Option<u32> {
+        date.years_since(self.date_of_birth)
+    }
+}
+
+/// Blood pressure category for children and adolescents (AAP 2017)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum PediatricCategory {
+    /// Below the 90th percentile (from 13: below 120/80)
+    Normal,
+
+    /// 90th to below the 95th percentile, or 120/80 if lower (from 13: 120-129/<80)
+    Elevated,
+
+    /// 95th percentile to below the 95th + 12 mmHg, or 130/80 if lower (from 13: 130-139/80-89)
+    Stage1,
+
+    /// At or above the 95th percentile + 12 mmHg, or 140/90 if lower (from 13: 140/90 and above)
+    Stage2,
+}
+
+impl PediatricCategory {
+    /// The adult category with the same clinical meaning
+    pub fn to_category(self) -> BloodPressureCategory {
+        match self {
+            PediatricCategory::Normal => BloodPressureCategory::Normal,
+            PediatricCategory::Elevated => BloodPressureCategory::Elevated,
+            PediatricCategory::Stage1 => BloodPressureCategory::Hypertension1,
+            PediatricCategory::Stage2 => BloodPressureCategory::Hypertension2,
+        }
+    }
+}
+
+/// Where a reading falls among the blood pressure percentiles for its age, sex and height
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum PercentileBand {
+    /// Below the 50th percentile
+    #[serde(rename = "below_50th")]
+    Below50th,
+
+    /// 50th to below the 90th percentile
+    #[serde(rename = "50th_to_90th")]
+    From50thTo90th,
+
+    /// 90th to below the 95th percentile
+    #[serde(rename = "90th_to_95th")]
+    From90thTo95th,
+
+    /// 95th percentile to below the 95th + 12 mmHg
+    #[serde(rename = "95th_to_95th_plus_12")]
+    From95thTo95thPlus12,
+
+    /// At or above the 95th percentile + 12 mmHg
+    #[serde(rename = "95th_plus_12_and_above")]
+    From95thPlus12,
+}
+
+/// Pediatric blood pressure classification of a reading
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct PediatricClassification {
+    /// Age in completed years
+    pub age_years: u32,
+
+    /// Sex the reference values are for
+    pub sex: Sex,
+
+    /// Height percentile column used, 5 to 95 (ages 1 to 12 only)
+    pub height_percentile: Option<u8>,
+
+    /// Higher of the systolic and diastolic percentile bands (ages 1 to 12 only)
+    pub percentile: Option<PercentileBand>,
+
+    /// AAP 2017 category
+    pub category: PediatricCategory,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::Utc;
     use validator::Validate;
 
+    /// Test that a partial update only overrides the provided fields
+    #[test]
+    fn test_update_request_merge() {
+        let reading = BloodPressureReading {
+            id: "reading-1".to_string(),
+            user_id: "user-1".to_string(),
+            systolic: 120,
+            diastolic: 80,
+            pulse: Some(72),
+            notes: Some("Morning".to_string()),
+            timestamp: "2024-01-01T08:00:00Z".parse().unwrap(),
+            position: Some(MeasurementPosition::Sitting),
+            arm: None,
+            device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        
+        let update = UpdateBloodPressureRequest {
+            systolic: Some(125),
+            arm: Some(MeasurementArm::Left),
+            ..Default::default()
+        };
+        
+        let merged = update.merge_into(reading);
+        assert_eq!(merged.systolic, 125);
+        assert_eq!(merged.diastolic, 80);
+        assert_eq!(merged.pulse, Some(72));
+        assert_eq!(merged.notes, Some("Morning".to_string()));
+        assert_eq!(merged.timestamp, "2024-01-01T08:00:00Z");
+        assert_eq!(merged.position, Some(MeasurementPosition::Sitting));
+        assert_eq!(merged.arm, Some(MeasurementArm::Left));
+    }
+
     /// Test timestamp validation in CreateBloodPressureRequest
     #[test]
     fn test_timestamp_validation() {
@@ -195,4 +1088,18 @@ mod tests {
         let err = result.unwrap_err();
         assert!(err.field_errors().contains_key("timestamp"));
     }
-} 
\ No newline at end of file
+
+    /// Test that stored positions and arms parse regardless of case
+    #[test]
+    fn test_parse_position_and_arm() {
+        assert_eq!(MeasurementPosition::parse("Sitting"), Some(MeasurementPosition::Sitting));
+        assert_eq!(MeasurementPosition::parse(" lying "), Some(MeasurementPosition::Lying));
+        assert_eq!(MeasurementPosition::parse("kneeling"), None);
+        assert_eq!(MeasurementArm::parse("RIGHT"), Some(MeasurementArm::Right));
+        assert_eq!(MeasurementArm::parse("wrist"), None);
+
+        for position in [MeasurementPosition::Sitting, MeasurementPosition::Standing, MeasurementPosition::Lying] {
+            assert_eq!(MeasurementPosition::parse(position.as_str()), Some(position));
+        }
+    }
+}
@@ -1,11 +1,20 @@
 use crate::entities::blood_pressure::{
-    BloodPressureReading, CreateBloodPressureRequest, BloodPressureInsights, BloodPressureCategory
+    BloodPressureReading, CreateBloodPressureRequest, BloodPressureFilter, BloodPressureInsights, BloodPressureCategory,
+    BloodPressureAlert, BloodPressureAlertKind, BloodPressureSession, MeasurementArm, MeasurementPosition,
+    AlertRule, AlertRuleCondition, CreateAlertRuleRequest, Sex,
 };
+use crate::services::guidelines::GuidelineSet;
+use crate::services::insights::{average_session, mean_arterial_pressure, pulse_pressure};
+use crate::entities::weight::{WeightReading, CreateWeightRequest};
+use crate::entities::user::User;
+use crate::entities::goal::{Goals, SetGoalsRequest};
+use crate::entities::profile::{Profile, SetProfileRequest};
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
-/// Conversion functions between domain entities and data models
-/// These functions follow the pattern convert_to_[target_layer]_[model_name]
-/// as specified in the architectural rules
+// Conversion functions between domain entities and data models
+// These functions follow the pattern convert_to_[target_layer]_[model_name]
+// as specified in the architectural rules
 
 /// Helper function to safely parse a string ID to UUID
 ///
@@ -21,36 +30,157 @@ pub fn parse_string_to_uuid(id: &str) -> Result<Uuid, String> {
     Uuid::parse_str(id).map_err(|_| format!("Invalid UUID format: {}", id))
 }
 
+/// Helper function to parse an RFC 3339 timestamp string into UTC
+///
+/// Requests carry timestamps as validated strings; this turns them into the
+/// typed timestamps the data layer stores.
+///
+/// # Arguments
+/// * `timestamp` - The RFC 3339 timestamp to parse
+///
+/// # Returns
+/// * `Result<DateTime<Utc>, String>` - The parsed timestamp or an error message
+pub fn parse_string_to_timestamp(timestamp: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid timestamp format: {}", timestamp))
+}
+
 /// Convert from data model to domain entity for blood pressure reading
 pub fn convert_to_domain_reading(data_reading: my_health_guide_data::models::blood_pressure::BloodPressureReading)
     -> BloodPressureReading
 {
     BloodPressureReading {
         id: data_reading.id,
+        user_id: data_reading.user_id,
         systolic: data_reading.systolic,
         diastolic: data_reading.diastolic,
         pulse: data_reading.pulse,
         notes: data_reading.notes,
         timestamp: data_reading.timestamp,
-        position: data_reading.position,
-        arm: data_reading.arm,
+        position: data_reading.position.as_deref().and_then(MeasurementPosition::parse),
+        arm: data_reading.arm.as_deref().and_then(MeasurementArm::parse),
         device_id: data_reading.device_id,
+        created_at: data_reading.created_at,
+        updated_at: data_reading.updated_at,
     }
 }
 
 /// Convert from domain entity to data model for create request
-pub fn convert_to_data_create_request(domain_request: &CreateBloodPressureRequest)
-    -> my_health_guide_data::models::blood_pressure::CreateBloodPressureRequest
+///
+/// The reading is categorized here with the user's `guideline` so the stored
+/// category always matches the stored values, whichever write path the
+/// request takes. Fails if the timestamp is not RFC 3339.
+pub fn convert_to_data_create_request(domain_request: &CreateBloodPressureRequest, guideline: &GuidelineSet)
+    -> Result<my_health_guide_data::models::blood_pressure::CreateBloodPressureRequest, String>
 {
-    my_health_guide_data::models::blood_pressure::CreateBloodPressureRequest {
+    let category = guideline.categorize(domain_request.systolic, domain_request.diastolic);
+    let timestamp = parse_string_to_timestamp(&domain_request.timestamp)?;
+
+    Ok(my_health_guide_data::models::blood_pressure::CreateBloodPressureRequest {
         systolic: domain_request.systolic,
         diastolic: domain_request.diastolic,
         pulse: domain_request.pulse,
         notes: domain_request.notes.clone(),
-        timestamp: domain_request.timestamp.clone(),
-        position: domain_request.position.clone(),
-        arm: domain_request.arm.clone(),
+        timestamp,
+        position: domain_request.position.map(|position| position.as_str().to_string()),
+        arm: domain_request.arm.map(|arm| arm.as_str().to_string()),
         device_id: domain_request.device_id.clone(),
+        category: Some(category.as_str().to_string()),
+    })
+}
+
+/// Convert from data model to domain entity for a measurement session
+///
+/// The data model only links reading IDs, so the caller looks up the readings
+/// that are still there. The average is worked out from them here and
+/// categorized with the user's `guideline`.
+pub fn convert_to_domain_session(
+    data_session: my_health_guide_data::models::blood_pressure::BloodPressureSession,
+    mut readings: Vec<BloodPressureReading>,
+    guideline: &GuidelineSet,
+) -> BloodPressureSession {
+    readings.sort_by_key(|reading| reading.timestamp);
+    let average = average_session(&readings, data_session.discard_first, guideline);
+
+    BloodPressureSession {
+        id: data_session.id,
+        user_id: data_session.user_id,
+        discard_first: data_session.discard_first,
+        notes: data_session.notes,
+        readings,
+        average,
+        created_at: data_session.created_at,
+    }
+}
+
+/// Convert from data model to domain entity for an alert
+///
+/// Fails if the stored kind is not one the domain knows.
+pub fn convert_to_domain_alert(data_alert: my_health_guide_data::models::blood_pressure::BloodPressureAlert)
+    -> Result<BloodPressureAlert, String>
+{
+    let kind = BloodPressureAlertKind::parse(&data_alert.kind)
+        .ok_or_else(|| format!("Invalid alert kind: {}", data_alert.kind))?;
+
+    Ok(BloodPressureAlert {
+        id: data_alert.id,
+        user_id: data_alert.user_id,
+        kind,
+        rule_id: data_alert.rule_id,
+        message: data_alert.message,
+        systolic: data_alert.systolic,
+        diastolic: data_alert.diastolic,
+        reading_ids: data_alert.reading_ids,
+        created_at: data_alert.created_at,
+    })
+}
+
+/// Convert from data model to domain entity for alert rules
+///
+/// Fails when the stored condition is not one this version understands.
+pub fn convert_to_domain_alert_rule(data_rule: my_health_guide_data::models::blood_pressure::BloodPressureAlertRule)
+    -> Result<AlertRule, String>
+{
+    let condition: AlertRuleCondition = serde_json::from_str(&data_rule.definition)
+        .map_err(|e| format!("Invalid condition for alert rule {}: {}", data_rule.id, e))?;
+
+    Ok(AlertRule {
+        id: data_rule.id,
+        user_id: data_rule.user_id,
+        name: data_rule.name,
+        condition,
+        enabled: data_rule.enabled,
+        created_at: data_rule.created_at,
+        updated_at: data_rule.updated_at,
+    })
+}
+
+/// Convert from domain entity to data model for alert rule requests
+pub fn convert_to_data_alert_rule_request(request: &CreateAlertRuleRequest)
+    -> Result<my_health_guide_data::models::blood_pressure::CreateBloodPressureAlertRuleRequest, String>
+{
+    let definition = serde_json::to_string(&request.condition)
+        .map_err(|e| format!("Invalid alert rule condition: {}", e))?;
+
+    Ok(my_health_guide_data::models::blood_pressure::CreateBloodPressureAlertRuleRequest {
+        name: request.name.clone(),
+        definition,
+        enabled: request.enabled,
+    })
+}
+
+/// Convert from domain entity to data model for reading filters
+pub fn convert_to_data_filter(domain_filter: BloodPressureFilter)
+    -> my_health_guide_data::models::blood_pressure::BloodPressureFilter
+{
+    my_health_guide_data::models::blood_pressure::BloodPressureFilter {
+        start_date: domain_filter.start_date,
+        end_date: domain_filter.end_date,
+        category: domain_filter.category.map(|category| category.as_str().to_string()),
+        position: domain_filter.position.map(|position| position.as_str().to_string()),
+        arm: domain_filter.arm.map(|arm| arm.as_str().to_string()),
+        device_id: domain_filter.device_id,
     }
 }
 
@@ -74,15 +204,22 @@ pub fn convert_to_data_insights(domain_insights: &BloodPressureInsights)
 }
 
 /// Convert from data model to domain entity for blood pressure insights
+///
+/// The stored summary only keeps averages and extremes, so the statistics
+/// that need the individual readings are left empty.
 pub fn convert_to_domain_insights(data_insights: my_health_guide_data::models::blood_pressure::BloodPressureInsights)
     -> Result<BloodPressureInsights, &'static str>
 {
     // Parse the category string to get the domain category enum
     let category = match data_insights.category.as_str() {
+        "Hypotension" => BloodPressureCategory::Hypotension,
+        "Optimal" => BloodPressureCategory::Optimal,
         "Normal" => BloodPressureCategory::Normal,
         "Elevated" => BloodPressureCategory::Elevated,
+        "High Normal" => BloodPressureCategory::HighNormal,
         "Hypertension Stage 1" => BloodPressureCategory::Hypertension1,
         "Hypertension Stage 2" => BloodPressureCategory::Hypertension2,
+        "Hypertension Grade 3" => BloodPressureCategory::Hypertension3,
         "Hypertensive Crisis" => BloodPressureCategory::HypertensiveCrisis,
         _ => return Err("Invalid blood pressure category string"),
     };
@@ -96,12 +233,118 @@ pub fn convert_to_domain_insights(data_insights: my_health_guide_data::models::b
         min_systolic: data_insights.min_systolic,
         min_diastolic: data_insights.min_diastolic,
         category,
+        // The stored summary is always categorized with ACC/AHA 2017
+        guideline: GuidelineSet::AccAha2017.name().to_string(),
+        pediatric: None,
+        systolic_sd: None,
+        diastolic_sd: None,
+        systolic_arv: None,
+        diastolic_arv: None,
+        systolic_cv: None,
+        diastolic_cv: None,
+        avg_pulse_pressure: pulse_pressure(data_insights.avg_systolic, data_insights.avg_diastolic),
+        avg_mean_arterial_pressure: mean_arterial_pressure(data_insights.avg_systolic, data_insights.avg_diastolic),
+        time_in_target_range: None,
+        systolic_trend: None,
+        diastolic_trend: None,
         reading_count: data_insights.reading_count,
         period_days: data_insights.period_days,
         generated_at: data_insights.generated_at,
     })
 }
 
+/// Convert from data model to domain entity for weight reading
+pub fn convert_to_domain_weight_reading(data_reading: my_health_guide_data::models::weight::WeightReading)
+    -> WeightReading
+{
+    WeightReading {
+        id: data_reading.id,
+        user_id: data_reading.user_id,
+        weight_kg: data_reading.weight_kg,
+        body_fat_percentage: data_reading.body_fat_percentage,
+        muscle_mass_kg: data_reading.muscle_mass_kg,
+        notes: data_reading.notes,
+        timestamp: data_reading.timestamp,
+    }
+}
+
+/// Convert from domain entity to data model for weight create request
+pub fn convert_to_data_create_weight_request(domain_request: &CreateWeightRequest)
+    -> my_health_guide_data::models::weight::CreateWeightRequest
+{
+    my_health_guide_data::models::weight::CreateWeightRequest {
+        weight_kg: domain_request.weight_kg,
+        body_fat_percentage: domain_request.body_fat_percentage,
+        muscle_mass_kg: domain_request.muscle_mass_kg,
+        notes: domain_request.notes.clone(),
+        timestamp: domain_request.timestamp.clone(),
+    }
+}
+
+/// Convert from data model to domain entity for user, dropping the password hash
+pub fn convert_to_domain_user(data_user: my_health_guide_data::models::user::User) -> User {
+    User {
+        id: data_user.id,
+        email: data_user.email,
+        name: data_user.name,
+        roles: data_user.roles,
+        created_at: data_user.created_at,
+    }
+}
+
+/// Convert from data model to domain entity for goals
+pub fn convert_to_domain_goals(data_goals: my_health_guide_data::models::goal::UserGoals) -> Goals {
+    Goals {
+        user_id: data_goals.user_id,
+        target_systolic: data_goals.target_systolic,
+        target_diastolic: data_goals.target_diastolic,
+        readings_per_week: data_goals.readings_per_week,
+        target_weight_kg: data_goals.target_weight_kg,
+        created_at: data_goals.created_at,
+        updated_at: data_goals.updated_at,
+    }
+}
+
+/// Convert from domain request to data request for setting goals
+pub fn convert_to_data_set_goals_request(domain_request: &SetGoalsRequest)
+    -> my_health_guide_data::models::goal::SetUserGoalsRequest
+{
+    my_health_guide_data::models::goal::SetUserGoalsRequest {
+        target_systolic: domain_request.target_systolic,
+        target_diastolic: domain_request.target_diastolic,
+        readings_per_week: domain_request.readings_per_week,
+        target_weight_kg: domain_request.target_weight_kg,
+    }
+}
+
+/// Convert from data model to domain entity for a profile
+pub fn convert_to_domain_profile(data_profile: my_health_guide_data::models::profile::UserProfile) -> Profile {
+    Profile {
+        user_id: data_profile.user_id,
+        time_zone: data_profile.time_zone,
+        guideline: data_profile.guideline,
+        date_of_birth: data_profile.date_of_birth.as_deref()
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()),
+        sex: data_profile.sex.as_deref().and_then(Sex::parse),
+        height_cm: data_profile.height_cm,
+        created_at: data_profile.created_at,
+        updated_at: data_profile.updated_at,
+    }
+}
+
+/// Convert from domain request to data request for setting a profile
+pub fn convert_to_data_set_profile_request(domain_request: &SetProfileRequest)
+    -> my_health_guide_data::models::profile::SetUserProfileRequest
+{
+    my_health_guide_data::models::profile::SetUserProfileRequest {
+        time_zone: domain_request.time_zone.clone(),
+        guideline: domain_request.guideline.clone(),
+        date_of_birth: domain_request.date_of_birth.map(|date| date.format("%Y-%m-%d").to_string()),
+        sex: domain_request.sex.map(|sex| sex.as_str().to_string()),
+        height_cm: domain_request.height_cm,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -112,14 +355,18 @@ mod tests {
         // Create a data model
         let data_reading = my_health_guide_data::models::blood_pressure::BloodPressureReading {
             id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            user_id: "user-123".to_string(),
             systolic: 120,
             diastolic: 80,
             pulse: Some(72),
             notes: Some("Test reading".to_string()),
-            timestamp: Utc::now().to_rfc3339(),
+            timestamp: Utc::now(),
             position: Some("Sitting".to_string()),
             arm: Some("Left".to_string()),
             device_id: Some("Device123".to_string()),
+            category: Some("Elevated".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         };
 
         // Convert to domain entity
@@ -127,14 +374,17 @@ mod tests {
 
         // Verify conversion
         assert_eq!(domain_reading.id, data_reading.id);
+        assert_eq!(domain_reading.user_id, data_reading.user_id);
         assert_eq!(domain_reading.systolic, data_reading.systolic);
         assert_eq!(domain_reading.diastolic, data_reading.diastolic);
         assert_eq!(domain_reading.pulse, data_reading.pulse);
         assert_eq!(domain_reading.notes, data_reading.notes);
         assert_eq!(domain_reading.timestamp, data_reading.timestamp);
-        assert_eq!(domain_reading.position, data_reading.position);
-        assert_eq!(domain_reading.arm, data_reading.arm);
+        assert_eq!(domain_reading.position, Some(MeasurementPosition::Sitting));
+        assert_eq!(domain_reading.arm, Some(MeasurementArm::Left));
         assert_eq!(domain_reading.device_id, data_reading.device_id);
+        assert_eq!(domain_reading.created_at, data_reading.created_at);
+        assert_eq!(domain_reading.updated_at, data_reading.updated_at);
     }
 
     #[test]
@@ -146,22 +396,23 @@ mod tests {
             pulse: Some(72),
             notes: Some("Test reading".to_string()),
             timestamp: Utc::now().to_rfc3339(),
-            position: Some("Sitting".to_string()),
-            arm: Some("Left".to_string()),
+            position: Some(MeasurementPosition::Sitting),
+            arm: Some(MeasurementArm::Left),
             device_id: Some("Device123".to_string()),
         };
 
         // Convert to data model
-        let data_request = convert_to_data_create_request(&domain_request);
+        let data_request = convert_to_data_create_request(&domain_request, &GuidelineSet::AccAha2017).unwrap();
 
         // Verify conversion
         assert_eq!(data_request.systolic, domain_request.systolic);
         assert_eq!(data_request.diastolic, domain_request.diastolic);
         assert_eq!(data_request.pulse, domain_request.pulse);
         assert_eq!(data_request.notes, domain_request.notes);
-        assert_eq!(data_request.timestamp, domain_request.timestamp);
-        assert_eq!(data_request.position, domain_request.position);
-        assert_eq!(data_request.arm, domain_request.arm);
+        assert_eq!(data_request.timestamp.to_rfc3339(), domain_request.timestamp);
+        assert_eq!(data_request.position.as_deref(), Some("sitting"));
+        assert_eq!(data_request.arm.as_deref(), Some("left"));
         assert_eq!(data_request.device_id, domain_request.device_id);
+        assert_eq!(data_request.category.as_deref(), Some("Hypertension1"));
     }
 }
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use validator::Validate;
+
+#[cfg(feature = "with-api")]
+use utoipa::ToSchema;
+
+/// Domain entity for a user's personal goals
+///
+/// Every goal is optional; progress is only reported for the ones set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct Goals {
+    /// Identifier of the user the goals belong to
+    pub user_id: String,
+
+    /// Systolic pressure to stay below, in mmHg
+    pub target_systolic: Option<u16>,
+
+    /// Diastolic pressure to stay below, in mmHg
+    pub target_diastolic: Option<u16>,
+
+    /// Blood pressure readings to take each week
+    pub readings_per_week: Option<u32>,
+
+    /// Weight to reach, in kilograms
+    pub target_weight_kg: Option<f32>,
+
+    /// When the goals were first set
+    pub created_at: DateTime<Utc>,
+
+    /// When the goals were last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request payload for setting a user's goals, replacing any set before
+///
+/// The blood pressure targets are set together, and at least one goal has
+/// to be set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct SetGoalsRequest {
+    /// Systolic pressure to stay below, in mmHg
+    #[validate(range(min = 90, max = 180, message = "Target systolic must be between 90 and 180 mmHg"))]
+    pub target_systolic: Option<u16>,
+
+    /// Diastolic pressure to stay below, in mmHg
+    #[validate(range(min = 50, max = 120, message = "Target diastolic must be between 50 and 120 mmHg"))]
+    pub target_diastolic: Option<u16>,
+
+    /// Blood pressure readings to take each week
+    #[validate(range(min = 1, max = 70, message = "Readings per week must be between 1 and 70"))]
+    pub readings_per_week: Option<u32>,
+
+    /// Weight to reach, in kilograms
+    #[validate(range(min = 20.0, max = 500.0, message = "Target weight must be between 20 and 500 kg"))]
+    pub target_weight_kg: Option<f32>,
+}
+
+/// Progress towards the blood pressure targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct BloodPressureGoalProgress {
+    /// Systolic pressure to stay below, in mmHg
+    pub target_systolic: u16,
+
+    /// Diastolic pressure to stay below, in mmHg
+    pub target_diastolic: u16,
+
+    /// Number of readings of the last 30 days, counting each session once
+    pub reading_count: usize,
+
+    /// Number of those readings below both targets
+    pub in_target_count: usize,
+
+    /// Percentage of the readings below both targets, if there are any
+    pub percent_in_target: Option<f64>,
+
+    /// Consecutive readings below both targets, up to the latest
+    pub current_streak: usize,
+
+    /// Longest run of consecutive readings below both targets
+    pub best_streak: usize,
+
+    /// When the trend reaches both targets, if it is heading there within a year
+    pub projected_date: Option<NaiveDate>,
+}
+
+/// Progress towards the reading frequency target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct ReadingFrequencyProgress {
+    /// Blood pressure readings to take each week
+    pub target_per_week: u32,
+
+    /// Readings taken in the last 7 days, counting each session once
+    pub readings_last_7_days: usize,
+
+    /// Whether the last 7 days met the target
+    pub met: bool,
+
+    /// Consecutive 7 day periods, back from now, that met the target
+    pub current_streak_weeks: usize,
+
+    /// Longest run of 7 day periods that met the target in the last 12 weeks
+    pub best_streak_weeks: usize,
+}
+
+/// Progress towards the weight target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct WeightGoalProgress {
+    /// Weight to reach, in kilograms
+    pub target_weight_kg: f32,
+
+    /// Most recent weight in kilograms, if there are readings
+    pub current_weight_kg: Option<f32>,
+
+    /// Change still needed to reach the target, negative when weight is to be lost
+    pub remaining_kg: Option<f32>,
+
+    /// Whether the latest weight is within half a kilogram of the target
+    pub reached: bool,
+
+    /// When the 90 day trend reaches the target, if it is heading there within a year
+    pub projected_date: Option<NaiveDate>,
+}
+
+/// Progress towards each of a user's goals that is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct GoalProgress {
+    /// Progress towards the blood pressure targets, if set
+    pub blood_pressure: Option<BloodPressureGoalProgress>,
+
+    /// Progress towards the reading frequency target, if set
+    pub reading_frequency: Option<ReadingFrequencyProgress>,
+
+    /// Progress towards the weight target, if set
+    pub weight: Option<WeightGoalProgress>,
+
+    /// Timestamp of the analysis
+    pub generated_at: DateTime<Utc>,
+}
+
+/// A user's goals together with their progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct GoalsWithProgress {
+    /// The goals
+    pub goals: Goals,
+
+    /// Progress towards them
+    pub progress: GoalProgress,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_goals_request_validation() {
+        let mut request = SetGoalsRequest {
+            target_systolic: Some(130),
+            target_diastolic: Some(80),
+            readings_per_week: Some(7),
+            target_weight_kg: Some(72.5),
+        };
+        assert!(request.validate().is_ok());
+
+        request.target_systolic = Some(60);
+        assert!(request.validate().is_err());
+
+        request.target_systolic = Some(130);
+        request.readings_per_week = Some(0);
+        assert!(request.validate().is_err());
+
+        request.readings_per_week = None;
+        request.target_weight_kg = Some(10.0);
+        assert!(request.validate().is_err());
+    }
+}
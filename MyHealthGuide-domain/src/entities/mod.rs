@@ -1,6 +1,14 @@
 // Domain entities and value objects
 pub mod blood_pressure;
+pub mod weight;
+pub mod user;
+pub mod goal;
+pub mod profile;
 pub mod conversions;
 
 // Re-export common types for easier imports
-pub use blood_pressure::{BloodPressureReading, CreateBloodPressureRequest, BloodPressureInsights, BloodPressureCategory}; 
\ No newline at end of file
+pub use blood_pressure::{BloodPressureReading, CreateBloodPressureRequest, UpdateBloodPressureRequest, BloodPressureInsights, BloodPressureTrend, BloodPressureCategory, TimeOfDayPattern};
+pub use weight::{WeightReading, CreateWeightRequest, WeightInsights, WeightTrend, BmiCategory};
+pub use user::{User, RegisterUserRequest};
+pub use goal::{Goals, SetGoalsRequest, GoalProgress, GoalsWithProgress};
+pub use profile::{Profile, SetProfileRequest};
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+
+#[cfg(feature = "with-api")]
+use utoipa::ToSchema;
+
+use crate::entities::blood_pressure::Sex;
+
+/// Domain entity for a user's profile
+///
+/// Holds the settings other features read instead of asking for them on
+/// every request. Every field is optional.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct Profile {
+    /// Identifier of the user the profile belongs to
+    pub user_id: String,
+
+    /// IANA time zone name local times are taken in, e.g. "Europe/Amsterdam"
+    pub time_zone: Option<String>,
+
+    /// Guideline set the user's readings are categorized with, e.g. "esc_esh_2018"
+    pub guideline: Option<String>,
+
+    /// Date of birth; under 18 the AAP 2017 pediatric thresholds are used
+    pub date_of_birth: Option<NaiveDate>,
+
+    /// Sex, selecting the pediatric reference values for boys or girls
+    pub sex: Option<Sex>,
+
+    /// Latest height in centimeters, for the pediatric height percentile
+    pub height_cm: Option<f64>,
+
+    /// When the profile was first set
+    pub created_at: DateTime<Utc>,
+
+    /// When the profile was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request payload for setting a user's profile, replacing any set before
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct SetProfileRequest {
+    /// IANA time zone name local times are taken in, e.g. "Europe/Amsterdam"
+    pub time_zone: Option<String>,
+
+    /// Guideline set the user's readings are categorized with, e.g. "esc_esh_2018"
+    pub guideline: Option<String>,
+
+    /// Date of birth; under 18 the AAP 2017 pediatric thresholds are used
+    pub date_of_birth: Option<NaiveDate>,
+
+    /// Sex, selecting the pediatric reference values for boys or girls
+    pub sex: Option<Sex>,
+
+    /// Latest height in centimeters, for the pediatric height percentile
+    pub height_cm: Option<f64>,
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[cfg(feature = "with-api")]
+use utoipa::ToSchema;
+
+/// Domain entity for a registered user
+///
+/// The password hash never leaves the service layer, so it is not part of
+/// this entity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct User {
+    /// Unique identifier for the user
+    pub id: String,
+
+    /// Email address used to log in
+    pub email: String,
+
+    /// Optional display name
+    pub name: Option<String>,
+
+    /// Roles granted to the user
+    pub roles: Vec<String>,
+
+    /// When the account was created
+    pub created_at: String,
+}
+
+/// Request payload for registering a new user
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct RegisterUserRequest {
+    /// Email address used to log in
+    #[validate(email(message = "Email must be a valid email address"))]
+    pub email: String,
+
+    /// Password, at least 8 characters long
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub password: String,
+
+    /// Optional display name
+    #[validate(length(max = 100, message = "Name cannot exceed 100 characters"))]
+    pub name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_request_validation() {
+        let mut request = RegisterUserRequest {
+            email: "alice@example.com".to_string(),
+            password: "password123".to_string(),
+            name: Some("Alice".to_string()),
+        };
+        assert!(request.validate().is_ok());
+
+        request.email = "not-an-email".to_string();
+        assert!(request.validate().is_err());
+
+        request.email = "alice@example.com".to_string();
+        request.password = "short".to_string();
+        assert!(request.validate().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use validator::Validate;
+
+#[cfg(feature = "with-api")]
+use utoipa::ToSchema;
+
+use super::blood_pressure::validate_timestamp;
+
+/// Domain entity for a weight reading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct WeightReading {
+    /// Unique identifier for the reading
+    pub id: String,
+
+    /// Identifier of the user who owns the reading
+    pub user_id: String,
+
+    /// Weight in kilograms
+    pub weight_kg: f32,
+
+    /// Optional body fat percentage
+    pub body_fat_percentage: Option<f32>,
+
+    /// Optional muscle mass in kilograms
+    pub muscle_mass_kg: Option<f32>,
+
+    /// Optional notes about the reading
+    pub notes: Option<String>,
+
+    /// When the reading was taken
+    pub timestamp: String,
+}
+
+/// Request payload for creating a new weight reading
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct CreateWeightRequest {
+    /// Weight in kilograms
+    #[validate(range(min = 20.0, max = 500.0, message = "Weight must be between 20 and 500 kg"))]
+    pub weight_kg: f32,
+
+    /// Optional body fat percentage
+    #[validate(range(min = 1.0, max = 70.0, message = "Body fat percentage must be between 1 and 70%"))]
+    pub body_fat_percentage: Option<f32>,
+
+    /// Optional muscle mass in kilograms
+    #[validate(range(min = 10.0, max = 200.0, message = "Muscle mass must be between 10 and 200 kg"))]
+    pub muscle_mass_kg: Option<f32>,
+
+    /// Optional notes about the reading
+    #[validate(length(max = 1000, message = "Notes cannot exceed 1000 characters"))]
+    pub notes: Option<String>,
+
+    /// When the reading was taken. Defaults to current time if not provided.
+    #[validate(custom = "validate_timestamp")]
+    pub timestamp: String,
+}
+
+/// Direction of a user's weight over the recent past
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum WeightTrend {
+    /// Weight went up by more than the noise threshold
+    Gaining,
+
+    /// Weight went down by more than the noise threshold
+    Losing,
+
+    /// Weight stayed within the noise threshold
+    Maintaining,
+}
+
+impl std::fmt::Display for WeightTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightTrend::Gaining => write!(f, "gaining"),
+            WeightTrend::Losing => write!(f, "losing"),
+            WeightTrend::Maintaining => write!(f, "maintaining"),
+        }
+    }
+}
+
+/// BMI category following the WHO adult classification
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub enum BmiCategory {
+    /// BMI below 18.5
+    Underweight,
+
+    /// BMI from 18.5 up to 25
+    Normal,
+
+    /// BMI from 25 up to 30
+    Overweight,
+
+    /// BMI of 30 or more
+    Obese,
+}
+
+impl std::fmt::Display for BmiCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BmiCategory::Underweight => write!(f, "underweight"),
+            BmiCategory::Normal => write!(f, "normal"),
+            BmiCategory::Overweight => write!(f, "overweight"),
+            BmiCategory::Obese => write!(f, "obese"),
+        }
+    }
+}
+
+/// Weight reading insights and analytics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "with-api", derive(ToSchema))]
+pub struct WeightInsights {
+    /// Most recent weight in kilograms
+    pub current_weight_kg: f32,
+
+    /// Change since the earliest reading of the last 30 days
+    pub change_30d_kg: f32,
+
+    /// Change since the earliest reading of the last 90 days
+    pub change_90d_kg: f32,
+
+    /// Direction of the 30 day change
+    pub trend: WeightTrend,
+
+    /// Most recently recorded body fat percentage
+    pub body_fat_percentage: Option<f32>,
+
+    /// Most recently recorded muscle mass in kilograms
+    pub muscle_mass_kg: Option<f32>,
+
+    /// BMI for the current weight, when the user's height is known
+    pub bmi: Option<f32>,
+
+    /// Category of the BMI, when the user's height is known
+    pub bmi_category: Option<BmiCategory>,
+
+    /// Number of readings analyzed
+    pub reading_count: usize,
+
+    /// Timestamp of the analysis
+    pub generated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_request_validation() {
+        let mut request = CreateWeightRequest {
+            weight_kg: 72.5,
+            body_fat_percentage: Some(18.0),
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+        };
+        assert!(request.validate().is_ok());
+
+        request.weight_kg = 10.0;
+        assert!(request.validate().is_err());
+
+        request.weight_kg = 72.5;
+        request.timestamp = "yesterday".to_string();
+        assert!(request.validate().is_err());
+    }
+}
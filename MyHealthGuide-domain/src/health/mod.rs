@@ -2,6 +2,7 @@
 //! This module provides health check services for the application
 
 use my_health_guide_data::database;
+use my_health_guide_data::repository::{self, StorageStrategy};
 use std::collections::HashMap;
 use async_trait::async_trait;
 
@@ -102,9 +103,17 @@ pub async fn get_system_health() -> SystemHealth {
         },
     };
 
-    let overall_status = if db_component.status == ComponentStatus::Unhealthy {
+    let mut components: HashMap<String, HealthComponent> = vec![
+        ("database".to_string(), db_component),
+    ].into_iter().collect();
+
+    if let Some(outbox_component) = check_storage_outbox() {
+        components.insert("storage_outbox".to_string(), outbox_component);
+    }
+
+    let overall_status = if components.values().any(|c| c.status == ComponentStatus::Unhealthy) {
         SystemStatus::Unhealthy
-    } else if db_component.status == ComponentStatus::Degraded {
+    } else if components.values().any(|c| c.status == ComponentStatus::Degraded) {
         SystemStatus::Degraded
     } else {
         SystemStatus::Healthy
@@ -112,12 +121,31 @@ pub async fn get_system_health() -> SystemHealth {
 
     SystemHealth {
         status: overall_status,
-        components: vec![
-            ("database".to_string(), db_component),
-        ].into_iter().collect(),
+        components,
     }
 }
 
+/// Report on the storage outbox when the outbox strategy is in use
+///
+/// The outbox is degraded while it holds writes that have not yet reached
+/// the database.
+fn check_storage_outbox() -> Option<HealthComponent> {
+    if repository::storage_strategy() != StorageStrategy::Outbox {
+        return None;
+    }
+
+    let metrics = repository::outbox().metrics();
+    let details = format!(
+        "{} pending, {} recorded, {} replayed, {} failed replays",
+        metrics.pending, metrics.recorded_total, metrics.replayed_total, metrics.failed_replays_total
+    );
+
+    Some(HealthComponent {
+        status: if metrics.pending > 0 { ComponentStatus::Degraded } else { ComponentStatus::Healthy },
+        details: Some(details),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
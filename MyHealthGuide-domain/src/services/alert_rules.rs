@@ -0,0 +1,265 @@
+//! Evaluation of user-defined alert rules
+//!
+//! A rule is evaluated against the few readings its condition looks at:
+//! [`readings_query`] says which to load and [`evaluate_rule`] decides
+//! whether the rule fires on them.
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::entities::blood_pressure::{
+    AlertRule, AlertRuleCondition, BloodPressureAlert, BloodPressureAlertKind, BloodPressureFilter, BloodPressureReading,
+};
+
+/// The readings a rule needs: a filter and the most to load, newest first
+///
+/// Without a limit every matching reading is loaded, so an average covers
+/// its whole window however many readings it holds.
+pub fn readings_query(condition: &AlertRuleCondition, now: DateTime<Utc>) -> (BloodPressureFilter, Option<usize>) {
+    match condition {
+        AlertRuleCondition::ConsecutiveAbove { count, .. } => (BloodPressureFilter::default(), Some(*count)),
+        AlertRuleCondition::AverageAbove { days, .. } => (
+            BloodPressureFilter::date_range(Some(now - Duration::days(*days as i64)), Some(now)),
+            None,
+        ),
+        AlertRuleCondition::NoReading { .. } => (BloodPressureFilter::default(), Some(1)),
+    }
+}
+
+/// Describe a rule's thresholds, e.g. "150 systolic or 95 diastolic"
+fn describe_thresholds(systolic: &Option<u16>, diastolic: &Option<u16>) -> String {
+    let systolic = systolic.map(|limit| format!("{} systolic", limit));
+    let diastolic = diastolic.map(|limit| format!("{} diastolic", limit));
+    systolic.into_iter().chain(diastolic).collect::<Vec<_>>().join(" or ")
+}
+
+/// Evaluate a rule against the readings [`readings_query`] selected for it
+///
+/// Returns the alert the rule fires, not yet stored, or `None`. A rule about
+/// missing readings only fires for users with at least one reading, as the
+/// alert points at the last one.
+pub fn evaluate_rule(rule: &AlertRule, readings: &[BloodPressureReading], now: DateTime<Utc>) -> Option<BloodPressureAlert> {
+    let mut timeline: Vec<&BloodPressureReading> = readings.iter()
+        .filter(|reading| reading.timestamp <= now)
+        .collect();
+    timeline.sort_by_key(|reading| reading.timestamp);
+    let latest = *timeline.last()?;
+
+    let (message, systolic, diastolic) = match &rule.condition {
+        AlertRuleCondition::ConsecutiveAbove { count, systolic, diastolic } => {
+            timeline = timeline.split_off(timeline.len().saturating_sub(*count));
+            let all_above = timeline.iter()
+                .all(|reading| rule.condition.is_above(reading.systolic as f64, reading.diastolic as f64));
+            if timeline.len() < *count || !all_above {
+                return None;
+            }
+
+            let message = format!(
+                "Alert rule \"{}\": the last {} readings were above {}, the latest {}/{} mmHg.",
+                rule.name, count, describe_thresholds(systolic, diastolic), latest.systolic, latest.diastolic,
+            );
+            (message, latest.systolic, latest.diastolic)
+        },
+        AlertRuleCondition::AverageAbove { days, systolic, diastolic } => {
+            let window_start = now - Duration::days(*days as i64);
+            timeline.retain(|reading| reading.timestamp >= window_start);
+            if timeline.is_empty() {
+                return None;
+            }
+
+            let count = timeline.len() as f64;
+            let avg_systolic = timeline.iter().map(|reading| reading.systolic as f64).sum::<f64>() / count;
+            let avg_diastolic = timeline.iter().map(|reading| reading.diastolic as f64).sum::<f64>() / count;
+            if !rule.condition.is_above(avg_systolic, avg_diastolic) {
+                return None;
+            }
+
+            let (avg_systolic, avg_diastolic) = (avg_systolic.round() as u16, avg_diastolic.round() as u16);
+            let message = format!(
+                "Alert rule \"{}\": the average of {} readings over the last {} days, {}/{} mmHg, is above {}.",
+                rule.name, timeline.len(), days, avg_systolic, avg_diastolic, describe_thresholds(systolic, diastolic),
+            );
+            (message, avg_systolic, avg_diastolic)
+        },
+        AlertRuleCondition::NoReading { days } => {
+            if now - latest.timestamp <= Duration::days(*days as i64) {
+                return None;
+            }
+
+            timeline = vec![latest];
+            let message = format!(
+                "Alert rule \"{}\": no reading in the {} days since {}, when it was {}/{} mmHg.",
+                rule.name, days, latest.timestamp.format("%Y-%m-%d"), latest.systolic, latest.diastolic,
+            );
+            (message, latest.systolic, latest.diastolic)
+        },
+    };
+
+    Some(BloodPressureAlert {
+        id: Uuid::new_v4().to_string(),
+        user_id: rule.user_id.clone(),
+        kind: BloodPressureAlertKind::Rule,
+        rule_id: Some(rule.id.clone()),
+        message,
+        systolic,
+        diastolic,
+        reading_ids: timeline.iter().map(|reading| reading.id.clone()).collect(),
+        created_at: now,
+    })
+}
+
+/// Whether an alert repeats the last one its rule fired
+///
+/// A rule fires again only once the readings behind it change, so a rule
+/// that keeps holding does not raise the same alert on every evaluation.
+/// `previous` are the user's alerts, newest first.
+pub fn is_repeat(alert: &BloodPressureAlert, previous: &[BloodPressureAlert]) -> bool {
+    previous.iter()
+        .find(|earlier| earlier.rule_id.is_some() && earlier.rule_id == alert.rule_id)
+        .is_some_and(|earlier| earlier.reading_ids == alert.reading_ids)
+}
+
+/// Evaluate a user's enabled alert rules in a background task
+///
+/// Called after a reading is stored, so a rule about the new reading fires
+/// without the request waiting on every rule's readings query.
+#[cfg(feature = "with-tokio")]
+pub fn spawn_alert_rule_evaluation(
+    service: std::sync::Arc<dyn crate::services::BloodPressureServiceTrait + Send + Sync>,
+    user_id: String,
+) {
+    use tracing::{debug, warn};
+
+    tokio::spawn(async move {
+        match service.evaluate_alert_rules(&user_id).await {
+            Ok(fired) if fired.is_empty() => {},
+            Ok(fired) => debug!("Alert rule evaluation fired {} alerts", fired.len()),
+            Err(e) => warn!("Failed to evaluate alert rules: {}", e),
+        }
+    });
+}
+
+/// Start a background task that evaluates every enabled alert rule
+///
+/// Every hour each user's enabled rules are evaluated, so rules about
+/// missing readings fire without a new reading coming in. It should be
+/// called once during application startup.
+#[cfg(feature = "with-tokio")]
+pub fn start_alert_rule_task(service: std::sync::Arc<dyn crate::services::BloodPressureServiceTrait + Send + Sync>) {
+    use tokio::time;
+    use std::time::Duration;
+    use tracing::{debug, warn};
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(3600));
+
+        loop {
+            interval.tick().await;
+            match service.evaluate_all_alert_rules().await {
+                Ok(0) => {},
+                Ok(fired) => debug!("Scheduled alert rule evaluation fired {} alerts", fired),
+                Err(e) => warn!("Failed to evaluate alert rules: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(hours_ago: i64, systolic: u16, diastolic: u16, now: DateTime<Utc>) -> BloodPressureReading {
+        BloodPressureReading {
+            id: Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: now - Duration::hours(hours_ago),
+            position: None,
+            arm: None,
+            device_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn rule(condition: AlertRuleCondition) -> AlertRule {
+        AlertRule {
+            id: Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            name: "Test rule".to_string(),
+            condition,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_consecutive_above() {
+        let now = Utc::now();
+        let three_above_150 = rule(AlertRuleCondition::ConsecutiveAbove { count: 3, systolic: Some(150), diastolic: None });
+
+        let readings = vec![reading(30, 120, 80, now), reading(20, 155, 90, now), reading(10, 152, 88, now), reading(1, 160, 95, now)];
+        let alert = evaluate_rule(&three_above_150, &readings, now).unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::Rule);
+        assert_eq!(alert.rule_id.as_ref(), Some(&three_above_150.id));
+        assert_eq!(alert.reading_ids, readings[1..].iter().map(|reading| reading.id.clone()).collect::<Vec<_>>());
+        assert_eq!((alert.systolic, alert.diastolic), (160, 95));
+        assert!(alert.message.contains("above 150 systolic"));
+
+        // Exactly at the threshold is not above it, and too few readings never fire
+        let readings = vec![reading(20, 155, 90, now), reading(10, 150, 88, now), reading(1, 160, 95, now)];
+        assert!(evaluate_rule(&three_above_150, &readings, now).is_none());
+        assert!(evaluate_rule(&three_above_150, &readings[..2], now).is_none());
+    }
+
+    #[test]
+    fn test_average_above() {
+        let now = Utc::now();
+        let weekly = rule(AlertRuleCondition::AverageAbove { days: 7, systolic: Some(135), diastolic: Some(85) });
+
+        // The old reading falls outside the week
+        let readings = vec![reading(24 * 10, 170, 100, now), reading(48, 130, 88, now), reading(2, 134, 84, now)];
+        let alert = evaluate_rule(&weekly, &readings, now).unwrap();
+        assert_eq!((alert.systolic, alert.diastolic), (132, 86));
+        assert_eq!(alert.reading_ids.len(), 2);
+
+        let readings = vec![reading(24 * 10, 170, 100, now), reading(48, 130, 80, now), reading(2, 134, 84, now)];
+        assert!(evaluate_rule(&weekly, &readings, now).is_none());
+        assert!(evaluate_rule(&weekly, &[], now).is_none());
+    }
+
+    #[test]
+    fn test_no_reading() {
+        let now = Utc::now();
+        let three_days = rule(AlertRuleCondition::NoReading { days: 3 });
+
+        let last = reading(24 * 4, 128, 82, now);
+        let alert = evaluate_rule(&three_days, std::slice::from_ref(&last), now).unwrap();
+        assert_eq!(alert.reading_ids, vec![last.id.clone()]);
+        assert!(alert.message.contains("no reading in the 3 days"));
+
+        assert!(evaluate_rule(&three_days, &[reading(24 * 2, 128, 82, now)], now).is_none());
+        assert!(evaluate_rule(&three_days, &[], now).is_none());
+
+        // The same silence is reported once
+        assert!(is_repeat(&evaluate_rule(&three_days, std::slice::from_ref(&last), now).unwrap(), std::slice::from_ref(&alert)));
+        let later = reading(24 * 5, 130, 85, now);
+        let other = BloodPressureAlert { rule_id: Some("another-rule".to_string()), ..alert.clone() };
+        assert!(!is_repeat(&evaluate_rule(&three_days, &[later], now).unwrap(), &[alert]));
+        assert!(!is_repeat(&evaluate_rule(&three_days, &[last], now).unwrap(), &[other]));
+    }
+
+    #[test]
+    fn test_readings_query() {
+        let now = Utc::now();
+        let (filter, limit) = readings_query(&AlertRuleCondition::ConsecutiveAbove { count: 3, systolic: Some(150), diastolic: None }, now);
+        assert_eq!((filter.start_date, limit), (None, Some(3)));
+
+        let (filter, limit) = readings_query(&AlertRuleCondition::AverageAbove { days: 7, systolic: None, diastolic: Some(90) }, now);
+        assert_eq!((filter.start_date, filter.end_date, limit), (Some(now - Duration::days(7)), Some(now), None));
+    }
+}
@@ -1,15 +1,96 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
 use thiserror::Error;
-use tracing::error;
-use chrono::Utc;
-use validator::Validate;
+use chrono::{DateTime, Days, Duration, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use validator::{Validate, ValidationErrors};
 use async_trait::async_trait;
+use tracing::warn;
+use uuid::Uuid;
 
 use crate::entities::blood_pressure::{
-    BloodPressureCategory, BloodPressureInsights, BloodPressureReading, CreateBloodPressureRequest,
+    AlertRule, BloodPressureAlert, BloodPressureAlertKind, BloodPressureCategory, BloodPressureFilter,
+    BloodPressureInsights, BloodPressureReading, BloodPressureSession, CreateAlertRuleRequest, CreateBloodPressureRequest,
+    CreateBloodPressureSessionRequest, HbpmPeriod, HbpmReport, HbpmSlot, PairedReadingAlerts, PediatricPatient, TimeOfDayPattern, UpdateBloodPressureRequest,
 };
 use crate::entities::conversions;
+use my_health_guide_data::models::blood_pressure::{BloodPressureAlertFilter, SessionAveragedEntry};
 use my_health_guide_data::repository::{BloodPressureRepositoryTrait, RepositoryError};
-use crate::services::insights::categorize_blood_pressure;
+use crate::services::alert_rules;
+use crate::services::guidelines::{guideline_set, GuidelineSet};
+use crate::services::insights;
+use crate::services::notifications::{self, AlertNotifier};
+use crate::services::profile::ProfileServiceTrait;
+
+/// Seconds in a week, the time unit of reading trends
+const SECONDS_PER_WEEK: f64 = 7.0 * 24.0 * 60.0 * 60.0;
+
+/// Longest gap between two readings that are compared as a pair, in minutes
+const PAIRED_READING_WINDOW_MINUTES: i64 = 5;
+
+/// Days in a home monitoring week, the first of which is discarded
+const HBPM_DAYS: u64 = 7;
+
+/// Readings required in each morning and evening of a home monitoring week
+const HBPM_READINGS_PER_SLOT: usize = 2;
+
+/// Stage 2 readings within a week that raise a repeated stage 2 alert, the new reading included
+const REPEATED_STAGE_2_READINGS: usize = 3;
+
+/// Days looked back over for repeated stage 2 readings
+const REPEATED_STAGE_2_DAYS: i64 = 7;
+
+/// Whether a category is stage 2 hypertension or worse
+fn is_stage_2_or_worse(category: &BloodPressureCategory) -> bool {
+    matches!(
+        category,
+        BloodPressureCategory::Hypertension2 | BloodPressureCategory::Hypertension3 | BloodPressureCategory::HypertensiveCrisis
+    )
+}
+
+/// The instant `date` starts in `time_zone`
+///
+/// Where daylight saving skips midnight, the day starts at the first local
+/// time that exists.
+fn start_of_local_day(date: NaiveDate, time_zone: Tz) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    (0..24)
+        .find_map(|hour| time_zone.from_local_datetime(&(midnight + Duration::hours(hour))).earliest())
+        .expect("daylight saving never skips a whole day")
+        .with_timezone(&Utc)
+}
+
+/// Home monitoring period of a local hour, using the time of day pattern's morning and evening
+fn hbpm_period(hour: u32) -> Option<HbpmPeriod> {
+    match hour {
+        4..=11 => Some(HbpmPeriod::Morning),
+        18..=23 => Some(HbpmPeriod::Evening),
+        _ => None,
+    }
+}
+
+/// Convert validation errors to a meaningful error message
+pub(crate) fn validation_message(validation_errors: &ValidationErrors) -> String {
+    validation_errors
+        .field_errors()
+        .iter()
+        .map(|(field, errors)| {
+            let error_msgs: Vec<String> = errors
+                .iter()
+                .map(|err| {
+                    if let Some(msg) = &err.message {
+                        msg.to_string()
+                    } else {
+                        format!("Invalid {}", field)
+                    }
+                })
+                .collect();
+            format!("{}: {}", field, error_msgs.join(", "))
+        })
+        .collect::<Vec<String>>()
+        .join("; ")
+}
 
 /// Blood pressure service errors
 #[derive(Debug, Error)]
@@ -26,6 +107,10 @@ pub enum BloodPressureServiceError {
     #[error("Repository error: {0}")]
     RepositoryError(String),
 
+    /// Storage unavailable error
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+
     /// Insufficient data error
     #[error("Insufficient data: {0}")]
     InsufficientData(String),
@@ -41,33 +126,409 @@ pub trait BloodPressureServiceTrait {
     ) -> Result<(), BloodPressureServiceError>;
 
     /// Calculate blood pressure insights from readings
+    ///
+    /// The average readings are categorized with `guideline`.
     fn calculate_insights(
         &self,
         readings: &[BloodPressureReading],
         timeframe_days: u32,
+        guideline: &GuidelineSet,
     ) -> Result<BloodPressureInsights, BloodPressureServiceError>;
 
-    /// Get severity category for a blood pressure reading
-    fn get_severity(&self, reading: &BloodPressureReading) -> BloodPressureCategory;
+    /// Calculate blood pressure insights for a patient whose age may call for pediatric thresholds
+    ///
+    /// Under 18 the average readings are categorized with AAP 2017 instead of
+    /// `guideline`; adults get the same insights as [`calculate_insights`](Self::calculate_insights).
+    fn calculate_patient_insights(
+        &self,
+        readings: &[BloodPressureReading],
+        timeframe_days: u32,
+        guideline: &GuidelineSet,
+        patient: &PediatricPatient,
+    ) -> Result<BloodPressureInsights, BloodPressureServiceError> {
+        let mut insights = self.calculate_insights(readings, timeframe_days, guideline)?;
+
+        let pediatric = insights::classify_pediatric_blood_pressure(
+            insights.avg_systolic.round() as u16,
+            insights.avg_diastolic.round() as u16,
+            patient,
+            Utc::now().date_naive(),
+        );
+        if let Some(classification) = pediatric {
+            insights.category = classification.category.to_category();
+            insights.guideline = "aap_2017".to_string();
+            insights.pediatric = Some(classification);
+        }
 
-    /// Check if a reading indicates a hypertensive crisis
-    fn is_hypertensive_crisis(&self, reading: &BloodPressureReading) -> bool;
+        Ok(insights)
+    }
 
-    /// Create a new blood pressure reading
-    async fn create_reading(&self, request: CreateBloodPressureRequest)
-        -> Result<BloodPressureReading, BloodPressureServiceError>;
+    /// Break readings down by the local time of day they were taken
+    ///
+    /// Local times are the reading times in `time_zone`. Dipping compares
+    /// the night (01:00-06:00) with the day (09:00-21:00), the narrow
+    /// fixed-time windows of the ESH ambulatory monitoring guidelines, and
+    /// the morning surge compares the morning (04:00-12:00) with that night.
+    fn calculate_time_of_day_pattern(
+        &self,
+        readings: &[BloodPressureReading],
+        time_zone: Tz,
+        timeframe_days: u32,
+    ) -> Result<TimeOfDayPattern, BloodPressureServiceError> {
+        if readings.is_empty() {
+            return Err(BloodPressureServiceError::InsufficientData(
+                "No readings available to analyze time of day patterns".to_string(),
+            ));
+        }
+
+        let morning = insights::average_in_hours(readings, time_zone, 4..12);
+        let evening = insights::average_in_hours(readings, time_zone, 18..24);
+        let daytime = insights::average_in_hours(readings, time_zone, 9..21);
+        let nighttime = insights::average_in_hours(readings, time_zone, 1..6);
+
+        let morning_surge = morning.zip(nighttime)
+            .map(|(morning, night)| morning.avg_systolic - night.avg_systolic);
+        let dipping_ratio = nighttime.zip(daytime)
+            .map(|(night, day)| night.avg_systolic / day.avg_systolic);
+
+        Ok(TimeOfDayPattern {
+            time_zone: time_zone.name().to_string(),
+            morning,
+            evening,
+            daytime,
+            nighttime,
+            morning_surge,
+            dipping_ratio,
+            dipping_pattern: dipping_ratio.map(insights::classify_dipping),
+            reading_count: readings.len(),
+            period_days: timeframe_days,
+            generated_at: Utc::now(),
+        })
+    }
+
+    /// Detect orthostatic hypotension and inter-arm differences from paired readings
+    ///
+    /// Readings are paired when taken at most five minutes apart: standing
+    /// after lying or sitting, and left arm with right arm.
+    fn detect_paired_reading_alerts(
+        &self,
+        readings: &[BloodPressureReading],
+        timeframe_days: u32,
+    ) -> PairedReadingAlerts {
+        let window = chrono::Duration::minutes(PAIRED_READING_WINDOW_MINUTES);
+        let mut alerts = insights::detect_orthostatic_hypotension(readings, window);
+        alerts.extend(insights::detect_inter_arm_differences(readings, window));
+        alerts.sort_by_key(|alert| alert.detected_at);
+
+        PairedReadingAlerts {
+            alerts,
+            reading_count: readings.len(),
+            period_days: timeframe_days,
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Evaluate a standard 7-day home blood pressure monitoring week starting on `start_date`
+    ///
+    /// Readings are placed in a morning or evening slot by their local time
+    /// in `time_zone`. Day 1 is discarded; for days 2 to 7 the first two
+    /// readings of each slot are averaged and slots with fewer are reported
+    /// as missing. Readings outside the slots are ignored. The average is
+    /// categorized with `guideline`.
+    fn evaluate_hbpm_protocol(
+        &self,
+        readings: &[BloodPressureReading],
+        start_date: NaiveDate,
+        time_zone: Tz,
+        guideline: &GuidelineSet,
+    ) -> HbpmReport {
+        let mut timeline: Vec<&BloodPressureReading> = readings.iter().collect();
+        timeline.sort_by_key(|reading| reading.timestamp);
+
+        let mut slots: BTreeMap<(NaiveDate, HbpmPeriod), Vec<&BloodPressureReading>> = BTreeMap::new();
+        for reading in timeline {
+            let local = reading.timestamp.with_timezone(&time_zone);
+            if let Some(period) = hbpm_period(local.hour()) {
+                slots.entry((local.date_naive(), period)).or_default().push(reading);
+            }
+        }
+
+        let mut used: Vec<&BloodPressureReading> = Vec::new();
+        let mut missing_slots = Vec::new();
+        for date in (1..HBPM_DAYS).map(|day| start_date + Days::new(day)) {
+            for period in [HbpmPeriod::Morning, HbpmPeriod::Evening] {
+                let slot = slots.get(&(date, period)).map_or(&[][..], Vec::as_slice);
+                used.extend(slot.iter().take(HBPM_READINGS_PER_SLOT));
+                if slot.len() < HBPM_READINGS_PER_SLOT {
+                    missing_slots.push(HbpmSlot { date, period, reading_count: slot.len() });
+                }
+            }
+        }
+
+        let avg_systolic = insights::mean(&used.iter().map(|reading| reading.systolic as f64).collect::<Vec<_>>());
+        let avg_diastolic = insights::mean(&used.iter().map(|reading| reading.diastolic as f64).collect::<Vec<_>>());
+        let category = avg_systolic.zip(avg_diastolic)
+            .map(|(systolic, diastolic)| guideline.categorize(systolic.round() as u16, diastolic.round() as u16));
+
+        HbpmReport {
+            time_zone: time_zone.name().to_string(),
+            start_date,
+            end_date: start_date + Days::new(HBPM_DAYS - 1),
+            complete: missing_slots.is_empty(),
+            avg_systolic,
+            avg_diastolic,
+            category,
+            reading_count: used.len(),
+            missing_slots,
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// The guideline set a user's readings are categorized and alerted with
+    ///
+    /// Services without access to user profiles use the deployment's set.
+    async fn user_guideline_set(&self, _user_id: &str) -> GuidelineSet {
+        guideline_set()
+    }
+
+    /// Get severity category for a blood pressure reading under `guideline`
+    fn get_severity(&self, reading: &BloodPressureReading, guideline: &GuidelineSet) -> BloodPressureCategory;
+
+    /// Check if a reading indicates a hypertensive crisis under `guideline`
+    fn is_hypertensive_crisis(&self, reading: &BloodPressureReading, guideline: &GuidelineSet) -> bool;
+
+    /// Decide whether a new reading raises an alert
+    ///
+    /// A reading in the hypertensive crisis range always does. Otherwise a
+    /// stage 2 reading does when, together with the user's other stage 2 or
+    /// worse readings in `recent`, there are three within a week. `recent`
+    /// may include the new reading itself. While any of those readings is
+    /// already covered by a repeated stage 2 alert in `previous`, the user's
+    /// earlier alerts, no new one is raised, so a run of high readings does
+    /// not raise an alert for every reading. Readings are categorized with
+    /// the user's `guideline`. The alert is not yet stored.
+    fn detect_reading_alert(
+        &self,
+        user_id: &str,
+        reading: &BloodPressureReading,
+        recent: &[BloodPressureReading],
+        previous: &[BloodPressureAlert],
+        guideline: &GuidelineSet,
+    ) -> Option<BloodPressureAlert> {
+        let (kind, message, reading_ids) = if self.is_hypertensive_crisis(reading, guideline) {
+            let message = format!(
+                "Reading of {}/{} mmHg is in the hypertensive crisis range. Wait five minutes and measure \
+                 again; if it is still this high, contact your doctor right away. Call emergency services if \
+                 you have chest pain, shortness of breath, back pain, numbness, weakness, vision changes or \
+                 difficulty speaking.",
+                reading.systolic, reading.diastolic,
+            );
+            (BloodPressureAlertKind::HypertensiveCrisis, message, vec![reading.id.clone()])
+        } else {
+            if !is_stage_2_or_worse(&guideline.categorize(reading.systolic, reading.diastolic)) {
+                return None;
+            }
+
+            let window_start = reading.timestamp - Duration::days(REPEATED_STAGE_2_DAYS);
+            let mut earlier: Vec<&BloodPressureReading> = recent.iter()
+                .filter(|other| other.id != reading.id)
+                .filter(|other| other.timestamp >= window_start && other.timestamp <= reading.timestamp)
+                .filter(|other| is_stage_2_or_worse(&guideline.categorize(other.systolic, other.diastolic)))
+                .collect();
+            if earlier.len() + 1 < REPEATED_STAGE_2_READINGS {
+                return None;
+            }
+            earlier.sort_by_key(|other| other.timestamp);
+
+            let mut reading_ids: Vec<String> = earlier.iter().map(|other| other.id.clone()).collect();
+            let covered = previous.iter()
+                .filter(|alert| alert.kind == BloodPressureAlertKind::RepeatedStage2)
+                .any(|alert| alert.reading_ids.iter().any(|id| reading_ids.contains(id)));
+            if covered {
+                return None;
+            }
+            reading_ids.push(reading.id.clone());
+            let message = format!(
+                "{} readings at stage 2 hypertension or above in the last {} days, the latest {}/{} mmHg. \
+                 Contact your doctor about your blood pressure.",
+                reading_ids.len(), REPEATED_STAGE_2_DAYS, reading.systolic, reading.diastolic,
+            );
+            (BloodPressureAlertKind::RepeatedStage2, message, reading_ids)
+        };
+
+        Some(BloodPressureAlert {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            kind,
+            rule_id: None,
+            message,
+            systolic: reading.systolic,
+            diastolic: reading.diastolic,
+            reading_ids,
+            created_at: Utc::now(),
+        })
+    }
 
-    /// Get all blood pressure readings
-    async fn get_all_readings(&self) -> Result<Vec<BloodPressureReading>, BloodPressureServiceError>;
+    /// Create a new blood pressure reading owned by the given user, with the alert it raised
+    ///
+    /// See [`detect_reading_alert`](Self::detect_reading_alert) for when a
+    /// reading raises an alert. Raised alerts are stored and delivered
+    /// through the service's notifiers.
+    async fn create_reading_with_alert(&self, user_id: &str, request: CreateBloodPressureRequest)
+        -> Result<(BloodPressureReading, Option<BloodPressureAlert>), BloodPressureServiceError>;
+
+    /// Create a new blood pressure reading owned by the given user
+    ///
+    /// Alerts are raised as with [`create_reading_with_alert`](Self::create_reading_with_alert).
+    async fn create_reading(&self, user_id: &str, request: CreateBloodPressureRequest)
+        -> Result<BloodPressureReading, BloodPressureServiceError>
+    {
+        self.create_reading_with_alert(user_id, request).await.map(|(reading, _)| reading)
+    }
 
-    /// Get a blood pressure reading by ID
-    async fn get_reading_by_id(&self, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError>;
+    /// Get the alerts raised by a user's readings, newest first
+    async fn get_alerts(&self, user_id: &str) -> Result<Vec<BloodPressureAlert>, BloodPressureServiceError>;
+
+    /// Get the last alert one of a user's rules fired, if it fired any
+    async fn get_latest_rule_alert(&self, user_id: &str, rule_id: &str)
+        -> Result<Option<BloodPressureAlert>, BloodPressureServiceError>;
+
+    /// Store an alert raised for a user and deliver it through the service's notifiers
+    ///
+    /// Failing to store the alert is logged; the alert is still delivered
+    /// and returned.
+    async fn record_alert(&self, user_id: &str, alert: BloodPressureAlert) -> BloodPressureAlert;
+
+    /// Create an alert rule for a user
+    async fn create_alert_rule(&self, user_id: &str, request: CreateAlertRuleRequest)
+        -> Result<AlertRule, BloodPressureServiceError>;
+
+    /// Get one of a user's alert rules by ID
+    async fn get_alert_rule_by_id(&self, user_id: &str, id: &str) -> Result<AlertRule, BloodPressureServiceError>;
+
+    /// Get all alert rules owned by a user, oldest first
+    async fn get_alert_rules(&self, user_id: &str) -> Result<Vec<AlertRule>, BloodPressureServiceError>;
+
+    /// Replace one of a user's alert rules
+    async fn update_alert_rule(&self, user_id: &str, id: &str, request: CreateAlertRuleRequest)
+        -> Result<AlertRule, BloodPressureServiceError>;
+
+    /// Delete one of a user's alert rules, keeping the alerts it fired
+    async fn delete_alert_rule(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError>;
+
+    /// Evaluate a user's enabled alert rules, recording the alerts they fire
+    ///
+    /// A rule that still holds on the same readings as the last alert it
+    /// fired does not fire again. Creating a reading does not evaluate the
+    /// rules; the API does that off the request with
+    /// [`spawn_alert_rule_evaluation`](alert_rules::spawn_alert_rule_evaluation).
+    async fn evaluate_alert_rules(&self, user_id: &str) -> Result<Vec<BloodPressureAlert>, BloodPressureServiceError> {
+        let rules: Vec<AlertRule> = self.get_alert_rules(user_id).await?
+            .into_iter()
+            .filter(|rule| rule.enabled)
+            .collect();
+        if rules.is_empty() {
+            return Ok(Vec::new());
+        }
 
-    /// Get filtered blood pressure readings
+        let now = Utc::now();
+        let mut fired = Vec::new();
+        for rule in &rules {
+            let (filter, limit) = alert_rules::readings_query(&rule.condition, now);
+            let (readings, _) = self.get_filtered_readings(user_id, filter, limit, None, Some(true)).await?;
+
+            if let Some(alert) = alert_rules::evaluate_rule(rule, &readings, now) {
+                let previous = self.get_latest_rule_alert(user_id, &rule.id).await?;
+                if !alert_rules::is_repeat(&alert, previous.as_slice()) {
+                    fired.push(self.record_alert(user_id, alert).await);
+                }
+            }
+        }
+
+        Ok(fired)
+    }
+
+    /// Evaluate the enabled alert rules of every user, returning how many alerts fired
+    ///
+    /// A user whose rules fail to evaluate is logged and skipped.
+    async fn evaluate_all_alert_rules(&self) -> Result<usize, BloodPressureServiceError>;
+
+    /// Get all blood pressure readings owned by a user
+    async fn get_all_readings(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, BloodPressureServiceError>;
+
+    /// Get one of a user's blood pressure readings by ID
+    async fn get_reading_by_id(&self, user_id: &str, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError>;
+
+    /// Get a user's blood pressure readings matching the filter
     async fn get_filtered_readings(
         &self,
-        start_date: Option<String>,
-        end_date: Option<String>,
+        user_id: &str,
+        filter: BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<BloodPressureReading>, usize), BloodPressureServiceError>;
+
+    /// Replace one of a user's blood pressure readings
+    ///
+    /// The request goes through the same validation as a new reading.
+    async fn update_reading(&self, user_id: &str, id: &str, request: CreateBloodPressureRequest)
+        -> Result<BloodPressureReading, BloodPressureServiceError>;
+
+    /// Apply a partial update to one of a user's blood pressure readings
+    async fn patch_reading(&self, user_id: &str, id: &str, request: UpdateBloodPressureRequest)
+        -> Result<BloodPressureReading, BloodPressureServiceError>
+    {
+        let existing = self.get_reading_by_id(user_id, id).await?;
+        self.update_reading(user_id, id, request.merge_into(existing)).await
+    }
+
+    /// Soft delete one of a user's blood pressure readings
+    async fn delete_reading(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError>;
+
+    /// Restore a soft deleted blood pressure reading
+    async fn restore_reading(&self, user_id: &str, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError>;
+
+    /// Record a measurement session together with its readings
+    async fn create_session(&self, user_id: &str, request: CreateBloodPressureSessionRequest)
+        -> Result<BloodPressureSession, BloodPressureServiceError>;
+
+    /// Get one of a user's measurement sessions by ID
+    async fn get_session_by_id(&self, user_id: &str, id: &str) -> Result<BloodPressureSession, BloodPressureServiceError>;
+
+    /// Get a page of the measurement sessions owned by a user, newest first, with their total number
+    async fn get_sessions(&self, user_id: &str, limit: Option<usize>, offset: Option<usize>)
+        -> Result<(Vec<BloodPressureSession>, usize), BloodPressureServiceError>;
+
+    /// Delete one of a user's measurement sessions, keeping its readings
+    async fn delete_session(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError>;
+
+    /// Get the home blood pressure monitoring report for the week starting on `start_date`
+    ///
+    /// The week runs from the start of `start_date` in `time_zone` until the
+    /// end of day 7 there, which is not always 7 times 24 hours later.
+    async fn get_hbpm_report(&self, user_id: &str, start_date: NaiveDate, time_zone: Tz)
+        -> Result<HbpmReport, BloodPressureServiceError>
+    {
+        let start = start_of_local_day(start_date, time_zone);
+        let end = start_of_local_day(start_date + Days::new(HBPM_DAYS), time_zone) - chrono::Duration::seconds(1);
+
+        let filter = BloodPressureFilter::date_range(Some(start), Some(end));
+        let (readings, _) = self.get_filtered_readings(user_id, filter, None, None, None).await?;
+        let guideline = self.user_guideline_set(user_id).await;
+
+        Ok(self.evaluate_hbpm_protocol(&readings, start_date, time_zone, &guideline))
+    }
+
+    /// Get a user's readings matching the filter, with each session averaged into one reading
+    ///
+    /// The filter is applied to the individual readings; a session is kept
+    /// when any of its readings matches and then averages all of them. It is
+    /// placed at the time of its first reading.
+    async fn get_session_averaged_readings(
+        &self,
+        user_id: &str,
+        filter: BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
@@ -77,12 +538,26 @@ pub trait BloodPressureServiceTrait {
 /// Blood pressure service for domain logic
 pub struct BloodPressureService<R: BloodPressureRepositoryTrait> {
     repository: R,
+    notifiers: Vec<Arc<dyn AlertNotifier>>,
+    profiles: Option<Arc<dyn ProfileServiceTrait + Send + Sync>>,
 }
 
 impl<R: BloodPressureRepositoryTrait> BloodPressureService<R> {
-    /// Create a new blood pressure service
+    /// Create a new blood pressure service that delivers alerts nowhere
     pub fn new(repository: R) -> Self {
-        Self { repository }
+        Self { repository, notifiers: Vec::new(), profiles: None }
+    }
+
+    /// Deliver raised alerts through the given notifiers
+    pub fn with_notifiers(mut self, notifiers: Vec<Arc<dyn AlertNotifier>>) -> Self {
+        self.notifiers = notifiers;
+        self
+    }
+
+    /// Categorize and alert each user's readings with the guideline set in their profile
+    pub fn with_profiles(mut self, profiles: Arc<dyn ProfileServiceTrait + Send + Sync>) -> Self {
+        self.profiles = Some(profiles);
+        self
     }
 
     /// Map repository errors to service errors
@@ -90,11 +565,120 @@ impl<R: BloodPressureRepositoryTrait> BloodPressureService<R> {
         match err {
             RepositoryError::NotFound(msg) => BloodPressureServiceError::NotFound(msg),
             RepositoryError::Validation(msg) => BloodPressureServiceError::ValidationError(msg),
+            RepositoryError::Unavailable(msg) => BloodPressureServiceError::StorageUnavailable(msg),
             _ => BloodPressureServiceError::RepositoryError(err.to_string()),
         }
     }
 }
 
+impl<R: BloodPressureRepositoryTrait + Send + Sync> BloodPressureService<R> {
+    /// Raise, store and deliver the alert a new reading calls for, if any
+    ///
+    /// When `reading` is a session's average, `members` are the IDs of the
+    /// session's readings: they are left out of the readings it is compared
+    /// with and the alert refers to them rather than to the session. A single
+    /// reading has no members.
+    ///
+    /// The reading is already stored, so failures here are logged rather
+    /// than failing its creation.
+    async fn raise_alert(
+        &self,
+        user_id: &str,
+        reading: &BloodPressureReading,
+        members: &[String],
+        guideline: &GuidelineSet,
+    ) -> Option<BloodPressureAlert> {
+        // Only a stage 2 reading outside the crisis range needs the week before it
+        let needs_history = !self.is_hypertensive_crisis(reading, guideline)
+            && is_stage_2_or_worse(&guideline.categorize(reading.systolic, reading.diastolic));
+        let (recent, previous) = if needs_history {
+            let window_start = reading.timestamp - Duration::days(REPEATED_STAGE_2_DAYS);
+            let filter = BloodPressureFilter::date_range(Some(window_start), Some(reading.timestamp));
+            // An alert covering readings in the window was raised after the window started
+            let alert_filter = BloodPressureAlertFilter {
+                kind: Some(BloodPressureAlertKind::RepeatedStage2.as_str().to_string()),
+                since: Some(window_start),
+                ..Default::default()
+            };
+            let history = async {
+                let (readings, _) = self.get_filtered_readings(user_id, filter, None, None, Some(false)).await?;
+                let alerts = self.find_alerts(user_id, alert_filter, None).await?;
+                Ok::<_, BloodPressureServiceError>((readings, alerts))
+            };
+            match history.await {
+                Ok(history) => history,
+                Err(e) => {
+                    warn!("Failed to load recent readings and alerts to check reading {} for alerts: {}", reading.id, e);
+                    (Vec::new(), Vec::new())
+                }
+            }
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        let recent: Vec<BloodPressureReading> = recent.into_iter()
+            .filter(|other| !members.contains(&other.id))
+            .collect();
+
+        let mut alert = self.detect_reading_alert(user_id, reading, &recent, &previous, guideline)?;
+        if !members.is_empty() {
+            alert.reading_ids.retain(|id| id != &reading.id);
+            alert.reading_ids.extend_from_slice(members);
+        }
+        Some(self.record_alert(user_id, alert).await)
+    }
+
+    /// Get up to `limit` of a user's alerts that match a filter, newest first
+    async fn find_alerts(
+        &self,
+        user_id: &str,
+        filter: BloodPressureAlertFilter,
+        limit: Option<usize>,
+    ) -> Result<Vec<BloodPressureAlert>, BloodPressureServiceError> {
+        let data_alerts = self.repository.get_alerts(user_id, filter, limit)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        data_alerts.into_iter()
+            .map(|data_alert| conversions::convert_to_domain_alert(data_alert)
+                .map_err(BloodPressureServiceError::RepositoryError))
+            .collect()
+    }
+
+    /// Turn stored sessions into domain sessions, looking up only the readings they refer to
+    ///
+    /// Deleted readings are not returned and drop out of their session.
+    async fn with_readings(
+        &self,
+        user_id: &str,
+        data_sessions: Vec<my_health_guide_data::models::blood_pressure::BloodPressureSession>,
+    ) -> Result<Vec<BloodPressureSession>, BloodPressureServiceError> {
+        if data_sessions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let reading_ids: Vec<String> = data_sessions.iter()
+            .flat_map(|data_session| data_session.reading_ids.iter().cloned())
+            .collect();
+        let mut readings: HashMap<String, BloodPressureReading> = self.repository.get_by_ids(user_id, &reading_ids)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .into_iter()
+            .map(|data_reading| (data_reading.id.clone(), conversions::convert_to_domain_reading(data_reading)))
+            .collect();
+        let guideline = self.user_guideline_set(user_id).await;
+
+        Ok(data_sessions.into_iter()
+            .map(|data_session| {
+                let session_readings = data_session.reading_ids.iter()
+                    .filter_map(|reading_id| readings.remove(reading_id))
+                    .collect();
+                conversions::convert_to_domain_session(data_session, session_readings, &guideline)
+            })
+            .collect())
+    }
+}
+
 #[async_trait]
 impl<R: BloodPressureRepositoryTrait + Send + Sync> BloodPressureServiceTrait for BloodPressureService<R> {
     /// Validate a create blood pressure request
@@ -104,27 +688,7 @@ impl<R: BloodPressureRepositoryTrait + Send + Sync> BloodPressureServiceTrait fo
     ) -> Result<(), BloodPressureServiceError> {
         // Use the validator crate's validation
         if let Err(validation_errors) = request.validate() {
-            // Convert validation errors to a meaningful error message
-            let error_message = validation_errors
-                .field_errors()
-                .iter()
-                .map(|(field, errors)| {
-                    let error_msgs: Vec<String> = errors
-                        .iter()
-                        .map(|err| {
-                            if let Some(msg) = &err.message {
-                                msg.to_string()
-                            } else {
-                                format!("Invalid {}", field)
-                            }
-                        })
-                        .collect();
-                    format!("{}: {}", field, error_msgs.join(", "))
-                })
-                .collect::<Vec<String>>()
-                .join("; ");
-
-            return Err(BloodPressureServiceError::ValidationError(error_message));
+            return Err(BloodPressureServiceError::ValidationError(validation_message(&validation_errors)));
         }
 
         // Additional validation: Validate that systolic is greater than diastolic
@@ -143,6 +707,7 @@ impl<R: BloodPressureRepositoryTrait + Send + Sync> BloodPressureServiceTrait fo
         &self,
         readings: &[BloodPressureReading],
         timeframe_days: u32,
+        guideline: &GuidelineSet,
     ) -> Result<BloodPressureInsights, BloodPressureServiceError> {
         if readings.is_empty() {
             return Err(BloodPressureServiceError::InsufficientData(
@@ -185,7 +750,27 @@ impl<R: BloodPressureRepositoryTrait + Send + Sync> BloodPressureServiceTrait fo
         };
 
         // Calculate the blood pressure category based on average readings
-        let category = categorize_blood_pressure(avg_systolic as u16, avg_diastolic as u16);
+        let category = guideline.categorize(avg_systolic.round() as u16, avg_diastolic.round() as u16);
+
+        // Variability and trends depend on the order the readings were taken in
+        let mut timeline: Vec<&BloodPressureReading> = readings.iter().collect();
+        timeline.sort_by_key(|reading| reading.timestamp);
+
+        let systolic: Vec<f64> = timeline.iter().map(|reading| reading.systolic as f64).collect();
+        let diastolic: Vec<f64> = timeline.iter().map(|reading| reading.diastolic as f64).collect();
+
+        let in_target = timeline.iter()
+            .filter(|reading| insights::is_in_target_range(reading.systolic as f64, reading.diastolic as f64))
+            .count();
+
+        let first_taken = timeline[0].timestamp;
+        let weeks: Vec<f64> = timeline.iter()
+            .map(|reading| (reading.timestamp - first_taken).num_seconds() as f64 / SECONDS_PER_WEEK)
+            .collect();
+        let trend = |values: &[f64]| {
+            let points: Vec<(f64, f64)> = weeks.iter().copied().zip(values.iter().copied()).collect();
+            insights::linear_trend(&points)
+        };
 
         Ok(BloodPressureInsights {
             avg_systolic,
@@ -196,110 +781,481 @@ impl<R: BloodPressureRepositoryTrait + Send + Sync> BloodPressureServiceTrait fo
             min_systolic,
             min_diastolic,
             category,
+            guideline: guideline.name().to_string(),
+            pediatric: None,
+            systolic_sd: insights::standard_deviation(&systolic),
+            diastolic_sd: insights::standard_deviation(&diastolic),
+            systolic_arv: insights::average_real_variability(&systolic),
+            diastolic_arv: insights::average_real_variability(&diastolic),
+            systolic_cv: insights::coefficient_of_variation(&systolic),
+            diastolic_cv: insights::coefficient_of_variation(&diastolic),
+            // Both are linear, so the averages give the average of the per-reading values
+            avg_pulse_pressure: insights::pulse_pressure(avg_systolic, avg_diastolic),
+            avg_mean_arterial_pressure: insights::mean_arterial_pressure(avg_systolic, avg_diastolic),
+            time_in_target_range: Some(in_target as f64 / readings.len() as f64 * 100.0),
+            systolic_trend: trend(&systolic),
+            diastolic_trend: trend(&diastolic),
             reading_count: readings.len(),
             period_days: timeframe_days,
             generated_at: Utc::now(),
         })
     }
 
+    /// The guideline set in the user's profile, or the deployment's
+    async fn user_guideline_set(&self, user_id: &str) -> GuidelineSet {
+        let Some(profiles) = &self.profiles else {
+            return guideline_set();
+        };
+
+        profiles.guideline_set(user_id, None).await.unwrap_or_else(|e| {
+            warn!("Failed to look up the guideline set of user {}, using the deployment's: {}", user_id, e);
+            guideline_set()
+        })
+    }
+
     /// Get severity category for a blood pressure reading
-    fn get_severity(&self, reading: &BloodPressureReading) -> BloodPressureCategory {
-        categorize_blood_pressure(reading.systolic, reading.diastolic)
+    fn get_severity(&self, reading: &BloodPressureReading, guideline: &GuidelineSet) -> BloodPressureCategory {
+        guideline.categorize(reading.systolic, reading.diastolic)
     }
 
     /// Check if a reading indicates a hypertensive crisis
-    fn is_hypertensive_crisis(&self, reading: &BloodPressureReading) -> bool {
-        reading.systolic > 180 || reading.diastolic > 120
+    fn is_hypertensive_crisis(&self, reading: &BloodPressureReading, guideline: &GuidelineSet) -> bool {
+        guideline.is_crisis(reading.systolic, reading.diastolic)
     }
 
-    /// Create a new blood pressure reading
-    async fn create_reading(&self, request: CreateBloodPressureRequest)
-        -> Result<BloodPressureReading, BloodPressureServiceError>
+    /// Create a new blood pressure reading owned by the given user, with the alert it raised
+    async fn create_reading_with_alert(&self, user_id: &str, request: CreateBloodPressureRequest)
+        -> Result<(BloodPressureReading, Option<BloodPressureAlert>), BloodPressureServiceError>
     {
         // Validate the request
         self.validate_create_request(&request)?;
 
-        // Convert domain entity to data model using the centralized conversion function
-        let data_request = conversions::convert_to_data_create_request(&request);
+        // Convert domain entity to data model using the centralized conversion function
+        let guideline = self.user_guideline_set(user_id).await;
+        let data_request = conversions::convert_to_data_create_request(&request, &guideline)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        // Call repository method
+        let data_reading = self.repository.create(user_id, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        // Convert back to domain entity using the centralized conversion function
+        let domain_reading = conversions::convert_to_domain_reading(data_reading);
+
+        let alert = self.raise_alert(user_id, &domain_reading, &[], &guideline).await;
+
+        Ok((domain_reading, alert))
+    }
+
+    /// Get the alerts raised by a user's readings, newest first
+    async fn get_alerts(&self, user_id: &str) -> Result<Vec<BloodPressureAlert>, BloodPressureServiceError> {
+        self.find_alerts(user_id, BloodPressureAlertFilter::default(), None).await
+    }
+
+    /// Get the last alert one of a user's rules fired, if it fired any
+    async fn get_latest_rule_alert(&self, user_id: &str, rule_id: &str)
+        -> Result<Option<BloodPressureAlert>, BloodPressureServiceError>
+    {
+        let filter = BloodPressureAlertFilter {
+            rule_id: Some(rule_id.to_string()),
+            ..Default::default()
+        };
+        Ok(self.find_alerts(user_id, filter, Some(1)).await?.pop())
+    }
+
+    /// Store an alert raised for a user and deliver it through the service's notifiers
+    async fn record_alert(&self, user_id: &str, alert: BloodPressureAlert) -> BloodPressureAlert {
+        let data_request = my_health_guide_data::models::blood_pressure::CreateBloodPressureAlertRequest {
+            kind: alert.kind.as_str().to_string(),
+            rule_id: alert.rule_id.clone(),
+            message: alert.message.clone(),
+            systolic: alert.systolic,
+            diastolic: alert.diastolic,
+            reading_ids: alert.reading_ids.clone(),
+        };
+        let alert = match self.repository.create_alert(user_id, data_request).await {
+            Ok(data_alert) => conversions::convert_to_domain_alert(data_alert).unwrap_or(alert),
+            Err(e) => {
+                warn!("Failed to store {} alert for user {}: {}", alert.kind.as_str(), user_id, e);
+                alert
+            }
+        };
+
+        notifications::dispatch_alert(&self.notifiers, &alert);
+        alert
+    }
+
+    /// Create an alert rule for a user
+    async fn create_alert_rule(&self, user_id: &str, request: CreateAlertRuleRequest)
+        -> Result<AlertRule, BloodPressureServiceError>
+    {
+        request.validate()
+            .map_err(|e| BloodPressureServiceError::ValidationError(validation_message(&e)))?;
+
+        let data_request = conversions::convert_to_data_alert_rule_request(&request)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+        let data_rule = self.repository.create_alert_rule(user_id, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        conversions::convert_to_domain_alert_rule(data_rule)
+            .map_err(BloodPressureServiceError::RepositoryError)
+    }
+
+    /// Get one of a user's alert rules by ID
+    async fn get_alert_rule_by_id(&self, user_id: &str, id: &str) -> Result<AlertRule, BloodPressureServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let data_rule = self.repository.get_alert_rule(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| BloodPressureServiceError::NotFound(
+                format!("Alert rule with ID {} not found", id)
+            ))?;
+
+        conversions::convert_to_domain_alert_rule(data_rule)
+            .map_err(BloodPressureServiceError::RepositoryError)
+    }
+
+    /// Get all alert rules owned by a user, oldest first
+    async fn get_alert_rules(&self, user_id: &str) -> Result<Vec<AlertRule>, BloodPressureServiceError> {
+        let data_rules = self.repository.get_alert_rules(user_id)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        data_rules.into_iter()
+            .map(|data_rule| conversions::convert_to_domain_alert_rule(data_rule)
+                .map_err(BloodPressureServiceError::RepositoryError))
+            .collect()
+    }
+
+    /// Replace one of a user's alert rules
+    async fn update_alert_rule(&self, user_id: &str, id: &str, request: CreateAlertRuleRequest)
+        -> Result<AlertRule, BloodPressureServiceError>
+    {
+        request.validate()
+            .map_err(|e| BloodPressureServiceError::ValidationError(validation_message(&e)))?;
+
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+        let data_request = conversions::convert_to_data_alert_rule_request(&request)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let data_rule = self.repository.update_alert_rule(user_id, id_uuid, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| BloodPressureServiceError::NotFound(
+                format!("Alert rule with ID {} not found", id)
+            ))?;
+
+        conversions::convert_to_domain_alert_rule(data_rule)
+            .map_err(BloodPressureServiceError::RepositoryError)
+    }
+
+    /// Delete one of a user's alert rules, keeping the alerts it fired
+    async fn delete_alert_rule(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let deleted = self.repository.delete_alert_rule(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        if deleted {
+            Ok(())
+        } else {
+            Err(BloodPressureServiceError::NotFound(
+                format!("Alert rule with ID {} not found", id)
+            ))
+        }
+    }
+
+    /// Evaluate the enabled alert rules of every user, returning how many alerts fired
+    async fn evaluate_all_alert_rules(&self) -> Result<usize, BloodPressureServiceError> {
+        let data_rules = self.repository.get_enabled_alert_rules()
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+        let user_ids: BTreeSet<String> = data_rules.into_iter()
+            .map(|data_rule| data_rule.user_id)
+            .collect();
+
+        let mut fired = 0;
+        for user_id in &user_ids {
+            match self.evaluate_alert_rules(user_id).await {
+                Ok(alerts) => fired += alerts.len(),
+                Err(e) => warn!("Failed to evaluate alert rules for user {}: {}", user_id, e),
+            }
+        }
+
+        Ok(fired)
+    }
+
+    /// Get all blood pressure readings owned by a user
+    async fn get_all_readings(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, BloodPressureServiceError> {
+        // Call repository method
+        let data_readings = self.repository.get_all(user_id)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        // Convert to domain entities using the centralized conversion function
+        let domain_readings = data_readings.into_iter()
+            .map(conversions::convert_to_domain_reading)
+            .collect();
+
+        Ok(domain_readings)
+    }
+
+    /// Get one of a user's blood pressure readings by ID
+    async fn get_reading_by_id(&self, user_id: &str, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError> {
+        // Convert to UUID using the centralized helper function
+        let id_uuid = crate::entities::conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        // Call repository method
+        let data_reading = self.repository.get_by_id(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| BloodPressureServiceError::NotFound(
+                format!("Blood pressure reading with ID {} not found", id)
+            ))?;
+
+        // Convert to domain entity using the centralized conversion function
+        let domain_reading = conversions::convert_to_domain_reading(data_reading);
+
+        Ok(domain_reading)
+    }
+
+    /// Get a user's blood pressure readings matching the filter
+    async fn get_filtered_readings(
+        &self,
+        user_id: &str,
+        filter: BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<BloodPressureReading>, usize), BloodPressureServiceError> {
+        // Call repository method
+        let (data_readings, total_count) = self.repository.get_filtered(
+            user_id,
+            conversions::convert_to_data_filter(filter),
+            limit,
+            offset,
+            sort_desc,
+        ).await
+        .map_err(|e| self.map_repo_error(e))?;
+
+        // Convert to domain entities using the centralized conversion function
+        let domain_readings = data_readings.into_iter()
+            .map(conversions::convert_to_domain_reading)
+            .collect();
+
+        Ok((domain_readings, total_count))
+    }
+
+    /// Replace one of a user's blood pressure readings
+    async fn update_reading(&self, user_id: &str, id: &str, request: CreateBloodPressureRequest)
+        -> Result<BloodPressureReading, BloodPressureServiceError>
+    {
+        // Edits follow the same rules as new readings
+        self.validate_create_request(&request)?;
+
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+        let guideline = self.user_guideline_set(user_id).await;
+        let data_request = conversions::convert_to_data_create_request(&request, &guideline)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let data_reading = self.repository.update(user_id, id_uuid, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| BloodPressureServiceError::NotFound(
+                format!("Blood pressure reading with ID {} not found", id)
+            ))?;
+
+        Ok(conversions::convert_to_domain_reading(data_reading))
+    }
 
-        // Call repository method
-        let data_reading = self.repository.create(data_request)
+    /// Soft delete one of a user's blood pressure readings
+    async fn delete_reading(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let deleted = self.repository.delete(user_id, id_uuid)
             .await
             .map_err(|e| self.map_repo_error(e))?;
 
-        // Convert back to domain entity using the centralized conversion function
-        let domain_reading = conversions::convert_to_domain_reading(data_reading);
+        if deleted {
+            Ok(())
+        } else {
+            Err(BloodPressureServiceError::NotFound(
+                format!("Blood pressure reading with ID {} not found", id)
+            ))
+        }
+    }
 
-        Ok(domain_reading)
+    /// Restore a soft deleted blood pressure reading
+    async fn restore_reading(&self, user_id: &str, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let data_reading = self.repository.restore(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| BloodPressureServiceError::NotFound(
+                format!("Blood pressure reading with ID {} not found", id)
+            ))?;
+
+        Ok(conversions::convert_to_domain_reading(data_reading))
     }
 
-    /// Get all blood pressure readings
-    async fn get_all_readings(&self) -> Result<Vec<BloodPressureReading>, BloodPressureServiceError> {
-        // Call repository method
-        let data_readings = self.repository.get_all()
+    /// Record a measurement session together with its readings
+    ///
+    /// Every reading is validated before any is stored, and the readings are
+    /// stored in one go with the session, so a failure leaves none of them
+    /// behind. Alerts are raised once, for the session's average.
+    async fn create_session(&self, user_id: &str, request: CreateBloodPressureSessionRequest)
+        -> Result<BloodPressureSession, BloodPressureServiceError>
+    {
+        if let Err(validation_errors) = request.validate() {
+            return Err(BloodPressureServiceError::ValidationError(validation_message(&validation_errors)));
+        }
+        for reading in &request.readings {
+            self.validate_create_request(reading)?;
+        }
+
+        let guideline = self.user_guideline_set(user_id).await;
+        let readings = request.readings.iter()
+            .map(|reading| conversions::convert_to_data_create_request(reading, &guideline))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(BloodPressureServiceError::ValidationError)?;
+        let data_request = my_health_guide_data::models::blood_pressure::CreateBloodPressureSessionRequest {
+            discard_first: request.discard_first,
+            notes: request.notes,
+            readings,
+        };
+        let (data_session, data_readings) = self.repository.create_session(user_id, data_request)
             .await
             .map_err(|e| self.map_repo_error(e))?;
-
-        // Convert to domain entities using the centralized conversion function
-        let domain_readings = data_readings.into_iter()
+        let readings = data_readings.into_iter()
             .map(conversions::convert_to_domain_reading)
             .collect();
+        let session = conversions::convert_to_domain_session(data_session, readings, &guideline);
 
-        Ok(domain_readings)
+        if let Some(average) = insights::session_reading(&session) {
+            let members: Vec<String> = session.readings.iter().map(|reading| reading.id.clone()).collect();
+            self.raise_alert(user_id, &average, &members, &guideline).await;
+        }
+
+        Ok(session)
     }
 
-    /// Get a blood pressure reading by ID
-    async fn get_reading_by_id(&self, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError> {
-        // Convert to UUID using the centralized helper function
-        let id_uuid = crate::entities::conversions::parse_string_to_uuid(id)
+    /// Get one of a user's measurement sessions by ID
+    async fn get_session_by_id(&self, user_id: &str, id: &str) -> Result<BloodPressureSession, BloodPressureServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
             .map_err(BloodPressureServiceError::ValidationError)?;
 
-        // Call repository method
-        let data_reading = self.repository.get_by_id(id_uuid)
+        let data_session = self.repository.get_session(user_id, id_uuid)
             .await
             .map_err(|e| self.map_repo_error(e))?
             .ok_or_else(|| BloodPressureServiceError::NotFound(
-                format!("Blood pressure reading with ID {} not found", id)
+                format!("Blood pressure session with ID {} not found", id)
             ))?;
 
-        // Convert to domain entity using the centralized conversion function
-        let domain_reading = conversions::convert_to_domain_reading(data_reading);
+        let session = self.with_readings(user_id, vec![data_session]).await?.pop();
+        Ok(session.expect("every session comes back with its readings"))
+    }
 
-        Ok(domain_reading)
+    /// Get a page of the measurement sessions owned by a user, newest first, with their total number
+    async fn get_sessions(&self, user_id: &str, limit: Option<usize>, offset: Option<usize>)
+        -> Result<(Vec<BloodPressureSession>, usize), BloodPressureServiceError>
+    {
+        let (data_sessions, total_count) = self.repository.get_sessions(user_id, limit, offset)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        Ok((self.with_readings(user_id, data_sessions).await?, total_count))
     }
 
-    /// Get filtered blood pressure readings
-    async fn get_filtered_readings(
+    /// Get a user's readings matching the filter, with each session averaged into one reading
+    async fn get_session_averaged_readings(
         &self,
-        start_date: Option<String>,
-        end_date: Option<String>,
+        user_id: &str,
+        filter: BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
     ) -> Result<(Vec<BloodPressureReading>, usize), BloodPressureServiceError> {
-        // Call repository method
-        let (data_readings, total_count) = self.repository.get_filtered(
-            start_date,
-            end_date,
+        let (entries, total_count) = self.repository.get_session_averaged_entries(
+            user_id,
+            conversions::convert_to_data_filter(filter),
             limit,
             offset,
             sort_desc,
         ).await
         .map_err(|e| self.map_repo_error(e))?;
 
-        // Convert to domain entities using the centralized conversion function
-        let domain_readings = data_readings.into_iter()
-            .map(conversions::convert_to_domain_reading)
+        // Only the sessions and readings on the page are looked up
+        let mut reading_ids = Vec::new();
+        let mut session_ids = Vec::new();
+        for entry in &entries {
+            match entry {
+                SessionAveragedEntry::Reading(id) => reading_ids.push(id.clone()),
+                SessionAveragedEntry::Session(id) => session_ids.push(id.clone()),
+            }
+        }
+        let data_sessions = self.repository.get_sessions_by_ids(user_id, &session_ids)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+        let sessions: HashMap<String, BloodPressureSession> = self.with_readings(user_id, data_sessions).await?
+            .into_iter()
+            .map(|session| (session.id.clone(), session))
+            .collect();
+        let mut readings: HashMap<String, BloodPressureReading> = self.repository.get_by_ids(user_id, &reading_ids)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .into_iter()
+            .map(|data_reading| (data_reading.id.clone(), conversions::convert_to_domain_reading(data_reading)))
             .collect();
 
-        Ok((domain_readings, total_count))
+        let page = entries.iter()
+            .filter_map(|entry| match entry {
+                SessionAveragedEntry::Reading(id) => readings.remove(id),
+                SessionAveragedEntry::Session(id) => sessions.get(id).and_then(insights::session_reading),
+            })
+            .collect();
+
+        Ok((page, total_count))
+    }
+
+    /// Delete one of a user's measurement sessions, keeping its readings
+    async fn delete_session(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let deleted = self.repository.delete_session(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        if deleted {
+            Ok(())
+        } else {
+            Err(BloodPressureServiceError::NotFound(
+                format!("Blood pressure session with ID {} not found", id)
+            ))
+        }
     }
 }
 
 /// Create a default blood pressure service using the repository from data layer
-pub fn create_default_blood_pressure_service() -> impl BloodPressureServiceTrait + Send + Sync {
+pub fn create_default_blood_pressure_service(
+    profiles: Arc<dyn ProfileServiceTrait + Send + Sync>,
+) -> impl BloodPressureServiceTrait + Send + Sync {
     let repository = my_health_guide_data::repository::BloodPressureRepository::new();
     BloodPressureService::new(repository)
+        .with_notifiers(notifications::notifiers_from_env())
+        .with_profiles(profiles)
 }
 
 /// Create a mock blood pressure service for testing
@@ -313,19 +1269,24 @@ pub fn create_mock_blood_pressure_service() -> impl BloodPressureServiceTrait +
 mod tests {
     use super::*;
     use chrono::Utc;
+    use chrono::Datelike;
+    use crate::entities::blood_pressure::DippingPattern;
 
     /// Create a test blood pressure reading
     fn create_test_reading(systolic: u16, diastolic: u16, pulse: Option<u16>) -> BloodPressureReading {
         BloodPressureReading {
             id: Utc::now().to_rfc3339(),
+            user_id: "test-user".to_string(),
             systolic,
             diastolic,
             pulse,
             notes: None,
-            timestamp: Utc::now().to_rfc3339(),
+            timestamp: Utc::now(),
             position: None,
             arm: None,
             device_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
         }
     }
 
@@ -441,12 +1402,252 @@ mod tests {
         let service = BloodPressureService::new(mock_repo);
 
         // Calculate insights
-        let insights = service.calculate_insights(&readings, 30).unwrap();
+        let insights = service.calculate_insights(&readings, 30, &GuidelineSet::default()).unwrap();
         assert_eq!(insights.reading_count, 3);
         assert_eq!(insights.period_days, 30);
         assert!(insights.avg_systolic > 0.0);
         assert!(insights.avg_diastolic > 0.0);
         assert!(insights.avg_pulse.unwrap() > 0.0);
+
+        // An average of 129.8/70 is rounded to 130 before it is categorized
+        let readings = vec![
+            create_test_reading(129, 70, None),
+            create_test_reading(130, 70, None),
+            create_test_reading(130, 70, None),
+            create_test_reading(130, 70, None),
+            create_test_reading(130, 70, None),
+        ];
+        let insights = service.calculate_insights(&readings, 30, &GuidelineSet::default()).unwrap();
+        assert_eq!(insights.category, BloodPressureCategory::Hypertension1);
+    }
+
+    #[test]
+    fn test_calculate_insights_analytics() {
+        // Given out of order, one week apart: 120/80, 124/78, 128/82, 132/84
+        let at_week = |week: i64, systolic: u16, diastolic: u16| BloodPressureReading {
+            timestamp: "2024-01-01T08:00:00Z".parse::<chrono::DateTime<Utc>>().unwrap()
+                + chrono::Duration::weeks(week),
+            ..create_test_reading(systolic, diastolic, None)
+        };
+        let readings = vec![
+            at_week(2, 128, 82),
+            at_week(0, 120, 80),
+            at_week(3, 132, 84),
+            at_week(1, 124, 78),
+        ];
+
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let insights = service.calculate_insights(&readings, 30, &GuidelineSet::default()).unwrap();
+
+        assert!((insights.systolic_sd.unwrap() - 5.163_978).abs() < 1e-6);
+        assert!((insights.systolic_cv.unwrap() - 4.098_395).abs() < 1e-6);
+        assert_eq!(insights.systolic_arv, Some(4.0));
+        // |-2| + |4| + |2| over 3 changes
+        assert!((insights.diastolic_arv.unwrap() - 8.0 / 3.0).abs() < 1e-9);
+        assert_eq!(insights.avg_pulse_pressure, 45.0);
+        assert_eq!(insights.avg_mean_arterial_pressure, 96.0);
+        // Only 124/78 is inside; 120/80 sits on the exclusive diastolic bound
+        assert_eq!(insights.time_in_target_range, Some(25.0));
+
+        let systolic_trend = insights.systolic_trend.unwrap();
+        assert!((systolic_trend.slope_per_week - 4.0).abs() < 1e-9);
+        assert!((systolic_trend.ci_lower - 4.0).abs() < 1e-6);
+        let diastolic_trend = insights.diastolic_trend.unwrap();
+        assert!(diastolic_trend.ci_lower < diastolic_trend.slope_per_week);
+        assert!(diastolic_trend.slope_per_week < diastolic_trend.ci_upper);
+    }
+
+    #[test]
+    fn test_detect_paired_reading_alerts() {
+        use crate::entities::blood_pressure::{MeasurementArm, MeasurementPosition, PairedReadingAlertKind};
+
+        let taken = "2024-01-01T08:00:00Z".parse::<chrono::DateTime<Utc>>().unwrap();
+        let reading = |minute: i64, systolic: u16, position, arm| BloodPressureReading {
+            id: format!("reading-{}", minute),
+            timestamp: taken + chrono::Duration::minutes(minute),
+            position,
+            arm,
+            ..create_test_reading(systolic, 80, None)
+        };
+        let readings = vec![
+            reading(10, 140, None, Some(MeasurementArm::Left)),
+            reading(11, 125, None, Some(MeasurementArm::Right)),
+            reading(0, 135, Some(MeasurementPosition::Sitting), None),
+            reading(3, 112, Some(MeasurementPosition::Standing), None),
+            // Nine minutes after the sitting reading is too late to pair
+            reading(9, 100, Some(MeasurementPosition::Standing), None),
+        ];
+
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let result = service.detect_paired_reading_alerts(&readings, 7);
+
+        let kinds: Vec<PairedReadingAlertKind> = result.alerts.iter().map(|alert| alert.kind).collect();
+        assert_eq!(kinds, vec![PairedReadingAlertKind::OrthostaticHypotension, PairedReadingAlertKind::InterArmDifference]);
+        assert_eq!(result.alerts[0].systolic_difference, 23);
+        assert_eq!(result.alerts[1].systolic_difference, 15);
+        assert_eq!(result.reading_count, 5);
+        assert_eq!(result.period_days, 7);
+    }
+
+    #[test]
+    fn test_calculate_patient_insights() {
+        use crate::entities::blood_pressure::{PediatricCategory, Sex};
+        use chrono::{Months, NaiveDate};
+
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let readings = vec![create_test_reading(120, 70, None), create_test_reading(122, 72, None)];
+
+        // A 10 year old boy of median height, whose 95th percentile is 116/77
+        let today = Utc::now().date_naive();
+        let child = PediatricPatient {
+            date_of_birth: today.checked_sub_months(Months::new(10 * 12 + 1)).unwrap(),
+            sex: Sex::Male,
+            height_cm: Some(141.0),
+        };
+        let insights = service.calculate_patient_insights(&readings, 30, &GuidelineSet::default(), &child).unwrap();
+        let pediatric = insights.pediatric.unwrap();
+        assert_eq!(pediatric.age_years, 10);
+        assert_eq!(pediatric.category, PediatricCategory::Stage1);
+        assert_eq!(insights.category, BloodPressureCategory::Hypertension1);
+        assert_eq!(insights.guideline, "aap_2017");
+
+        let adult = PediatricPatient {
+            date_of_birth: NaiveDate::from_ymd_opt(1980, 1, 1).unwrap(),
+            sex: Sex::Male,
+            height_cm: None,
+        };
+        let insights = service.calculate_patient_insights(&readings, 30, &GuidelineSet::default(), &adult).unwrap();
+        assert!(insights.pediatric.is_none());
+        assert_eq!(insights.category, BloodPressureCategory::Elevated);
+        assert_eq!(insights.guideline, GuidelineSet::default().name());
+    }
+
+    #[test]
+    fn test_calculate_insights_single_reading() {
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+
+        let insights = service.calculate_insights(&[create_test_reading(118, 76, None)], 30, &GuidelineSet::default()).unwrap();
+        assert_eq!(insights.time_in_target_range, Some(100.0));
+        assert!(insights.systolic_sd.is_none());
+        assert!(insights.systolic_arv.is_none());
+        assert!(insights.systolic_trend.is_none());
+    }
+
+    #[test]
+    fn test_time_of_day_pattern() {
+        // Local times in Johannesburg, at +02:00: two mornings, an evening, an afternoon and a night
+        let at = |timestamp: &str, systolic: u16, diastolic: u16| BloodPressureReading {
+            timestamp: timestamp.parse::<chrono::DateTime<Utc>>().unwrap(),
+            ..create_test_reading(systolic, diastolic, None)
+        };
+        let readings = vec![
+            at("2024-03-01T05:00:00Z", 140, 90),  // 07:00
+            at("2024-03-02T08:00:00Z", 136, 86),  // 10:00
+            at("2024-03-01T13:00:00Z", 130, 84),  // 15:00
+            at("2024-03-01T18:00:00Z", 124, 80),  // 20:00
+            at("2024-03-01T01:00:00Z", 115, 72),  // 03:00
+        ];
+
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let pattern = service.calculate_time_of_day_pattern(&readings, Tz::Africa__Johannesburg, 30).unwrap();
+
+        assert_eq!(pattern.time_zone, "Africa/Johannesburg");
+        assert_eq!(pattern.reading_count, 5);
+        assert_eq!(pattern.morning.unwrap().avg_systolic, 138.0);
+        assert_eq!(pattern.morning.unwrap().reading_count, 2);
+        assert_eq!(pattern.evening.unwrap().avg_systolic, 124.0);
+        // The day holds 10:00, 15:00 and 20:00
+        assert_eq!(pattern.daytime.unwrap().avg_systolic, 130.0);
+        assert_eq!(pattern.nighttime.unwrap().avg_systolic, 115.0);
+        assert_eq!(pattern.morning_surge, Some(23.0));
+        assert!((pattern.dipping_ratio.unwrap() - 115.0 / 130.0).abs() < 1e-9);
+        assert_eq!(pattern.dipping_pattern, Some(DippingPattern::Dipper));
+    }
+
+    #[test]
+    fn test_evaluate_hbpm_protocol() {
+        // Local times in Johannesburg, at +02:00: readings at 07:00, 07:02, 20:00 and 20:02 on each day
+        let start = "2024-03-01T05:00:00Z".parse::<chrono::DateTime<Utc>>().unwrap();
+        let at = |day: i64, minutes: i64, systolic: u16, diastolic: u16| BloodPressureReading {
+            timestamp: start + chrono::Duration::days(day) + chrono::Duration::minutes(minutes),
+            ..create_test_reading(systolic, diastolic, None)
+        };
+        let mut readings = Vec::new();
+        for day in 0..7 {
+            // Day 1 is discarded, so its high readings do not count
+            let (first, second) = if day == 0 { ((160, 100), (158, 98)) } else { ((130, 80), (126, 78)) };
+            for minutes in [0, 13 * 60] {
+                readings.push(at(day, minutes, first.0, first.1));
+                readings.push(at(day, minutes + 2, second.0, second.1));
+            }
+        }
+        // A third reading in a slot and one in the afternoon are left out
+        readings.push(at(3, 4, 200, 120));
+        readings.push(at(3, 8 * 60, 200, 120));
+
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let time_zone = Tz::Africa__Johannesburg;
+        let start_date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+
+        let report = service.evaluate_hbpm_protocol(&readings, start_date, time_zone, &GuidelineSet::AccAha2017);
+        assert!(report.complete);
+        assert_eq!(report.end_date, NaiveDate::from_ymd_opt(2024, 3, 7).unwrap());
+        assert_eq!(report.reading_count, 24);
+        assert_eq!((report.avg_systolic, report.avg_diastolic), (Some(128.0), Some(79.0)));
+        assert_eq!(report.category, Some(BloodPressureCategory::Elevated));
+
+        // Drop the last evening reading and both readings of the third morning
+        readings.retain(|reading| {
+            let local = reading.timestamp.with_timezone(&time_zone);
+            let (day, hour) = (local.date_naive().day(), local.hour());
+            let dropped = (day == 3 && hour == 7) || (day == 7 && hour == 20 && local.minute() == 2);
+            !dropped
+        });
+        let report = service.evaluate_hbpm_protocol(&readings, start_date, time_zone, &GuidelineSet::AccAha2017);
+        assert!(!report.complete);
+        assert_eq!(report.reading_count, 21);
+        assert_eq!(report.missing_slots, vec![
+            HbpmSlot { date: NaiveDate::from_ymd_opt(2024, 3, 3).unwrap(), period: HbpmPeriod::Morning, reading_count: 0 },
+            HbpmSlot { date: NaiveDate::from_ymd_opt(2024, 3, 7).unwrap(), period: HbpmPeriod::Evening, reading_count: 1 },
+        ]);
+
+        let report = service.evaluate_hbpm_protocol(&[], start_date, time_zone, &GuidelineSet::AccAha2017);
+        assert_eq!(report.missing_slots.len(), 12);
+        assert!(report.avg_systolic.is_none() && report.category.is_none());
+    }
+
+    #[test]
+    fn test_start_of_local_day_follows_daylight_saving() {
+        let amsterdam = Tz::Europe__Amsterdam;
+        let start = |date: NaiveDate| start_of_local_day(date, amsterdam).to_rfc3339();
+
+        // Clocks go forward on 31 March 2024, so that week is an hour short
+        assert_eq!(start(NaiveDate::from_ymd_opt(2024, 3, 30).unwrap()), "2024-03-29T23:00:00+00:00");
+        assert_eq!(start(NaiveDate::from_ymd_opt(2024, 4, 6).unwrap()), "2024-04-05T22:00:00+00:00");
+    }
+
+    #[test]
+    fn test_time_of_day_pattern_without_night_readings() {
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let reading = BloodPressureReading {
+            timestamp: "2024-03-01T08:00:00Z".parse().unwrap(),
+            ..create_test_reading(128, 82, None)
+        };
+        let pattern = service.calculate_time_of_day_pattern(&[reading], Tz::UTC, 30).unwrap();
+        assert!(pattern.morning.is_some());
+        assert!(pattern.nighttime.is_none());
+        assert!(pattern.morning_surge.is_none());
+        assert!(pattern.dipping_pattern.is_none());
+
+        let result = service.calculate_time_of_day_pattern(&[], Tz::UTC, 30);
+        assert!(matches!(result, Err(BloodPressureServiceError::InsufficientData(_))));
     }
 
     #[test]
@@ -459,7 +1660,7 @@ mod tests {
         let service = BloodPressureService::new(mock_repo);
 
         // Calculate insights should fail
-        let result = service.calculate_insights(&readings, 30);
+        let result = service.calculate_insights(&readings, 30, &GuidelineSet::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("No readings"));
     }
@@ -477,56 +1678,424 @@ mod tests {
         let service = BloodPressureService::new(mock_repo);
 
         // Test crisis detection
-        assert!(service.is_hypertensive_crisis(&crisis_reading));
-        assert!(!service.is_hypertensive_crisis(&normal_reading));
+        let guideline = GuidelineSet::AccAha2017;
+        assert!(service.is_hypertensive_crisis(&crisis_reading, &guideline));
+        assert!(!service.is_hypertensive_crisis(&normal_reading, &guideline));
+
+        // The check and the category agree at the boundary
+        let boundary_reading = create_test_reading(180, 120, None);
+        assert!(!service.is_hypertensive_crisis(&boundary_reading, &guideline));
+        assert_eq!(service.get_severity(&boundary_reading, &guideline), BloodPressureCategory::Hypertension2);
+
+        // ESC/ESH 2018 already treats 180/110 as a crisis
+        let guideline = GuidelineSet::EscEsh2018;
+        assert!(service.is_hypertensive_crisis(&boundary_reading, &guideline));
+        assert_eq!(service.get_severity(&boundary_reading, &guideline), BloodPressureCategory::Hypertension3);
+    }
+
+    #[test]
+    fn test_detect_reading_alert() {
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+        let reading_at = |days_ago: i64, systolic: u16, diastolic: u16| BloodPressureReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            ..create_test_reading(systolic, diastolic, None)
+        };
+        let guideline = GuidelineSet::AccAha2017;
+
+        // A crisis reading raises an alert on its own
+        let crisis = reading_at(0, 200, 110);
+        let alert = service.detect_reading_alert("test-user", &crisis, &[], &[], &guideline).unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::HypertensiveCrisis);
+        assert_eq!(alert.reading_ids, vec![crisis.id.clone()]);
+        assert_eq!((alert.systolic, alert.diastolic), (200, 110));
+
+        // Normal and single stage 2 readings do not
+        let latest = reading_at(0, 150, 95);
+        assert!(service.detect_reading_alert("test-user", &reading_at(0, 118, 76), &[], &[], &guideline).is_none());
+        assert!(service.detect_reading_alert("test-user", &latest, std::slice::from_ref(&latest), &[], &guideline).is_none());
+
+        // The third stage 2 reading within a week does; older and lower readings do not count
+        let first = reading_at(5, 145, 92);
+        let second = reading_at(2, 160, 100);
+        let recent = vec![reading_at(9, 155, 95), second.clone(), reading_at(1, 125, 78), first.clone(), latest.clone()];
+        let alert = service.detect_reading_alert("test-user", &latest, &recent, &[], &guideline).unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::RepeatedStage2);
+        assert_eq!(alert.reading_ids, vec![first.id, second.id, latest.id.clone()]);
+        assert!(alert.message.starts_with("3 readings at stage 2"));
+
+        // A fourth one is covered by that alert, until its readings leave the week
+        let fourth = reading_at(0, 155, 98);
+        let mut with_fourth = recent.clone();
+        with_fourth.push(fourth.clone());
+        assert!(service.detect_reading_alert("test-user", &fourth, &with_fourth, std::slice::from_ref(&alert), &guideline).is_none());
+        let crisis_alert = BloodPressureAlert { kind: BloodPressureAlertKind::HypertensiveCrisis, ..alert.clone() };
+        assert!(service.detect_reading_alert("test-user", &fourth, &with_fourth, &[crisis_alert], &guideline).is_some());
+
+        let recent = vec![reading_at(9, 155, 95), reading_at(2, 160, 100), latest.clone()];
+        assert!(service.detect_reading_alert("test-user", &latest, &recent, &[], &guideline).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_reading_raises_alerts() {
+        let stage_2 = |days_ago: i64| my_health_guide_data::models::blood_pressure::BloodPressureReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            systolic: 150,
+            diastolic: 95,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            position: None,
+            arm: None,
+            device_id: None,
+            category: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::with_readings(
+            vec![stage_2(4), stage_2(2)],
+        );
+        let service = BloodPressureService::new(mock_repo);
+        let request = |systolic: u16, diastolic: u16| CreateBloodPressureRequest {
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+            position: None,
+            arm: None,
+            device_id: None,
+        };
+
+        let (_, alert) = service.create_reading_with_alert("alice", request(120, 80)).await.unwrap();
+        assert!(alert.is_none());
+
+        let (reading, alert) = service.create_reading_with_alert("alice", request(152, 96)).await.unwrap();
+        let alert = alert.unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::RepeatedStage2);
+        assert_eq!(alert.reading_ids.len(), 3);
+        assert_eq!(alert.reading_ids.last(), Some(&reading.id));
+
+        // Another user's history does not count
+        let (_, alert) = service.create_reading_with_alert("bob", request(152, 96)).await.unwrap();
+        assert!(alert.is_none());
+
+        let (reading, alert) = service.create_reading_with_alert("bob", request(190, 125)).await.unwrap();
+        let alert = alert.unwrap();
+        assert_eq!(alert.kind, BloodPressureAlertKind::HypertensiveCrisis);
+        assert_eq!(alert.user_id, "bob");
+        assert_eq!(alert.reading_ids, vec![reading.id]);
+    }
+
+    #[tokio::test]
+    async fn test_profile_guideline_decides_alerts() {
+        use crate::entities::profile::SetProfileRequest;
+        use crate::services::profile::ProfileService;
+
+        let profiles = Arc::new(ProfileService::new(my_health_guide_data::repository::tests::MockProfileRepository::new()));
+        let request = SetProfileRequest { guideline: Some("esc_esh_2018".to_string()), ..Default::default() };
+        profiles.set_profile("alice", request).await.unwrap();
+        let service = BloodPressureService::new(my_health_guide_data::repository::tests::MockBloodPressureRepository::new())
+            .with_profiles(profiles);
+        let request = CreateBloodPressureRequest {
+            systolic: 180,
+            diastolic: 100,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+            position: None,
+            arm: None,
+            device_id: None,
+        };
+
+        assert_eq!(service.user_guideline_set("alice").await, GuidelineSet::EscEsh2018);
+        let (_, alert) = service.create_reading_with_alert("alice", request.clone()).await.unwrap();
+        assert_eq!(alert.unwrap().kind, BloodPressureAlertKind::HypertensiveCrisis);
+
+        // Without a profile the deployment's guideline applies
+        assert_eq!(service.user_guideline_set("bob").await, guideline_set());
+        let (_, alert) = service.create_reading_with_alert("bob", request).await.unwrap();
+        assert_eq!(alert.is_some(), guideline_set().is_crisis(180, 100));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_alert_rules() {
+        use crate::entities::blood_pressure::AlertRuleCondition;
+
+        let reading = |days_ago: i64, systolic: u16| my_health_guide_data::models::blood_pressure::BloodPressureReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            systolic,
+            diastolic: 90,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            position: None,
+            arm: None,
+            device_id: None,
+            category: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let rule = |user_id: &str, condition: AlertRuleCondition, enabled: bool| my_health_guide_data::models::blood_pressure::BloodPressureAlertRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: "Test rule".to_string(),
+            definition: serde_json::to_string(&condition).unwrap(),
+            enabled,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let two_above_150 = rule("alice", AlertRuleCondition::ConsecutiveAbove { count: 2, systolic: Some(150), diastolic: None }, true);
+        let no_reading = rule("alice", AlertRuleCondition::NoReading { days: 1 }, true);
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::with_readings(
+            vec![reading(5, 120), reading(3, 155), reading(2, 158)],
+        ).with_alert_rules(vec![
+            two_above_150.clone(),
+            no_reading.clone(),
+            rule("alice", AlertRuleCondition::AverageAbove { days: 7, systolic: Some(130), diastolic: None }, false),
+            rule("bob", AlertRuleCondition::NoReading { days: 1 }, true),
+        ]);
+        let service = BloodPressureService::new(mock_repo);
+
+        let alerts = service.evaluate_alert_rules("alice").await.unwrap();
+        let rule_ids: Vec<Option<String>> = alerts.iter().map(|alert| alert.rule_id.clone()).collect();
+        assert_eq!(rule_ids, vec![Some(two_above_150.id), Some(no_reading.id)]);
+        assert!(alerts.iter().all(|alert| alert.kind == BloodPressureAlertKind::Rule));
+        assert_eq!(alerts[0].reading_ids.len(), 2);
+        assert_eq!(alerts[0].systolic, 158);
+
+        // Bob has no readings for his rule to fire on, and the disabled rule is skipped
+        assert!(service.evaluate_alert_rules("bob").await.unwrap().is_empty());
+        assert_eq!(service.evaluate_all_alert_rules().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_alert_rule_validates_condition() {
+        use crate::entities::blood_pressure::AlertRuleCondition;
+
+        let service = BloodPressureService::new(my_health_guide_data::repository::tests::MockBloodPressureRepository::new());
+        let request = |condition: AlertRuleCondition| CreateAlertRuleRequest {
+            name: "Weekly average".to_string(),
+            condition,
+            enabled: true,
+        };
+
+        let rule = service.create_alert_rule("alice", request(
+            AlertRuleCondition::AverageAbove { days: 7, systolic: Some(135), diastolic: Some(85) },
+        )).await.unwrap();
+        assert_eq!(rule.user_id, "alice");
+        assert_eq!(rule.condition, AlertRuleCondition::AverageAbove { days: 7, systolic: Some(135), diastolic: Some(85) });
+
+        for condition in [
+            AlertRuleCondition::ConsecutiveAbove { count: 0, systolic: Some(150), diastolic: None },
+            AlertRuleCondition::ConsecutiveAbove { count: 3, systolic: None, diastolic: None },
+            AlertRuleCondition::AverageAbove { days: 7, systolic: Some(400), diastolic: None },
+            AlertRuleCondition::NoReading { days: 0 },
+        ] {
+            let result = service.create_alert_rule("alice", request(condition)).await;
+            assert!(matches!(result, Err(BloodPressureServiceError::ValidationError(_))));
+        }
     }
 
     #[test]
     fn test_create_reading() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
 
     #[test]
     fn test_get_all_readings() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
 
     #[test]
     fn test_get_reading_by_id() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
 
     #[test]
     fn test_get_filtered_readings() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
 
     #[test]
     fn test_get_filtered_readings_with_sort() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
 
     #[test]
     fn test_get_filtered_readings_with_limit_offset() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
 
     #[test]
     fn test_get_filtered_readings_with_date_range() {
         // ... existing code ...
-        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let _mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
         // ... existing code ...
     }
+
+    #[tokio::test]
+    async fn test_readings_are_scoped_to_owner() {
+        let owned = my_health_guide_data::models::blood_pressure::BloodPressureReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            systolic: 120,
+            diastolic: 80,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now(),
+            position: None,
+            arm: None,
+            device_id: None,
+            category: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::with_readings(
+            vec![owned.clone()],
+        );
+        let service = BloodPressureService::new(mock_repo);
+
+        assert_eq!(service.get_all_readings("alice").await.unwrap().len(), 1);
+        assert!(service.get_all_readings("bob").await.unwrap().is_empty());
+
+        assert!(service.get_reading_by_id("alice", &owned.id).await.is_ok());
+        assert!(matches!(
+            service.get_reading_by_id("bob", &owned.id).await,
+            Err(BloodPressureServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_reading_applies_create_validation() {
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+
+        let request = CreateBloodPressureRequest {
+            systolic: 80,
+            diastolic: 90, // Not lower than systolic
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+            position: None,
+            arm: None,
+            device_id: None,
+        };
+
+        let result = service.update_reading("alice", &uuid::Uuid::new_v4().to_string(), request).await;
+        assert!(matches!(result, Err(BloodPressureServiceError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_reading_is_not_found() {
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+
+        let result = service.delete_reading("alice", &uuid::Uuid::new_v4().to_string()).await;
+        assert!(matches!(result, Err(BloodPressureServiceError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_averaged_and_scoped_to_owner() {
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let data_reading = |minute: i64, systolic: u16, diastolic: u16| {
+            my_health_guide_data::models::blood_pressure::BloodPressureReading {
+                id: uuid::Uuid::new_v4().to_string(),
+                user_id: "alice".to_string(),
+                systolic,
+                diastolic,
+                pulse: None,
+                notes: None,
+                timestamp: start + chrono::Duration::minutes(minute),
+                position: None,
+                arm: None,
+                device_id: None,
+                category: None,
+                created_at: start,
+                updated_at: start,
+            }
+        };
+        let readings = vec![data_reading(0, 150, 95), data_reading(1, 130, 84), data_reading(2, 126, 80), data_reading(30, 118, 76)];
+        let session = my_health_guide_data::models::blood_pressure::BloodPressureSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "alice".to_string(),
+            discard_first: true,
+            notes: None,
+            reading_ids: readings[..3].iter().map(|reading| reading.id.clone()).collect(),
+            created_at: start,
+        };
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::with_readings(readings)
+            .with_sessions(vec![session.clone()]);
+        let service = BloodPressureService::new(mock_repo);
+
+        let found = service.get_session_by_id("alice", &session.id).await.unwrap();
+        assert_eq!(found.readings.len(), 3);
+        let average = found.average.unwrap();
+        assert_eq!((average.systolic, average.diastolic, average.reading_count), (128.0, 82.0, 2));
+        assert!(matches!(
+            service.get_session_by_id("bob", &session.id).await,
+            Err(BloodPressureServiceError::NotFound(_))
+        ));
+
+        let (averaged, total_count) = service.get_session_averaged_readings(
+            "alice", BloodPressureFilter::default(), None, None, Some(false),
+        ).await.unwrap();
+        assert_eq!(total_count, 2);
+        assert_eq!(averaged[0].id, session.id);
+        assert_eq!((averaged[0].systolic, averaged[0].diastolic), (128, 82));
+        assert_eq!(averaged[1].systolic, 118);
+
+        // A page counts the session once
+        let (page, total_count) = service.get_session_averaged_readings(
+            "alice", BloodPressureFilter::default(), Some(1), Some(1), Some(false),
+        ).await.unwrap();
+        assert_eq!((page.len(), total_count), (1, 2));
+        assert_eq!(page[0].systolic, 118);
+
+        let (sessions, total_count) = service.get_sessions("alice", Some(10), None).await.unwrap();
+        assert_eq!((sessions.len(), total_count), (1, 1));
+        assert_eq!(sessions[0].readings.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_create_session_validates_every_reading() {
+        let mock_repo = my_health_guide_data::repository::tests::MockBloodPressureRepository::new();
+        let service = BloodPressureService::new(mock_repo);
+
+        let reading = |systolic: u16, diastolic: u16| CreateBloodPressureRequest {
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+            position: None,
+            arm: None,
+            device_id: None,
+        };
+
+        let single = CreateBloodPressureSessionRequest { readings: vec![reading(120, 80)], discard_first: false, notes: None };
+        assert!(matches!(service.create_session("alice", single).await, Err(BloodPressureServiceError::ValidationError(_))));
+
+        let invalid = CreateBloodPressureSessionRequest {
+            readings: vec![reading(120, 80), reading(80, 90)],
+            discard_first: false,
+            notes: None,
+        };
+        assert!(matches!(service.create_session("alice", invalid).await, Err(BloodPressureServiceError::ValidationError(_))));
+    }
 }
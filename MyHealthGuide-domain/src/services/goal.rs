@@ -0,0 +1,587 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+use chrono::{DateTime, Days, Duration, Utc};
+use validator::Validate;
+use async_trait::async_trait;
+
+use crate::entities::blood_pressure::{BloodPressureFilter, BloodPressureReading};
+use crate::entities::goal::{
+    BloodPressureGoalProgress, GoalProgress, Goals, GoalsWithProgress, ReadingFrequencyProgress, SetGoalsRequest,
+    WeightGoalProgress,
+};
+use crate::entities::weight::WeightReading;
+use crate::entities::conversions;
+use my_health_guide_data::repository::{GoalRepositoryTrait, RepositoryError};
+use crate::services::blood_pressure::{validation_message, BloodPressureServiceError, BloodPressureServiceTrait};
+use crate::services::insights;
+use crate::services::weight::{WeightServiceError, WeightServiceTrait};
+
+/// Period the share of readings in target is computed over, in days
+const BLOOD_PRESSURE_PERIOD_DAYS: i64 = 30;
+
+/// Number of 7 day periods the reading frequency streaks look back over
+const FREQUENCY_PERIOD_WEEKS: i64 = 12;
+
+/// Period the weight trend is fitted over, in days
+const WEIGHT_PERIOD_DAYS: i64 = 90;
+
+/// A weight within this many kilograms of the target counts as reached
+const WEIGHT_REACHED_MARGIN_KG: f32 = 0.5;
+
+/// Furthest ahead a goal is projected, in days
+const MAX_PROJECTION_DAYS: f64 = 365.0;
+
+/// Seconds in a day, the time unit of goal projections
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+/// Goal service errors
+#[derive(Debug, Error)]
+pub enum GoalServiceError {
+    /// Validation error
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    /// The user has no goals set
+    #[error("Goals not found: {0}")]
+    NotFound(String),
+
+    /// Repository error
+    #[error("Repository error: {0}")]
+    RepositoryError(String),
+
+    /// Storage unavailable error
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+}
+
+impl From<BloodPressureServiceError> for GoalServiceError {
+    fn from(err: BloodPressureServiceError) -> Self {
+        match err {
+            BloodPressureServiceError::StorageUnavailable(msg) => GoalServiceError::StorageUnavailable(msg),
+            _ => GoalServiceError::RepositoryError(err.to_string()),
+        }
+    }
+}
+
+impl From<WeightServiceError> for GoalServiceError {
+    fn from(err: WeightServiceError) -> Self {
+        match err {
+            WeightServiceError::StorageUnavailable(msg) => GoalServiceError::StorageUnavailable(msg),
+            _ => GoalServiceError::RepositoryError(err.to_string()),
+        }
+    }
+}
+
+/// Trait for goal service operations
+#[async_trait]
+pub trait GoalServiceTrait {
+    /// Calculate progress towards goals from a user's readings
+    ///
+    /// The blood pressure readings should cover the last 12 weeks with each
+    /// session averaged into one reading, and the weight readings the last
+    /// 90 days. Readings taken after `now` are ignored.
+    fn calculate_progress(
+        &self,
+        goals: &Goals,
+        blood_pressure_readings: &[BloodPressureReading],
+        weight_readings: &[WeightReading],
+        now: DateTime<Utc>,
+    ) -> GoalProgress;
+
+    /// Get a user's goals
+    async fn get_goals(&self, user_id: &str) -> Result<Goals, GoalServiceError>;
+
+    /// Set a user's goals, replacing any set before
+    async fn set_goals(&self, user_id: &str, request: SetGoalsRequest) -> Result<Goals, GoalServiceError>;
+
+    /// Clear a user's goals
+    async fn delete_goals(&self, user_id: &str) -> Result<(), GoalServiceError>;
+
+    /// Get a user's goals with their progress computed from the user's readings
+    async fn get_progress(&self, user_id: &str) -> Result<GoalsWithProgress, GoalServiceError>;
+}
+
+/// Goal service for domain logic
+///
+/// Progress is computed from the readings kept by the blood pressure and
+/// weight services.
+pub struct GoalService<R: GoalRepositoryTrait> {
+    repository: R,
+    blood_pressure: Arc<dyn BloodPressureServiceTrait + Send + Sync>,
+    weight: Arc<dyn WeightServiceTrait + Send + Sync>,
+}
+
+impl<R: GoalRepositoryTrait> GoalService<R> {
+    /// Create a new goal service
+    pub fn new(
+        repository: R,
+        blood_pressure: Arc<dyn BloodPressureServiceTrait + Send + Sync>,
+        weight: Arc<dyn WeightServiceTrait + Send + Sync>,
+    ) -> Self {
+        Self { repository, blood_pressure, weight }
+    }
+
+    /// Map repository errors to service errors
+    fn map_repo_error(&self, err: RepositoryError) -> GoalServiceError {
+        match err {
+            RepositoryError::NotFound(msg) => GoalServiceError::NotFound(msg),
+            RepositoryError::Validation(msg) => GoalServiceError::ValidationError(msg),
+            RepositoryError::Unavailable(msg) => GoalServiceError::StorageUnavailable(msg),
+            _ => GoalServiceError::RepositoryError(err.to_string()),
+        }
+    }
+
+    /// Build the not found error for a user without goals
+    fn not_found(user_id: &str) -> GoalServiceError {
+        GoalServiceError::NotFound(format!("No goals set for user {}", user_id))
+    }
+}
+
+/// Check the rules of a set goals request that span several fields
+fn validate_goal_combination(request: &SetGoalsRequest) -> Result<(), GoalServiceError> {
+    match (request.target_systolic, request.target_diastolic) {
+        (Some(systolic), Some(diastolic)) if systolic <= diastolic => {
+            return Err(GoalServiceError::ValidationError(
+                "Target systolic must be greater than target diastolic".to_string(),
+            ));
+        },
+        (Some(_), None) | (None, Some(_)) => {
+            return Err(GoalServiceError::ValidationError(
+                "Target systolic and diastolic must be set together".to_string(),
+            ));
+        },
+        _ => {},
+    }
+
+    if request.target_systolic.is_none() && request.readings_per_week.is_none() && request.target_weight_kg.is_none() {
+        return Err(GoalServiceError::ValidationError("At least one goal must be set".to_string()));
+    }
+
+    Ok(())
+}
+
+/// The current and longest runs of hits, given oldest first
+fn streaks(hits: &[bool]) -> (usize, usize) {
+    let current = hits.iter().rev().take_while(|hit| **hit).count();
+    let best = hits.split(|hit| !hit).map(<[bool]>::len).max().unwrap_or(0);
+    (current, best)
+}
+
+/// Days from now until the trend through `points` reaches `target`
+///
+/// `points` are `(days relative to now, value)` and `latest` is the last
+/// value, which gives the direction the target lies in. Returns zero when the
+/// trend is already at or past the target, and `None` when it heads away
+/// from it, there are too few points, or it would take longer than a year.
+fn days_to_reach(points: &[(f64, f64)], target: f64, latest: f64) -> Option<f64> {
+    let direction = (target - latest).signum();
+    let (slope, now_value) = insights::least_squares_line(points)?;
+
+    if (target - now_value) * direction <= 0.0 {
+        return Some(0.0);
+    }
+    if slope * direction <= 0.0 {
+        return None;
+    }
+
+    let days = (target - now_value) / slope;
+    (days <= MAX_PROJECTION_DAYS).then_some(days)
+}
+
+/// Days between `now` and a timestamp, negative for the past
+fn days_from(now: DateTime<Utc>, timestamp: DateTime<Utc>) -> f64 {
+    (timestamp - now).num_seconds() as f64 / SECONDS_PER_DAY
+}
+
+/// The date `days` from now, rounded up to a whole day
+fn projected_date(now: DateTime<Utc>, days: f64) -> Option<chrono::NaiveDate> {
+    now.date_naive().checked_add_days(Days::new(days.ceil() as u64))
+}
+
+/// Progress towards the blood pressure targets over the last 30 days
+fn blood_pressure_progress(
+    target_systolic: u16,
+    target_diastolic: u16,
+    timeline: &[&BloodPressureReading],
+    now: DateTime<Utc>,
+) -> BloodPressureGoalProgress {
+    let since = now - Duration::days(BLOOD_PRESSURE_PERIOD_DAYS);
+    let readings: Vec<&BloodPressureReading> = timeline.iter()
+        .copied()
+        .filter(|reading| reading.timestamp >= since)
+        .collect();
+
+    let hits: Vec<bool> = readings.iter()
+        .map(|reading| reading.systolic < target_systolic && reading.diastolic < target_diastolic)
+        .collect();
+    let in_target_count = hits.iter().filter(|hit| **hit).count();
+    let (current_streak, best_streak) = streaks(&hits);
+
+    // Nothing to project while the latest reading is already in target
+    let projected_date = match readings.last() {
+        Some(latest) if current_streak == 0 => {
+            let days = |value: fn(&BloodPressureReading) -> u16, target: u16| {
+                let points: Vec<(f64, f64)> = readings.iter()
+                    .map(|reading| (days_from(now, reading.timestamp), value(reading) as f64))
+                    .collect();
+                // Below the target means reaching one less, in whole mmHg
+                let target = target as f64 - 1.0;
+                let latest = value(latest) as f64;
+                if latest <= target { Some(0.0) } else { days_to_reach(&points, target, latest) }
+            };
+            let systolic_days = days(|reading| reading.systolic, target_systolic);
+            let diastolic_days = days(|reading| reading.diastolic, target_diastolic);
+            systolic_days.zip(diastolic_days)
+                .and_then(|(systolic, diastolic)| projected_date(now, systolic.max(diastolic)))
+        },
+        _ => None,
+    };
+
+    BloodPressureGoalProgress {
+        target_systolic,
+        target_diastolic,
+        reading_count: readings.len(),
+        in_target_count,
+        percent_in_target: (!readings.is_empty())
+            .then(|| in_target_count as f64 / readings.len() as f64 * 100.0),
+        current_streak,
+        best_streak,
+        projected_date,
+    }
+}
+
+/// Progress towards the reading frequency target over rolling 7 day periods
+fn reading_frequency_progress(
+    target_per_week: u32,
+    timeline: &[&BloodPressureReading],
+    now: DateTime<Utc>,
+) -> ReadingFrequencyProgress {
+    // Readings in each 7 day period back from now, oldest period first
+    let counts: Vec<usize> = (0..FREQUENCY_PERIOD_WEEKS).rev()
+        .map(|weeks_ago| {
+            let end = now - Duration::weeks(weeks_ago);
+            let start = end - Duration::weeks(1);
+            timeline.iter()
+                .filter(|reading| reading.timestamp > start && reading.timestamp <= end)
+                .count()
+        })
+        .collect();
+
+    let hits: Vec<bool> = counts.iter().map(|count| *count >= target_per_week as usize).collect();
+    let (current_streak_weeks, best_streak_weeks) = streaks(&hits);
+    let readings_last_7_days = counts.last().copied().unwrap_or(0);
+
+    ReadingFrequencyProgress {
+        target_per_week,
+        readings_last_7_days,
+        met: readings_last_7_days >= target_per_week as usize,
+        current_streak_weeks,
+        best_streak_weeks,
+    }
+}
+
+/// Progress towards the weight target from the last 90 days of readings
+fn weight_progress(target_weight_kg: f32, readings: &[WeightReading], now: DateTime<Utc>) -> WeightGoalProgress {
+    let since = now - Duration::days(WEIGHT_PERIOD_DAYS);
+
+    // Order readings by when they were taken, ignoring any with a broken timestamp
+    let mut timeline: Vec<(DateTime<Utc>, f32)> = readings.iter()
+        .filter_map(|reading| {
+            DateTime::parse_from_rfc3339(&reading.timestamp)
+                .ok()
+                .map(|dt| (dt.with_timezone(&Utc), reading.weight_kg))
+        })
+        .filter(|(timestamp, _)| *timestamp >= since && *timestamp <= now)
+        .collect();
+    timeline.sort_by_key(|(timestamp, _)| *timestamp);
+
+    let current_weight_kg = timeline.last().map(|(_, weight)| *weight);
+    let remaining_kg = current_weight_kg.map(|current| target_weight_kg - current);
+    let reached = remaining_kg.is_some_and(|remaining| remaining.abs() <= WEIGHT_REACHED_MARGIN_KG);
+
+    let projected_date = match current_weight_kg {
+        Some(current) if !reached => {
+            let points: Vec<(f64, f64)> = timeline.iter()
+                .map(|(timestamp, weight)| (days_from(now, *timestamp), *weight as f64))
+                .collect();
+            days_to_reach(&points, target_weight_kg as f64, current as f64)
+                .and_then(|days| projected_date(now, days))
+        },
+        _ => None,
+    };
+
+    WeightGoalProgress {
+        target_weight_kg,
+        current_weight_kg,
+        remaining_kg,
+        reached,
+        projected_date,
+    }
+}
+
+#[async_trait]
+impl<R: GoalRepositoryTrait + Send + Sync> GoalServiceTrait for GoalService<R> {
+    /// Calculate progress towards goals from a user's readings
+    fn calculate_progress(
+        &self,
+        goals: &Goals,
+        blood_pressure_readings: &[BloodPressureReading],
+        weight_readings: &[WeightReading],
+        now: DateTime<Utc>,
+    ) -> GoalProgress {
+        let mut timeline: Vec<&BloodPressureReading> = blood_pressure_readings.iter()
+            .filter(|reading| reading.timestamp <= now)
+            .collect();
+        timeline.sort_by_key(|reading| reading.timestamp);
+
+        GoalProgress {
+            blood_pressure: goals.target_systolic.zip(goals.target_diastolic)
+                .map(|(systolic, diastolic)| blood_pressure_progress(systolic, diastolic, &timeline, now)),
+            reading_frequency: goals.readings_per_week
+                .map(|target| reading_frequency_progress(target, &timeline, now)),
+            weight: goals.target_weight_kg
+                .map(|target| weight_progress(target, weight_readings, now)),
+            generated_at: now,
+        }
+    }
+
+    /// Get a user's goals
+    async fn get_goals(&self, user_id: &str) -> Result<Goals, GoalServiceError> {
+        let goals = self.repository.get_goals(user_id)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        goals.map(conversions::convert_to_domain_goals)
+            .ok_or_else(|| Self::not_found(user_id))
+    }
+
+    /// Set a user's goals
+    async fn set_goals(&self, user_id: &str, request: SetGoalsRequest) -> Result<Goals, GoalServiceError> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(GoalServiceError::ValidationError(validation_message(&validation_errors)));
+        }
+        validate_goal_combination(&request)?;
+
+        let data_request = conversions::convert_to_data_set_goals_request(&request);
+        let goals = self.repository.set_goals(user_id, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        Ok(conversions::convert_to_domain_goals(goals))
+    }
+
+    /// Clear a user's goals
+    async fn delete_goals(&self, user_id: &str) -> Result<(), GoalServiceError> {
+        let deleted = self.repository.delete_goals(user_id)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        if deleted { Ok(()) } else { Err(Self::not_found(user_id)) }
+    }
+
+    /// Get a user's goals with their progress
+    async fn get_progress(&self, user_id: &str) -> Result<GoalsWithProgress, GoalServiceError> {
+        let goals = self.get_goals(user_id).await?;
+        let now = Utc::now();
+
+        // Only load the readings the goals that are set look at
+        let blood_pressure_readings = if goals.target_systolic.is_some() || goals.readings_per_week.is_some() {
+            let period = Duration::weeks(FREQUENCY_PERIOD_WEEKS).max(Duration::days(BLOOD_PRESSURE_PERIOD_DAYS));
+            let filter = BloodPressureFilter::date_range(Some(now - period), Some(now));
+            let (readings, _) = self.blood_pressure
+                .get_session_averaged_readings(user_id, filter, None, None, Some(false))
+                .await?;
+            readings
+        } else {
+            Vec::new()
+        };
+
+        let weight_readings = if goals.target_weight_kg.is_some() {
+            let start_date = (now - Duration::days(WEIGHT_PERIOD_DAYS)).to_rfc3339();
+            let (readings, _) = self.weight
+                .get_filtered_readings(user_id, Some(start_date), None, None, None, Some(false))
+                .await?;
+            readings
+        } else {
+            Vec::new()
+        };
+
+        let progress = self.calculate_progress(&goals, &blood_pressure_readings, &weight_readings, now);
+        Ok(GoalsWithProgress { goals, progress })
+    }
+}
+
+/// Create a default goal service using the repository from data layer
+pub fn create_default_goal_service(
+    blood_pressure: Arc<dyn BloodPressureServiceTrait + Send + Sync>,
+    weight: Arc<dyn WeightServiceTrait + Send + Sync>,
+) -> impl GoalServiceTrait + Send + Sync {
+    let repository = my_health_guide_data::repository::GoalRepository::new();
+    GoalService::new(repository, blood_pressure, weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::blood_pressure::BloodPressureService;
+    use crate::services::weight::WeightService;
+    use my_health_guide_data::repository::tests::{MockBloodPressureRepository, MockGoalRepository, MockWeightRepository};
+
+    fn service() -> GoalService<MockGoalRepository> {
+        GoalService::new(
+            MockGoalRepository::new(),
+            Arc::new(BloodPressureService::new(MockBloodPressureRepository::new())),
+            Arc::new(WeightService::new(MockWeightRepository::new())),
+        )
+    }
+
+    fn goals(request: SetGoalsRequest) -> Goals {
+        Goals {
+            user_id: "user-1".to_string(),
+            target_systolic: request.target_systolic,
+            target_diastolic: request.target_diastolic,
+            readings_per_week: request.readings_per_week,
+            target_weight_kg: request.target_weight_kg,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn reading(now: DateTime<Utc>, days_ago: i64, systolic: u16, diastolic: u16) -> BloodPressureReading {
+        let timestamp = now - Duration::days(days_ago);
+        BloodPressureReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "user-1".to_string(),
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp,
+            position: None,
+            arm: None,
+            device_id: None,
+            created_at: timestamp,
+            updated_at: timestamp,
+        }
+    }
+
+    fn weight(now: DateTime<Utc>, days_ago: i64, weight_kg: f32) -> WeightReading {
+        WeightReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "user-1".to_string(),
+            weight_kg,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: (now - Duration::days(days_ago)).to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_blood_pressure_progress() {
+        let now = Utc::now();
+        let goals = goals(SetGoalsRequest {
+            target_systolic: Some(130),
+            target_diastolic: Some(80),
+            ..Default::default()
+        });
+
+        // Two in target, one above, then the last two in target; the first is too old to count
+        let readings = vec![
+            reading(now, 40, 120, 70),
+            reading(now, 9, 125, 75),
+            reading(now, 7, 128, 78),
+            reading(now, 5, 135, 78),
+            reading(now, 3, 126, 76),
+            reading(now, 1, 124, 74),
+        ];
+        let progress = service().calculate_progress(&goals, &readings, &[], now).blood_pressure.unwrap();
+        assert_eq!((progress.reading_count, progress.in_target_count), (5, 4));
+        assert_eq!(progress.percent_in_target, Some(80.0));
+        assert_eq!((progress.current_streak, progress.best_streak), (2, 2));
+        assert!(progress.projected_date.is_none());
+
+        // Falling 1 mmHg a day from 140/85, the systolic target is reached last
+        let readings: Vec<_> = (0..5).map(|i| reading(now, 4 - i, 140 - i as u16, 85 - i as u16)).collect();
+        let progress = service().calculate_progress(&goals, &readings, &[], now).blood_pressure.unwrap();
+        assert_eq!((progress.current_streak, progress.best_streak), (0, 0));
+        assert_eq!(progress.projected_date, Some(now.date_naive() + Days::new(7)));
+
+        // A rising trend never gets there
+        let readings: Vec<_> = (0..5).map(|i| reading(now, 4 - i, 135 + i as u16, 85)).collect();
+        let progress = service().calculate_progress(&goals, &readings, &[], now).blood_pressure.unwrap();
+        assert!(progress.projected_date.is_none());
+    }
+
+    #[test]
+    fn test_reading_frequency_progress() {
+        let now = Utc::now();
+        let goals = goals(SetGoalsRequest { readings_per_week: Some(3), ..Default::default() });
+
+        // Three readings in each of the last two weeks, one the week before
+        let readings: Vec<_> = [1, 3, 5, 8, 10, 12, 16].iter().map(|days| reading(now, *days, 120, 75)).collect();
+        let progress = service().calculate_progress(&goals, &readings, &[], now);
+        let frequency = progress.reading_frequency.unwrap();
+        assert_eq!(frequency.readings_last_7_days, 3);
+        assert!(frequency.met);
+        assert_eq!((frequency.current_streak_weeks, frequency.best_streak_weeks), (2, 2));
+        assert!(progress.blood_pressure.is_none() && progress.weight.is_none());
+    }
+
+    #[test]
+    fn test_weight_progress() {
+        let now = Utc::now();
+        let goals = goals(SetGoalsRequest { target_weight_kg: Some(75.0), ..Default::default() });
+
+        // Losing 0.1 kg a day from 80 kg, with a reading too broken to use
+        let mut readings: Vec<_> = (0..=10).map(|i| weight(now, 20 - 2 * i, 80.0 - 0.2 * i as f32)).collect();
+        readings.push(WeightReading { timestamp: "yesterday".to_string(), ..weight(now, 0, 60.0) });
+        let progress = service().calculate_progress(&goals, &[], &readings, now).weight.unwrap();
+        assert_eq!(progress.current_weight_kg, Some(78.0));
+        assert!((progress.remaining_kg.unwrap() + 3.0).abs() < 1e-4);
+        assert!(!progress.reached);
+        let projected = progress.projected_date.unwrap();
+        assert!((29..=31).contains(&(projected - now.date_naive()).num_days()));
+
+        // Within half a kilogram counts as reached, with nothing left to project
+        let readings = vec![weight(now, 1, 75.4)];
+        let progress = service().calculate_progress(&goals, &[], &readings, now).weight.unwrap();
+        assert!(progress.reached);
+        assert!(progress.projected_date.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_get_and_delete_goals() {
+        let service = service();
+        assert!(matches!(service.get_goals("user-1").await, Err(GoalServiceError::NotFound(_))));
+
+        let invalid = [
+            SetGoalsRequest::default(),
+            SetGoalsRequest { target_systolic: Some(130), ..Default::default() },
+            SetGoalsRequest { target_systolic: Some(100), target_diastolic: Some(100), ..Default::default() },
+            SetGoalsRequest { readings_per_week: Some(0), ..Default::default() },
+        ];
+        for request in invalid {
+            let result = service.set_goals("user-1", request).await;
+            assert!(matches!(result, Err(GoalServiceError::ValidationError(_))));
+        }
+
+        let request = SetGoalsRequest {
+            target_systolic: Some(130),
+            target_diastolic: Some(80),
+            readings_per_week: Some(7),
+            target_weight_kg: None,
+        };
+        let set = service.set_goals("user-1", request).await.unwrap();
+        assert_eq!(set.readings_per_week, Some(7));
+
+        let with_progress = service.get_progress("user-1").await.unwrap();
+        assert_eq!(with_progress.progress.blood_pressure.unwrap().reading_count, 0);
+        assert!(!with_progress.progress.reading_frequency.unwrap().met);
+        assert!(with_progress.progress.weight.is_none());
+
+        service.delete_goals("user-1").await.unwrap();
+        assert!(matches!(service.delete_goals("user-1").await, Err(GoalServiceError::NotFound(_))));
+    }
+}
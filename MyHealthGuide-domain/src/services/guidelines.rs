@@ -0,0 +1,313 @@
+//! Clinical guideline sets used to categorize blood pressure
+//!
+//! Each [`GuidelineSet`] is a [`ThresholdTable`]. The deployment picks one
+//! with the `BP_GUIDELINE` variable, and users may pick another named set in
+//! their profile. A user's set categorizes their stored readings and decides
+//! which readings raise alerts; requests may still ask for another one when
+//! reporting insights. Stored categories follow the guideline that applied
+//! when the reading was written.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::entities::blood_pressure::BloodPressureCategory;
+
+/// A systolic and diastolic pair of limits, in mmHg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thresholds {
+    /// Systolic limit
+    pub systolic: u16,
+
+    /// Diastolic limit
+    pub diastolic: u16,
+}
+
+/// Lower limits of one category of a threshold table
+///
+/// A limit that is not set never matches, e.g. ACC/AHA elevated pressure
+/// only depends on the systolic value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdBand {
+    /// Category of readings in the band
+    pub category: BloodPressureCategory,
+
+    /// Lowest systolic value in the band
+    pub systolic: Option<u16>,
+
+    /// Lowest diastolic value in the band
+    pub diastolic: Option<u16>,
+}
+
+impl ThresholdBand {
+    fn new(category: BloodPressureCategory, systolic: Option<u16>, diastolic: Option<u16>) -> Self {
+        Self { category, systolic, diastolic }
+    }
+
+    fn contains(&self, systolic: u16, diastolic: u16) -> bool {
+        self.systolic.is_some_and(|limit| systolic >= limit)
+            || self.diastolic.is_some_and(|limit| diastolic >= limit)
+    }
+}
+
+/// The thresholds of a blood pressure classification
+///
+/// A reading falls in the highest band where either its systolic or its
+/// diastolic value reaches the band's limit, so when the two values fall in
+/// different categories the higher one wins. Readings below every band are
+/// hypotensive when either value is below the hypotension limits, and
+/// otherwise in the base category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdTable {
+    /// Category of readings below every band
+    pub base: BloodPressureCategory,
+
+    /// Bands ordered from the lowest to the highest
+    pub bands: Vec<ThresholdBand>,
+
+    /// Readings below either limit are hypotensive
+    pub hypotension: Option<Thresholds>,
+
+    /// Readings at or above either limit are a hypertensive crisis
+    pub crisis: Thresholds,
+}
+
+impl ThresholdTable {
+    /// Categorize a reading
+    pub fn categorize(&self, systolic: u16, diastolic: u16) -> BloodPressureCategory {
+        if let Some(band) = self.bands.iter().rev().find(|band| band.contains(systolic, diastolic)) {
+            return band.category;
+        }
+
+        match self.hypotension {
+            Some(limits) if systolic < limits.systolic || diastolic < limits.diastolic => {
+                BloodPressureCategory::Hypotension
+            },
+            _ => self.base,
+        }
+    }
+
+    /// Whether a reading needs immediate attention
+    pub fn is_crisis(&self, systolic: u16, diastolic: u16) -> bool {
+        systolic >= self.crisis.systolic || diastolic >= self.crisis.diastolic
+    }
+}
+
+/// A clinical guideline for categorizing blood pressure
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GuidelineSet {
+    /// ACC/AHA 2017: normal, elevated, stage 1 and 2, and crisis above 180/120
+    #[default]
+    AccAha2017,
+
+    /// ESC/ESH 2018: optimal, normal, high-normal and grades 1 to 3
+    EscEsh2018,
+
+    /// ISH 2020: normal, high-normal and grades 1 and 2
+    Ish2020,
+
+    /// Thresholds configured for the deployment
+    Custom(ThresholdTable),
+}
+
+impl FromStr for GuidelineSet {
+    type Err = String;
+
+    /// Parse a named guideline set; custom tables are only read from the environment
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "acc_aha" | "acc_aha_2017" => Ok(GuidelineSet::AccAha2017),
+            "esc_esh" | "esc_esh_2018" => Ok(GuidelineSet::EscEsh2018),
+            "ish" | "ish_2020" => Ok(GuidelineSet::Ish2020),
+            _ => Err(format!("Unknown guideline set: {}", s)),
+        }
+    }
+}
+
+impl GuidelineSet {
+    /// Identifier of the guideline set, as accepted by `BP_GUIDELINE`
+    pub fn name(&self) -> &'static str {
+        match self {
+            GuidelineSet::AccAha2017 => "acc_aha_2017",
+            GuidelineSet::EscEsh2018 => "esc_esh_2018",
+            GuidelineSet::Ish2020 => "ish_2020",
+            GuidelineSet::Custom(_) => "custom",
+        }
+    }
+
+    /// The threshold table of the guideline set
+    pub fn table(&self) -> Cow<'_, ThresholdTable> {
+        use BloodPressureCategory::*;
+
+        let table = match self {
+            GuidelineSet::AccAha2017 => ThresholdTable {
+                base: Normal,
+                bands: vec![
+                    ThresholdBand::new(Elevated, Some(120), None),
+                    ThresholdBand::new(Hypertension1, Some(130), Some(80)),
+                    ThresholdBand::new(Hypertension2, Some(140), Some(90)),
+                    // Strictly above 180/120
+                    ThresholdBand::new(HypertensiveCrisis, Some(181), Some(121)),
+                ],
+                hypotension: Some(Thresholds { systolic: 90, diastolic: 60 }),
+                crisis: Thresholds { systolic: 181, diastolic: 121 },
+            },
+            GuidelineSet::EscEsh2018 => ThresholdTable {
+                base: Optimal,
+                bands: vec![
+                    ThresholdBand::new(Normal, Some(120), Some(80)),
+                    ThresholdBand::new(HighNormal, Some(130), Some(85)),
+                    ThresholdBand::new(Hypertension1, Some(140), Some(90)),
+                    ThresholdBand::new(Hypertension2, Some(160), Some(100)),
+                    ThresholdBand::new(Hypertension3, Some(180), Some(110)),
+                ],
+                hypotension: Some(Thresholds { systolic: 90, diastolic: 60 }),
+                crisis: Thresholds { systolic: 180, diastolic: 110 },
+            },
+            GuidelineSet::Ish2020 => ThresholdTable {
+                base: Normal,
+                bands: vec![
+                    ThresholdBand::new(HighNormal, Some(130), Some(85)),
+                    ThresholdBand::new(Hypertension1, Some(140), Some(90)),
+                    ThresholdBand::new(Hypertension2, Some(160), Some(100)),
+                ],
+                hypotension: Some(Thresholds { systolic: 90, diastolic: 60 }),
+                crisis: Thresholds { systolic: 180, diastolic: 110 },
+            },
+            GuidelineSet::Custom(table) => return Cow::Borrowed(table),
+        };
+
+        Cow::Owned(table)
+    }
+
+    /// Categorize a reading
+    pub fn categorize(&self, systolic: u16, diastolic: u16) -> BloodPressureCategory {
+        self.table().categorize(systolic, diastolic)
+    }
+
+    /// Whether a reading is a hypertensive crisis under this guideline
+    pub fn is_crisis(&self, systolic: u16, diastolic: u16) -> bool {
+        self.table().is_crisis(systolic, diastolic)
+    }
+
+    /// Read the guideline set from the environment, defaulting to ACC/AHA 2017
+    ///
+    /// `BP_GUIDELINE=custom` reads the table as JSON from `BP_GUIDELINE_THRESHOLDS`.
+    pub fn from_env() -> Self {
+        let Ok(value) = std::env::var("BP_GUIDELINE") else {
+            return GuidelineSet::default();
+        };
+
+        if value.eq_ignore_ascii_case("custom") {
+            let table = std::env::var("BP_GUIDELINE_THRESHOLDS")
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str::<ThresholdTable>(&json).map_err(|e| e.to_string()));
+            return match table {
+                Ok(table) => GuidelineSet::Custom(table),
+                Err(e) => {
+                    warn!("Invalid BP_GUIDELINE_THRESHOLDS ({}), using ACC/AHA 2017", e);
+                    GuidelineSet::default()
+                },
+            };
+        }
+
+        value.parse().unwrap_or_else(|e| {
+            warn!("{}, using ACC/AHA 2017", e);
+            GuidelineSet::default()
+        })
+    }
+}
+
+/// The deployment's guideline set, read from the environment on first use
+static GUIDELINE_SET: Lazy<GuidelineSet> = Lazy::new(|| {
+    let guideline = GuidelineSet::from_env();
+    info!("Categorizing blood pressure with the {} guideline set", guideline.name());
+    guideline
+});
+
+/// Get the deployment's guideline set
+pub fn guideline_set() -> GuidelineSet {
+    GUIDELINE_SET.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BloodPressureCategory::*;
+
+    #[test]
+    fn test_acc_aha_2017() {
+        let guideline = GuidelineSet::AccAha2017;
+
+        assert_eq!(guideline.categorize(85, 55), Hypotension);
+        assert_eq!(guideline.categorize(110, 75), Normal);
+        assert_eq!(guideline.categorize(125, 75), Elevated);
+        assert_eq!(guideline.categorize(120, 85), Hypertension1);
+        assert_eq!(guideline.categorize(145, 75), Hypertension2);
+        assert_eq!(guideline.categorize(180, 120), Hypertension2);
+        assert_eq!(guideline.categorize(181, 75), HypertensiveCrisis);
+        assert_eq!(guideline.categorize(120, 121), HypertensiveCrisis);
+
+        // Crisis is strictly above 180/120, for categories and checks alike
+        assert!(!guideline.is_crisis(180, 120));
+        assert!(guideline.is_crisis(181, 80));
+        assert!(guideline.is_crisis(150, 121));
+    }
+
+    #[test]
+    fn test_esc_esh_2018() {
+        let guideline = GuidelineSet::EscEsh2018;
+
+        assert_eq!(guideline.categorize(115, 75), Optimal);
+        assert_eq!(guideline.categorize(125, 82), Normal);
+        assert_eq!(guideline.categorize(135, 80), HighNormal);
+        assert_eq!(guideline.categorize(150, 85), Hypertension1);
+        assert_eq!(guideline.categorize(130, 105), Hypertension2);
+        assert_eq!(guideline.categorize(180, 90), Hypertension3);
+        assert!(guideline.is_crisis(170, 110));
+    }
+
+    #[test]
+    fn test_ish_2020() {
+        let guideline = GuidelineSet::Ish2020;
+
+        assert_eq!(guideline.categorize(88, 70), Hypotension);
+        assert_eq!(guideline.categorize(125, 82), Normal);
+        assert_eq!(guideline.categorize(132, 80), HighNormal);
+        assert_eq!(guideline.categorize(145, 92), Hypertension1);
+        assert_eq!(guideline.categorize(190, 115), Hypertension2);
+        assert!(guideline.is_crisis(190, 115));
+    }
+
+    #[test]
+    fn test_custom_table_from_json() {
+        let table: ThresholdTable = serde_json::from_str(r#"{
+            "base": "Normal",
+            "bands": [
+                {"category": "Hypertension1", "systolic": 135, "diastolic": 85},
+                {"category": "Hypertension2", "systolic": 150, "diastolic": null}
+            ],
+            "hypotension": null,
+            "crisis": {"systolic": 200, "diastolic": 130}
+        }"#).unwrap();
+        let guideline = GuidelineSet::Custom(table);
+
+        assert_eq!(guideline.name(), "custom");
+        assert_eq!(guideline.categorize(80, 50), Normal);
+        assert_eq!(guideline.categorize(134, 85), Hypertension1);
+        assert_eq!(guideline.categorize(150, 70), Hypertension2);
+        assert!(!guideline.is_crisis(190, 120));
+    }
+
+    #[test]
+    fn test_parse_guideline_set() {
+        assert_eq!("ACC-AHA-2017".parse::<GuidelineSet>().unwrap(), GuidelineSet::AccAha2017);
+        assert_eq!("esc_esh".parse::<GuidelineSet>().unwrap(), GuidelineSet::EscEsh2018);
+        assert_eq!("ish_2020".parse::<GuidelineSet>().unwrap(), GuidelineSet::Ish2020);
+        assert!("custom".parse::<GuidelineSet>().is_err());
+        assert!("jnc7".parse::<GuidelineSet>().is_err());
+    }
+}
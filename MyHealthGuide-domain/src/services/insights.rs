@@ -1,23 +1,798 @@
-use crate::entities::blood_pressure::BloodPressureCategory;
+use std::collections::HashMap;
+use std::ops::Range;
 
-/// Categorize blood pressure based on measurements
+use chrono::{Duration, NaiveDate, Timelike};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+
+use crate::services::guidelines::{guideline_set, GuidelineSet};
+use crate::entities::blood_pressure::{
+    BloodPressureCategory, BloodPressureReading, BloodPressureSession, BloodPressureTrend, DippingPattern,
+    MeasurementArm, MeasurementPosition, PairedReadingAlert, PairedReadingAlertKind, PediatricCategory,
+    PediatricClassification, PediatricPatient, PercentileBand, SessionAverage, Sex, TimeOfDayAverage,
+};
+use crate::entities::weight::BmiCategory;
+
+/// Categorize blood pressure with the deployment's guideline set
 pub fn categorize_blood_pressure(systolic: u16, diastolic: u16) -> BloodPressureCategory {
-    if systolic >= 180 || diastolic >= 120 {
-        BloodPressureCategory::HypertensiveCrisis
-    } else if systolic >= 140 || diastolic >= 90 {
-        BloodPressureCategory::Hypertension2
-    } else if systolic >= 130 || diastolic >= 80 {
-        BloodPressureCategory::Hypertension1
-    } else if systolic >= 120 && diastolic < 80 {
-        BloodPressureCategory::Elevated
+    guideline_set().categorize(systolic, diastolic)
+}
+
+/// Upper bounds of the target range, exclusive (systolic, diastolic)
+const TARGET_UPPER: (f64, f64) = (130.0, 80.0);
+
+/// Lower bounds of the target range, inclusive (systolic, diastolic)
+const TARGET_LOWER: (f64, f64) = (90.0, 60.0);
+
+/// Two-sided 95% critical values of Student's t for 1 to 30 degrees of freedom
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// Arithmetic mean, or `None` for an empty series
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Sample standard deviation, or `None` with fewer than two values
+pub fn standard_deviation(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values)?;
+    let squares: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+    Some((squares / (values.len() - 1) as f64).sqrt())
+}
+
+/// Average real variability: the mean absolute change between consecutive values
+///
+/// Unlike the standard deviation this depends on the order of the series,
+/// so values have to be in the order they were measured.
+pub fn average_real_variability(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let changes: f64 = values.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum();
+    Some(changes / (values.len() - 1) as f64)
+}
+
+/// Coefficient of variation as a percentage of the mean
+pub fn coefficient_of_variation(values: &[f64]) -> Option<f64> {
+    let mean = mean(values)?;
+    if mean == 0.0 {
+        return None;
+    }
+    Some(standard_deviation(values)? / mean * 100.0)
+}
+
+/// Pulse pressure: the difference between systolic and diastolic pressure
+pub fn pulse_pressure(systolic: f64, diastolic: f64) -> f64 {
+    systolic - diastolic
+}
+
+/// Mean arterial pressure, estimated as diastolic plus a third of the pulse pressure
+pub fn mean_arterial_pressure(systolic: f64, diastolic: f64) -> f64 {
+    diastolic + pulse_pressure(systolic, diastolic) / 3.0
+}
+
+/// Whether a reading lies in the target range of 90-129 systolic and 60-79 diastolic
+///
+/// The upper bounds are the 2017 ACC/AHA treatment goal of below 130/80; the
+/// lower bounds exclude hypotensive readings.
+pub fn is_in_target_range(systolic: f64, diastolic: f64) -> bool {
+    (TARGET_LOWER.0..TARGET_UPPER.0).contains(&systolic)
+        && (TARGET_LOWER.1..TARGET_UPPER.1).contains(&diastolic)
+}
+
+/// Fit a least-squares line through `(weeks, mmHg)` points
+///
+/// Returns the slope in mmHg per week with its 95% confidence interval, or
+/// `None` with fewer than three points or when all points share one time.
+pub fn linear_trend(points: &[(f64, f64)]) -> Option<BloodPressureTrend> {
+    if points.len() < 3 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in points {
+        sxx += (x - mean_x).powi(2);
+        sxy += (x - mean_x) * (y - mean_y);
+        syy += (y - mean_y).powi(2);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let degrees_of_freedom = points.len() - 2;
+    // Rounding can push a perfect fit slightly below zero
+    let residual_variance = (syy - slope * sxy).max(0.0) / degrees_of_freedom as f64;
+    let margin = t_critical_95(degrees_of_freedom) * (residual_variance / sxx).sqrt();
+
+    Some(BloodPressureTrend {
+        slope_per_week: slope,
+        ci_lower: slope - margin,
+        ci_upper: slope + margin,
+    })
+}
+
+/// Fit a least-squares line through points, returning its slope and intercept
+///
+/// Returns `None` with fewer than three points or when all points share one
+/// x value.
+pub fn least_squares_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 3 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (mut sxx, mut sxy) = (0.0, 0.0);
+    for (x, y) in points {
+        sxx += (x - mean_x).powi(2);
+        sxy += (x - mean_x) * (y - mean_y);
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+/// Two-sided 95% critical value of Student's t
+///
+/// Beyond the table the values are stepped down towards the normal 1.96.
+fn t_critical_95(degrees_of_freedom: usize) -> f64 {
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        1..=30 => T_CRITICAL_95[degrees_of_freedom - 1],
+        31..=40 => 2.021,
+        41..=60 => 2.000,
+        61..=120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+/// Average the readings taken within `hours` of the local day
+///
+/// Local time is the reading time in `time_zone`, converted per reading so
+/// daylight saving is followed. Returns `None` when no reading falls in
+/// those hours.
+pub fn average_in_hours(
+    readings: &[BloodPressureReading],
+    time_zone: Tz,
+    hours: Range<u32>,
+) -> Option<TimeOfDayAverage> {
+    let in_hours: Vec<&BloodPressureReading> = readings.iter()
+        .filter(|reading| hours.contains(&reading.timestamp.with_timezone(&time_zone).hour()))
+        .collect();
+    if in_hours.is_empty() {
+        return None;
+    }
+
+    let count = in_hours.len() as f64;
+    Some(TimeOfDayAverage {
+        avg_systolic: in_hours.iter().map(|reading| reading.systolic as f64).sum::<f64>() / count,
+        avg_diastolic: in_hours.iter().map(|reading| reading.diastolic as f64).sum::<f64>() / count,
+        reading_count: in_hours.len(),
+    })
+}
+
+/// Classify a night-to-day systolic ratio
+pub fn classify_dipping(ratio: f64) -> DippingPattern {
+    if ratio <= 0.9 {
+        DippingPattern::Dipper
+    } else if ratio <= 1.0 {
+        DippingPattern::NonDipper
+    } else {
+        DippingPattern::ReverseDipper
+    }
+}
+
+/// Systolic drop on standing that counts as orthostatic hypotension, in mmHg
+const ORTHOSTATIC_SYSTOLIC_DROP: i32 = 20;
+
+/// Diastolic drop on standing that counts as orthostatic hypotension, in mmHg
+const ORTHOSTATIC_DIASTOLIC_DROP: i32 = 10;
+
+/// Systolic difference between arms that is clinically significant, in mmHg
+const INTER_ARM_SYSTOLIC_DIFFERENCE: i32 = 10;
+
+/// Compare the two readings of a pair
+fn paired_alert(
+    kind: PairedReadingAlertKind,
+    baseline: &BloodPressureReading,
+    compared: &BloodPressureReading,
+) -> PairedReadingAlert {
+    PairedReadingAlert {
+        kind,
+        baseline_reading_id: baseline.id.clone(),
+        compared_reading_id: compared.id.clone(),
+        systolic_difference: baseline.systolic as i32 - compared.systolic as i32,
+        diastolic_difference: baseline.diastolic as i32 - compared.diastolic as i32,
+        detected_at: baseline.timestamp.max(compared.timestamp),
+    }
+}
+
+/// Readings in the order they were taken
+fn in_time_order(readings: &[BloodPressureReading]) -> Vec<&BloodPressureReading> {
+    let mut timeline: Vec<&BloodPressureReading> = readings.iter().collect();
+    timeline.sort_by_key(|reading| reading.timestamp);
+    timeline
+}
+
+/// Detect orthostatic hypotension from standing readings taken soon after lying or sitting
+///
+/// Each standing reading is paired with the latest lying or sitting reading
+/// taken at most `window` before it. A drop of at least 20 systolic or 10
+/// diastolic raises an alert.
+pub fn detect_orthostatic_hypotension(readings: &[BloodPressureReading], window: Duration) -> Vec<PairedReadingAlert> {
+    let timeline = in_time_order(readings);
+    timeline.iter()
+        .filter(|reading| reading.position == Some(MeasurementPosition::Standing))
+        .filter_map(|standing| {
+            let baseline = timeline.iter()
+                .filter(|reading| matches!(reading.position, Some(MeasurementPosition::Lying | MeasurementPosition::Sitting)))
+                .rfind(|reading| reading.timestamp <= standing.timestamp && standing.timestamp - reading.timestamp <= window)?;
+            let alert = paired_alert(PairedReadingAlertKind::OrthostaticHypotension, baseline, standing);
+            (alert.systolic_difference >= ORTHOSTATIC_SYSTOLIC_DROP
+                || alert.diastolic_difference >= ORTHOSTATIC_DIASTOLIC_DROP)
+                .then_some(alert)
+        })
+        .collect()
+}
+
+/// Detect clinically significant differences between readings on the left and right arm
+///
+/// Each left arm reading is paired with the closest right arm reading taken
+/// within `window` of it, before or after. A systolic difference of at least
+/// 10 either way raises an alert.
+pub fn detect_inter_arm_differences(readings: &[BloodPressureReading], window: Duration) -> Vec<PairedReadingAlert> {
+    let timeline = in_time_order(readings);
+    timeline.iter()
+        .filter(|reading| reading.arm == Some(MeasurementArm::Left))
+        .filter_map(|left| {
+            let right = timeline.iter()
+                .filter(|reading| reading.arm == Some(MeasurementArm::Right))
+                .filter(|reading| (reading.timestamp - left.timestamp).abs() <= window)
+                .min_by_key(|reading| (reading.timestamp - left.timestamp).abs())?;
+            let alert = paired_alert(PairedReadingAlertKind::InterArmDifference, left, right);
+            (alert.systolic_difference.abs() >= INTER_ARM_SYSTOLIC_DIFFERENCE).then_some(alert)
+        })
+        .collect()
+}
+
+/// Average the readings of a measurement session
+///
+/// With `discard_first` the earliest reading is left out, as long as another
+/// one remains to average, and the average is categorized with `guideline`.
+/// Returns `None` for a session without readings.
+pub fn average_session(readings: &[BloodPressureReading], discard_first: bool, guideline: &GuidelineSet) -> Option<SessionAverage> {
+    let timeline = in_time_order(readings);
+    let averaged = &timeline[usize::from(discard_first && timeline.len() > 1)..];
+
+    let systolic = mean(&averaged.iter().map(|reading| reading.systolic as f64).collect::<Vec<_>>())?;
+    let diastolic = mean(&averaged.iter().map(|reading| reading.diastolic as f64).collect::<Vec<_>>())?;
+    let pulses: Vec<f64> = averaged.iter().filter_map(|reading| reading.pulse).map(f64::from).collect();
+
+    Some(SessionAverage {
+        systolic,
+        diastolic,
+        pulse: mean(&pulses),
+        category: guideline.categorize(systolic.round() as u16, diastolic.round() as u16),
+        reading_count: averaged.len(),
+    })
+}
+
+/// The value every item has in common, if there is one
+fn shared<T: PartialEq>(mut values: impl Iterator<Item = Option<T>>) -> Option<T> {
+    let first = values.next()??;
+    values.all(|value| value.as_ref() == Some(&first)).then_some(first)
+}
+
+/// A session's average as a single reading
+///
+/// The reading takes the session's ID and notes and the time of its first
+/// reading. Position, arm and device are kept only when every reading in the
+/// session shares them. Returns `None` for a session without readings.
+pub fn session_reading(session: &BloodPressureSession) -> Option<BloodPressureReading> {
+    let average = session.average?;
+    let first = session.readings.iter().min_by_key(|reading| reading.timestamp)?;
+
+    Some(BloodPressureReading {
+        id: session.id.clone(),
+        user_id: session.user_id.clone(),
+        systolic: average.systolic.round() as u16,
+        diastolic: average.diastolic.round() as u16,
+        pulse: average.pulse.map(|pulse| pulse.round() as u16),
+        notes: session.notes.clone(),
+        timestamp: first.timestamp,
+        position: shared(session.readings.iter().map(|reading| reading.position)),
+        arm: shared(session.readings.iter().map(|reading| reading.arm)),
+        device_id: shared(session.readings.iter().map(|reading| reading.device_id.clone())),
+        created_at: session.created_at,
+        updated_at: session.created_at,
+    })
+}
+
+/// Replace the readings that belong to a session with the session's average
+///
+/// A session stands in for all of its readings as soon as one of them is in
+/// `readings`, so the average always covers the whole session. Readings
+/// outside any session are kept as they are.
+pub fn collapse_sessions(readings: Vec<BloodPressureReading>, sessions: &[BloodPressureSession]) -> Vec<BloodPressureReading> {
+    let session_of: HashMap<&str, usize> = sessions.iter()
+        .enumerate()
+        .flat_map(|(index, session)| session.readings.iter().map(move |reading| (reading.id.as_str(), index)))
+        .collect();
+
+    let mut included = vec![false; sessions.len()];
+    let mut collapsed = Vec::with_capacity(readings.len());
+    for reading in readings {
+        match session_of.get(reading.id.as_str()) {
+            Some(&index) => included[index] = true,
+            None => collapsed.push(reading),
+        }
+    }
+
+    collapsed.extend(sessions.iter()
+        .zip(included)
+        .filter(|(_, included)| *included)
+        .filter_map(|(session, _)| session_reading(session)));
+    collapsed
+}
+
+/// Age from which the adult guideline sets apply
+pub const ADULT_AGE: u32 = 18;
+
+/// Oldest age in the AAP tables; from 13 the guideline uses static thresholds
+const AAP_TABLE_MAX_AGE: u32 = 12;
+
+/// Static thresholds capping the percentile ones, (elevated, stage 1, stage 2)
+const AAP_SYSTOLIC_CAPS: (u16, u16, u16) = (120, 130, 140);
+const AAP_DIASTOLIC_CAPS: (u16, u16, u16) = (80, 80, 90);
+
+/// Margin above the 95th percentile where stage 2 starts, in mmHg
+const AAP_STAGE_2_MARGIN: u16 = 12;
+
+/// AAP 2017 blood pressure percentiles for ages 1 to 12, see the file header for the source
+const AAP_2017_TABLE: &str = include_str!("../../data/aap_2017_blood_pressure.csv");
+
+static AAP_2017_ROWS: Lazy<Vec<PercentileRow>> = Lazy::new(|| parse_percentile_table(AAP_2017_TABLE));
+
+/// Blood pressure percentiles for one age, sex and height percentile
+#[derive(Debug, Clone, Copy)]
+struct PercentileRow {
+    sex: Sex,
+    age: u32,
+    height_percentile: u8,
+    height_cm: f64,
+    /// 50th, 90th and 95th percentiles
+    systolic: [u16; 3],
+    diastolic: [u16; 3],
+}
+
+/// Parse the embedded table, skipping comments and the header
+///
+/// The table is compiled in, so a malformed row is a bug rather than an
+/// input error.
+fn parse_percentile_table(table: &str) -> Vec<PercentileRow> {
+    table.lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .skip(1)
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            assert_eq!(fields.len(), 10, "malformed AAP 2017 row: {}", line);
+            let number = |index: usize| fields[index].parse::<u16>()
+                .unwrap_or_else(|_| panic!("malformed AAP 2017 row: {}", line));
+            PercentileRow {
+                sex: Sex::parse(fields[0]).unwrap_or_else(|| panic!("malformed AAP 2017 row: {}", line)),
+                age: number(1) as u32,
+                height_percentile: number(2) as u8,
+                height_cm: fields[3].parse()
+                    .unwrap_or_else(|_| panic!("malformed AAP 2017 row: {}", line)),
+                systolic: [number(4), number(5), number(6)],
+                diastolic: [number(7), number(8), number(9)],
+            }
+        })
+        .collect()
+}
+
+/// Pick the row for an age and sex whose height is closest to `height_cm`
+///
+/// Without a height the 50th height percentile is used.
+fn percentile_row(sex: Sex, age: u32, height_cm: Option<f64>) -> Option<PercentileRow> {
+    let rows = AAP_2017_ROWS.iter().filter(|row| row.sex == sex && row.age == age);
+    match height_cm {
+        Some(height_cm) => rows.min_by(|a, b| {
+            (a.height_cm - height_cm).abs().total_cmp(&(b.height_cm - height_cm).abs())
+        }),
+        None => rows.into_iter().find(|row| row.height_percentile == 50),
+    }
+    .copied()
+}
+
+/// Category of one measurement against its 90th and 95th percentiles and the static caps
+fn percentile_category(value: u16, p90: u16, p95: u16, caps: (u16, u16, u16)) -> PediatricCategory {
+    if value >= (p95 + AAP_STAGE_2_MARGIN).min(caps.2) {
+        PediatricCategory::Stage2
+    } else if value >= p95.min(caps.1) {
+        PediatricCategory::Stage1
+    } else if value >= p90.min(caps.0) {
+        PediatricCategory::Elevated
     } else {
-        BloodPressureCategory::Normal
+        PediatricCategory::Normal
+    }
+}
+
+/// Percentile band of one measurement
+fn percentile_band(value: u16, percentiles: [u16; 3]) -> PercentileBand {
+    let [p50, p90, p95] = percentiles;
+    if value >= p95 + AAP_STAGE_2_MARGIN {
+        PercentileBand::From95thPlus12
+    } else if value >= p95 {
+        PercentileBand::From95thTo95thPlus12
+    } else if value >= p90 {
+        PercentileBand::From90thTo95th
+    } else if value >= p50 {
+        PercentileBand::From50thTo90th
+    } else {
+        PercentileBand::Below50th
+    }
+}
+
+/// Classify a reading for a child or adolescent with the AAP 2017 guideline
+///
+/// Ages 1 to 12 are compared with the percentiles for the patient's age,
+/// sex and height; from 13 the guideline's static thresholds apply. The
+/// higher of the systolic and diastolic categories wins. Returns `None`
+/// below one year, which the tables do not cover, and from 18.
+pub fn classify_pediatric_blood_pressure(
+    systolic: u16,
+    diastolic: u16,
+    patient: &PediatricPatient,
+    on: NaiveDate,
+) -> Option<PediatricClassification> {
+    let age = patient.age_on(on)?;
+    if !(1..ADULT_AGE).contains(&age) {
+        return None;
+    }
+
+    if age > AAP_TABLE_MAX_AGE {
+        let category = if systolic >= 140 || diastolic >= 90 {
+            PediatricCategory::Stage2
+        } else if systolic >= 130 || diastolic >= 80 {
+            PediatricCategory::Stage1
+        } else if systolic >= 120 {
+            PediatricCategory::Elevated
+        } else {
+            PediatricCategory::Normal
+        };
+        return Some(PediatricClassification {
+            age_years: age,
+            sex: patient.sex,
+            height_percentile: None,
+            percentile: None,
+            category,
+        });
+    }
+
+    let row = percentile_row(patient.sex, age, patient.height_cm)?;
+    let category = percentile_category(systolic, row.systolic[1], row.systolic[2], AAP_SYSTOLIC_CAPS)
+        .max(percentile_category(diastolic, row.diastolic[1], row.diastolic[2], AAP_DIASTOLIC_CAPS));
+    let percentile = percentile_band(systolic, row.systolic).max(percentile_band(diastolic, row.diastolic));
+
+    Some(PediatricClassification {
+        age_years: age,
+        sex: patient.sex,
+        height_percentile: Some(row.height_percentile),
+        percentile: Some(percentile),
+        category,
+    })
+}
+
+/// Calculate body mass index from a weight in kilograms and a height in centimetres
+pub fn calculate_bmi(weight_kg: f32, height_cm: f32) -> f32 {
+    let height_m = height_cm / 100.0;
+    weight_kg / (height_m * height_m)
+}
+
+/// Categorize a BMI value using the WHO adult thresholds
+pub fn categorize_bmi(bmi: f32) -> BmiCategory {
+    if bmi < 18.5 {
+        BmiCategory::Underweight
+    } else if bmi < 25.0 {
+        BmiCategory::Normal
+    } else if bmi < 30.0 {
+        BmiCategory::Overweight
+    } else {
+        BmiCategory::Obese
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn reading_at(
+        minute: i64,
+        systolic: u16,
+        diastolic: u16,
+        position: Option<MeasurementPosition>,
+        arm: Option<MeasurementArm>,
+    ) -> BloodPressureReading {
+        let timestamp = "2024-01-01T08:00:00Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap()
+            + Duration::minutes(minute);
+        BloodPressureReading {
+            id: format!("reading-{}", minute),
+            user_id: "user-123".to_string(),
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp,
+            position,
+            arm,
+            device_id: None,
+            created_at: timestamp,
+            updated_at: timestamp,
+        }
+    }
+
+    #[test]
+    fn test_detect_orthostatic_hypotension() {
+        use MeasurementPosition::{Lying, Sitting, Standing};
+        let readings = vec![
+            // Systolic drop of 20 within the window
+            reading_at(0, 130, 80, Some(Lying), None),
+            reading_at(3, 110, 78, Some(Standing), None),
+            // Diastolic drop of 10, paired with the later sitting reading
+            reading_at(60, 150, 90, Some(Sitting), None),
+            reading_at(62, 125, 85, Some(Sitting), None),
+            reading_at(64, 120, 75, Some(Standing), None),
+            // A drop too small, and one too late to pair
+            reading_at(120, 130, 80, Some(Sitting), None),
+            reading_at(122, 115, 75, Some(Standing), None),
+            reading_at(200, 150, 90, Some(Sitting), None),
+            reading_at(220, 110, 70, Some(Standing), None),
+        ];
+
+        let alerts = detect_orthostatic_hypotension(&readings, Duration::minutes(5));
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].kind, PairedReadingAlertKind::OrthostaticHypotension);
+        assert_eq!((alerts[0].systolic_difference, alerts[0].diastolic_difference), (20, 2));
+        assert_eq!(alerts[1].baseline_reading_id, "reading-62");
+        assert_eq!(alerts[1].compared_reading_id, "reading-64");
+        assert_eq!((alerts[1].systolic_difference, alerts[1].diastolic_difference), (5, 10));
+        assert_eq!(alerts[1].detected_at, readings[4].timestamp);
+    }
+
+    #[test]
+    fn test_detect_inter_arm_differences() {
+        use MeasurementArm::{Left, Right};
+        let readings = vec![
+            reading_at(0, 142, 88, None, Some(Left)),
+            reading_at(1, 130, 84, None, Some(Right)),
+            // The right arm reading is taken first this time
+            reading_at(59, 128, 80, None, Some(Right)),
+            reading_at(60, 117, 78, None, Some(Left)),
+            // Under 10 apart, and a left reading without a right one
+            reading_at(120, 130, 80, None, Some(Left)),
+            reading_at(121, 125, 80, None, Some(Right)),
+            reading_at(300, 160, 95, None, Some(Left)),
+        ];
+
+        let alerts = detect_inter_arm_differences(&readings, Duration::minutes(5));
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].kind, PairedReadingAlertKind::InterArmDifference);
+        assert_eq!((alerts[0].systolic_difference, alerts[0].diastolic_difference), (12, 4));
+        assert_eq!(alerts[1].baseline_reading_id, "reading-60");
+        assert_eq!(alerts[1].compared_reading_id, "reading-59");
+        assert_eq!(alerts[1].systolic_difference, -11);
+        assert_eq!(alerts[1].detected_at, readings[3].timestamp);
+    }
+
+    fn session(id: &str, readings: Vec<BloodPressureReading>, discard_first: bool) -> BloodPressureSession {
+        BloodPressureSession {
+            id: id.to_string(),
+            user_id: "user-123".to_string(),
+            discard_first,
+            notes: Some("Morning".to_string()),
+            average: average_session(&readings, discard_first, &GuidelineSet::AccAha2017),
+            created_at: readings[0].created_at,
+            readings,
+        }
+    }
+
+    #[test]
+    fn test_average_session() {
+        let mut readings = vec![
+            reading_at(1, 128, 82, None, None),
+            reading_at(0, 150, 95, None, None),
+            reading_at(2, 124, 79, None, None),
+        ];
+        readings[1].pulse = Some(80);
+        readings[2].pulse = Some(70);
+
+        let average = average_session(&readings, false, &GuidelineSet::AccAha2017).unwrap();
+        assert!((average.systolic - 134.0).abs() < 1e-9);
+        assert!((average.diastolic - 85.333).abs() < 1e-3);
+        assert_eq!(average.pulse, Some(75.0));
+        assert_eq!(average.reading_count, 3);
+
+        // The earliest reading is dropped, not the first one in the list
+        let average = average_session(&readings, true, &GuidelineSet::AccAha2017).unwrap();
+        assert!((average.systolic - 126.0).abs() < 1e-9);
+        assert!((average.diastolic - 80.5).abs() < 1e-9);
+        assert_eq!(average.pulse, Some(70.0));
+        assert_eq!(average.category, BloodPressureCategory::Hypertension1);
+        let average = average_session(&readings, true, &GuidelineSet::EscEsh2018).unwrap();
+        assert_eq!(average.category, BloodPressureCategory::Normal);
+        assert_eq!(average.reading_count, 2);
+
+        // A lone reading is kept even when the first is discarded
+        assert_eq!(average_session(&readings[..1], true, &GuidelineSet::AccAha2017).unwrap().reading_count, 1);
+        assert!(average_session(&[], false, &GuidelineSet::AccAha2017).is_none());
+    }
+
+    #[test]
+    fn test_collapse_sessions() {
+        use MeasurementArm::{Left, Right};
+        let first = session("session-1", vec![
+            reading_at(0, 140, 90, Some(MeasurementPosition::Sitting), Some(Left)),
+            reading_at(1, 130, 84, Some(MeasurementPosition::Sitting), Some(Right)),
+        ], false);
+        let second = session("session-2", vec![
+            reading_at(60, 120, 80, None, None),
+            reading_at(61, 124, 82, None, None),
+        ], false);
+        let untouched = session("session-3", vec![reading_at(120, 118, 76, None, None)], false);
+
+        // Only one reading of the first session matched the caller's filter
+        let readings = vec![
+            first.readings[1].clone(),
+            reading_at(30, 122, 78, None, None),
+            second.readings[0].clone(),
+            second.readings[1].clone(),
+        ];
+        let collapsed = collapse_sessions(readings, &[first, second, untouched]);
+
+        let ids: Vec<&str> = collapsed.iter().map(|reading| reading.id.as_str()).collect();
+        assert_eq!(ids, vec!["reading-30", "session-1", "session-2"]);
+        assert_eq!((collapsed[1].systolic, collapsed[1].diastolic), (135, 87));
+        assert_eq!(collapsed[1].timestamp, reading_at(0, 0, 0, None, None).timestamp);
+        assert_eq!(collapsed[1].position, Some(MeasurementPosition::Sitting));
+        assert_eq!(collapsed[1].arm, None);
+        assert_eq!(collapsed[1].notes.as_deref(), Some("Morning"));
+        assert_eq!((collapsed[2].systolic, collapsed[2].diastolic), (122, 81));
+    }
+    
+    fn patient(sex: Sex, age: u32, height_cm: Option<f64>) -> (PediatricPatient, NaiveDate) {
+        let date_of_birth = NaiveDate::from_ymd_opt(2010, 6, 15).unwrap();
+        let on = NaiveDate::from_ymd_opt(2010 + age as i32, 7, 1).unwrap();
+        (PediatricPatient { date_of_birth, sex, height_cm }, on)
+    }
+
+    fn pediatric_category(systolic: u16, diastolic: u16, patient: (PediatricPatient, NaiveDate)) -> PediatricCategory {
+        classify_pediatric_blood_pressure(systolic, diastolic, &patient.0, patient.1).unwrap().category
+    }
+
+    #[test]
+    fn test_aap_table_is_complete() {
+        for sex in [Sex::Male, Sex::Female] {
+            for age in 1..=AAP_TABLE_MAX_AGE {
+                let rows: Vec<&PercentileRow> = AAP_2017_ROWS.iter()
+                    .filter(|row| row.sex == sex && row.age == age)
+                    .collect();
+                let percentiles: Vec<u8> = rows.iter().map(|row| row.height_percentile).collect();
+                assert_eq!(percentiles, vec![5, 10, 25, 50, 75, 90, 95], "{:?} aged {}", sex, age);
+                for row in rows {
+                    assert!(row.systolic[0] < row.systolic[1] && row.systolic[1] < row.systolic[2]);
+                    assert!(row.diastolic[0] < row.diastolic[1] && row.diastolic[1] < row.diastolic[2]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_aap_table_matches_screening_table() {
+        // The AAP screening table (Table 6) lists the 90th percentile at the
+        // 5th height percentile for ages 1 to 12
+        let boys = [(98, 52), (100, 55), (101, 58), (102, 60), (103, 63), (105, 66),
+            (106, 68), (107, 69), (107, 70), (108, 72), (110, 74), (113, 75)];
+        let girls = [(98, 54), (101, 58), (102, 60), (103, 62), (104, 64), (105, 67),
+            (106, 68), (107, 69), (108, 71), (109, 72), (111, 74), (114, 75)];
+
+        for (sex, screening) in [(Sex::Male, boys), (Sex::Female, girls)] {
+            for (index, (systolic, diastolic)) in screening.into_iter().enumerate() {
+                let age = index as u32 + 1;
+                let row = AAP_2017_ROWS.iter()
+                    .find(|row| row.sex == sex && row.age == age && row.height_percentile == 5)
+                    .unwrap();
+                assert_eq!((row.systolic[1], row.diastolic[1]), (systolic, diastolic), "{:?} aged {}", sex, age);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pediatric_categories_by_percentile() {
+        // A 10 year old boy of median height: 90th 112/74, 95th 116/77
+        let boy = patient(Sex::Male, 10, Some(141.0));
+        assert_eq!(pediatric_category(111, 70, boy), PediatricCategory::Normal);
+        assert_eq!(pediatric_category(112, 70, boy), PediatricCategory::Elevated);
+        assert_eq!(pediatric_category(105, 74, boy), PediatricCategory::Elevated);
+        assert_eq!(pediatric_category(116, 70, boy), PediatricCategory::Stage1);
+        assert_eq!(pediatric_category(127, 70, boy), PediatricCategory::Stage1);
+        assert_eq!(pediatric_category(128, 70, boy), PediatricCategory::Stage2);
+        assert_eq!(pediatric_category(105, 89, boy), PediatricCategory::Stage2);
+
+        let classification = classify_pediatric_blood_pressure(113, 60, &boy.0, boy.1).unwrap();
+        assert_eq!(classification.age_years, 10);
+        assert_eq!(classification.height_percentile, Some(50));
+        assert_eq!(classification.percentile, Some(PercentileBand::From90thTo95th));
+    }
+
+    #[test]
+    fn test_pediatric_static_caps() {
+        // A tall 12 year old girl has a 90th percentile of 122 systolic,
+        // but 120 is elevated regardless
+        let girl = patient(Sex::Female, 12, Some(167.0));
+        assert_eq!(pediatric_category(119, 70, girl), PediatricCategory::Normal);
+        assert_eq!(pediatric_category(120, 70, girl), PediatricCategory::Elevated);
+        // Her 95th percentile + 12 is 138, below the 140 cap
+        assert_eq!(pediatric_category(138, 70, girl), PediatricCategory::Stage2);
+    }
+
+    #[test]
+    fn test_pediatric_height_percentile() {
+        let (boy, on) = patient(Sex::Male, 8, Some(140.0));
+        let classification = classify_pediatric_blood_pressure(100, 60, &boy, on).unwrap();
+        assert_eq!(classification.height_percentile, Some(95));
+
+        let (boy, on) = patient(Sex::Male, 8, None);
+        let classification = classify_pediatric_blood_pressure(100, 60, &boy, on).unwrap();
+        assert_eq!(classification.height_percentile, Some(50));
+        assert_eq!(classification.percentile, Some(PercentileBand::From50thTo90th));
+    }
+
+    #[test]
+    fn test_adolescent_static_thresholds() {
+        let teen = patient(Sex::Female, 15, None);
+        assert_eq!(pediatric_category(119, 79, teen), PediatricCategory::Normal);
+        assert_eq!(pediatric_category(125, 75, teen), PediatricCategory::Elevated);
+        assert_eq!(pediatric_category(125, 80, teen), PediatricCategory::Stage1);
+        assert_eq!(pediatric_category(140, 70, teen), PediatricCategory::Stage2);
+
+        let classification = classify_pediatric_blood_pressure(125, 75, &teen.0, teen.1).unwrap();
+        assert_eq!(classification.height_percentile, None);
+        assert_eq!(classification.percentile, None);
+    }
+
+    #[test]
+    fn test_pediatric_age_limits() {
+        let (adult, on) = patient(Sex::Male, 18, None);
+        assert!(classify_pediatric_blood_pressure(120, 80, &adult, on).is_none());
+
+        let infant = PediatricPatient {
+            date_of_birth: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            sex: Sex::Female,
+            height_cm: None,
+        };
+        let on = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+        assert!(classify_pediatric_blood_pressure(90, 50, &infant, on).is_none());
+    }
     
     #[test]
     fn test_bp_category_normal() {
@@ -63,4 +838,142 @@ mod tests {
         let category = categorize_blood_pressure(120, 125);
         assert_eq!(category, BloodPressureCategory::HypertensiveCrisis);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_bp_category_hypotension() {
+        let category = categorize_blood_pressure(85, 65);
+        assert_eq!(category, BloodPressureCategory::Hypotension);
+
+        let category = categorize_blood_pressure(100, 55);
+        assert_eq!(category, BloodPressureCategory::Hypotension);
+    }
+    
+    #[test]
+    fn test_dispersion_of_known_series() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+        assert_eq!(mean(&values), Some(5.0));
+        assert!((standard_deviation(&values).unwrap() - 2.138_090).abs() < 1e-6);
+        assert!((coefficient_of_variation(&values).unwrap() - 42.761_799).abs() < 1e-6);
+        // |2| + |0| + |0| + |1| + |0| + |2| + |2| over 7 changes
+        assert!((average_real_variability(&values).unwrap() - 1.0).abs() < 1e-9);
+
+        assert_eq!(mean(&[]), None);
+        assert_eq!(standard_deviation(&[120.0]), None);
+        assert_eq!(average_real_variability(&[120.0]), None);
+    }
+
+    #[test]
+    fn test_average_real_variability_depends_on_order() {
+        assert_eq!(average_real_variability(&[120.0, 130.0, 125.0]), Some(7.5));
+        assert_eq!(average_real_variability(&[120.0, 125.0, 130.0]), Some(5.0));
+    }
+
+    #[test]
+    fn test_pulse_and_mean_arterial_pressure() {
+        assert_eq!(pulse_pressure(120.0, 80.0), 40.0);
+        assert!((mean_arterial_pressure(120.0, 80.0) - 93.333_333).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_target_range_bounds() {
+        assert!(is_in_target_range(120.0, 75.0));
+        assert!(is_in_target_range(90.0, 60.0));
+        assert!(!is_in_target_range(130.0, 75.0));
+        assert!(!is_in_target_range(120.0, 80.0));
+        assert!(!is_in_target_range(85.0, 55.0));
+    }
+
+    #[test]
+    fn test_linear_trend_of_exact_line() {
+        let points = [(0.0, 120.0), (1.0, 122.0), (2.0, 124.0), (3.0, 126.0)];
+
+        let trend = linear_trend(&points).unwrap();
+        assert!((trend.slope_per_week - 2.0).abs() < 1e-9);
+        assert!((trend.ci_lower - 2.0).abs() < 1e-6);
+        assert!((trend.ci_upper - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_linear_trend_confidence_interval() {
+        // Sxx = 10, Sxy = 8, Syy = 10: slope 0.8, standard error sqrt(1.2 / 10)
+        let points = [(0.0, 1.0), (1.0, 3.0), (2.0, 2.0), (3.0, 5.0), (4.0, 4.0)];
+
+        let trend = linear_trend(&points).unwrap();
+        assert!((trend.slope_per_week - 0.8).abs() < 1e-9);
+        assert!((trend.ci_lower - -0.302_278).abs() < 1e-5);
+        assert!((trend.ci_upper - 1.902_278).abs() < 1e-5);
+
+        assert!(linear_trend(&points[..2]).is_none());
+        assert!(linear_trend(&[(1.0, 120.0), (1.0, 130.0), (1.0, 125.0)]).is_none());
+    }
+
+    #[test]
+    fn test_least_squares_line() {
+        let (slope, intercept) = least_squares_line(&[(0.0, 80.0), (10.0, 79.0), (20.0, 78.0)]).unwrap();
+        assert!((slope + 0.1).abs() < 1e-9);
+        assert!((intercept - 80.0).abs() < 1e-9);
+
+        assert!(least_squares_line(&[(0.0, 80.0), (10.0, 79.0)]).is_none());
+        assert!(least_squares_line(&[(5.0, 80.0), (5.0, 79.0), (5.0, 78.0)]).is_none());
+    }
+
+    #[test]
+    fn test_average_in_local_hours() {
+        let reading = |timestamp: &str, systolic: u16, diastolic: u16| BloodPressureReading {
+            id: timestamp.to_string(),
+            user_id: "user-1".to_string(),
+            systolic,
+            diastolic,
+            pulse: None,
+            notes: None,
+            timestamp: timestamp.parse().unwrap(),
+            position: None,
+            arm: None,
+            device_id: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        // 07:30 and 10:00 in New York, but 12:30 and 15:00 in UTC
+        let readings = [
+            reading("2024-03-01T12:30:00Z", 130, 85),
+            reading("2024-03-01T15:00:00Z", 126, 81),
+        ];
+
+        let morning = average_in_hours(&readings, Tz::America__New_York, 4..12).unwrap();
+        assert_eq!(morning.avg_systolic, 128.0);
+        assert_eq!(morning.avg_diastolic, 83.0);
+        assert_eq!(morning.reading_count, 2);
+
+        assert!(average_in_hours(&readings, Tz::UTC, 4..12).is_none());
+
+        // 11:30 in New York once daylight saving starts, but 10:30 the day before
+        let readings = [
+            reading("2024-03-09T15:30:00Z", 130, 85),
+            reading("2024-03-10T15:30:00Z", 126, 81),
+        ];
+        let morning = average_in_hours(&readings, Tz::America__New_York, 4..11).unwrap();
+        assert_eq!(morning.reading_count, 1);
+        assert_eq!(morning.avg_systolic, 130.0);
+    }
+
+    #[test]
+    fn test_dipping_classification() {
+        assert_eq!(classify_dipping(0.85), DippingPattern::Dipper);
+        assert_eq!(classify_dipping(0.9), DippingPattern::Dipper);
+        assert_eq!(classify_dipping(0.95), DippingPattern::NonDipper);
+        assert_eq!(classify_dipping(1.0), DippingPattern::NonDipper);
+        assert_eq!(classify_dipping(1.05), DippingPattern::ReverseDipper);
+    }
+
+    #[test]
+    fn test_bmi_calculation_and_category() {
+        let bmi = calculate_bmi(70.0, 175.0);
+        assert!((bmi - 22.86).abs() < 0.01);
+        assert_eq!(categorize_bmi(bmi), BmiCategory::Normal);
+        
+        assert_eq!(categorize_bmi(18.4), BmiCategory::Underweight);
+        assert_eq!(categorize_bmi(25.0), BmiCategory::Overweight);
+        assert_eq!(categorize_bmi(30.0), BmiCategory::Obese);
+    }
+}
@@ -1,11 +1,23 @@
+pub mod guidelines;
 pub mod insights;
 pub mod blood_pressure;
+pub mod weight;
+pub mod user;
+pub mod goal;
+pub mod profile;
+pub mod storage;
+pub mod notifications;
+pub mod alert_rules;
 
 // Domain services
 // This module contains business logic implementations.
 
 // Re-export service traits and factory functions
 pub use blood_pressure::{BloodPressureServiceTrait, create_default_blood_pressure_service};
+pub use weight::{WeightServiceTrait, create_default_weight_service};
+pub use user::{UserServiceTrait, create_default_user_service};
+pub use goal::{GoalServiceTrait, create_default_goal_service};
+pub use profile::{ProfileServiceTrait, create_default_profile_service};
 
 // Re-export mock service factory functions when the mock feature is enabled
 #[cfg(feature = "mock")]
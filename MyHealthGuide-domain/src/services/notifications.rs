@@ -0,0 +1,393 @@
+//! Delivery of blood pressure alerts
+//!
+//! Alerts are raised by the blood pressure service and handed to every
+//! configured notifier. `ALERT_NOTIFIERS` selects the backends as a comma
+//! separated list of `log`, `webhook` and `smtp` (default: `log`).
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+use crate::entities::blood_pressure::{BloodPressureAlert, BloodPressureAlertKind};
+
+/// How long a notifier may take to deliver one alert
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Alert notifier errors
+#[derive(Debug, Error)]
+pub enum NotifierError {
+    /// The backend could not be reached or refused the alert
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+
+    /// The backend did not answer in time
+    #[error("Delivery timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+/// A backend that delivers alerts to someone who can act on them
+#[async_trait]
+pub trait AlertNotifier: Send + Sync {
+    /// Short name of the backend, used in logs
+    fn name(&self) -> &'static str;
+
+    /// Deliver one alert
+    async fn notify(&self, alert: &BloodPressureAlert) -> Result<(), NotifierError>;
+}
+
+/// Subject line for an alert
+fn subject(alert: &BloodPressureAlert) -> &'static str {
+    match alert.kind {
+        BloodPressureAlertKind::HypertensiveCrisis => "Blood pressure alert: hypertensive crisis",
+        BloodPressureAlertKind::RepeatedStage2 => "Blood pressure alert: repeated stage 2 readings",
+        BloodPressureAlertKind::Rule => "Blood pressure alert: alert rule fired",
+    }
+}
+
+/// Writes alerts to the application log
+#[derive(Debug, Default)]
+pub struct LogNotifier;
+
+#[async_trait]
+impl AlertNotifier for LogNotifier {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn notify(&self, alert: &BloodPressureAlert) -> Result<(), NotifierError> {
+        warn!(
+            alert_id = %alert.id,
+            user_id = %alert.user_id,
+            kind = alert.kind.as_str(),
+            "{}: {}",
+            subject(alert),
+            alert.message
+        );
+        Ok(())
+    }
+}
+
+/// Posts alerts as JSON to a webhook URL
+///
+/// Any 2xx answer counts as delivered.
+#[cfg(feature = "with-webhooks")]
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "with-webhooks")]
+impl WebhookNotifier {
+    /// Create a notifier posting to `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), client: reqwest::Client::new() }
+    }
+}
+
+#[cfg(feature = "with-webhooks")]
+#[async_trait]
+impl AlertNotifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn notify(&self, alert: &BloodPressureAlert) -> Result<(), NotifierError> {
+        self.client.post(&self.url)
+            .json(alert)
+            .timeout(DELIVERY_TIMEOUT)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| NotifierError::Delivery(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Mails alerts over plain SMTP
+///
+/// Meant for a local mail catcher such as Mailpit or MailHog, or a relay on
+/// the same host: it speaks SMTP without TLS or authentication.
+#[derive(Debug, Clone)]
+pub struct SmtpNotifier {
+    address: String,
+    from: String,
+    to: String,
+}
+
+impl SmtpNotifier {
+    /// Create a notifier mailing `to` through the server at `address` (host:port)
+    pub fn new(address: impl Into<String>, from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self { address: address.into(), from: from.into(), to: to.into() }
+    }
+
+    /// The message for an alert, headers included, with lines ending in CRLF
+    fn message(&self, alert: &BloodPressureAlert) -> String {
+        let body = format!(
+            "{}\n\nReading: {}/{} mmHg\nReadings involved: {}\nRaised at: {}\nAlert ID: {}\n",
+            alert.message,
+            alert.systolic,
+            alert.diastolic,
+            alert.reading_ids.join(", "),
+            alert.created_at.to_rfc3339(),
+            alert.id,
+        );
+
+        let mut message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            self.to,
+            subject(alert),
+            alert.created_at.to_rfc2822(),
+        );
+        for line in body.lines() {
+            // A line starting with a dot is doubled so it cannot end the data early
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message
+    }
+
+    /// Run one SMTP transaction
+    async fn send(&self, message: &str) -> Result<(), NotifierError> {
+        let stream = TcpStream::connect(&self.address).await
+            .map_err(|e| NotifierError::Delivery(format!("connecting to {}: {}", self.address, e)))?;
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+
+        expect_reply(&mut reader, 220).await?;
+        for (command, code) in [
+            ("EHLO myhealthguide".to_string(), 250),
+            (format!("MAIL FROM:<{}>", self.from), 250),
+            (format!("RCPT TO:<{}>", self.to), 250),
+            ("DATA".to_string(), 354),
+        ] {
+            send_line(&mut writer, &command).await?;
+            expect_reply(&mut reader, code).await?;
+        }
+
+        writer.write_all(message.as_bytes()).await
+            .map_err(|e| NotifierError::Delivery(e.to_string()))?;
+        send_line(&mut writer, ".").await?;
+        expect_reply(&mut reader, 250).await?;
+
+        // The message is accepted at this point, so a failed goodbye is ignored
+        let _ = send_line(&mut writer, "QUIT").await;
+        Ok(())
+    }
+}
+
+/// Write one SMTP command
+async fn send_line(writer: &mut (impl AsyncWriteExt + Unpin), line: &str) -> Result<(), NotifierError> {
+    writer.write_all(format!("{}\r\n", line).as_bytes()).await
+        .map_err(|e| NotifierError::Delivery(e.to_string()))
+}
+
+/// Read an SMTP reply, which may span several lines, and check its code
+async fn expect_reply(reader: &mut (impl AsyncBufReadExt + Unpin), code: u16) -> Result<(), NotifierError> {
+    let expected = code.to_string();
+    loop {
+        let mut line = String::new();
+        let read = reader.read_line(&mut line).await
+            .map_err(|e| NotifierError::Delivery(e.to_string()))?;
+        if read == 0 {
+            return Err(NotifierError::Delivery("connection closed by the SMTP server".to_string()));
+        }
+        if !line.starts_with(&expected) {
+            return Err(NotifierError::Delivery(format!("unexpected SMTP reply: {}", line.trim_end())));
+        }
+        // "250-" continues the reply, "250 " ends it
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+#[async_trait]
+impl AlertNotifier for SmtpNotifier {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn notify(&self, alert: &BloodPressureAlert) -> Result<(), NotifierError> {
+        self.send(&self.message(alert)).await
+    }
+}
+
+/// Create the notifiers selected by the `ALERT_NOTIFIERS` variable
+///
+/// `webhook` posts to `ALERT_WEBHOOK_URL` and is skipped without it. `smtp`
+/// mails `ALERT_SMTP_TO` through `ALERT_SMTP_HOST`:`ALERT_SMTP_PORT`
+/// (default: localhost:1025, a local mail catcher) from `ALERT_SMTP_FROM`.
+pub fn notifiers_from_env() -> Vec<Arc<dyn AlertNotifier>> {
+    let selected = std::env::var("ALERT_NOTIFIERS").unwrap_or_else(|_| "log".to_string());
+
+    let mut notifiers: Vec<Arc<dyn AlertNotifier>> = Vec::new();
+    for name in selected.split(',').map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()) {
+        match name.as_str() {
+            "log" => notifiers.push(Arc::new(LogNotifier)),
+            #[cfg(feature = "with-webhooks")]
+            "webhook" => match std::env::var("ALERT_WEBHOOK_URL") {
+                Ok(url) => notifiers.push(Arc::new(WebhookNotifier::new(url))),
+                Err(_) => warn!("ALERT_WEBHOOK_URL is not set, alerts will not be posted to a webhook"),
+            },
+            "smtp" => {
+                let host = std::env::var("ALERT_SMTP_HOST").unwrap_or_else(|_| "localhost".to_string());
+                let port = std::env::var("ALERT_SMTP_PORT").unwrap_or_else(|_| "1025".to_string());
+                let from = std::env::var("ALERT_SMTP_FROM").unwrap_or_else(|_| "alerts@myhealth.guide".to_string());
+                let to = std::env::var("ALERT_SMTP_TO").unwrap_or_else(|_| "care-team@myhealth.guide".to_string());
+                notifiers.push(Arc::new(SmtpNotifier::new(format!("{}:{}", host, port), from, to)));
+            },
+            other => warn!("Unknown alert notifier '{}' in ALERT_NOTIFIERS, ignoring it", other),
+        }
+    }
+
+    info!(
+        "Delivering blood pressure alerts through: {}",
+        notifiers.iter().map(|notifier| notifier.name()).collect::<Vec<_>>().join(", ")
+    );
+    notifiers
+}
+
+/// Deliver an alert through every notifier in the background
+///
+/// Delivery never holds up the request that raised the alert; failures are
+/// logged, as the alert is already stored and returned to the user.
+pub fn dispatch_alert(notifiers: &[Arc<dyn AlertNotifier>], alert: &BloodPressureAlert) {
+    for notifier in notifiers {
+        let notifier = Arc::clone(notifier);
+        let alert = alert.clone();
+        tokio::spawn(async move {
+            let delivered = tokio::time::timeout(DELIVERY_TIMEOUT, notifier.notify(&alert)).await
+                .unwrap_or(Err(NotifierError::Timeout(DELIVERY_TIMEOUT)));
+            if let Err(e) = delivered {
+                warn!("Failed to deliver alert {} through {}: {}", alert.id, notifier.name(), e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    fn crisis_alert() -> BloodPressureAlert {
+        BloodPressureAlert {
+            id: "alert-1".to_string(),
+            user_id: "user-123".to_string(),
+            kind: BloodPressureAlertKind::HypertensiveCrisis,
+            rule_id: None,
+            message: "Reading of 200/130 mmHg is in the hypertensive crisis range.\n.Measure again.".to_string(),
+            systolic: 200,
+            diastolic: 130,
+            reading_ids: vec!["reading-1".to_string()],
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_smtp_notifier_delivers_to_mail_catcher() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        // A minimal mail catcher that records the commands and the message
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            writer.write_all(b"220 catcher ready\r\n").await.unwrap();
+
+            let mut transcript = String::new();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                transcript.push_str(&line);
+                let reply: &[u8] = if in_data {
+                    if line != ".\r\n" {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-catcher\r\n250 8BITMIME\r\n"
+                } else if line.starts_with("DATA") {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+            transcript
+        });
+
+        let notifier = SmtpNotifier::new(address, "alerts@example.com", "doctor@example.com");
+        notifier.notify(&crisis_alert()).await.unwrap();
+
+        let transcript = server.await.unwrap();
+        assert!(transcript.contains("MAIL FROM:<alerts@example.com>\r\n"));
+        assert!(transcript.contains("RCPT TO:<doctor@example.com>\r\n"));
+        assert!(transcript.contains("Subject: Blood pressure alert: hypertensive crisis\r\n"));
+        assert!(transcript.contains("Reading: 200/130 mmHg\r\n"));
+        // The line starting with a dot is stuffed
+        assert!(transcript.contains("\r\n..Measure again.\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_smtp_notifier_reports_rejection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"554 no service\r\n").await.unwrap();
+        });
+
+        let notifier = SmtpNotifier::new(address, "alerts@example.com", "doctor@example.com");
+        let result = notifier.notify(&crisis_alert()).await;
+        assert!(matches!(result, Err(NotifierError::Delivery(message)) if message.contains("554")));
+    }
+
+    #[cfg(feature = "with-webhooks")]
+    #[tokio::test]
+    async fn test_webhook_notifier_posts_alert() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks/alerts", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            // Read until the JSON body is complete
+            while !request.ends_with(b"}") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        WebhookNotifier::new(url).notify(&crisis_alert()).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hooks/alerts HTTP/1.1\r\n"));
+        assert!(request.contains("\"kind\":\"hypertensive_crisis\""));
+        assert!(request.contains("\"systolic\":200"));
+    }
+}
@@ -0,0 +1,234 @@
+use std::ops::RangeInclusive;
+
+use thiserror::Error;
+use chrono::Utc;
+use chrono_tz::Tz;
+use async_trait::async_trait;
+
+use crate::entities::profile::{Profile, SetProfileRequest};
+use crate::entities::conversions;
+use crate::services::guidelines::{guideline_set, GuidelineSet};
+use my_health_guide_data::repository::{ProfileRepositoryTrait, RepositoryError};
+
+/// Heights accepted for the pediatric height percentile, in centimeters
+pub const HEIGHT_RANGE_CM: RangeInclusive<f64> = 50.0..=272.0;
+
+/// Profile service errors
+#[derive(Debug, Error)]
+pub enum ProfileServiceError {
+    /// Validation error
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    /// The user has no profile set
+    #[error("Profile not found: {0}")]
+    NotFound(String),
+
+    /// Repository error
+    #[error("Repository error: {0}")]
+    RepositoryError(String),
+
+    /// Storage unavailable error
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+}
+
+/// Parse an IANA time zone name such as `Europe/Amsterdam`
+pub fn parse_time_zone(name: &str) -> Result<Tz, ProfileServiceError> {
+    name.parse::<Tz>().map_err(|_| {
+        ProfileServiceError::ValidationError(format!(
+            "Unknown time zone '{}'. Use an IANA time zone name such as Europe/Amsterdam", name
+        ))
+    })
+}
+
+/// Parse a named guideline set such as `esc_esh_2018`
+pub fn parse_guideline(name: &str) -> Result<GuidelineSet, ProfileServiceError> {
+    name.parse::<GuidelineSet>().map_err(|e| {
+        ProfileServiceError::ValidationError(format!(
+            "{}. Use acc_aha_2017, esc_esh_2018 or ish_2020", e
+        ))
+    })
+}
+
+/// Trait for profile service operations
+#[async_trait]
+pub trait ProfileServiceTrait {
+    /// Get a user's profile
+    async fn get_profile(&self, user_id: &str) -> Result<Profile, ProfileServiceError>;
+
+    /// Set a user's profile, replacing any set before
+    async fn set_profile(&self, user_id: &str, request: SetProfileRequest) -> Result<Profile, ProfileServiceError>;
+
+    /// Get a user's profile, or `None` when they have not set one
+    async fn find_profile(&self, user_id: &str) -> Result<Option<Profile>, ProfileServiceError> {
+        match self.get_profile(user_id).await {
+            Ok(profile) => Ok(Some(profile)),
+            Err(ProfileServiceError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The time zone to take a user's local times in
+    ///
+    /// A `requested` zone wins over the one in the profile; without either
+    /// local times are UTC.
+    async fn time_zone(&self, user_id: &str, requested: Option<&str>) -> Result<Tz, ProfileServiceError> {
+        if let Some(name) = requested {
+            return parse_time_zone(name);
+        }
+
+        let stored = self.find_profile(user_id).await?.and_then(|profile| profile.time_zone);
+        match stored {
+            Some(name) => parse_time_zone(&name),
+            None => Ok(Tz::UTC),
+        }
+    }
+
+    /// The guideline set to categorize a user's readings with
+    ///
+    /// A `requested` set wins over the one in the profile; without either
+    /// the deployment's set is used.
+    async fn guideline_set(&self, user_id: &str, requested: Option<&str>) -> Result<GuidelineSet, ProfileServiceError> {
+        if let Some(name) = requested {
+            return parse_guideline(name);
+        }
+
+        let stored = self.find_profile(user_id).await?.and_then(|profile| profile.guideline);
+        match stored {
+            Some(name) => parse_guideline(&name),
+            None => Ok(guideline_set()),
+        }
+    }
+}
+
+/// Profile service for domain logic
+pub struct ProfileService<R: ProfileRepositoryTrait> {
+    repository: R,
+}
+
+impl<R: ProfileRepositoryTrait> ProfileService<R> {
+    /// Create a new profile service
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    /// Map repository errors to service errors
+    fn map_repo_error(&self, err: RepositoryError) -> ProfileServiceError {
+        match err {
+            RepositoryError::NotFound(msg) => ProfileServiceError::NotFound(msg),
+            RepositoryError::Validation(msg) => ProfileServiceError::ValidationError(msg),
+            RepositoryError::Unavailable(msg) => ProfileServiceError::StorageUnavailable(msg),
+            _ => ProfileServiceError::RepositoryError(err.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl<R: ProfileRepositoryTrait + Send + Sync> ProfileServiceTrait for ProfileService<R> {
+    /// Get a user's profile
+    async fn get_profile(&self, user_id: &str) -> Result<Profile, ProfileServiceError> {
+        let profile = self.repository.get_profile(user_id)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        profile.map(conversions::convert_to_domain_profile)
+            .ok_or_else(|| ProfileServiceError::NotFound(format!("No profile set for user {}", user_id)))
+    }
+
+    /// Set a user's profile
+    async fn set_profile(&self, user_id: &str, request: SetProfileRequest) -> Result<Profile, ProfileServiceError> {
+        if let Some(name) = request.time_zone.as_deref() {
+            parse_time_zone(name)?;
+        }
+        if let Some(name) = request.guideline.as_deref() {
+            parse_guideline(name)?;
+        }
+        if request.date_of_birth.is_some_and(|date| date > Utc::now().date_naive()) {
+            return Err(ProfileServiceError::ValidationError("date_of_birth cannot be in the future".to_string()));
+        }
+        if request.height_cm.is_some_and(|height_cm| !HEIGHT_RANGE_CM.contains(&height_cm)) {
+            return Err(ProfileServiceError::ValidationError("height_cm must be between 50 and 272".to_string()));
+        }
+
+        let data_request = conversions::convert_to_data_set_profile_request(&request);
+        let profile = self.repository.set_profile(user_id, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        Ok(conversions::convert_to_domain_profile(profile))
+    }
+}
+
+/// Create a default profile service using the repository from data layer
+pub fn create_default_profile_service() -> impl ProfileServiceTrait + Send + Sync {
+    let repository = my_health_guide_data::repository::ProfileRepository::new();
+    ProfileService::new(repository)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::blood_pressure::Sex;
+    use my_health_guide_data::repository::tests::MockProfileRepository;
+
+    fn service() -> ProfileService<MockProfileRepository> {
+        ProfileService::new(MockProfileRepository::new())
+    }
+
+    #[tokio::test]
+    async fn test_set_and_get_profile() {
+        let service = service();
+        assert!(matches!(service.get_profile("user-1").await, Err(ProfileServiceError::NotFound(_))));
+        assert!(service.find_profile("user-1").await.unwrap().is_none());
+
+        let request = SetProfileRequest { time_zone: Some("UTC+2".to_string()), ..Default::default() };
+        let result = service.set_profile("user-1", request).await;
+        assert!(matches!(result, Err(ProfileServiceError::ValidationError(_))));
+
+        let request = SetProfileRequest { height_cm: Some(300.0), ..Default::default() };
+        let result = service.set_profile("user-1", request).await;
+        assert!(matches!(result, Err(ProfileServiceError::ValidationError(_))));
+
+        let request = SetProfileRequest {
+            time_zone: Some("Europe/Amsterdam".to_string()),
+            date_of_birth: chrono::NaiveDate::from_ymd_opt(2014, 6, 1),
+            sex: Some(Sex::Female),
+            height_cm: Some(142.0),
+            ..Default::default()
+        };
+        service.set_profile("user-1", request).await.unwrap();
+        let profile = service.get_profile("user-1").await.unwrap();
+        assert_eq!(profile.time_zone.as_deref(), Some("Europe/Amsterdam"));
+        assert_eq!(profile.date_of_birth, chrono::NaiveDate::from_ymd_opt(2014, 6, 1));
+        assert_eq!((profile.sex, profile.height_cm), (Some(Sex::Female), Some(142.0)));
+    }
+
+    #[tokio::test]
+    async fn test_requested_time_zone_wins_over_the_profile() {
+        let service = service();
+        assert_eq!(service.time_zone("user-1", None).await.unwrap(), Tz::UTC);
+
+        let request = SetProfileRequest { time_zone: Some("Europe/Amsterdam".to_string()), ..Default::default() };
+        service.set_profile("user-1", request).await.unwrap();
+        assert_eq!(service.time_zone("user-1", None).await.unwrap(), Tz::Europe__Amsterdam);
+        assert_eq!(service.time_zone("user-1", Some("America/New_York")).await.unwrap(), Tz::America__New_York);
+        assert!(service.time_zone("user-1", Some("Mars/Olympus_Mons")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requested_guideline_wins_over_the_profile() {
+        let service = service();
+        assert_eq!(service.guideline_set("user-1", None).await.unwrap(), guideline_set());
+
+        let request = SetProfileRequest { guideline: Some("jnc7".to_string()), ..Default::default() };
+        let result = service.set_profile("user-1", request).await;
+        assert!(matches!(result, Err(ProfileServiceError::ValidationError(_))));
+
+        let request = SetProfileRequest { guideline: Some("esc_esh_2018".to_string()), ..Default::default() };
+        service.set_profile("user-1", request).await.unwrap();
+        assert_eq!(service.guideline_set("user-1", None).await.unwrap(), GuidelineSet::EscEsh2018);
+        assert_eq!(service.guideline_set("user-1", Some("ish")).await.unwrap(), GuidelineSet::Ish2020);
+        assert!(service.guideline_set("user-1", Some("custom")).await.is_err());
+    }
+}
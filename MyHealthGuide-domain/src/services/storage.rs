@@ -0,0 +1,34 @@
+//! Background maintenance for reading storage
+
+#[cfg(feature = "with-tokio")]
+use tracing::{debug, warn};
+
+/// Start a background task that replays the storage outbox
+///
+/// Every 30 seconds, while a database is configured, pending writes recorded
+/// by the outbox storage strategy are moved into the database. It should be
+/// called during application startup when `STORAGE_STRATEGY=outbox`.
+#[cfg(feature = "with-tokio")]
+pub fn start_outbox_replay_task() {
+    use tokio::time;
+    use std::time::Duration;
+    use my_health_guide_data::database::get_db_pool;
+    use my_health_guide_data::repository::outbox;
+
+    tokio::spawn(async move {
+        let mut interval = time::interval(Duration::from_secs(30));
+
+        loop {
+            interval.tick().await;
+            let Ok(pool) = get_db_pool() else {
+                continue;
+            };
+
+            match outbox().replay(&pool).await {
+                Ok(0) => {},
+                Ok(replayed) => debug!("Replayed {} writes from the storage outbox", replayed),
+                Err(e) => warn!("Failed to replay the storage outbox: {}", e),
+            }
+        }
+    });
+}
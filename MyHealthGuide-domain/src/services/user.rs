@@ -0,0 +1,194 @@
+use thiserror::Error;
+use chrono::Utc;
+use validator::Validate;
+use async_trait::async_trait;
+
+use crate::auth::password;
+use crate::entities::user::{RegisterUserRequest, User};
+use crate::entities::conversions;
+use crate::services::blood_pressure::validation_message;
+use my_health_guide_data::repository::{RepositoryError, UserRepositoryTrait};
+
+/// Role granted to every registered user
+///
+/// Registration never grants more than this. Admins are assigned out of band
+/// by updating the stored roles of an existing account.
+const DEFAULT_ROLE: &str = "user";
+
+/// User service errors
+#[derive(Debug, Error)]
+pub enum UserServiceError {
+    /// Validation error
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    /// The email address already belongs to an account
+    #[error("Email already registered: {0}")]
+    EmailTaken(String),
+
+    /// Unknown email or wrong password
+    #[error("Invalid email or password")]
+    InvalidCredentials,
+
+    /// Repository error
+    #[error("Repository error: {0}")]
+    RepositoryError(String),
+
+    /// Storage unavailable error
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+}
+
+/// Trait for user account operations
+#[async_trait]
+pub trait UserServiceTrait {
+    /// Register a new user with a password
+    async fn register(&self, request: RegisterUserRequest) -> Result<User, UserServiceError>;
+
+    /// Check an email and password pair, returning the matching user
+    async fn authenticate(&self, email: &str, password: &str) -> Result<User, UserServiceError>;
+}
+
+/// User service shared between request handlers
+pub type SharedUserService = std::sync::Arc<dyn UserServiceTrait + Send + Sync>;
+
+/// User service for domain logic
+pub struct UserService<R: UserRepositoryTrait> {
+    repository: R,
+}
+
+impl<R: UserRepositoryTrait> UserService<R> {
+    /// Create a new user service
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    /// Map repository errors to service errors
+    fn map_repo_error(&self, err: RepositoryError) -> UserServiceError {
+        match err {
+            RepositoryError::Conflict(msg) => UserServiceError::EmailTaken(msg),
+            RepositoryError::Validation(msg) => UserServiceError::ValidationError(msg),
+            RepositoryError::Unavailable(msg) => UserServiceError::StorageUnavailable(msg),
+            _ => UserServiceError::RepositoryError(err.to_string()),
+        }
+    }
+}
+
+/// Normalize an email address so lookups are case insensitive
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Run a CPU heavy password operation off the async executor
+async fn run_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Result<T, UserServiceError> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| UserServiceError::RepositoryError(format!("Password task failed: {}", e)))
+}
+
+#[async_trait]
+impl<R: UserRepositoryTrait + Send + Sync> UserServiceTrait for UserService<R> {
+    /// Register a new user with a password
+    async fn register(&self, request: RegisterUserRequest) -> Result<User, UserServiceError> {
+        let request = RegisterUserRequest {
+            email: normalize_email(&request.email),
+            ..request
+        };
+
+        if let Err(validation_errors) = request.validate() {
+            return Err(UserServiceError::ValidationError(validation_message(&validation_errors)));
+        }
+
+        let plain_password = request.password;
+        let password_hash = run_blocking(move || password::hash_password(&plain_password))
+            .await?
+            .map_err(UserServiceError::RepositoryError)?;
+
+        let data_user = my_health_guide_data::models::user::User {
+            id: uuid::Uuid::new_v4().to_string(),
+            roles: vec![DEFAULT_ROLE.to_string()],
+            email: request.email,
+            password_hash,
+            name: request.name,
+            created_at: Utc::now().to_rfc3339(),
+        };
+
+        let stored = self.repository.create(data_user)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        Ok(conversions::convert_to_domain_user(stored))
+    }
+
+    /// Check an email and password pair, returning the matching user
+    async fn authenticate(&self, email: &str, password: &str) -> Result<User, UserServiceError> {
+        let stored = self.repository.get_by_email(&normalize_email(email))
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        let plain_password = password.to_string();
+        let Some(stored) = stored else {
+            // Spend the same time as a real check so unknown emails are not revealed
+            run_blocking(move || password::verify_dummy_password(&plain_password)).await?;
+            return Err(UserServiceError::InvalidCredentials);
+        };
+
+        let password_hash = stored.password_hash.clone();
+        let verified = run_blocking(move || password::verify_password(&plain_password, &password_hash)).await?;
+        if !verified {
+            return Err(UserServiceError::InvalidCredentials);
+        }
+
+        Ok(conversions::convert_to_domain_user(stored))
+    }
+}
+
+/// Create a default user service using the repository from data layer
+pub fn create_default_user_service() -> impl UserServiceTrait + Send + Sync {
+    let repository = my_health_guide_data::repository::UserRepository::new();
+    UserService::new(repository)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use my_health_guide_data::repository::tests::MockUserRepository;
+
+    fn registration(email: &str) -> RegisterUserRequest {
+        RegisterUserRequest {
+            email: email.to_string(),
+            password: "password123".to_string(),
+            name: Some("Alice".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_then_authenticate() {
+        let service = UserService::new(MockUserRepository::new());
+
+        let user = service.register(registration("Alice@Example.com ")).await.unwrap();
+        assert_eq!(user.email, "alice@example.com");
+        assert_eq!(user.roles, vec!["user"]);
+
+        let authenticated = service.authenticate("ALICE@example.com", "password123").await.unwrap();
+        assert_eq!(authenticated.id, user.id);
+
+        let result = service.authenticate("alice@example.com", "wrong-password").await;
+        assert!(matches!(result, Err(UserServiceError::InvalidCredentials)));
+
+        let result = service.authenticate("bob@example.com", "password123").await;
+        assert!(matches!(result, Err(UserServiceError::InvalidCredentials)));
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_duplicates_and_invalid_input() {
+        let service = UserService::new(MockUserRepository::new());
+        service.register(registration("alice@example.com")).await.unwrap();
+
+        let result = service.register(registration("ALICE@example.com")).await;
+        assert!(matches!(result, Err(UserServiceError::EmailTaken(_))));
+
+        let result = service.register(registration("not-an-email")).await;
+        assert!(matches!(result, Err(UserServiceError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,413 @@
+use thiserror::Error;
+use chrono::{DateTime, Duration, Utc};
+use validator::Validate;
+use async_trait::async_trait;
+
+use crate::entities::weight::{CreateWeightRequest, WeightInsights, WeightReading, WeightTrend};
+use crate::entities::conversions;
+use my_health_guide_data::repository::{RepositoryError, WeightRepositoryTrait};
+use crate::services::blood_pressure::validation_message;
+use crate::services::insights::{calculate_bmi, categorize_bmi};
+
+/// Longest period covered by weight insights, in days
+const INSIGHTS_PERIOD_DAYS: i64 = 90;
+
+/// Changes within this many kilograms over 30 days count as maintaining
+const TREND_THRESHOLD_KG: f32 = 0.5;
+
+/// Weight service errors
+#[derive(Debug, Error)]
+pub enum WeightServiceError {
+    /// Validation error
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    /// Not found error
+    #[error("Reading not found: {0}")]
+    NotFound(String),
+
+    /// Repository error
+    #[error("Repository error: {0}")]
+    RepositoryError(String),
+
+    /// Storage unavailable error
+    #[error("Storage unavailable: {0}")]
+    StorageUnavailable(String),
+
+    /// Insufficient data error
+    #[error("Insufficient data: {0}")]
+    InsufficientData(String),
+}
+
+/// Trait for weight service operations
+#[async_trait]
+pub trait WeightServiceTrait {
+    /// Validate a create weight request
+    fn validate_create_request(&self, request: &CreateWeightRequest) -> Result<(), WeightServiceError>;
+
+    /// Calculate weight insights from readings
+    ///
+    /// `height_cm` is needed for the BMI fields, which are left empty without it.
+    fn calculate_insights(
+        &self,
+        readings: &[WeightReading],
+        height_cm: Option<f32>,
+    ) -> Result<WeightInsights, WeightServiceError>;
+
+    /// Create a new weight reading owned by the given user
+    async fn create_reading(&self, user_id: &str, request: CreateWeightRequest)
+        -> Result<WeightReading, WeightServiceError>;
+
+    /// Get one of a user's weight readings by ID
+    async fn get_reading_by_id(&self, user_id: &str, id: &str) -> Result<WeightReading, WeightServiceError>;
+
+    /// Get a user's filtered weight readings
+    async fn get_filtered_readings(
+        &self,
+        user_id: &str,
+        start_date: Option<String>,
+        end_date: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<WeightReading>, usize), WeightServiceError>;
+
+    /// Replace one of a user's weight readings
+    ///
+    /// The request goes through the same validation as a new reading.
+    async fn update_reading(&self, user_id: &str, id: &str, request: CreateWeightRequest)
+        -> Result<WeightReading, WeightServiceError>;
+
+    /// Soft delete one of a user's weight readings
+    async fn delete_reading(&self, user_id: &str, id: &str) -> Result<(), WeightServiceError>;
+
+    /// Restore a soft deleted weight reading
+    async fn restore_reading(&self, user_id: &str, id: &str) -> Result<WeightReading, WeightServiceError>;
+
+    /// Generate insights from a user's weight readings of the last 90 days
+    async fn get_insights(&self, user_id: &str, height_cm: Option<f32>)
+        -> Result<WeightInsights, WeightServiceError>
+    {
+        if let Some(height) = height_cm {
+            if !(50.0..=272.0).contains(&height) {
+                return Err(WeightServiceError::ValidationError(
+                    "Height must be between 50 and 272 cm".to_string(),
+                ));
+            }
+        }
+
+        let start_date = (Utc::now() - Duration::days(INSIGHTS_PERIOD_DAYS)).to_rfc3339();
+        let (readings, _) = self.get_filtered_readings(
+            user_id,
+            Some(start_date),
+            None,
+            None,
+            None,
+            Some(false),
+        ).await?;
+
+        self.calculate_insights(&readings, height_cm)
+    }
+}
+
+/// Weight service for domain logic
+pub struct WeightService<R: WeightRepositoryTrait> {
+    repository: R,
+}
+
+impl<R: WeightRepositoryTrait> WeightService<R> {
+    /// Create a new weight service
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    /// Map repository errors to service errors
+    fn map_repo_error(&self, err: RepositoryError) -> WeightServiceError {
+        match err {
+            RepositoryError::NotFound(msg) => WeightServiceError::NotFound(msg),
+            RepositoryError::Validation(msg) => WeightServiceError::ValidationError(msg),
+            RepositoryError::Unavailable(msg) => WeightServiceError::StorageUnavailable(msg),
+            _ => WeightServiceError::RepositoryError(err.to_string()),
+        }
+    }
+
+    /// Build the not found error for a reading ID
+    fn not_found(id: &str) -> WeightServiceError {
+        WeightServiceError::NotFound(format!("Weight reading with ID {} not found", id))
+    }
+}
+
+/// Weight change between the first reading at or after `since` and `current`
+///
+/// `readings` must be sorted oldest first. Returns zero when no reading falls
+/// inside the window.
+fn change_since(readings: &[(DateTime<Utc>, &WeightReading)], since: DateTime<Utc>, current: f32) -> f32 {
+    readings.iter()
+        .find(|(timestamp, _)| *timestamp >= since)
+        .map_or(0.0, |(_, baseline)| current - baseline.weight_kg)
+}
+
+#[async_trait]
+impl<R: WeightRepositoryTrait + Send + Sync> WeightServiceTrait for WeightService<R> {
+    /// Validate a create weight request
+    fn validate_create_request(&self, request: &CreateWeightRequest) -> Result<(), WeightServiceError> {
+        if let Err(validation_errors) = request.validate() {
+            return Err(WeightServiceError::ValidationError(validation_message(&validation_errors)));
+        }
+
+        Ok(())
+    }
+
+    /// Calculate weight insights from readings
+    fn calculate_insights(
+        &self,
+        readings: &[WeightReading],
+        height_cm: Option<f32>,
+    ) -> Result<WeightInsights, WeightServiceError> {
+        // Order readings by when they were taken, ignoring any with a broken timestamp
+        let mut timeline: Vec<(DateTime<Utc>, &WeightReading)> = readings.iter()
+            .filter_map(|reading| {
+                DateTime::parse_from_rfc3339(&reading.timestamp)
+                    .ok()
+                    .map(|dt| (dt.with_timezone(&Utc), reading))
+            })
+            .collect();
+        timeline.sort_by_key(|(timestamp, _)| *timestamp);
+
+        let Some((_, latest)) = timeline.last() else {
+            return Err(WeightServiceError::InsufficientData(
+                "No readings available to generate insights".to_string(),
+            ));
+        };
+
+        let now = Utc::now();
+        let current_weight_kg = latest.weight_kg;
+        let change_30d_kg = change_since(&timeline, now - Duration::days(30), current_weight_kg);
+        let change_90d_kg = change_since(&timeline, now - Duration::days(90), current_weight_kg);
+
+        let trend = if change_30d_kg > TREND_THRESHOLD_KG {
+            WeightTrend::Gaining
+        } else if change_30d_kg < -TREND_THRESHOLD_KG {
+            WeightTrend::Losing
+        } else {
+            WeightTrend::Maintaining
+        };
+
+        // Body composition is optional, so report the most recent known values
+        let body_fat_percentage = timeline.iter().rev().find_map(|(_, r)| r.body_fat_percentage);
+        let muscle_mass_kg = timeline.iter().rev().find_map(|(_, r)| r.muscle_mass_kg);
+
+        let bmi = height_cm.map(|height| calculate_bmi(current_weight_kg, height));
+
+        Ok(WeightInsights {
+            current_weight_kg,
+            change_30d_kg,
+            change_90d_kg,
+            trend,
+            body_fat_percentage,
+            muscle_mass_kg,
+            bmi,
+            bmi_category: bmi.map(categorize_bmi),
+            reading_count: timeline.len(),
+            generated_at: now,
+        })
+    }
+
+    /// Create a new weight reading owned by the given user
+    async fn create_reading(&self, user_id: &str, request: CreateWeightRequest)
+        -> Result<WeightReading, WeightServiceError>
+    {
+        self.validate_create_request(&request)?;
+
+        let data_request = conversions::convert_to_data_create_weight_request(&request);
+
+        let data_reading = self.repository.create(user_id, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        Ok(conversions::convert_to_domain_weight_reading(data_reading))
+    }
+
+    /// Get one of a user's weight readings by ID
+    async fn get_reading_by_id(&self, user_id: &str, id: &str) -> Result<WeightReading, WeightServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(WeightServiceError::ValidationError)?;
+
+        let data_reading = self.repository.get_by_id(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| Self::not_found(id))?;
+
+        Ok(conversions::convert_to_domain_weight_reading(data_reading))
+    }
+
+    /// Get a user's filtered weight readings
+    async fn get_filtered_readings(
+        &self,
+        user_id: &str,
+        start_date: Option<String>,
+        end_date: Option<String>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<WeightReading>, usize), WeightServiceError> {
+        let (data_readings, total_count) = self.repository.get_filtered(
+            user_id,
+            start_date,
+            end_date,
+            limit,
+            offset,
+            sort_desc,
+        ).await
+        .map_err(|e| self.map_repo_error(e))?;
+
+        let domain_readings = data_readings.into_iter()
+            .map(conversions::convert_to_domain_weight_reading)
+            .collect();
+
+        Ok((domain_readings, total_count))
+    }
+
+    /// Replace one of a user's weight readings
+    async fn update_reading(&self, user_id: &str, id: &str, request: CreateWeightRequest)
+        -> Result<WeightReading, WeightServiceError>
+    {
+        // Edits follow the same rules as new readings
+        self.validate_create_request(&request)?;
+
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(WeightServiceError::ValidationError)?;
+        let data_request = conversions::convert_to_data_create_weight_request(&request);
+
+        let data_reading = self.repository.update(user_id, id_uuid, data_request)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| Self::not_found(id))?;
+
+        Ok(conversions::convert_to_domain_weight_reading(data_reading))
+    }
+
+    /// Soft delete one of a user's weight readings
+    async fn delete_reading(&self, user_id: &str, id: &str) -> Result<(), WeightServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(WeightServiceError::ValidationError)?;
+
+        let deleted = self.repository.delete(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?;
+
+        if deleted {
+            Ok(())
+        } else {
+            Err(Self::not_found(id))
+        }
+    }
+
+    /// Restore a soft deleted weight reading
+    async fn restore_reading(&self, user_id: &str, id: &str) -> Result<WeightReading, WeightServiceError> {
+        let id_uuid = conversions::parse_string_to_uuid(id)
+            .map_err(WeightServiceError::ValidationError)?;
+
+        let data_reading = self.repository.restore(user_id, id_uuid)
+            .await
+            .map_err(|e| self.map_repo_error(e))?
+            .ok_or_else(|| Self::not_found(id))?;
+
+        Ok(conversions::convert_to_domain_weight_reading(data_reading))
+    }
+}
+
+/// Create a default weight service using the repository from data layer
+pub fn create_default_weight_service() -> impl WeightServiceTrait + Send + Sync {
+    let repository = my_health_guide_data::repository::WeightRepository::new();
+    WeightService::new(repository)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use my_health_guide_data::repository::tests::MockWeightRepository;
+
+    /// Create a test weight reading taken the given number of days ago
+    fn reading_days_ago(days: i64, weight_kg: f32) -> WeightReading {
+        WeightReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "test-user".to_string(),
+            weight_kg,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: (Utc::now() - Duration::days(days)).to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_insights_changes_and_trend() {
+        let service = WeightService::new(MockWeightRepository::new());
+        let mut older = reading_days_ago(80, 84.0);
+        older.body_fat_percentage = Some(25.0);
+        let readings = vec![
+            reading_days_ago(1, 80.0),
+            reading_days_ago(20, 82.0),
+            older,
+        ];
+
+        let insights = service.calculate_insights(&readings, Some(180.0)).unwrap();
+        assert_eq!(insights.current_weight_kg, 80.0);
+        assert_eq!(insights.change_30d_kg, -2.0);
+        assert_eq!(insights.change_90d_kg, -4.0);
+        assert_eq!(insights.trend, WeightTrend::Losing);
+        assert_eq!(insights.body_fat_percentage, Some(25.0));
+        assert_eq!(insights.bmi_category, Some(crate::entities::weight::BmiCategory::Normal));
+        assert_eq!(insights.reading_count, 3);
+    }
+
+    #[test]
+    fn test_insights_without_height_or_readings() {
+        let service = WeightService::new(MockWeightRepository::new());
+
+        let insights = service.calculate_insights(&[reading_days_ago(2, 70.0)], None).unwrap();
+        assert_eq!(insights.trend, WeightTrend::Maintaining);
+        assert!(insights.bmi.is_none());
+        assert!(insights.bmi_category.is_none());
+
+        let result = service.calculate_insights(&[], None);
+        assert!(matches!(result, Err(WeightServiceError::InsufficientData(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_reading_validates_request() {
+        let service = WeightService::new(MockWeightRepository::new());
+        let request = CreateWeightRequest {
+            weight_kg: 600.0,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: Utc::now().to_rfc3339(),
+        };
+
+        let result = service.create_reading("test-user", request).await;
+        assert!(matches!(result, Err(WeightServiceError::ValidationError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_insights_rejects_implausible_height() {
+        let stored = my_health_guide_data::models::weight::WeightReading {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: "test-user".to_string(),
+            weight_kg: 70.0,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: (Utc::now() - Duration::days(1)).to_rfc3339(),
+        };
+        let service = WeightService::new(MockWeightRepository::with_readings(vec![stored]));
+
+        let result = service.get_insights("test-user", Some(20.0)).await;
+        assert!(matches!(result, Err(WeightServiceError::ValidationError(_))));
+
+        let insights = service.get_insights("test-user", Some(175.0)).await.unwrap();
+        assert_eq!(insights.reading_count, 1);
+        assert!(insights.bmi.is_some());
+    }
+}
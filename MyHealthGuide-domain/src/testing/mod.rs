@@ -4,16 +4,27 @@
 // Re-export useful test mocks from the data layer
 pub use my_health_guide_data::repository::tests::MockBloodPressureRepository;
 
-use crate::entities::blood_pressure::{BloodPressureReading, CreateBloodPressureRequest, BloodPressureInsights, BloodPressureCategory};
+use crate::entities::blood_pressure::{
+    BloodPressureReading, CreateBloodPressureRequest, BloodPressureFilter, BloodPressureInsights, BloodPressureCategory,
+    BloodPressureSession, CreateBloodPressureSessionRequest, BloodPressureAlert, AlertRule, CreateAlertRuleRequest,
+};
+use crate::entities::conversions::parse_string_to_timestamp;
 use crate::services::blood_pressure::{BloodPressureServiceTrait, BloodPressureServiceError};
+use crate::services::guidelines::{guideline_set, GuidelineSet};
+use crate::services::insights::{average_session, collapse_sessions};
 use std::sync::RwLock;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use crate::health::{SystemHealth, SystemStatus, ComponentStatus, HealthComponent, HealthServiceTrait};
 use async_trait::async_trait;
+use validator::Validate;
 
 /// Mock implementation of the BloodPressureServiceTrait for testing
 pub struct MockBloodPressureService {
     readings: RwLock<HashMap<String, BloodPressureReading>>,
+    deleted: RwLock<HashMap<String, BloodPressureReading>>,
+    sessions: RwLock<HashMap<String, BloodPressureSession>>,
+    alerts: RwLock<Vec<BloodPressureAlert>>,
+    alert_rules: RwLock<HashMap<String, AlertRule>>,
     should_fail_validation: bool,
     should_fail_creation: bool,
 }
@@ -29,6 +40,10 @@ impl MockBloodPressureService {
     pub fn new() -> Self {
         Self {
             readings: RwLock::new(HashMap::new()),
+            deleted: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(HashMap::new()),
+            alerts: RwLock::new(Vec::new()),
+            alert_rules: RwLock::new(HashMap::new()),
             should_fail_validation: false,
             should_fail_creation: false,
         }
@@ -86,6 +101,7 @@ impl BloodPressureServiceTrait for MockBloodPressureService {
         &self,
         readings: &[BloodPressureReading],
         timeframe_days: u32,
+        guideline: &GuidelineSet,
     ) -> Result<crate::entities::blood_pressure::BloodPressureInsights, BloodPressureServiceError> {
         if readings.is_empty() {
             return Err(BloodPressureServiceError::InsufficientData(
@@ -103,32 +119,35 @@ impl BloodPressureServiceTrait for MockBloodPressureService {
             min_systolic: 110,
             min_diastolic: 70,
             category: BloodPressureCategory::Normal,
+            guideline: guideline.name().to_string(),
+            pediatric: None,
+            systolic_sd: None,
+            diastolic_sd: None,
+            systolic_arv: None,
+            diastolic_arv: None,
+            systolic_cv: None,
+            diastolic_cv: None,
+            avg_pulse_pressure: 40.0,
+            avg_mean_arterial_pressure: 93.3,
+            time_in_target_range: Some(100.0),
+            systolic_trend: None,
+            diastolic_trend: None,
             reading_count: readings.len(),
             period_days: timeframe_days,
             generated_at: chrono::Utc::now(),
         })
     }
 
-    fn get_severity(&self, reading: &BloodPressureReading) -> crate::entities::blood_pressure::BloodPressureCategory {
-        if reading.systolic >= 180 || reading.diastolic >= 120 {
-            BloodPressureCategory::HypertensiveCrisis
-        } else if reading.systolic >= 140 || reading.diastolic >= 90 {
-            BloodPressureCategory::Hypertension2
-        } else if reading.systolic >= 130 || reading.diastolic >= 80 {
-            BloodPressureCategory::Hypertension1
-        } else if reading.systolic >= 120 && reading.diastolic < 80 {
-            BloodPressureCategory::Elevated
-        } else {
-            BloodPressureCategory::Normal
-        }
+    fn get_severity(&self, reading: &BloodPressureReading, guideline: &GuidelineSet) -> crate::entities::blood_pressure::BloodPressureCategory {
+        guideline.categorize(reading.systolic, reading.diastolic)
     }
 
-    fn is_hypertensive_crisis(&self, reading: &BloodPressureReading) -> bool {
-        reading.systolic >= 180 || reading.diastolic >= 120
+    fn is_hypertensive_crisis(&self, reading: &BloodPressureReading, guideline: &GuidelineSet) -> bool {
+        guideline.is_crisis(reading.systolic, reading.diastolic)
     }
 
-    async fn create_reading(&self, request: CreateBloodPressureRequest)
-        -> Result<BloodPressureReading, BloodPressureServiceError>
+    async fn create_reading_with_alert(&self, user_id: &str, request: CreateBloodPressureRequest)
+        -> Result<(BloodPressureReading, Option<BloodPressureAlert>), BloodPressureServiceError>
     {
         // First validate the request
         self.validate_create_request(&request)?;
@@ -141,36 +160,167 @@ impl BloodPressureServiceTrait for MockBloodPressureService {
 
         // Generate a new reading
         let id = uuid::Uuid::new_v4().to_string();
+        let timestamp = parse_string_to_timestamp(&request.timestamp)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+        let now = chrono::Utc::now();
         let reading = BloodPressureReading {
             id,
+            user_id: user_id.to_string(),
             systolic: request.systolic,
             diastolic: request.diastolic,
             pulse: request.pulse,
-            timestamp: request.timestamp,
+            timestamp,
             notes: request.notes,
             position: request.position,
             arm: request.arm,
             device_id: request.device_id,
+            created_at: now,
+            updated_at: now,
         };
 
         // Store the reading
-        let mut readings = self.readings.write().unwrap();
-        let id = reading.id.clone();
-        readings.insert(id, reading.clone());
+        let alert = {
+            let mut readings = self.readings.write().unwrap();
+            let id = reading.id.clone();
+            readings.insert(id, reading.clone());
+
+            // Check it against all of the user's readings and alerts, without going through storage
+            let recent: Vec<BloodPressureReading> = readings.values()
+                .filter(|r| r.user_id == user_id)
+                .cloned()
+                .collect();
+            let previous: Vec<BloodPressureAlert> = self.alerts.read().unwrap().iter()
+                .filter(|alert| alert.user_id == user_id)
+                .cloned()
+                .collect();
+            self.detect_reading_alert(user_id, &reading, &recent, &previous, &guideline_set())
+        };
+        if let Some(alert) = &alert {
+            self.alerts.write().unwrap().push(alert.clone());
+        }
 
-        Ok(reading)
+        Ok((reading, alert))
+    }
+
+    async fn get_alerts(&self, user_id: &str) -> Result<Vec<BloodPressureAlert>, BloodPressureServiceError> {
+        let alerts = self.alerts.read().unwrap();
+        Ok(alerts.iter()
+            .rev()
+            .filter(|alert| alert.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_latest_rule_alert(&self, user_id: &str, rule_id: &str)
+        -> Result<Option<BloodPressureAlert>, BloodPressureServiceError>
+    {
+        let alerts = self.alerts.read().unwrap();
+        Ok(alerts.iter()
+            .rev()
+            .find(|alert| alert.user_id == user_id && alert.rule_id.as_deref() == Some(rule_id))
+            .cloned())
+    }
+
+    async fn record_alert(&self, _user_id: &str, alert: BloodPressureAlert) -> BloodPressureAlert {
+        self.alerts.write().unwrap().push(alert.clone());
+        alert
+    }
+
+    async fn create_alert_rule(&self, user_id: &str, request: CreateAlertRuleRequest)
+        -> Result<AlertRule, BloodPressureServiceError>
+    {
+        request.validate()
+            .map_err(|e| BloodPressureServiceError::ValidationError(e.to_string()))?;
+
+        let now = chrono::Utc::now();
+        let rule = AlertRule {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: request.name,
+            condition: request.condition,
+            enabled: request.enabled,
+            created_at: now,
+            updated_at: now,
+        };
+        self.alert_rules.write().unwrap().insert(rule.id.clone(), rule.clone());
+        Ok(rule)
+    }
+
+    async fn get_alert_rule_by_id(&self, user_id: &str, id: &str) -> Result<AlertRule, BloodPressureServiceError> {
+        self.alert_rules.read().unwrap()
+            .get(id)
+            .filter(|rule| rule.user_id == user_id)
+            .cloned()
+            .ok_or_else(|| BloodPressureServiceError::NotFound(format!("Alert rule with ID {} not found", id)))
     }
 
-    async fn get_all_readings(&self) -> Result<Vec<BloodPressureReading>, BloodPressureServiceError> {
+    async fn get_alert_rules(&self, user_id: &str) -> Result<Vec<AlertRule>, BloodPressureServiceError> {
+        let mut rules: Vec<AlertRule> = self.alert_rules.read().unwrap()
+            .values()
+            .filter(|rule| rule.user_id == user_id)
+            .cloned()
+            .collect();
+        rules.sort_by_key(|rule| rule.created_at);
+        Ok(rules)
+    }
+
+    async fn update_alert_rule(&self, user_id: &str, id: &str, request: CreateAlertRuleRequest)
+        -> Result<AlertRule, BloodPressureServiceError>
+    {
+        request.validate()
+            .map_err(|e| BloodPressureServiceError::ValidationError(e.to_string()))?;
+
+        let existing = self.get_alert_rule_by_id(user_id, id).await?;
+        let rule = AlertRule {
+            name: request.name,
+            condition: request.condition,
+            enabled: request.enabled,
+            updated_at: chrono::Utc::now(),
+            ..existing
+        };
+        self.alert_rules.write().unwrap().insert(rule.id.clone(), rule.clone());
+        Ok(rule)
+    }
+
+    async fn delete_alert_rule(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError> {
+        let mut rules = self.alert_rules.write().unwrap();
+        if rules.get(id).is_none_or(|rule| rule.user_id != user_id) {
+            return Err(BloodPressureServiceError::NotFound(
+                format!("Alert rule with ID {} not found", id),
+            ));
+        }
+
+        rules.remove(id);
+        Ok(())
+    }
+
+    async fn evaluate_all_alert_rules(&self) -> Result<usize, BloodPressureServiceError> {
+        let user_ids: BTreeSet<String> = self.alert_rules.read().unwrap()
+            .values()
+            .filter(|rule| rule.enabled)
+            .map(|rule| rule.user_id.clone())
+            .collect();
+
+        let mut fired = 0;
+        for user_id in &user_ids {
+            fired += self.evaluate_alert_rules(user_id).await?.len();
+        }
+        Ok(fired)
+    }
+
+    async fn get_all_readings(&self, user_id: &str) -> Result<Vec<BloodPressureReading>, BloodPressureServiceError> {
         let readings = self.readings.read().unwrap();
-        let readings_vec: Vec<BloodPressureReading> = readings.values().cloned().collect();
+        let readings_vec: Vec<BloodPressureReading> = readings.values()
+            .filter(|r| r.user_id == user_id)
+            .cloned()
+            .collect();
         Ok(readings_vec)
     }
 
-    async fn get_reading_by_id(&self, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError> {
+    async fn get_reading_by_id(&self, user_id: &str, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError> {
         let readings = self.readings.read().unwrap();
 
-        match readings.get(id) {
+        match readings.get(id).filter(|r| r.user_id == user_id) {
             Some(reading) => Ok(reading.clone()),
             None => Err(BloodPressureServiceError::NotFound(
                 format!("Reading with ID {} not found", id),
@@ -180,24 +330,44 @@ impl BloodPressureServiceTrait for MockBloodPressureService {
 
     async fn get_filtered_readings(
         &self,
-        start_date: Option<String>,
-        end_date: Option<String>,
+        user_id: &str,
+        filter: BloodPressureFilter,
         limit: Option<usize>,
         offset: Option<usize>,
         sort_desc: Option<bool>,
     ) -> Result<(Vec<BloodPressureReading>, usize), BloodPressureServiceError> {
         let readings = self.readings.read().unwrap();
-        let mut readings_vec: Vec<BloodPressureReading> = readings.values().cloned().collect();
+        let mut readings_vec: Vec<BloodPressureReading> = readings.values()
+            .filter(|r| r.user_id == user_id)
+            .cloned()
+            .collect();
 
         // Filter by date range if provided
-        if let Some(start) = &start_date {
+        if let Some(start) = &filter.start_date {
             readings_vec.retain(|r| r.timestamp >= *start);
         }
 
-        if let Some(end) = &end_date {
+        if let Some(end) = &filter.end_date {
             readings_vec.retain(|r| r.timestamp <= *end);
         }
 
+        if let Some(category) = filter.category {
+            let guideline = guideline_set();
+            readings_vec.retain(|r| self.get_severity(r, &guideline) == category);
+        }
+
+        if let Some(position) = &filter.position {
+            readings_vec.retain(|r| r.position.as_ref() == Some(position));
+        }
+
+        if let Some(arm) = &filter.arm {
+            readings_vec.retain(|r| r.arm.as_ref() == Some(arm));
+        }
+
+        if let Some(device_id) = &filter.device_id {
+            readings_vec.retain(|r| r.device_id.as_ref() == Some(device_id));
+        }
+
         // Sort by timestamp
         readings_vec.sort_by(|a, b| {
             if sort_desc.unwrap_or(false) {
@@ -225,6 +395,160 @@ impl BloodPressureServiceTrait for MockBloodPressureService {
 
         Ok((readings_vec, total_count))
     }
+
+    async fn update_reading(&self, user_id: &str, id: &str, request: CreateBloodPressureRequest)
+        -> Result<BloodPressureReading, BloodPressureServiceError>
+    {
+        self.validate_create_request(&request)?;
+        let timestamp = parse_string_to_timestamp(&request.timestamp)
+            .map_err(BloodPressureServiceError::ValidationError)?;
+
+        let mut readings = self.readings.write().unwrap();
+        match readings.get_mut(id).filter(|r| r.user_id == user_id) {
+            Some(reading) => {
+                reading.systolic = request.systolic;
+                reading.diastolic = request.diastolic;
+                reading.pulse = request.pulse;
+                reading.notes = request.notes;
+                reading.timestamp = timestamp;
+                reading.position = request.position;
+                reading.arm = request.arm;
+                reading.device_id = request.device_id;
+                reading.updated_at = chrono::Utc::now();
+                Ok(reading.clone())
+            },
+            None => Err(BloodPressureServiceError::NotFound(
+                format!("Reading with ID {} not found", id),
+            )),
+        }
+    }
+
+    async fn delete_reading(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError> {
+        let mut readings = self.readings.write().unwrap();
+        if readings.get(id).is_none_or(|r| r.user_id != user_id) {
+            return Err(BloodPressureServiceError::NotFound(
+                format!("Reading with ID {} not found", id),
+            ));
+        }
+
+        if let Some(reading) = readings.remove(id) {
+            self.deleted.write().unwrap().insert(id.to_string(), reading);
+        }
+        Ok(())
+    }
+
+    async fn restore_reading(&self, user_id: &str, id: &str) -> Result<BloodPressureReading, BloodPressureServiceError> {
+        let mut readings = self.readings.write().unwrap();
+        if let Some(reading) = readings.get(id).filter(|r| r.user_id == user_id) {
+            return Ok(reading.clone());
+        }
+
+        let mut deleted = self.deleted.write().unwrap();
+        if deleted.get(id).is_none_or(|r| r.user_id != user_id) {
+            return Err(BloodPressureServiceError::NotFound(
+                format!("Reading with ID {} not found", id),
+            ));
+        }
+
+        let reading = deleted.remove(id).expect("deleted reading checked above");
+        readings.insert(id.to_string(), reading.clone());
+        Ok(reading)
+    }
+
+    async fn create_session(&self, user_id: &str, request: CreateBloodPressureSessionRequest)
+        -> Result<BloodPressureSession, BloodPressureServiceError>
+    {
+        if request.readings.len() < 2 {
+            return Err(BloodPressureServiceError::ValidationError(
+                "A session must have between 2 and 6 readings".to_string(),
+            ));
+        }
+
+        let mut readings = Vec::with_capacity(request.readings.len());
+        for reading in request.readings {
+            readings.push(self.create_reading(user_id, reading).await?);
+        }
+        readings.sort_by_key(|reading| reading.timestamp);
+
+        let session = BloodPressureSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            discard_first: request.discard_first,
+            notes: request.notes,
+            average: average_session(&readings, request.discard_first, &guideline_set()),
+            readings,
+            created_at: chrono::Utc::now(),
+        };
+        self.sessions.write().unwrap().insert(session.id.clone(), session.clone());
+
+        Ok(session)
+    }
+
+    async fn get_session_by_id(&self, user_id: &str, id: &str) -> Result<BloodPressureSession, BloodPressureServiceError> {
+        let sessions = self.sessions.read().unwrap();
+
+        match sessions.get(id).filter(|s| s.user_id == user_id) {
+            Some(session) => Ok(session.clone()),
+            None => Err(BloodPressureServiceError::NotFound(
+                format!("Session with ID {} not found", id),
+            )),
+        }
+    }
+
+    async fn get_sessions(&self, user_id: &str, limit: Option<usize>, offset: Option<usize>)
+        -> Result<(Vec<BloodPressureSession>, usize), BloodPressureServiceError>
+    {
+        let sessions = self.sessions.read().unwrap();
+        let mut sessions_vec: Vec<BloodPressureSession> = sessions.values()
+            .filter(|s| s.user_id == user_id)
+            .cloned()
+            .collect();
+        sessions_vec.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+
+        let total_count = sessions_vec.len();
+        let page = sessions_vec.into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok((page, total_count))
+    }
+
+    async fn get_session_averaged_readings(
+        &self,
+        user_id: &str,
+        filter: BloodPressureFilter,
+        limit: Option<usize>,
+        offset: Option<usize>,
+        sort_desc: Option<bool>,
+    ) -> Result<(Vec<BloodPressureReading>, usize), BloodPressureServiceError> {
+        let (readings, _) = self.get_filtered_readings(user_id, filter, None, None, None).await?;
+        let (sessions, _) = self.get_sessions(user_id, None, None).await?;
+
+        let mut readings = collapse_sessions(readings, &sessions);
+        readings.sort_by_key(|reading| reading.timestamp);
+        if sort_desc.unwrap_or(true) {
+            readings.reverse();
+        }
+
+        let total_count = readings.len();
+        let page = readings.into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok((page, total_count))
+    }
+
+    async fn delete_session(&self, user_id: &str, id: &str) -> Result<(), BloodPressureServiceError> {
+        let mut sessions = self.sessions.write().unwrap();
+        if sessions.get(id).is_none_or(|s| s.user_id != user_id) {
+            return Err(BloodPressureServiceError::NotFound(
+                format!("Session with ID {} not found", id),
+            ));
+        }
+
+        sessions.remove(id);
+        Ok(())
+    }
 }
 
 /// Mock implementation of health services for testing system health
@@ -0,0 +1,312 @@
+//! Services backed by a real SQLite database
+//!
+//! The unit tests run the services against mock repositories. These go
+//! through the default services and the data layer's SQL instead, so they
+//! catch what only shows up in the queries. The database pool is global, so
+//! every test in this file shares one database file in the temp directory
+//! and writes its readings under a user of its own.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use once_cell::sync::Lazy;
+use uuid::Uuid;
+
+use my_health_guide_domain::database::initialize_database_pool;
+use my_health_guide_domain::entities::blood_pressure::{
+    AlertRuleCondition, BloodPressureAlertKind, BloodPressureCategory, BloodPressureFilter, CreateAlertRuleRequest,
+    CreateBloodPressureRequest, CreateBloodPressureSessionRequest,
+};
+use my_health_guide_domain::entities::goal::SetGoalsRequest;
+use my_health_guide_domain::entities::profile::SetProfileRequest;
+use my_health_guide_domain::entities::weight::CreateWeightRequest;
+use my_health_guide_domain::services::guidelines::guideline_set;
+use my_health_guide_domain::services::{
+    create_default_blood_pressure_service, create_default_goal_service, create_default_profile_service,
+    create_default_weight_service, BloodPressureServiceTrait, GoalServiceTrait, ProfileServiceTrait, WeightServiceTrait,
+};
+
+/// Set up the database the repositories use, once for the whole file
+static DATABASE: Lazy<()> = Lazy::new(|| {
+    let path = std::env::temp_dir().join(format!("myhealthguide-test-{}.db", Uuid::new_v4()));
+    std::env::set_var("DB_TYPE", "sqlite");
+    std::env::set_var("DB_SQLITE_PATH", &path);
+    initialize_database_pool().expect("Failed to set up the SQLite test database");
+});
+
+/// A user ID no other test has written readings for
+fn fresh_user() -> String {
+    Lazy::force(&DATABASE);
+    format!("sqlite-test-{}", Uuid::new_v4())
+}
+
+/// The default services, with the blood pressure service reading the user's profile
+fn services() -> (Arc<dyn ProfileServiceTrait + Send + Sync>, impl BloodPressureServiceTrait + Send + Sync) {
+    let profiles: Arc<dyn ProfileServiceTrait + Send + Sync> = Arc::new(create_default_profile_service());
+    (profiles.clone(), create_default_blood_pressure_service(profiles))
+}
+
+fn request(systolic: u16, diastolic: u16, timestamp: DateTime<Utc>) -> CreateBloodPressureRequest {
+    CreateBloodPressureRequest {
+        systolic,
+        diastolic,
+        pulse: None,
+        notes: None,
+        timestamp: timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+        position: None,
+        arm: None,
+        device_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_readings_without_a_limit_are_all_returned() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let start = Utc::now() - Duration::days(30);
+    for hour in 0..120 {
+        service.create_reading(&user_id, request(118, 76, start + Duration::hours(hour))).await.unwrap();
+    }
+    let newest = service.create_reading(&user_id, request(142, 91, start + Duration::hours(120))).await.unwrap();
+
+    let (readings, total) = service
+        .get_filtered_readings(&user_id, BloodPressureFilter::default(), None, None, Some(false))
+        .await
+        .unwrap();
+    assert_eq!((readings.len(), total), (121, 121));
+    assert_eq!(readings.last().unwrap().id, newest.id);
+
+    let (averaged, total) = service
+        .get_session_averaged_readings(&user_id, BloodPressureFilter::default(), None, None, None)
+        .await
+        .unwrap();
+    assert_eq!((averaged.len(), total), (121, 121));
+    assert_eq!(averaged[0].id, newest.id);
+}
+
+#[tokio::test]
+async fn test_insights_cover_every_reading_in_the_timeframe() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let now = Utc::now();
+
+    // A high reading early in the timeframe, then more than 100 normal ones
+    service.create_reading(&user_id, request(168, 104, now - Duration::days(29))).await.unwrap();
+    for hour in 0..110 {
+        service.create_reading(&user_id, request(118, 76, now - Duration::days(20) + Duration::hours(hour))).await.unwrap();
+    }
+
+    // Fetched the way the insights endpoint does
+    let filter = BloodPressureFilter::date_range(Some(now - Duration::days(30)), Some(now));
+    let (readings, _) = service.get_filtered_readings(&user_id, filter, None, None, None).await.unwrap();
+    let insights = service.calculate_insights(&readings, 30, &guideline_set()).unwrap();
+
+    assert_eq!(readings.len(), 111);
+    assert_eq!((insights.max_systolic, insights.max_diastolic), (168, 104));
+}
+
+#[tokio::test]
+async fn test_profile_guideline_categorizes_stored_readings() {
+    let user_id = fresh_user();
+    let (profiles, service) = services();
+    let request_guideline = SetProfileRequest { guideline: Some("esc_esh_2018".to_string()), ..Default::default() };
+    profiles.set_profile(&user_id, request_guideline).await.unwrap();
+
+    // Stage 1 under ACC/AHA 2017, normal under ESC/ESH 2018
+    let now = Utc::now();
+    let (reading, alert) = service.create_reading_with_alert(&user_id, request(125, 82, now - Duration::hours(1))).await.unwrap();
+    assert!(alert.is_none());
+
+    let filter = BloodPressureFilter { category: Some(BloodPressureCategory::Normal), ..Default::default() };
+    let (readings, _) = service.get_filtered_readings(&user_id, filter, None, None, None).await.unwrap();
+    assert_eq!(readings.iter().map(|r| r.id.clone()).collect::<Vec<_>>(), vec![reading.id]);
+
+    // ESC/ESH 2018 already treats 180/100 as a crisis
+    let (_, alert) = service.create_reading_with_alert(&user_id, request(180, 100, now)).await.unwrap();
+    assert_eq!(alert.unwrap().kind, BloodPressureAlertKind::HypertensiveCrisis);
+}
+
+#[tokio::test]
+async fn test_hbpm_report_covers_a_week_of_more_than_100_readings() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let start_date = (Utc::now() - Duration::days(20)).date_naive();
+    let midnight = start_date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+    // Nine readings each morning and evening of the counted days, so the
+    // oldest slot is past the first 100; only the first two of a slot count
+    for day in 1..7 {
+        for hour in [7, 20] {
+            for minute in 0..9 {
+                let (systolic, diastolic) = match minute {
+                    0 => (130, 80),
+                    1 => (126, 78),
+                    _ => (170, 105),
+                };
+                let taken = midnight + Duration::days(day) + Duration::hours(hour) + Duration::minutes(minute);
+                service.create_reading(&user_id, request(systolic, diastolic, taken)).await.unwrap();
+            }
+        }
+    }
+    // The morning after the week is left out
+    service.create_reading(&user_id, request(170, 105, midnight + Duration::days(7) + Duration::hours(7))).await.unwrap();
+
+    let report = service.get_hbpm_report(&user_id, start_date, Tz::UTC).await.unwrap();
+    assert!(report.complete, "missing slots: {:?}", report.missing_slots);
+    assert_eq!(report.reading_count, 24);
+    assert_eq!((report.avg_systolic, report.avg_diastolic), (Some(128.0), Some(79.0)));
+}
+
+#[tokio::test]
+async fn test_repeated_stage_2_alert_covers_a_run_of_readings() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let now = Utc::now();
+
+    // A stage 2 reading every day: the third raises an alert, and the next
+    // one only comes once those three have left the week
+    let mut raised = Vec::new();
+    for days_ago in (0..=10).rev() {
+        let (_, alert) = service.create_reading_with_alert(&user_id, request(152, 96, now - Duration::days(days_ago))).await.unwrap();
+        if let Some(alert) = alert {
+            assert_eq!(alert.kind, BloodPressureAlertKind::RepeatedStage2);
+            raised.push((days_ago, alert.reading_ids.len()));
+        }
+    }
+    assert_eq!(raised, vec![(8, 3), (0, 8)]);
+    assert_eq!(service.get_alerts(&user_id).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_session_raises_one_alert_for_its_average() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let now = Utc::now();
+
+    let session = service.create_session(&user_id, CreateBloodPressureSessionRequest {
+        readings: (0..3).map(|minute| request(190, 110, now - Duration::minutes(3 - minute))).collect(),
+        discard_first: false,
+        notes: None,
+    }).await.unwrap();
+
+    let mut reading_ids: Vec<String> = session.readings.iter().map(|reading| reading.id.clone()).collect();
+    reading_ids.sort();
+    let mut alerts = service.get_alerts(&user_id).await.unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].kind, BloodPressureAlertKind::HypertensiveCrisis);
+    alerts[0].reading_ids.sort();
+    assert_eq!(alerts[0].reading_ids, reading_ids);
+    assert_eq!(service.get_session_by_id(&user_id, &session.id).await.unwrap().readings.len(), 3);
+}
+
+#[tokio::test]
+async fn test_session_averaged_readings_are_paged_in_the_query() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let now = Utc::now();
+
+    service.create_reading(&user_id, request(120, 80, now - Duration::hours(3))).await.unwrap();
+    let session = service.create_session(&user_id, CreateBloodPressureSessionRequest {
+        readings: vec![request(140, 90, now - Duration::hours(2)), request(130, 80, now - Duration::hours(2) + Duration::minutes(1))],
+        discard_first: false,
+        notes: None,
+    }).await.unwrap();
+    let latest = service.create_reading(&user_id, request(118, 76, now - Duration::hours(1))).await.unwrap();
+
+    // Only the second reading of the session matches, yet the session keeps its first reading's time
+    let filter = BloodPressureFilter::date_range(Some(now - Duration::hours(2) + Duration::seconds(30)), Some(now));
+    let (page, total) = service
+        .get_session_averaged_readings(&user_id, filter.clone(), Some(1), Some(0), None)
+        .await
+        .unwrap();
+    assert_eq!((page.len(), total), (1, 2));
+    assert_eq!(page[0].id, latest.id);
+
+    let (page, _) = service
+        .get_session_averaged_readings(&user_id, filter, Some(1), Some(1), None)
+        .await
+        .unwrap();
+    assert_eq!(page[0].id, session.id);
+    assert_eq!((page[0].systolic, page[0].diastolic), (135, 85));
+    assert_eq!(page[0].timestamp.timestamp(), (now - Duration::hours(2)).timestamp());
+
+    let (sessions, total) = service.get_sessions(&user_id, Some(1), None).await.unwrap();
+    assert_eq!((sessions.len(), total), (1, 1));
+    assert_eq!(sessions[0].readings.len(), 2);
+}
+
+#[tokio::test]
+async fn test_average_rule_covers_every_reading_in_its_window() {
+    let user_id = fresh_user();
+    let (_, service) = services();
+    let start = Utc::now() - Duration::days(7) + Duration::hours(12);
+
+    // Hourly over the week, 50 high readings, then 100 lower ones: only the newest 100 average below the limit
+    for hour in 0..150 {
+        let systolic = if hour < 50 { 170 } else { 125 };
+        service.create_reading(&user_id, request(systolic, 80, start + Duration::hours(hour))).await.unwrap();
+    }
+    let rule = service.create_alert_rule(&user_id, CreateAlertRuleRequest {
+        name: "Weekly average".to_string(),
+        condition: AlertRuleCondition::AverageAbove { days: 7, systolic: Some(135), diastolic: None },
+        enabled: true,
+    }).await.unwrap();
+
+    let fired = service.evaluate_alert_rules(&user_id).await.unwrap();
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].rule_id.as_deref(), Some(rule.id.as_str()));
+    assert_eq!((fired[0].systolic, fired[0].reading_ids.len()), (140, 150));
+}
+
+#[tokio::test]
+async fn test_goal_progress_follows_the_latest_readings() {
+    let user_id = fresh_user();
+    let (_, blood_pressure) = services();
+    let blood_pressure = Arc::new(blood_pressure);
+    let weight = Arc::new(create_default_weight_service());
+    let goals = create_default_goal_service(blood_pressure.clone(), weight.clone());
+    let now = Utc::now();
+
+    // 100 readings above the targets over the month, then 20 below them
+    let start = now - Duration::days(28) + Duration::hours(1);
+    for reading in 0..100 {
+        blood_pressure.create_reading(&user_id, request(150, 95, start + Duration::hours(6 * reading))).await.unwrap();
+    }
+    let start = now - Duration::hours(60);
+    for reading in 0..20 {
+        blood_pressure.create_reading(&user_id, request(120, 78, start + Duration::hours(3 * reading))).await.unwrap();
+    }
+
+    // 100 weights well above the target, then 10 that reach it
+    let start = now - Duration::days(85);
+    for reading in 0..110 {
+        let weight_kg = if reading < 100 { 95.0 } else { 80.2 };
+        let taken = start + Duration::hours(18 * reading);
+        weight.create_reading(&user_id, CreateWeightRequest {
+            weight_kg,
+            body_fat_percentage: None,
+            muscle_mass_kg: None,
+            notes: None,
+            timestamp: taken.to_rfc3339_opts(SecondsFormat::Secs, true),
+        }).await.unwrap();
+    }
+
+    goals.set_goals(&user_id, SetGoalsRequest {
+        target_systolic: Some(135),
+        target_diastolic: Some(85),
+        readings_per_week: Some(30),
+        target_weight_kg: Some(80.0),
+    }).await.unwrap();
+    let progress = goals.get_progress(&user_id).await.unwrap().progress;
+
+    let blood_pressure = progress.blood_pressure.unwrap();
+    assert_eq!((blood_pressure.reading_count, blood_pressure.in_target_count), (120, 20));
+    assert_eq!(blood_pressure.current_streak, 20);
+    let frequency = progress.reading_frequency.unwrap();
+    assert_eq!(frequency.readings_last_7_days, 36);
+    assert!(frequency.met);
+    let weight = progress.weight.unwrap();
+    assert_eq!(weight.current_weight_kg, Some(80.2));
+    assert!(weight.reached);
+}